@@ -0,0 +1,46 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use milli::CboRoaringBitmapCodec;
+use roaring::RoaringBitmap;
+
+#[global_allocator]
+static ALLOC: mimalloc::MiMalloc = mimalloc::MiMalloc;
+
+/// Intersect the bitmap of a word pair present in most of the documents of the index
+/// with a tiny universe, as the proximity ranking rule does for popular pairs.
+fn bench_cbo_intersection(c: &mut Criterion) {
+    let pair_docids = RoaringBitmap::from_iter((0..2_000_000).filter(|docid| docid % 7 != 0));
+    let mut bytes = Vec::new();
+    CboRoaringBitmapCodec::serialize_into(&pair_docids, &mut bytes);
+
+    let mut group = c.benchmark_group("cbo intersection: popular pair");
+    for universe_len in [10u32, 1_000] {
+        let universe = RoaringBitmap::from_iter((0..universe_len).map(|i| i * 1_997));
+
+        group.bench_with_input(
+            BenchmarkId::new("full decode", universe_len),
+            &universe,
+            |b, universe| {
+                b.iter(|| {
+                    let docids = CboRoaringBitmapCodec::deserialize_from(&bytes).unwrap();
+                    black_box(docids & universe)
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("partial decode", universe_len),
+            &universe,
+            |b, universe| {
+                b.iter(|| {
+                    black_box(
+                        CboRoaringBitmapCodec::intersection_with_serialized(&bytes, universe)
+                            .unwrap(),
+                    )
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_cbo_intersection);
+criterion_main!(benches);
@@ -0,0 +1,60 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId};
+use milli::documents::documents_batch_reader_from_objects;
+use milli::{GeoSortStrategy, Object, RankingRuleBench, TempIndex};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde_json::json;
+
+#[global_allocator]
+static ALLOC: mimalloc::MiMalloc = mimalloc::MiMalloc;
+
+/// Index documents spread uniformly over the globe, the same ones for every run.
+fn geo_index(documents: u32) -> TempIndex {
+    let index = TempIndex::new_with_map_size(100 * 1024 * 1024);
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_sortable_fields(["_geo".to_owned()].into_iter().collect());
+        })
+        .unwrap();
+
+    let mut rng = ChaCha8Rng::seed_from_u64(42);
+    let documents = (0..documents).map(|id| {
+        let lat: f64 = rng.gen_range(-90.0..90.0);
+        let lng: f64 = rng.gen_range(-180.0..180.0);
+        let document = json!({ "id": id, "_geo": { "lat": lat, "lng": lng } });
+        serde_json::from_value::<Object>(document).unwrap()
+    });
+    index.add_documents(documents_batch_reader_from_objects(documents)).unwrap();
+    index
+}
+
+/// Compares the iterative and rtree strategies of the geo sort, ranking the first buckets
+/// around Lille.
+fn bench_geo_sort(c: &mut criterion::Criterion) {
+    let lille = [50.62999333378238, 3.086269263384099];
+
+    for documents in [1_000, 100_000] {
+        let index = geo_index(documents);
+        let rtxn = index.read_txn().unwrap();
+
+        let mut group = c.benchmark_group(format!("geo_sort of {documents} documents"));
+        for (name, strategy) in [
+            ("iterative", GeoSortStrategy::AlwaysIterative(100)),
+            ("rtree", GeoSortStrategy::AlwaysRtree(100)),
+        ] {
+            for ascending in [true, false] {
+                let mut bench =
+                    RankingRuleBench::geo_sort(&index, &rtxn, lille, ascending, strategy).unwrap();
+                let order = if ascending { "asc" } else { "desc" };
+                group.bench_function(BenchmarkId::new(name, order), |b| {
+                    b.iter(|| black_box(bench.run(Some(20)).unwrap()))
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_geo_sort);
+criterion_main!(benches);
@@ -0,0 +1,189 @@
+mod datasets_paths;
+mod utils;
+
+use std::alloc::{GlobalAlloc, Layout};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::measurement::{Measurement, ValueFormatter};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use milli::score_details::ScoringStrategy;
+use milli::update::Settings;
+use milli::{
+    execute_search, DefaultSearchLogger, GeoSortStrategy, InternerPool, SearchContext,
+    TermsMatchingStrategy, TimeBudget,
+};
+use utils::Conf;
+
+/// Counts the allocations made by the search, to compare creating a context from scratch with
+/// creating it from the interner pool of the previous search.
+struct CountingAllocator {
+    allocations: AtomicUsize,
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+        mimalloc::MiMalloc.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        mimalloc::MiMalloc.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+        mimalloc::MiMalloc.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAllocator = CountingAllocator { allocations: AtomicUsize::new(0) };
+
+fn base_conf(builder: &mut Settings) {
+    let searchable_fields = ["title", "album", "artist"].iter().map(|s| s.to_string()).collect();
+    builder.set_searchable_fields(searchable_fields);
+
+    let mut synonyms = BTreeMap::new();
+    synonyms.insert("the".to_string(), vec!["a".to_string(), "an".to_string()]);
+    synonyms.insert("love".to_string(), vec!["heart".to_string(), "romance".to_string()]);
+    synonyms.insert("song".to_string(), vec!["tune".to_string(), "track".to_string()]);
+    synonyms.insert("new york".to_string(), vec!["nyc".to_string(), "big apple".to_string()]);
+    builder.set_synonyms(synonyms);
+}
+
+#[rustfmt::skip]
+const BASE_CONF: Conf = Conf {
+    dataset: datasets_paths::SMOL_SONGS,
+    // the words of these queries are expanded by the single and multi-word synonyms above
+    queries: &[
+        "the love song of new york",
+        "love song from the big apple to nyc",
+        "the new york love song",
+    ],
+    configure: base_conf,
+    primary_key: Some("id"),
+    ..Conf::BASE
+};
+
+fn search(ctx: &mut SearchContext, query: &str) {
+    let universe = ctx.index.documents_ids(ctx.txn).unwrap();
+    let result = execute_search(
+        ctx,
+        Some(query),
+        TermsMatchingStrategy::Last,
+        ScoringStrategy::Skip,
+        false,
+        universe,
+        &None,
+        &None,
+        GeoSortStrategy::default(),
+        0,
+        20,
+        None,
+        &mut DefaultSearchLogger,
+        &mut DefaultSearchLogger,
+        TimeBudget::max(),
+        None,
+        None,
+    )
+    .unwrap();
+    black_box(result);
+}
+
+/// Measures the number of allocations made by a benchmarked routine instead of its duration.
+struct Allocations;
+
+impl Measurement for Allocations {
+    type Intermediate = usize;
+    type Value = usize;
+
+    fn start(&self) -> Self::Intermediate {
+        ALLOC.allocations.load(Ordering::Relaxed)
+    }
+
+    fn end(&self, start: Self::Intermediate) -> Self::Value {
+        ALLOC.allocations.load(Ordering::Relaxed) - start
+    }
+
+    fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+        v1 + v2
+    }
+
+    fn zero(&self) -> Self::Value {
+        0
+    }
+
+    fn to_f64(&self, value: &Self::Value) -> f64 {
+        *value as f64
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        &AllocationsFormatter
+    }
+}
+
+struct AllocationsFormatter;
+
+impl ValueFormatter for AllocationsFormatter {
+    fn scale_values(&self, _typical_value: f64, _values: &mut [f64]) -> &'static str {
+        "allocs"
+    }
+
+    fn scale_throughputs(
+        &self,
+        _typical_value: f64,
+        _throughput: &Throughput,
+        _values: &mut [f64],
+    ) -> &'static str {
+        "allocs"
+    }
+
+    fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+        "allocs"
+    }
+}
+
+fn bench_search_interner_pool<M: Measurement>(c: &mut Criterion<M>, group_name: &str) {
+    let index = utils::base_setup(&BASE_CONF);
+
+    let mut group = c.benchmark_group(group_name);
+    for &query in BASE_CONF.queries {
+        group.bench_with_input(BenchmarkId::new("without pool", query), &query, |b, &query| {
+            let rtxn = index.read_txn().unwrap();
+            b.iter(|| {
+                let mut ctx = SearchContext::new(&index, &rtxn).unwrap();
+                search(&mut ctx, query);
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("with pool", query), &query, |b, &query| {
+            let rtxn = index.read_txn().unwrap();
+            let mut pool = Some(InternerPool::default());
+            b.iter(|| {
+                let pool_in = pool.take().unwrap();
+                let mut ctx = SearchContext::with_interner_pool(&index, &rtxn, pool_in).unwrap();
+                search(&mut ctx, query);
+                pool = Some(ctx.into_interner_pool());
+            })
+        });
+    }
+    group.finish();
+
+    index.prepare_for_closing().wait();
+}
+
+fn bench_duration(c: &mut Criterion) {
+    bench_search_interner_pool(c, "smol-songs: search interner pool");
+}
+
+fn bench_allocations(c: &mut Criterion<Allocations>) {
+    bench_search_interner_pool(c, "smol-songs: search interner pool allocations");
+}
+
+criterion_group!(benches, bench_duration);
+criterion_group! {
+    name = allocations;
+    config = Criterion::default().with_measurement(Allocations);
+    targets = bench_allocations
+}
+criterion_main!(benches, allocations);
@@ -0,0 +1,89 @@
+mod datasets_paths;
+mod utils;
+
+use std::collections::BTreeMap;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId};
+use milli::score_details::ScoringStrategy;
+use milli::update::Settings;
+use milli::{
+    execute_search, DefaultSearchLogger, GeoSortStrategy, SearchContext, TermsMatchingStrategy,
+    TimeBudget,
+};
+use utils::Conf;
+
+#[global_allocator]
+static ALLOC: mimalloc::MiMalloc = mimalloc::MiMalloc;
+
+fn base_conf(builder: &mut Settings) {
+    let searchable_fields = ["title", "album", "artist"].iter().map(|s| s.to_string()).collect();
+    builder.set_searchable_fields(searchable_fields);
+
+    let mut synonyms = BTreeMap::new();
+    synonyms.insert("love".to_string(), vec!["heart".to_string(), "romance".to_string()]);
+    synonyms.insert("new york".to_string(), vec!["nyc".to_string(), "big apple".to_string()]);
+    builder.set_synonyms(synonyms);
+}
+
+#[rustfmt::skip]
+const BASE_CONF: Conf = Conf {
+    dataset: datasets_paths::SMOL_SONGS,
+    queries: &[
+        "love song new york",
+        "i will always love",
+    ],
+    configure: base_conf,
+    primary_key: Some("id"),
+    ..Conf::BASE
+};
+
+fn search(ctx: &mut SearchContext, query: &str) {
+    let universe = ctx.index.documents_ids(ctx.txn).unwrap();
+    let result = execute_search(
+        ctx,
+        Some(query),
+        TermsMatchingStrategy::Last,
+        ScoringStrategy::Skip,
+        false,
+        universe,
+        &None,
+        &None,
+        GeoSortStrategy::default(),
+        0,
+        20,
+        None,
+        &mut DefaultSearchLogger,
+        &mut DefaultSearchLogger,
+        TimeBudget::max(),
+        None,
+        None,
+    )
+    .unwrap();
+    black_box(result);
+}
+
+/// Repeats the same 4-word query on a single search context, as an autocomplete would.
+fn bench_search_query_graph_cache(c: &mut criterion::Criterion) {
+    let index = utils::base_setup(&BASE_CONF);
+
+    let mut group = c.benchmark_group("smol-songs: query graph cache");
+    for &query in BASE_CONF.queries {
+        group.bench_with_input(BenchmarkId::new("without cache", query), &query, |b, &query| {
+            let rtxn = index.read_txn().unwrap();
+            let mut ctx = SearchContext::new(&index, &rtxn).unwrap();
+            b.iter(|| search(&mut ctx, query))
+        });
+        group.bench_with_input(BenchmarkId::new("with cache", query), &query, |b, &query| {
+            let rtxn = index.read_txn().unwrap();
+            let mut ctx = SearchContext::new(&index, &rtxn).unwrap();
+            ctx.enable_query_graph_cache(8);
+            b.iter(|| search(&mut ctx, query))
+        });
+    }
+    group.finish();
+
+    index.prepare_for_closing().wait();
+}
+
+criterion_group!(benches, bench_search_query_graph_cache);
+criterion_main!(benches);
@@ -81,6 +81,19 @@ fn bench_songs(c: &mut criterion::Criterion) {
             optional_words: false,
             ..BASE_CONF
         },
+        utils::Conf {
+            group_name: "proximity rare and common words",
+            queries: &[
+                "the mingus ",
+                "john notstandskomitee ",
+                "love tamo ",
+                "the john bowie ",
+            ],
+            criterion: Some(&["proximity"]),
+            optional_words: false,
+            proximity_rarest_first: true,
+            ..BASE_CONF
+        },
         utils::Conf {
             group_name: "typo",
             queries: &[
@@ -101,6 +114,19 @@ fn bench_songs(c: &mut criterion::Criterion) {
             optional_words: false,
             ..BASE_CONF
         },
+        utils::Conf {
+            group_name: "typo overlapping stems",
+            queries: &[
+                // five words sharing a stem, long enough to be typo tolerant, so that
+                // their typo derivations are computed in a single traversal of the FST
+                "lovers lovely lovesick loveless lovesong ",
+                "dancer dancers dancing dancefloor danceable ",
+                "colours coloured colourful colouring colourless ",
+            ],
+            criterion: Some(&["typo"]),
+            optional_words: false,
+            ..BASE_CONF
+        },
         utils::Conf {
             group_name: "words",
             queries: &[
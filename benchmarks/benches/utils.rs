@@ -35,6 +35,8 @@ pub struct Conf<'a> {
     pub sort: Option<Vec<&'a str>>,
     /// enable or disable the optional words on the query
     pub optional_words: bool,
+    /// explore the proximity paths through the rarest query terms first
+    pub proximity_rarest_first: bool,
     /// primary key, if there is None we'll auto-generate docids for every documents
     pub primary_key: Option<&'a str>,
 }
@@ -51,6 +53,7 @@ impl Conf<'_> {
         filter: None,
         sort: None,
         optional_words: true,
+        proximity_rarest_first: false,
         primary_key: None,
     };
 }
@@ -122,6 +125,7 @@ pub fn run_benches(c: &mut criterion::Criterion, confs: &[Conf]) {
                     let rtxn = index.read_txn().unwrap();
                     let mut search = index.search(&rtxn);
                     search.query(query).terms_matching_strategy(TermsMatchingStrategy::default());
+                    search.proximity_rarest_first(conf.proximity_rarest_first);
                     if let Some(filter) = conf.filter {
                         let filter = Filter::from_str(filter).unwrap().unwrap();
                         search.filter(filter);
@@ -500,7 +500,16 @@ pub fn perform_federated_search(
                     documents_ids,
                     document_scores,
                     degraded: query_degraded,
+                    degradation_reasons: _,
                     used_negative_operator: query_used_negative_operator,
+                    has_more: _,
+                    filter_funnel: _,
+                    below_threshold: _,
+                    page_boundary: _,
+                    geo_distance_buckets: _,
+                    query_stats: _,
+                    execution_trace: _,
+                    next_cursor: _,
                 } = result;
 
                 candidates |= query_candidates;
@@ -909,7 +909,16 @@ pub fn perform_search(
             candidates,
             document_scores,
             degraded,
+            degradation_reasons: _,
             used_negative_operator,
+            has_more: _,
+            filter_funnel: _,
+            below_threshold: _,
+            page_boundary: _,
+            geo_distance_buckets: _,
+            query_stats: _,
+            execution_trace: _,
+            next_cursor: _,
         },
         semantic_hit_count,
     ) = search_from_kind(search_kind, search)?;
@@ -1454,7 +1463,16 @@ pub fn perform_similar(
         candidates,
         document_scores,
         degraded: _,
+        degradation_reasons: _,
         used_negative_operator: _,
+        has_more: _,
+        filter_funnel: _,
+        below_threshold: _,
+        page_boundary: _,
+        geo_distance_buckets: _,
+        query_stats: _,
+        execution_trace: _,
+        next_cursor: _,
     } = similar.execute().map_err(|err| match err {
         milli::Error::UserError(milli::UserError::InvalidFilter(_)) => {
             ResponseError::from_msg(err.to_string(), Code::InvalidSimilarFilter)
@@ -1507,16 +1525,23 @@ pub fn perform_similar(
 fn insert_geo_distance(sorts: &[String], document: &mut Document) {
     lazy_static::lazy_static! {
         static ref GEO_REGEX: Regex =
-            Regex::new(r"_geoPoint\(\s*([[:digit:].\-]+)\s*,\s*([[:digit:].\-]+)\s*\)").unwrap();
+            Regex::new(r"_geoPoint\(\s*([[:digit:].\-]+)\s*,\s*([[:digit:].\-]+)\s*(?:,\s*([[:digit:].\-]+)\s*)?\)").unwrap();
     };
     if let Some(capture_group) = sorts.iter().find_map(|sort| GEO_REGEX.captures(sort)) {
         // TODO: TAMO: milli encountered an internal error, what do we want to do?
         let base = [capture_group[1].parse().unwrap(), capture_group[2].parse().unwrap()];
-        let geo_point = &document.get("_geo").unwrap_or(&json!(null));
+        let base_alt = capture_group.get(3).map(|alt| alt.as_str().parse::<f64>().unwrap());
+        let geo_point = document.get("_geo").cloned().unwrap_or(json!(null));
+        // a GeoJSON point stores its coordinates as `[lng, lat]`
+        let geo_point = &milli::update::normalize_geojson_point(geo_point).unwrap_or(json!(null));
         if let Some((lat, lng)) =
             extract_geo_value(&geo_point["lat"]).zip(extract_geo_value(&geo_point["lng"]))
         {
-            let distance = milli::distance_between_two_points(&base, &[lat, lng]);
+            let mut distance = milli::distance_between_two_points(&base, &[lat, lng]);
+            // like the geo sort, only the documents with an altitude are compared in 3D
+            if let Some((base_alt, alt)) = base_alt.zip(extract_geo_value(&geo_point["alt"])) {
+                distance = distance.hypot(base_alt - alt);
+            }
             document.insert("_geoDistance".to_string(), json!(distance.round() as usize));
         }
     }
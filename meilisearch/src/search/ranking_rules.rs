@@ -591,7 +591,8 @@ fn canonicalize_sort(
                     }
                 }
             }
-            AscDesc::Asc(Member::Geo(_)) | AscDesc::Desc(Member::Geo(_)) => match geo_sorted {
+            // the other members are the geo members
+            AscDesc::Asc(_) | AscDesc::Desc(_) => match geo_sorted {
                 Some(earlier_sort_index) => {
                     canonicalization_actions.push(CanonicalizationAction::RemovedDuplicate {
                         earlier_occurrence: RankingRuleSource::Sort {
@@ -688,8 +689,8 @@ impl RankingRuleSource {
                         AscDesc::Desc(Member::Field(field_name)) => {
                             format!("{field_name}:desc")
                         }
-                        AscDesc::Asc(Member::Geo(_)) => "_geo(..):asc".to_string(),
-                        AscDesc::Desc(Member::Geo(_)) => "_geo(..):desc".to_string(),
+                        AscDesc::Asc(_) => "_geo(..):asc".to_string(),
+                        AscDesc::Desc(_) => "_geo(..):desc".to_string(),
                     },
                     None => "unknown".into(),
                 }
@@ -749,8 +750,8 @@ impl RankingRule {
         let kind = match asc_desc {
             AscDesc::Asc(Member::Field(_)) => RankingRuleKind::AscendingSort,
             AscDesc::Desc(Member::Field(_)) => RankingRuleKind::DescendingSort,
-            AscDesc::Asc(Member::Geo(_)) => RankingRuleKind::AscendingGeoSort,
-            AscDesc::Desc(Member::Geo(_)) => RankingRuleKind::DescendingGeoSort,
+            AscDesc::Asc(_) => RankingRuleKind::AscendingGeoSort,
+            AscDesc::Desc(_) => RankingRuleKind::DescendingGeoSort,
         };
         Self {
             source: RankingRuleSource::Sort {
@@ -53,29 +53,78 @@ impl From<AscDescError> for CriterionError {
 pub enum Member {
     Field(String),
     Geo([f64; 2]),
+    /// A point with an altitude, in meters, sorting the documents by their 3D distance to it.
+    Geo3D([f64; 3]),
+    /// A path of at least two points, sorting the documents by their distance to the nearest
+    /// segment of the path.
+    GeoRoute(Vec<[f64; 2]>),
+    /// The vertices of a polygon, at least three, sorting the documents by their distance to
+    /// the nearest vertex.
+    GeoPolygonVertices(Vec<[f64; 2]>),
+}
+
+/// Parse the `latitude, longitude, ...` points of a geo sort, at least `min_points` of them.
+fn parse_geo_points(
+    text: &str,
+    points: &str,
+    min_points: usize,
+) -> Result<Vec<[f64; 2]>, AscDescError> {
+    let coordinates = points
+        .split(',')
+        .map(|coordinate| coordinate.trim().parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| AscDescError::ReservedKeyword { name: text.to_string() })?;
+    if coordinates.len() < min_points * 2 || coordinates.len() % 2 != 0 {
+        return Err(AscDescError::ReservedKeyword { name: text.to_string() });
+    }
+    coordinates
+        .chunks_exact(2)
+        .map(|point| validate_lat_lng(point[0], point[1]).map(|()| [point[0], point[1]]))
+        .collect()
+}
+
+/// Check that the given latitude and longitude are in their valid ranges.
+fn validate_lat_lng(lat: f64, lng: f64) -> Result<(), AscDescError> {
+    if !(-90.0..=90.0).contains(&lat) {
+        Err(BadGeoError::Lat(lat))?
+    } else if !(-180.0..=180.0).contains(&lng) {
+        Err(BadGeoError::Lng(lng))?
+    } else {
+        Ok(())
+    }
 }
 
 impl FromStr for Member {
     type Err = AscDescError;
 
     fn from_str(text: &str) -> Result<Member, Self::Err> {
+        if let Some(route) = text.strip_prefix("_geoRoute(").and_then(|text| text.strip_suffix(')'))
+        {
+            return Ok(Member::GeoRoute(parse_geo_points(text, route, 2)?));
+        }
+        if let Some(vertices) =
+            text.strip_prefix("_geoPolygonVertices(").and_then(|text| text.strip_suffix(')'))
+        {
+            return Ok(Member::GeoPolygonVertices(parse_geo_points(text, vertices, 3)?));
+        }
+
         match text.strip_prefix("_geoPoint(").and_then(|text| text.strip_suffix(')')) {
             Some(point) => {
-                let (lat, lng) = point
-                    .split_once(',')
-                    .ok_or_else(|| AscDescError::ReservedKeyword { name: text.to_string() })
-                    .and_then(|(lat, lng)| {
-                        lat.trim()
-                            .parse()
-                            .and_then(|lat| lng.trim().parse().map(|lng| (lat, lng)))
-                            .map_err(|_| AscDescError::ReservedKeyword { name: text.to_string() })
-                    })?;
-                if !(-90.0..=90.0).contains(&lat) {
-                    return Err(BadGeoError::Lat(lat))?;
-                } else if !(-180.0..=180.0).contains(&lng) {
-                    return Err(BadGeoError::Lng(lng))?;
+                let coordinates = point
+                    .split(',')
+                    .map(|coordinate| coordinate.trim().parse::<f64>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| AscDescError::ReservedKeyword { name: text.to_string() })?;
+                let (lat, lng, alt) = match coordinates.as_slice() {
+                    [lat, lng] => (*lat, *lng, None),
+                    [lat, lng, alt] if alt.is_finite() => (*lat, *lng, Some(*alt)),
+                    _ => return Err(AscDescError::ReservedKeyword { name: text.to_string() }),
+                };
+                validate_lat_lng(lat, lng)?;
+                match alt {
+                    Some(alt) => Ok(Member::Geo3D([lat, lng, alt])),
+                    None => Ok(Member::Geo([lat, lng])),
                 }
-                Ok(Member::Geo([lat, lng]))
             }
             None => {
                 if is_reserved_keyword(text)
@@ -83,6 +132,8 @@ impl FromStr for Member {
                     || text.starts_with("_geoBoundingBox(")
                     || text.starts_with("_geo(")
                     || text.starts_with("_geoDistance(")
+                    || text.starts_with("_geoRoute")
+                    || text.starts_with("_geoPolygonVertices")
                 {
                     return Err(AscDescError::ReservedKeyword { name: text.to_string() })?;
                 }
@@ -97,6 +148,15 @@ impl fmt::Display for Member {
         match self {
             Member::Field(name) => f.write_str(name),
             Member::Geo([lat, lng]) => write!(f, "_geoPoint({}, {})", lat, lng),
+            Member::Geo3D([lat, lng, alt]) => write!(f, "_geoPoint({}, {}, {})", lat, lng, alt),
+            Member::GeoRoute(route) => {
+                let points = route.iter().map(|[lat, lng]| format!("{}, {}", lat, lng));
+                write!(f, "_geoRoute({})", points.collect::<Vec<_>>().join(", "))
+            }
+            Member::GeoPolygonVertices(vertices) => {
+                let points = vertices.iter().map(|[lat, lng]| format!("{}, {}", lat, lng));
+                write!(f, "_geoPolygonVertices({})", points.collect::<Vec<_>>().join(", "))
+            }
         }
     }
 }
@@ -105,14 +165,39 @@ impl Member {
     pub fn field(&self) -> Option<&str> {
         match self {
             Member::Field(field) => Some(field),
-            Member::Geo(_) => None,
+            Member::Geo(_)
+            | Member::Geo3D(_)
+            | Member::GeoRoute(_)
+            | Member::GeoPolygonVertices(_) => None,
         }
     }
 
     pub fn geo_point(&self) -> Option<&[f64; 2]> {
         match self {
             Member::Geo(point) => Some(point),
-            Member::Field(_) => None,
+            Member::Geo3D(point) => point[..2].try_into().ok(),
+            Member::GeoRoute(_) | Member::GeoPolygonVertices(_) | Member::Field(_) => None,
+        }
+    }
+
+    /// Whether the member sorts the documents by their distance to a point or a shape.
+    pub fn is_geo(&self) -> bool {
+        match self {
+            Member::Field(_) => false,
+            Member::Geo(_)
+            | Member::Geo3D(_)
+            | Member::GeoRoute(_)
+            | Member::GeoPolygonVertices(_) => true,
+        }
+    }
+
+    pub fn altitude(&self) -> Option<f64> {
+        match self {
+            Member::Geo3D([_, _, alt]) => Some(*alt),
+            Member::Geo(_)
+            | Member::GeoRoute(_)
+            | Member::GeoPolygonVertices(_)
+            | Member::Field(_) => None,
         }
     }
 }
@@ -155,6 +240,14 @@ pub enum SortError {
     #[error("Invalid syntax for the geo parameter: expected expression formated like \
                     `_geoPoint(latitude, longitude)` and ending by `:asc` or `:desc`, found `{name}`.")]
     BadGeoPointUsage { name: String },
+    #[error("Invalid syntax for the geo route parameter: expected expression formated like \
+                    `_geoRoute(latitude, longitude, latitude, longitude, ...)` with at least two points \
+                    and ending by `:asc` or `:desc`, found `{name}`.")]
+    BadGeoRouteUsage { name: String },
+    #[error("Invalid syntax for the geo polygon vertices parameter: expected expression formated like \
+                    `_geoPolygonVertices(latitude, longitude, latitude, longitude, ...)` with at least three points \
+                    and ending by `:asc` or `:desc`, found `{name}`.")]
+    BadGeoPolygonVerticesUsage { name: String },
     #[error("Invalid syntax for the sort parameter: expected expression ending by `:asc` or `:desc`, found `{name}`.")]
     InvalidName { name: String },
     #[error("`{name}` is a reserved keyword and thus can't be used as a sort expression.")]
@@ -175,6 +268,12 @@ impl From<AscDescError> for SortError {
             AscDescError::ReservedKeyword { name } if name.starts_with("_geoPoint") => {
                 SortError::BadGeoPointUsage { name }
             }
+            AscDescError::ReservedKeyword { name } if name.starts_with("_geoRoute") => {
+                SortError::BadGeoRouteUsage { name }
+            }
+            AscDescError::ReservedKeyword { name } if name.starts_with("_geoPolygonVertices") => {
+                SortError::BadGeoPolygonVerticesUsage { name }
+            }
             AscDescError::ReservedKeyword { name } if &name == "_geo" => {
                 SortError::ReservedNameForSettings { name }
             }
@@ -221,6 +320,17 @@ mod tests {
             ("_geoPoint(-90, -180.0000000000):asc", Asc(Geo([-90., -180.]))),
             ("_geoPoint(42.0002, 59.895):desc", Desc(Geo([42.0002, 59.895]))),
             ("_geoPoint(42., 59.):desc", Desc(Geo([42., 59.]))),
+            ("_geoPoint(35, 85, 75):asc", Asc(Geo3D([35., 85., 75.]))),
+            ("_geoPoint(35, 85, -12.5):desc", Desc(Geo3D([35., 85., -12.5]))),
+            ("_geoRoute(1, 2, 3, 4):asc", Asc(GeoRoute(vec![[1., 2.], [3., 4.]]))),
+            (
+                "_geoRoute(1, 2, 3.5, 4, -5, 6):desc",
+                Desc(GeoRoute(vec![[1., 2.], [3.5, 4.], [-5., 6.]])),
+            ),
+            (
+                "_geoPolygonVertices(1, 2, 3, 4, 5, 6):asc",
+                Asc(GeoPolygonVertices(vec![[1., 2.], [3., 4.], [5., 6.]])),
+            ),
             ("truc(12, 13):desc", Desc(Field(S("truc(12, 13)")))),
         ];
 
@@ -260,7 +370,21 @@ mod tests {
                 "_geoPoint(42,12 , 59,598):desc",
                 ReservedKeyword { name: S("_geoPoint(42,12 , 59,598)") },
             ),
-            ("_geoPoint(35, 85, 75):asc", ReservedKeyword { name: S("_geoPoint(35, 85, 75)") }),
+            (
+                "_geoPoint(35, 85, 75, 12):asc",
+                ReservedKeyword { name: S("_geoPoint(35, 85, 75, 12)") },
+            ),
+            ("_geoPoint(35, 85, inf):asc", ReservedKeyword { name: S("_geoPoint(35, 85, inf)") }),
+            ("_geoRoute:asc", ReservedKeyword { name: S("_geoRoute") }),
+            ("_geoRoute(1, 2):asc", ReservedKeyword { name: S("_geoRoute(1, 2)") }),
+            ("_geoRoute(1, 2, 3):asc", ReservedKeyword { name: S("_geoRoute(1, 2, 3)") }),
+            ("_geoRoute(1, 2, 95, 4):asc", GeoError(BadGeoError::Lat(95.))),
+            ("_geoRoute(1, 2, 3, 190):desc", GeoError(BadGeoError::Lng(190.))),
+            (
+                "_geoPolygonVertices(1, 2, 3, 4):asc",
+                ReservedKeyword { name: S("_geoPolygonVertices(1, 2, 3, 4)") },
+            ),
+            ("_geoPoint(200, 85, 75):asc", GeoError(BadGeoError::Lat(200.))),
             ("_geoPoint(18):asc", ReservedKeyword { name: S("_geoPoint(18)") }),
             ("_geoPoint(200, 200):asc", GeoError(BadGeoError::Lat(200.))),
             ("_geoPoint(90.000001, 0):asc", GeoError(BadGeoError::Lat(90.000001))),
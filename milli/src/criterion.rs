@@ -55,6 +55,30 @@ impl Criterion {
             _otherwise => None,
         }
     }
+
+    /// Returns the kind of this criterion, `None` for the custom ranking rules.
+    pub fn kind(&self) -> Option<RankingRuleKind> {
+        match self {
+            Criterion::Words => Some(RankingRuleKind::Words),
+            Criterion::Typo => Some(RankingRuleKind::Typo),
+            Criterion::Proximity => Some(RankingRuleKind::Proximity),
+            Criterion::Attribute => Some(RankingRuleKind::Attribute),
+            Criterion::Sort => Some(RankingRuleKind::Sort),
+            Criterion::Exactness => Some(RankingRuleKind::Exactness),
+            Criterion::Asc(_) | Criterion::Desc(_) => None,
+        }
+    }
+}
+
+/// The built-in ranking rules, which can be disabled for a single search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RankingRuleKind {
+    Words,
+    Typo,
+    Proximity,
+    Attribute,
+    Sort,
+    Exactness,
 }
 
 impl FromStr for Criterion {
@@ -71,7 +95,7 @@ impl FromStr for Criterion {
             text => match AscDesc::from_str(text)? {
                 AscDesc::Asc(Member::Field(field)) => Ok(Criterion::Asc(field)),
                 AscDesc::Desc(Member::Field(field)) => Ok(Criterion::Desc(field)),
-                AscDesc::Asc(Member::Geo(_)) | AscDesc::Desc(Member::Geo(_)) => {
+                AscDesc::Asc(_) | AscDesc::Desc(_) => {
                     Err(CriterionError::ReservedNameForSort { name: "_geoPoint".to_string() })?
                 }
             },
@@ -107,6 +131,89 @@ impl fmt::Display for Criterion {
     }
 }
 
+/// A valid ordering of the ranking rules in which some of them can't have the intended effect.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CriteriaWarning {
+    /// The ranking rule is listed several times, only its first occurrence is used.
+    Duplicated(Criterion),
+    /// The words ranking rule is always applied before the given ranking rule, whatever
+    /// its own position.
+    ImplicitWords(Criterion),
+    /// A ranking rule sorting by attribute values is placed before a relevancy ranking rule,
+    /// which can then only break the ties of the sort.
+    SortBeforeRelevancy { sort: Criterion, relevancy: Criterion },
+}
+
+impl fmt::Display for CriteriaWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CriteriaWarning::Duplicated(criterion) => {
+                write!(f, "`{criterion}` is listed several times, only the first one is used")
+            }
+            CriteriaWarning::ImplicitWords(criterion) => {
+                write!(f, "`words` is always applied before `{criterion}`, whatever its position")
+            }
+            CriteriaWarning::SortBeforeRelevancy { sort, relevancy } => {
+                write!(f, "`{sort}` before `{relevancy}` makes `{relevancy}` a tie-breaker only")
+            }
+        }
+    }
+}
+
+/// Inspect the given ranking rules, in order, and return the ones that can't have
+/// the intended effect.
+///
+/// The `sort` ranking rule only sorts the documents when sort criteria are given at search
+/// time, it is considered as sorting when `sorting` is `true`.
+pub fn analyze_criteria(criteria: &[Criterion], sorting: bool) -> Vec<CriteriaWarning> {
+    let mut warnings = Vec::new();
+    let mut seen = Vec::new();
+    let mut words = false;
+    let mut first_sort = None;
+    for criterion in criteria {
+        if seen.contains(&criterion) {
+            warnings.push(CriteriaWarning::Duplicated(criterion.clone()));
+            continue;
+        }
+        seen.push(criterion);
+
+        let relevancy = match criterion {
+            Criterion::Words => {
+                words = true;
+                true
+            }
+            Criterion::Typo
+            | Criterion::Proximity
+            | Criterion::Attribute
+            | Criterion::Exactness => {
+                if !words {
+                    warnings.push(CriteriaWarning::ImplicitWords(criterion.clone()));
+                    words = true;
+                }
+                true
+            }
+            Criterion::Sort => {
+                if sorting {
+                    first_sort.get_or_insert(criterion);
+                }
+                false
+            }
+            Criterion::Asc(_) | Criterion::Desc(_) => {
+                first_sort.get_or_insert(criterion);
+                false
+            }
+        };
+
+        if let Some(sort) = first_sort.filter(|_| relevancy) {
+            warnings.push(CriteriaWarning::SortBeforeRelevancy {
+                sort: sort.clone(),
+                relevancy: criterion.clone(),
+            });
+        }
+    }
+    warnings
+}
+
 #[cfg(test)]
 mod tests {
     use big_s::S;
@@ -185,4 +292,55 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn analyze_criteria_warnings() {
+        assert_eq!(analyze_criteria(&default_criteria(), true), vec![]);
+        assert_eq!(analyze_criteria(&default_criteria(), false), vec![]);
+
+        let criteria = [Criterion::Sort, Criterion::Words, Criterion::Typo];
+        assert_eq!(analyze_criteria(&criteria, false), vec![]);
+        let warnings = analyze_criteria(&criteria, true);
+        assert_eq!(
+            warnings,
+            vec![
+                CriteriaWarning::SortBeforeRelevancy {
+                    sort: Criterion::Sort,
+                    relevancy: Criterion::Words
+                },
+                CriteriaWarning::SortBeforeRelevancy {
+                    sort: Criterion::Sort,
+                    relevancy: Criterion::Typo
+                },
+            ]
+        );
+        assert_eq!(
+            warnings[0].to_string(),
+            "`sort` before `words` makes `words` a tie-breaker only"
+        );
+
+        let criteria = [
+            Criterion::Typo,
+            Criterion::Words,
+            Criterion::Desc(S("price")),
+            Criterion::Typo,
+            Criterion::Exactness,
+        ];
+        let warnings = analyze_criteria(&criteria, false);
+        assert_eq!(
+            warnings,
+            vec![
+                CriteriaWarning::ImplicitWords(Criterion::Typo),
+                CriteriaWarning::Duplicated(Criterion::Typo),
+                CriteriaWarning::SortBeforeRelevancy {
+                    sort: Criterion::Desc(S("price")),
+                    relevancy: Criterion::Exactness
+                },
+            ]
+        );
+        assert_eq!(
+            warnings[0].to_string(),
+            "`words` is always applied before `typo`, whatever its position"
+        );
+    }
 }
@@ -25,6 +25,8 @@ pub enum Error {
     IoError(#[from] io::Error),
     #[error(transparent)]
     UserError(#[from] UserError),
+    #[error("The search was cancelled.")]
+    SearchCancelled,
 }
 
 #[derive(Error, Debug)]
@@ -177,6 +179,16 @@ only composed of alphanumeric characters (a-z A-Z 0-9), hyphens (-) and undersco
     InvalidLmdbOpenOptions,
     #[error("You must specify where `sort` is listed in the rankingRules setting to use the sort parameter at search time.")]
     SortRankingRuleMissing,
+    #[error("Only one `_geoPoint` can be used in the sort parameter, but found `{}`.", .0.join("`, `"))]
+    MultipleGeoSort(Vec<String>),
+    #[error("Invalid search cursor: {0}.")]
+    InvalidSearchCursor(String),
+    #[error("The geo sort can't always use the rtree with the geo fields `{0}` and `{1}`, the rtree is only built on the `_geo` field.")]
+    GeoRtreeOnCustomFields(String, String),
+    #[error("The proximity weight of the field `{field}` must be a finite positive number or zero, but found `{weight}`.")]
+    InvalidProximityFieldWeight { field: String, weight: f64 },
+    #[error("The geo score must have a weight between `0` and `1` and a finite positive scale, but found a weight of `{weight}` and a scale of `{scale_meters}` meters.")]
+    InvalidGeoScore { weight: f64, scale_meters: f64 },
     #[error("The database file is in an invalid state.")]
     InvalidStoreFile,
     #[error("Maximum database size has been reached.")]
@@ -321,6 +333,8 @@ pub enum GeoError {
     BadLatitude { document_id: Value, value: Value },
     #[error("Could not parse longitude in the document with the id: `{document_id}`. Was expecting a finite number but instead got `{value}`.")]
     BadLongitude { document_id: Value, value: Value },
+    #[error("Could not parse altitude in the document with the id: `{document_id}`. Was expecting a finite number but instead got `{value}`.")]
+    BadAltitude { document_id: Value, value: Value },
 }
 
 fn format_invalid_filter_distribution(
@@ -209,6 +209,30 @@ mod tests {
         assert!(roaring_size > bo_size);
     }
 
+    #[test]
+    fn intersection_with_serialized_matches_full_decode() {
+        let universe = RoaringBitmap::from_iter([3, 42, 70_000, 999_999, 2_000_000]);
+
+        let inputs = [
+            // encoded with byteorder
+            RoaringBitmap::from_iter([1, 3, 70_000]),
+            // encoded with roaring, covering most of the universe
+            RoaringBitmap::from_iter(0..1_000_000),
+            // encoded with roaring, disjoint from the universe
+            RoaringBitmap::from_iter(100..200),
+        ];
+
+        for input in inputs {
+            let mut bytes = Vec::new();
+            CboRoaringBitmapCodec::serialize_into(&input, &mut bytes);
+
+            let full = CboRoaringBitmapCodec::deserialize_from(&bytes).unwrap() & &universe;
+            let partial =
+                CboRoaringBitmapCodec::intersection_with_serialized(&bytes, &universe).unwrap();
+            assert_eq!(full, partial);
+        }
+    }
+
     #[test]
     fn merge_cbo_roaring_bitmaps() {
         let mut buffer = Vec::new();
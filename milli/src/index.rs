@@ -1681,27 +1681,20 @@ pub struct IndexEmbeddingConfig {
     pub user_provided: RoaringBitmap,
 }
 
-#[cfg(test)]
-pub(crate) mod tests {
-    use std::collections::HashSet;
+/// The temporary index the tests and the benchmarks of the ranking rules are built on.
+#[cfg(any(test, feature = "bench-harness"))]
+pub mod temp_index {
     use std::ops::Deref;
 
-    use big_s::S;
-    use heed::{EnvOpenOptions, RwTxn};
-    use maplit::{btreemap, hashset};
+    use heed::{EnvOpenOptions, RoTxn, RwTxn};
     use tempfile::TempDir;
 
     use crate::documents::DocumentsBatchReader;
-    use crate::error::{Error, InternalError};
-    use crate::index::{DEFAULT_MIN_WORD_LEN_ONE_TYPO, DEFAULT_MIN_WORD_LEN_TWO_TYPOS};
-    use crate::update::{
-        self, IndexDocuments, IndexDocumentsConfig, IndexDocumentsMethod, IndexerConfig, Setting,
-        Settings,
-    };
-    use crate::vector::settings::{EmbedderSource, EmbeddingSettings};
-    use crate::{db_snap, obkv_to_json, Filter, Index, Search, SearchResult};
+    use crate::update::{self, IndexDocuments, IndexDocumentsConfig, IndexerConfig, Settings};
+    use crate::Index;
 
-    pub(crate) struct TempIndex {
+    /// An index in a temporary directory, removed when it is dropped.
+    pub struct TempIndex {
         pub inner: Index,
         pub indexer_config: IndexerConfig,
         pub index_documents_config: IndexDocumentsConfig,
@@ -1819,6 +1812,43 @@ pub(crate) mod tests {
         }
     }
 
+    /// Returns the values of the given field of the documents, as JSON, or
+    /// `__does_not_exist__` for the documents without the field.
+    pub fn collect_field_values(
+        index: &Index,
+        txn: &RoTxn<'_>,
+        fid: &str,
+        docids: &[u32],
+    ) -> Vec<String> {
+        let mut values = vec![];
+        let fid = index.fields_ids_map(txn).unwrap().id(fid).unwrap();
+        for doc in index.documents(txn, docids.iter().copied()).unwrap() {
+            if let Some(v) = doc.1.get(fid) {
+                let v: serde_json::Value = serde_json::from_slice(v).unwrap();
+                let v = v.to_string();
+                values.push(v);
+            } else {
+                values.push("__does_not_exist__".to_owned());
+            }
+        }
+        values
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::collections::HashSet;
+
+    use big_s::S;
+    use maplit::{btreemap, hashset};
+
+    pub(crate) use super::temp_index::TempIndex;
+    use crate::error::{Error, InternalError};
+    use crate::index::{DEFAULT_MIN_WORD_LEN_ONE_TYPO, DEFAULT_MIN_WORD_LEN_TWO_TYPOS};
+    use crate::update::{IndexDocuments, IndexDocumentsMethod, Setting};
+    use crate::vector::settings::{EmbedderSource, EmbeddingSettings};
+    use crate::{db_snap, obkv_to_json, Filter, Search, SearchResult};
+
     #[test]
     fn aborting_indexation() {
         use std::sync::atomic::AtomicBool;
@@ -2669,7 +2699,16 @@ pub(crate) mod tests {
             document_scores: _,
             mut documents_ids,
             degraded: _,
+            degradation_reasons: _,
             used_negative_operator: _,
+            has_more: _,
+            filter_funnel: _,
+            below_threshold: _,
+            page_boundary: _,
+            geo_distance_buckets: _,
+            query_stats: _,
+            execution_trace: _,
+            next_cursor: _,
         } = search.execute().unwrap();
         let primary_key_id = index.fields_ids_map(&rtxn).unwrap().id("primary_key").unwrap();
         documents_ids.sort_unstable();
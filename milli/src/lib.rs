@@ -40,16 +40,28 @@ use charabia::normalizer::{CharNormalizer, CompatibilityDecompositionNormalizer}
 pub use filter_parser::{Condition, FilterCondition, Span, Token};
 use fxhash::{FxHasher32, FxHasher64};
 pub use grenad::CompressionType;
+#[cfg(feature = "bench-harness")]
+pub use index::temp_index::{collect_field_values, TempIndex};
+#[cfg(feature = "search-capture")]
+pub use search::new::DatabaseCacheCapture;
+#[cfg(feature = "bench-harness")]
+pub use search::new::RankingRuleBench;
 pub use search::new::{
-    execute_search, filtered_universe, DefaultSearchLogger, GeoSortStrategy, SearchContext,
-    SearchLogger, VisualSearchLogger,
+    any_exact_word_docids, execute_search, filtered_universe, BinarySearchEvent,
+    BinarySearchLogger, BucketKey, DedupInterner, DefaultSearchLogger, DegradationReason,
+    GeoDecay, GeoDistanceBuckets, GeoScore, GeoSortStrategy, Interned, InternerPool,
+    NormalizerConfig, PageBoundary, QueryStats, ResumePoint, RuleStep, SearchContext,
+    SearchLogger, SharedDatabaseCache, VisualSearchLogger, WordBloomFilter,
 };
 use serde_json::Value;
 pub use thread_pool_no_abort::{PanicCatched, ThreadPoolNoAbort, ThreadPoolNoAbortBuilder};
 pub use {charabia as tokenizer, heed, rhai};
 
 pub use self::asc_desc::{AscDesc, AscDescError, Member, SortError};
-pub use self::criterion::{default_criteria, Criterion, CriterionError};
+pub use self::criterion::{
+    analyze_criteria, default_criteria, CriteriaWarning, Criterion, CriterionError,
+    RankingRuleKind,
+};
 pub use self::error::{
     Error, FieldIdMapMissingEntry, InternalError, SerializationError, UserError,
 };
@@ -66,10 +78,14 @@ pub use self::index::Index;
 pub use self::localized_attributes_rules::LocalizedAttributesRule;
 use self::localized_attributes_rules::LocalizedFieldIds;
 pub use self::search::facet::{FacetValueHit, SearchForFacetValues};
+pub use self::search::federated::{FederatedSearch, IndexTag};
+pub use self::search::multi::MultiSearch;
 pub use self::search::similar::Similar;
 pub use self::search::{
-    FacetDistribution, Filter, FormatOptions, MatchBounds, MatcherBuilder, MatchingWords, OrderBy,
-    Search, SearchResult, SemanticSearch, TermsMatchingStrategy, DEFAULT_VALUES_PER_FACET,
+    compare_results, FacetCounts, FacetDistribution, FieldExtent, Filter, FilterBuilder,
+    FormatOptions, MatchBounds, MatchPosition, MatcherBuilder, MatchingWords, OrderBy, ResultDiff,
+    Search, SearchResult, SearchSpec, SemanticSearch, TermsMatchingStrategy,
+    DEFAULT_VALUES_PER_FACET,
 };
 
 pub type Result<T> = std::result::Result<T, error::Error>;
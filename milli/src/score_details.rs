@@ -3,13 +3,16 @@ use std::cmp::Ordering;
 use itertools::Itertools;
 use serde::Serialize;
 
-use crate::distance_between_two_points;
+use crate::{distance_between_two_points, Member};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ScoreDetails {
     Words(Words),
     Typo(Typo),
     Proximity(Rank),
+    /// The proximity of the words of the query weighted by the fields they are in, always
+    /// following the proximity rule.
+    ProximityFields(Rank),
     Fid(Rank),
     Position(Rank),
     ExactAttribute(ExactAttribute),
@@ -46,6 +49,7 @@ impl ScoreDetails {
             ScoreDetails::Words(details) => Some(details.rank()),
             ScoreDetails::Typo(details) => Some(details.rank()),
             ScoreDetails::Proximity(details) => Some(*details),
+            ScoreDetails::ProximityFields(details) => Some(*details),
             ScoreDetails::Fid(details) => Some(*details),
             ScoreDetails::Position(details) => Some(*details),
             ScoreDetails::ExactAttribute(details) => Some(details.rank()),
@@ -92,6 +96,7 @@ impl ScoreDetails {
             ScoreDetails::Words(w) => RankOrValue::Rank(w.rank()),
             ScoreDetails::Typo(t) => RankOrValue::Rank(t.rank()),
             ScoreDetails::Proximity(p) => RankOrValue::Rank(*p),
+            ScoreDetails::ProximityFields(p) => RankOrValue::Rank(*p),
             ScoreDetails::Fid(f) => RankOrValue::Rank(*f),
             ScoreDetails::Position(p) => RankOrValue::Rank(*p),
             ScoreDetails::ExactAttribute(e) => RankOrValue::Rank(e.rank()),
@@ -114,6 +119,7 @@ impl ScoreDetails {
     ) -> serde_json::Map<String, serde_json::Value> {
         let mut order = 0;
         let mut fid_details = None;
+        let mut proximity_rank = None;
         let mut details_map = serde_json::Map::default();
         for details in details {
             match details {
@@ -138,6 +144,8 @@ impl ScoreDetails {
                     order += 1;
                 }
                 ScoreDetails::Proximity(proximity) => {
+                    // copy the rank for future use in ProximityFields.
+                    proximity_rank = Some(*proximity);
                     let proximity_details = serde_json::json!({
                         "order": order,
                         "score": proximity.local_score(),
@@ -145,6 +153,36 @@ impl ScoreDetails {
                     details_map.insert("proximity".into(), proximity_details);
                     order += 1;
                 }
+                ScoreDetails::ProximityFields(proximity_fields) => {
+                    // For now, proximity fields is a virtual rule always preceded by the
+                    // "proximity" rule, whose details it completes
+                    let proximity_details = details_map
+                        .get_mut("proximity")
+                        .and_then(|details| details.as_object_mut());
+                    match (proximity_details, proximity_rank) {
+                        (Some(proximity_details), Some(proximity)) => {
+                            proximity_details.remove("score");
+                            proximity_details.insert(
+                                "fieldWeightScore".into(),
+                                proximity_fields.local_score().into(),
+                            );
+                            let score =
+                                Rank::global_score([proximity, *proximity_fields].iter().copied());
+                            proximity_details.insert("score".into(), score.into());
+
+                            // do not update the order since this was already done by proximity
+                        }
+                        _ => {
+                            let proximity_fields_details = serde_json::json!({
+                                "order": order,
+                                "fieldWeightScore": proximity_fields.local_score(),
+                                "score": proximity_fields.local_score(),
+                            });
+                            details_map.insert("proximityFields".into(), proximity_fields_details);
+                            order += 1;
+                        }
+                    }
+                }
                 ScoreDetails::Fid(fid) => {
                     // copy the rank for future use in Position.
                     fid_details = Some(*fid);
@@ -229,16 +267,23 @@ impl ScoreDetails {
                     order += 1;
                 }
                 ScoreDetails::GeoSort(details) => {
-                    let sort = format!(
-                        "_geoPoint({}, {}):{}",
-                        details.target_point[0],
-                        details.target_point[1],
-                        if details.ascending { "asc" } else { "desc" }
-                    );
-                    let point = if let Some(value) = details.value {
-                        serde_json::json!({ "lat": value[0], "lng": value[1]})
-                    } else {
-                        serde_json::Value::Null
+                    let target = match (&details.target_shape, details.target_altitude) {
+                        (Some(shape), _) => shape.clone(),
+                        (None, Some(alt)) => {
+                            Member::Geo3D([details.target_point[0], details.target_point[1], alt])
+                        }
+                        (None, None) => Member::Geo(details.target_point),
+                    };
+                    let sort =
+                        format!("{target}:{}", if details.ascending { "asc" } else { "desc" });
+                    let point = match (details.value, details.altitude) {
+                        (Some(value), Some(alt)) => {
+                            serde_json::json!({ "lat": value[0], "lng": value[1], "alt": alt })
+                        }
+                        (Some(value), None) => {
+                            serde_json::json!({ "lat": value[0], "lng": value[1]})
+                        }
+                        (None, _) => serde_json::Value::Null,
                     };
                     let sort_details = serde_json::json!({
                         "order": order,
@@ -454,11 +499,16 @@ impl PartialOrd for Sort {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct GeoSort {
     pub target_point: [f64; 2],
+    /// The route or polygon of the sort, if any, `target_point` being then its point
+    /// nearest to the document.
+    pub target_shape: Option<Member>,
     pub ascending: bool,
     pub value: Option<[f64; 2]>,
+    pub target_altitude: Option<f64>,
+    pub altitude: Option<f64>,
 }
 
 impl PartialOrd for GeoSort {
@@ -490,6 +540,15 @@ pub struct Vector {
 
 impl GeoSort {
     pub fn distance(&self) -> Option<f64> {
-        self.value.map(|value| distance_between_two_points(&self.target_point, &value))
+        self.value.map(|value| {
+            let distance = distance_between_two_points(&self.target_point, &value);
+            // the documents without an altitude are only compared on the horizontal plane
+            match (self.target_altitude, self.altitude) {
+                (Some(target_altitude), Some(altitude)) => {
+                    distance.hypot(target_altitude - altitude)
+                }
+                _ => distance,
+            }
+        })
     }
 }
@@ -0,0 +1,468 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use charabia::Language;
+use fxhash::FxHasher64;
+use serde::{Deserialize, Serialize};
+
+use super::new::{
+    GeoDecay, GeoScore, GeoSortStrategy, NormalizerConfig, ResumePoint, SharedDatabaseCache,
+};
+use super::{SemanticSearch, TermsMatchingStrategy};
+use crate::{
+    AscDesc, Criterion, Filter, Member, RankingRuleKind, Result, Search, SearchResult, UserError,
+};
+
+/// The number of documents ranked before and after the former position of the last document
+/// of the previous page in which it is looked for, when the documents are skipped by their
+/// number.
+const RESUME_WINDOW: usize = 20;
+
+/// The position of the last document of a page, to resume the search after it.
+///
+/// It is encoded as a base64 JSON object in [`SearchResult::next_cursor`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchCursor {
+    /// A hash of the parameters changing the order of the documents, the cursor is only
+    /// valid for the search it was built by.
+    signature: u64,
+    /// The number of documents returned before the cursor, to resume the searches reordering
+    /// the documents after the ranking rules.
+    offset: usize,
+    /// The last returned document with its sort values, geo distance, and the ranks of its
+    /// buckets of the ranking rules.
+    resume_after: ResumePoint,
+}
+
+impl SearchCursor {
+    fn encode(&self) -> String {
+        // a struct of plain values always serializes
+        let json = serde_json::to_vec(self).unwrap();
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    fn decode(cursor: &str) -> Result<Self> {
+        let invalid = || UserError::InvalidSearchCursor(format!("`{cursor}` is not a cursor"));
+        let json = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+        Ok(serde_json::from_slice(&json).map_err(|_| invalid())?)
+    }
+}
+
+impl<'a> Search<'a> {
+    /// Resume the search after the last document of the page that returned the given
+    /// [`SearchResult::next_cursor`], instead of skipping [`Search::offset`] documents.
+    ///
+    /// The cursor is decoded when the search is executed, it must come from a search with the
+    /// same parameters, except for the limit, and on an index with the same ranking rules.
+    ///
+    /// With a [`Search::geo_score`] or a [`Search::diversify_by`], the last document of the
+    /// previous page is looked for among the documents ranked around its former position. When
+    /// it was deleted or moved farther, the documents are skipped by their number, and the
+    /// documents added or deleted before the cursor can return a document again or skip one.
+    pub fn from_cursor(&mut self, cursor: &str) -> &mut Search<'a> {
+        self.cursor = Some(cursor.to_string());
+        self
+    }
+
+    /// Return, in the results, the cursor of the next page in [`SearchResult::next_cursor`].
+    pub fn next_cursor(&mut self, enabled: bool) -> &mut Search<'a> {
+        self.next_cursor = enabled;
+        self
+    }
+
+    /// A hash of the parameters of the search changing the order of the documents.
+    fn cursor_signature(&self) -> Result<u64> {
+        let Search {
+            query,
+            filter,
+            offset: _,
+            limit: _,
+            sort_criteria,
+            distinct,
+            searchable_attributes,
+            geo_strategy,
+            terms_matching_strategy,
+            scoring_strategy: _,
+            words_limit,
+            exhaustive_number_hits: _,
+            rtxn: _,
+            index,
+            semantic,
+            time_budget: _,
+            ranking_score_threshold,
+            locales,
+            ordered_proximity,
+            external_sorts,
+            diversify_by,
+            has_more: _,
+            parallelism: _,
+            query_synonyms,
+            skip_ranking_when_under,
+            proximity_ignores_stop_words,
+            word_filter: _,
+            geo_fields,
+            filter_funnel: _,
+            geo_distance_quantum,
+            phrase_slop,
+            page_boundary: _,
+            normalizer,
+            any_exact,
+            max_total_typos,
+            word_window,
+            max_ranked_candidates,
+            geo_distance_buckets,
+            cancel_token: _,
+            proximity_field_weights,
+            disabled_ranking_rules,
+            geo_score,
+            pinned,
+            bucket_limits,
+            query_stats: _,
+            min_word_match_ratio,
+            keep_duplicate_tokens,
+            execution_trace: _,
+            min_prefix_len,
+            cursor: _,
+            next_cursor: _,
+            invert_proximity,
+            proximity_rarest_first: _,
+            max_values_per_facet: _,
+        } = self;
+
+        let criteria = index.criteria(self.rtxn)?;
+        let parameters: &[&dyn SignatureHash] = &[
+            &criteria,
+            query,
+            filter,
+            sort_criteria,
+            distinct,
+            searchable_attributes,
+            geo_strategy,
+            terms_matching_strategy,
+            words_limit,
+            semantic,
+            ranking_score_threshold,
+            locales,
+            ordered_proximity,
+            external_sorts,
+            diversify_by,
+            query_synonyms,
+            skip_ranking_when_under,
+            proximity_ignores_stop_words,
+            geo_fields,
+            geo_distance_quantum,
+            phrase_slop,
+            normalizer,
+            any_exact,
+            max_total_typos,
+            word_window,
+            max_ranked_candidates,
+            geo_distance_buckets,
+            proximity_field_weights,
+            disabled_ranking_rules,
+            geo_score,
+            pinned,
+            bucket_limits,
+            min_word_match_ratio,
+            keep_duplicate_tokens,
+            min_prefix_len,
+            invert_proximity,
+        ];
+        let mut state = FxHasher64::default();
+        for parameter in parameters {
+            parameter.signature_hash(&mut state);
+        }
+        Ok(state.finish())
+    }
+
+    /// Execute the search from the page following the cursor set by [`Search::from_cursor`],
+    /// or from the offset when there is none, and fill [`SearchResult::next_cursor`] when
+    /// [`Search::next_cursor`] is enabled.
+    pub(super) fn execute_with_cursor(
+        &self,
+        shared_cache: Option<&mut SharedDatabaseCache<'a>>,
+    ) -> Result<SearchResult> {
+        let Some(cursor) = &self.cursor else {
+            let mut result = self.execute_page(shared_cache, None, self.offset, self.limit)?;
+            result.next_cursor = self.build_next_cursor(self.offset, &result)?;
+            return Ok(result);
+        };
+
+        let cursor = SearchCursor::decode(cursor)?;
+        if cursor.signature != self.cursor_signature()? {
+            return Err(UserError::InvalidSearchCursor(
+                "the cursor was built by a search with other parameters".to_string(),
+            )
+            .into());
+        }
+
+        // the bucket sort skips the buckets before the last document of the previous page,
+        // whatever the documents added or deleted before it
+        if self.geo_score.is_none() && self.diversify_by.is_none() {
+            let mut result =
+                self.execute_page(shared_cache, Some(cursor.resume_after), 0, self.limit)?;
+            result.next_cursor = self.build_next_cursor(cursor.offset, &result)?;
+            return Ok(result);
+        }
+
+        // the geo score and the diversification reorder the ranked documents, the documents
+        // returned before are skipped by their number instead. The last document of the
+        // previous page is looked for around its former position and the page resumes right
+        // after it, whatever the documents added or deleted before it
+        let start = cursor.offset.saturating_sub(RESUME_WINDOW);
+        let window = cursor.offset - start + RESUME_WINDOW;
+        let mut result = self.execute_page(shared_cache, None, start, window + self.limit)?;
+        let window = window.min(result.documents_ids.len());
+        let resume_after = result.documents_ids[..window]
+            .iter()
+            .position(|&docid| docid == cursor.resume_after.docid);
+        let skipped = match resume_after {
+            Some(position) => position + 1,
+            // the last document of the previous page was deleted or moved away
+            None => (cursor.offset - start).min(result.documents_ids.len()),
+        };
+        result.documents_ids.drain(..skipped);
+        result.document_scores.drain(..skipped);
+        result.has_more =
+            self.has_more && (result.has_more || result.documents_ids.len() > self.limit);
+        if result.documents_ids.len() > self.limit {
+            result.documents_ids.truncate(self.limit);
+            result.document_scores.truncate(self.limit);
+            // the boundary was computed for a document that was just removed
+            result.page_boundary = None;
+        }
+        result.next_cursor = self.build_next_cursor(start + skipped, &result)?;
+        Ok(result)
+    }
+
+    /// The cursor of the page following the documents of the result, which come after `offset`
+    /// documents, `None` when it isn't requested or the page isn't full.
+    fn build_next_cursor(&self, offset: usize, result: &SearchResult) -> Result<Option<String>> {
+        if !self.next_cursor || self.limit == 0 || result.documents_ids.len() < self.limit {
+            return Ok(None);
+        }
+        let (Some(&docid), Some(scores)) =
+            (result.documents_ids.last(), result.document_scores.last())
+        else {
+            return Ok(None);
+        };
+
+        let cursor = SearchCursor {
+            signature: self.cursor_signature()?,
+            offset: offset + result.documents_ids.len(),
+            resume_after: ResumePoint::new(scores, docid),
+        };
+        Ok(Some(cursor.encode()))
+    }
+}
+
+/// A parameter of the search hashed into the signature of its cursors.
+///
+/// The encoding is defined here rather than derived from the `Debug` output of the parameters,
+/// so that the signature of a cursor stays the same as long as the search doesn't change.
+trait SignatureHash {
+    fn signature_hash(&self, state: &mut FxHasher64);
+}
+
+macro_rules! signature_hash_with_hash {
+    ($($ty:ty),+) => {
+        $(
+            impl SignatureHash for $ty {
+                fn signature_hash(&self, state: &mut FxHasher64) {
+                    self.hash(state);
+                }
+            }
+        )+
+    };
+}
+
+signature_hash_with_hash!(bool, u8, u16, u32, usize, str, String, Language, RankingRuleKind);
+
+impl SignatureHash for f32 {
+    fn signature_hash(&self, state: &mut FxHasher64) {
+        self.to_bits().hash(state);
+    }
+}
+
+impl SignatureHash for f64 {
+    fn signature_hash(&self, state: &mut FxHasher64) {
+        self.to_bits().hash(state);
+    }
+}
+
+impl<T: SignatureHash> SignatureHash for Option<T> {
+    fn signature_hash(&self, state: &mut FxHasher64) {
+        match self {
+            None => 0u8.hash(state),
+            Some(value) => {
+                1u8.hash(state);
+                value.signature_hash(state);
+            }
+        }
+    }
+}
+
+impl<T: SignatureHash> SignatureHash for [T] {
+    fn signature_hash(&self, state: &mut FxHasher64) {
+        self.len().hash(state);
+        for value in self {
+            value.signature_hash(state);
+        }
+    }
+}
+
+impl<T: SignatureHash, const N: usize> SignatureHash for [T; N] {
+    fn signature_hash(&self, state: &mut FxHasher64) {
+        self.as_slice().signature_hash(state);
+    }
+}
+
+impl<T: SignatureHash> SignatureHash for Vec<T> {
+    fn signature_hash(&self, state: &mut FxHasher64) {
+        self.as_slice().signature_hash(state);
+    }
+}
+
+impl<T: SignatureHash + ToOwned + ?Sized> SignatureHash for Cow<'_, T> {
+    fn signature_hash(&self, state: &mut FxHasher64) {
+        (**self).signature_hash(state);
+    }
+}
+
+impl<T: SignatureHash + ?Sized> SignatureHash for Arc<T> {
+    fn signature_hash(&self, state: &mut FxHasher64) {
+        (**self).signature_hash(state);
+    }
+}
+
+impl<A: SignatureHash, B: SignatureHash> SignatureHash for (A, B) {
+    fn signature_hash(&self, state: &mut FxHasher64) {
+        self.0.signature_hash(state);
+        self.1.signature_hash(state);
+    }
+}
+
+impl<K: SignatureHash, V: SignatureHash, S> SignatureHash for HashMap<K, V, S> {
+    fn signature_hash(&self, state: &mut FxHasher64) {
+        // the hashes of the entries are summed so that the signature doesn't depend on the
+        // iteration order of the map
+        let entries = self.iter().fold(0u64, |sum, (key, value)| {
+            let mut entry = FxHasher64::default();
+            key.signature_hash(&mut entry);
+            value.signature_hash(&mut entry);
+            sum.wrapping_add(entry.finish())
+        });
+        self.len().hash(state);
+        entries.hash(state);
+    }
+}
+
+impl SignatureHash for Criterion {
+    fn signature_hash(&self, state: &mut FxHasher64) {
+        match self {
+            Criterion::Words => 0u8.hash(state),
+            Criterion::Typo => 1u8.hash(state),
+            Criterion::Proximity => 2u8.hash(state),
+            Criterion::Attribute => 3u8.hash(state),
+            Criterion::Sort => 4u8.hash(state),
+            Criterion::Exactness => 5u8.hash(state),
+            Criterion::Asc(field) => (6u8, field).hash(state),
+            Criterion::Desc(field) => (7u8, field).hash(state),
+        }
+    }
+}
+
+impl SignatureHash for Filter<'_> {
+    fn signature_hash(&self, state: &mut FxHasher64) {
+        self.to_string().hash(state);
+    }
+}
+
+impl SignatureHash for AscDesc {
+    fn signature_hash(&self, state: &mut FxHasher64) {
+        let (direction, member) = match self {
+            AscDesc::Asc(member) => (0u8, member),
+            AscDesc::Desc(member) => (1u8, member),
+        };
+        direction.hash(state);
+        member.signature_hash(state);
+    }
+}
+
+impl SignatureHash for Member {
+    fn signature_hash(&self, state: &mut FxHasher64) {
+        match self {
+            Member::Field(field) => (0u8, field).hash(state),
+            Member::Geo(point) => {
+                1u8.hash(state);
+                point.signature_hash(state);
+            }
+            Member::Geo3D(point) => {
+                2u8.hash(state);
+                point.signature_hash(state);
+            }
+            Member::GeoRoute(points) => {
+                3u8.hash(state);
+                points.signature_hash(state);
+            }
+            Member::GeoPolygonVertices(points) => {
+                4u8.hash(state);
+                points.signature_hash(state);
+            }
+        }
+    }
+}
+
+impl SignatureHash for GeoSortStrategy {
+    fn signature_hash(&self, state: &mut FxHasher64) {
+        match self {
+            GeoSortStrategy::AlwaysIterative(size) => (0u8, size).hash(state),
+            GeoSortStrategy::AlwaysRtree(size) => (1u8, size).hash(state),
+            GeoSortStrategy::Dynamic(size) => (2u8, size).hash(state),
+        }
+    }
+}
+
+impl SignatureHash for TermsMatchingStrategy {
+    fn signature_hash(&self, state: &mut FxHasher64) {
+        match self {
+            TermsMatchingStrategy::Last => 0u8.hash(state),
+            TermsMatchingStrategy::All => 1u8.hash(state),
+            TermsMatchingStrategy::Frequency => 2u8.hash(state),
+        }
+    }
+}
+
+impl SignatureHash for SemanticSearch {
+    fn signature_hash(&self, state: &mut FxHasher64) {
+        let SemanticSearch { vector, embedder_name, embedder: _ } = self;
+        embedder_name.signature_hash(state);
+        vector.signature_hash(state);
+    }
+}
+
+impl SignatureHash for NormalizerConfig {
+    fn signature_hash(&self, state: &mut FxHasher64) {
+        let NormalizerConfig { lowercase, fold_diacritics, separators } = self;
+        lowercase.signature_hash(state);
+        fold_diacritics.signature_hash(state);
+        separators.signature_hash(state);
+    }
+}
+
+impl SignatureHash for GeoScore {
+    fn signature_hash(&self, state: &mut FxHasher64) {
+        let GeoScore { weight, scale_meters, decay } = self;
+        weight.signature_hash(state);
+        scale_meters.signature_hash(state);
+        match decay {
+            GeoDecay::Exponential => 0u8.hash(state),
+            GeoDecay::Linear => 1u8.hash(state),
+        }
+    }
+}
@@ -114,6 +114,12 @@ impl<'a> From<Filter<'a>> for FilterCondition<'a> {
     }
 }
 
+impl<'a> Display for Filter<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.condition)
+    }
+}
+
 impl<'a> Filter<'a> {
     pub fn from_json(facets: &'a Value) -> Result<Option<Self>> {
         match facets {
@@ -221,6 +227,16 @@ impl<'a> Filter<'a> {
         Ok(Some(Self { condition }))
     }
 
+    /// Create a filter from a condition that was not parsed, rejecting it when it is too deep
+    /// like the parsed filters.
+    pub(crate) fn from_condition(condition: FilterCondition<'a>) -> Result<Self> {
+        if let Some(token) = condition.token_at_depth(MAX_FILTER_DEPTH) {
+            return Err(token.as_external_error(FilterError::TooDeep).into());
+        }
+
+        Ok(Self { condition })
+    }
+
     pub fn use_contains_operator(&self) -> Option<&Token> {
         self.condition.use_contains_operator()
     }
@@ -233,6 +249,34 @@ impl<'a> Filter<'a> {
         self.inner_evaluate(rtxn, index, &filterable_fields, None)
     }
 
+    /// Evaluate the filter like [`Self::evaluate`] and return, along with the documents, the
+    /// number of documents remaining after each of its top-level `AND` clauses, in order.
+    pub fn evaluate_funnel(
+        &self,
+        rtxn: &heed::RoTxn<'_>,
+        index: &Index,
+    ) -> Result<(RoaringBitmap, Vec<(String, u64)>)> {
+        let filterable_fields = index.filterable_fields(rtxn)?;
+        let clauses = match &self.condition {
+            FilterCondition::And(subfilters) => subfilters.as_slice(),
+            condition => std::slice::from_ref(condition),
+        };
+
+        let mut funnel = Vec::with_capacity(clauses.len());
+        let mut bitmap: Option<RoaringBitmap> = None;
+        for clause in clauses {
+            let filter: Filter = clause.clone().into();
+            let docids = filter.inner_evaluate(rtxn, index, &filterable_fields, bitmap.as_ref())?;
+            let docids = match bitmap {
+                Some(bitmap) => bitmap & docids,
+                None => docids,
+            };
+            funnel.push((clause.to_string(), docids.len()));
+            bitmap = Some(docids);
+        }
+        Ok((bitmap.unwrap_or_default(), funnel))
+    }
+
     fn evaluate_operator(
         rtxn: &heed::RoTxn<'_>,
         index: &Index,
@@ -0,0 +1,173 @@
+use std::fmt::Display;
+use std::ops::Not;
+
+use filter_parser::{Condition, FilterCondition, Span, Token};
+
+use super::Filter;
+use crate::Result;
+
+/// Builds a [`Filter`] from typed conditions instead of a string of the filter syntax, which
+/// saves the callers from quoting and escaping the fields and values themselves.
+///
+/// A built filter selects the same documents as the equivalent parsed filter, e.g.
+/// `FilterBuilder::eq("genre", "horror").and(FilterBuilder::range("price", 10, 20))` selects
+/// the documents of `genre = horror AND price 10 TO 20`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterBuilder {
+    condition: FilterCondition<'static>,
+}
+
+impl FilterBuilder {
+    /// Select the documents whose field is equal to the value, like `field = value`.
+    pub fn eq(field: impl Into<String>, value: impl Display) -> Self {
+        let op = Condition::Equal(token(value.to_string()));
+        Self { condition: FilterCondition::Condition { fid: token(field.into()), op } }
+    }
+
+    /// Select the documents whose field is between the two values, included, like
+    /// `field from TO to`.
+    pub fn range(field: impl Into<String>, from: impl Display, to: impl Display) -> Self {
+        let op = Condition::Between { from: token(from.to_string()), to: token(to.to_string()) };
+        Self { condition: FilterCondition::Condition { fid: token(field.into()), op } }
+    }
+
+    /// Select the documents whose `_geo` point is at most `meters` away from the `[lat, lng]`
+    /// center, like `_geoRadius(lat, lng, meters)`.
+    pub fn geo_radius(center: [f64; 2], meters: f64) -> Self {
+        let [lat, lng] = center;
+        let point = [token(lat.to_string()), token(lng.to_string())];
+        Self {
+            condition: FilterCondition::GeoLowerThan { point, radius: token(meters.to_string()) },
+        }
+    }
+
+    /// Select the documents selected by both filters, like `self AND other`.
+    pub fn and(self, other: Self) -> Self {
+        let mut conditions = match self.condition {
+            FilterCondition::And(conditions) => conditions,
+            condition => vec![condition],
+        };
+        match other.condition {
+            FilterCondition::And(others) => conditions.extend(others),
+            condition => conditions.push(condition),
+        }
+        Self { condition: FilterCondition::And(conditions) }
+    }
+
+    /// Select the documents selected by any of the filters, like `self OR other`.
+    pub fn or(self, other: Self) -> Self {
+        let mut conditions = match self.condition {
+            FilterCondition::Or(conditions) => conditions,
+            condition => vec![condition],
+        };
+        match other.condition {
+            FilterCondition::Or(others) => conditions.extend(others),
+            condition => conditions.push(condition),
+        }
+        Self { condition: FilterCondition::Or(conditions) }
+    }
+
+    /// Return the filter, which is too deep to be evaluated when it nests too many conditions.
+    pub fn build(self) -> Result<Filter<'static>> {
+        Filter::from_condition(self.condition)
+    }
+}
+
+impl Not for FilterBuilder {
+    type Output = Self;
+
+    /// Select the documents not selected by the filter, like `NOT self`.
+    fn not(self) -> Self {
+        Self { condition: FilterCondition::Not(Box::new(self.condition)) }
+    }
+}
+
+/// A token that doesn't come from a parsed filter, only its value is set.
+fn token(value: String) -> Token<'static> {
+    Token::new(Span::new_extra("", ""), Some(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use big_s::S;
+    use maplit::hashset;
+
+    use super::FilterBuilder;
+    use crate::index::tests::TempIndex;
+    use crate::Filter;
+
+    fn create_index() -> TempIndex {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_filterable_fields(hashset! { S("genre"), S("price"), S("_geo") });
+            })
+            .unwrap();
+
+        index
+            .add_documents(documents!([
+                { "id": 0, "genre": "horror", "price": 10, "_geo": { "lat": 45.4777599, "lng": 9.1967508 } },
+                { "id": 1, "genre": "horror", "price": 25.5, "_geo": { "lat": 45.4632046, "lng": 9.1719421 } },
+                { "id": 2, "genre": "sci-fi", "price": 15, "_geo": { "lat": 48.8566, "lng": 2.3522 } },
+                { "id": 3, "genre": "it's \"quoted\"", "price": 20 },
+                { "id": 4, "genre": "comedy" },
+            ]))
+            .unwrap();
+        index
+    }
+
+    #[track_caller]
+    fn assert_same_documents(index: &TempIndex, builder: FilterBuilder, expression: &str) {
+        let rtxn = index.read_txn().unwrap();
+        let built = builder.build().unwrap().evaluate(&rtxn, index).unwrap();
+        let parsed = Filter::from_str(expression).unwrap().unwrap().evaluate(&rtxn, index).unwrap();
+        assert_eq!(built, parsed, "{expression}");
+    }
+
+    #[test]
+    fn same_documents_as_the_parsed_filters() {
+        let index = create_index();
+
+        assert_same_documents(&index, FilterBuilder::eq("genre", "horror"), "genre = horror");
+        assert_same_documents(&index, FilterBuilder::eq("price", 25.5), "price = 25.5");
+        assert_same_documents(&index, FilterBuilder::range("price", 10, 20), "price 10 TO 20");
+        assert_same_documents(
+            &index,
+            FilterBuilder::geo_radius([45.4777599, 9.1967508], 2000.),
+            "_geoRadius(45.4777599, 9.1967508, 2000)",
+        );
+        assert_same_documents(
+            &index,
+            FilterBuilder::eq("genre", "horror").and(!FilterBuilder::eq("price", 10)),
+            "genre = horror AND NOT price = 10",
+        );
+        assert_same_documents(
+            &index,
+            FilterBuilder::eq("genre", "comedy")
+                .or(FilterBuilder::range("price", 12, 22).and(FilterBuilder::eq("genre", "sci-fi"))),
+            r#"genre = comedy OR (price 12 TO 22 AND genre = "sci-fi")"#,
+        );
+    }
+
+    #[test]
+    fn values_are_not_escaped() {
+        let index = create_index();
+        let rtxn = index.read_txn().unwrap();
+
+        let filter = FilterBuilder::eq("genre", r#"it's "quoted""#).build().unwrap();
+        let documents = filter.evaluate(&rtxn, &index).unwrap();
+        assert_eq!(documents.into_iter().collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[test]
+    fn combinators_are_flattened() {
+        let a = || FilterBuilder::eq("genre", "horror");
+        let b = || FilterBuilder::range("price", 10, 20);
+        let c = || FilterBuilder::eq("genre", "comedy");
+
+        assert_eq!(a().and(b()).and(c()), a().and(b().and(c())));
+        assert_eq!(a().or(b()).or(c()), a().or(b().or(c())));
+        assert_ne!(a().and(b()).or(c()), a().and(b().or(c())));
+    }
+}
@@ -0,0 +1,85 @@
+use std::cmp::Ordering;
+
+use crate::score_details::{ScoreDetails, ScoringStrategy};
+use crate::{DocumentId, Index, Result, Search, SearchResult};
+
+/// Identifies the index a document of the results of a [`FederatedSearch`] comes from:
+/// the position of the index in the order it was added to the search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IndexTag(pub usize);
+
+/// A search of the same query over several indexes, whose results are merged into
+/// a single ranked list.
+///
+/// Every index ranks its documents with its own settings, and the documents are then
+/// merged by their normalized ranking score, which is comparable across indexes.
+pub struct FederatedSearch<'a> {
+    query: Option<String>,
+    offset: usize,
+    limit: usize,
+    indexes: Vec<(&'a Index, &'a heed::RoTxn<'a>)>,
+}
+
+impl<'a> FederatedSearch<'a> {
+    pub fn new() -> FederatedSearch<'a> {
+        FederatedSearch { query: None, offset: 0, limit: 20, indexes: Vec::new() }
+    }
+
+    pub fn query(&mut self, query: impl Into<String>) -> &mut FederatedSearch<'a> {
+        self.query = Some(query.into());
+        self
+    }
+
+    pub fn offset(&mut self, offset: usize) -> &mut FederatedSearch<'a> {
+        self.offset = offset;
+        self
+    }
+
+    pub fn limit(&mut self, limit: usize) -> &mut FederatedSearch<'a> {
+        self.limit = limit;
+        self
+    }
+
+    /// Add an index to search, returning the tag of its documents in the results.
+    pub fn add_index(&mut self, index: &'a Index, rtxn: &'a heed::RoTxn<'a>) -> IndexTag {
+        self.indexes.push((index, rtxn));
+        IndexTag(self.indexes.len() - 1)
+    }
+
+    /// Search every index and return the merged page of documents, with the index they
+    /// come from and their ranking score, sorted by decreasing score.
+    ///
+    /// The documents with the same score are ordered by index and then by their rank
+    /// in their own index.
+    pub fn execute(&self) -> Result<Vec<(IndexTag, DocumentId, f64)>> {
+        let mut merged = Vec::new();
+        for (position, &(index, rtxn)) in self.indexes.iter().enumerate() {
+            let mut search = Search::new(rtxn, index);
+            if let Some(query) = &self.query {
+                search.query(query);
+            }
+            // every index must return enough documents to fill the merged page on its own
+            search.offset(0);
+            search.limit(self.offset + self.limit);
+            search.scoring_strategy(ScoringStrategy::Detailed);
+
+            let SearchResult { documents_ids, document_scores, .. } = search.execute()?;
+            merged.extend(documents_ids.into_iter().zip(document_scores).map(|(docid, scores)| {
+                (IndexTag(position), docid, ScoreDetails::global_score(scores.iter()))
+            }));
+        }
+
+        // the sort is stable: the rank of the documents in their index is kept on ties
+        merged.sort_by(|(left_tag, _, left), (right_tag, _, right)| {
+            right.partial_cmp(left).unwrap_or(Ordering::Equal).then(left_tag.cmp(right_tag))
+        });
+
+        Ok(merged.into_iter().skip(self.offset).take(self.limit).collect())
+    }
+}
+
+impl Default for FederatedSearch<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
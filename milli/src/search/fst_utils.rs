@@ -116,6 +116,30 @@ impl<A: Automaton, B: Automaton> Automaton for Union<A, B> {
         UnionState(self.0.accept(&state.0, byte), self.1.accept(&state.1, byte))
     }
 }
+/// An automaton that matches when any of its component automata match.
+///
+/// Unlike [`Union`], it keeps the state of each automaton, to know which of them matched.
+#[derive(Clone, Debug)]
+pub struct UnionAll<A>(pub Vec<A>);
+
+impl<A: Automaton> Automaton for UnionAll<A> {
+    type State = Vec<A::State>;
+    fn start(&self) -> Vec<A::State> {
+        self.0.iter().map(|automaton| automaton.start()).collect()
+    }
+    fn is_match(&self, state: &Vec<A::State>) -> bool {
+        self.0.iter().zip(state).any(|(automaton, state)| automaton.is_match(state))
+    }
+    fn can_match(&self, state: &Vec<A::State>) -> bool {
+        self.0.iter().zip(state).any(|(automaton, state)| automaton.can_match(state))
+    }
+    fn will_always_match(&self, state: &Vec<A::State>) -> bool {
+        self.0.iter().zip(state).any(|(automaton, state)| automaton.will_always_match(state))
+    }
+    fn accept(&self, state: &Vec<A::State>, byte: u8) -> Vec<A::State> {
+        self.0.iter().zip(state).map(|(automaton, state)| automaton.accept(state, byte)).collect()
+    }
+}
 /// An automaton that matches when both of its component automata match.
 #[derive(Clone, Debug)]
 pub struct Intersection<A, B>(pub A, pub B);
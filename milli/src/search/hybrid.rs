@@ -5,7 +5,7 @@ use roaring::RoaringBitmap;
 
 use crate::score_details::{ScoreDetails, ScoreValue, ScoringStrategy};
 use crate::search::SemanticSearch;
-use crate::{MatchingWords, Result, Search, SearchResult};
+use crate::{DocumentId, MatchingWords, Result, Search, SearchResult};
 
 struct ScoreWithRatioResult {
     matching_words: MatchingWords,
@@ -13,6 +13,7 @@ struct ScoreWithRatioResult {
     document_scores: Vec<(u32, ScoreWithRatio)>,
     degraded: bool,
     used_negative_operator: bool,
+    has_more: bool,
 }
 
 type ScoreWithRatio = (Vec<ScoreDetails>, f32);
@@ -82,15 +83,20 @@ impl ScoreWithRatioResult {
             document_scores,
             degraded: results.degraded,
             used_negative_operator: results.used_negative_operator,
+            has_more: results.has_more,
         }
     }
 
+    /// Merge the hits of both searches by score, after the pinned documents, which lead the
+    /// keyword results and keep their order.
     #[tracing::instrument(level = "trace", skip_all, target = "search::hybrid")]
     fn merge(
         vector_results: Self,
         keyword_results: Self,
+        pinned: &[DocumentId],
         from: usize,
         length: usize,
+        has_more: bool,
     ) -> (SearchResult, u32) {
         #[derive(Clone, Copy)]
         enum ResultSource {
@@ -106,28 +112,36 @@ impl ScoreWithRatioResult {
             vector_results.document_scores.len() + keyword_results.document_scores.len(),
         );
 
-        let mut documents_seen = RoaringBitmap::new();
-        for ((docid, (main_score, _sub_score)), source) in vector_results
+        let pinned_len = keyword_results
+            .document_scores
+            .iter()
+            .take_while(|(docid, _)| pinned.contains(docid))
+            .count();
+        let mut keyword_hits = keyword_results
             .document_scores
             .into_iter()
-            .zip(std::iter::repeat(ResultSource::Semantic))
-            .merge_by(
-                keyword_results
+            .zip(std::iter::repeat(ResultSource::Keyword));
+        let pinned_hits: Vec<_> = keyword_hits.by_ref().take(pinned_len).collect();
+
+        let mut documents_seen = RoaringBitmap::new();
+        let mut merged_results = pinned_hits
+            .into_iter()
+            .chain(
+                vector_results
                     .document_scores
                     .into_iter()
-                    .zip(std::iter::repeat(ResultSource::Keyword)),
-                |((_, left), _), ((_, right), _)| {
-                    // the first value is the one with the greatest score
-                    compare_scores(left, right).is_ge()
-                },
+                    .zip(std::iter::repeat(ResultSource::Semantic))
+                    .merge_by(keyword_hits, |((_, left), _), ((_, right), _)| {
+                        // the first value is the one with the greatest score
+                        compare_scores(left, right).is_ge()
+                    }),
             )
             // remove documents we already saw
             .filter(|((docid, _), _)| documents_seen.insert(*docid))
             // start skipping **after** the filter
-            .skip(from)
-            // take **after** skipping
-            .take(length)
-        {
+            .skip(from);
+        // take **after** skipping
+        for ((docid, (main_score, _sub_score)), source) in merged_results.by_ref().take(length) {
             if let ResultSource::Semantic = source {
                 semantic_hit_count += 1;
             }
@@ -136,6 +150,11 @@ impl ScoreWithRatioResult {
             document_scores.push(main_score);
         }
 
+        let has_more = has_more
+            && (vector_results.has_more
+                || keyword_results.has_more
+                || merged_results.next().is_some());
+
         (
             SearchResult {
                 matching_words: keyword_results.matching_words,
@@ -143,8 +162,35 @@ impl ScoreWithRatioResult {
                 documents_ids,
                 document_scores,
                 degraded: vector_results.degraded | keyword_results.degraded,
+                degradation_reasons: match (
+                    vector_results.degradation_reasons,
+                    keyword_results.degradation_reasons,
+                ) {
+                    (Some(mut reasons), Some(keyword_reasons)) => {
+                        for reason in keyword_reasons {
+                            if !reasons.contains(&reason) {
+                                reasons.push(reason);
+                            }
+                        }
+                        Some(reasons)
+                    }
+                    (vector_reasons, keyword_reasons) => vector_reasons.or(keyword_reasons),
+                },
                 used_negative_operator: vector_results.used_negative_operator
                     | keyword_results.used_negative_operator,
+                has_more,
+                filter_funnel: keyword_results.filter_funnel,
+                // the same documents can be cut from both results
+                below_threshold: vector_results
+                    .below_threshold
+                    .max(keyword_results.below_threshold),
+                // the merged results don't follow the buckets of a single search
+                page_boundary: None,
+                geo_distance_buckets: None,
+                // the vector search doesn't build a query graph
+                query_stats: keyword_results.query_stats,
+                execution_trace: keyword_results.execution_trace,
+                next_cursor: None,
             },
             semantic_hit_count,
         )
@@ -163,7 +209,7 @@ impl<'a> Search<'a> {
             limit: self.limit + self.offset,
             sort_criteria: self.sort_criteria.clone(),
             distinct: self.distinct.clone(),
-            searchable_attributes: self.searchable_attributes,
+            searchable_attributes: self.searchable_attributes.clone(),
             geo_strategy: self.geo_strategy,
             terms_matching_strategy: self.terms_matching_strategy,
             scoring_strategy: ScoringStrategy::Detailed,
@@ -175,6 +221,43 @@ impl<'a> Search<'a> {
             time_budget: self.time_budget.clone(),
             ranking_score_threshold: self.ranking_score_threshold,
             locales: self.locales.clone(),
+            ordered_proximity: self.ordered_proximity,
+            external_sorts: self.external_sorts.clone(),
+            diversify_by: self.diversify_by.clone(),
+            has_more: self.has_more,
+            parallelism: self.parallelism,
+            query_synonyms: self.query_synonyms.clone(),
+            skip_ranking_when_under: self.skip_ranking_when_under,
+            proximity_ignores_stop_words: self.proximity_ignores_stop_words,
+            word_filter: self.word_filter,
+            geo_fields: self.geo_fields.clone(),
+            filter_funnel: self.filter_funnel,
+            geo_distance_quantum: self.geo_distance_quantum,
+            phrase_slop: self.phrase_slop,
+            page_boundary: self.page_boundary,
+            normalizer: self.normalizer.clone(),
+            any_exact: self.any_exact.clone(),
+            max_total_typos: self.max_total_typos,
+            word_window: self.word_window,
+            max_ranked_candidates: self.max_ranked_candidates,
+            geo_distance_buckets: self.geo_distance_buckets.clone(),
+            cancel_token: self.cancel_token.clone(),
+            proximity_field_weights: self.proximity_field_weights.clone(),
+            disabled_ranking_rules: self.disabled_ranking_rules.clone(),
+            geo_score: self.geo_score,
+            pinned: self.pinned.clone(),
+            bucket_limits: self.bucket_limits.clone(),
+            query_stats: self.query_stats,
+            min_word_match_ratio: self.min_word_match_ratio,
+            keep_duplicate_tokens: self.keep_duplicate_tokens,
+            execution_trace: self.execution_trace,
+            min_prefix_len: self.min_prefix_len,
+            // the pages of a hybrid search are cut from a single keyword search
+            cursor: None,
+            next_cursor: false,
+            invert_proximity: self.invert_proximity,
+            proximity_rarest_first: self.proximity_rarest_first,
+            max_values_per_facet: self.max_values_per_facet,
         };
 
         let semantic = search.semantic.take();
@@ -220,8 +303,14 @@ impl<'a> Search<'a> {
         let keyword_results = ScoreWithRatioResult::new(keyword_results, 1.0 - semantic_ratio);
         let vector_results = ScoreWithRatioResult::new(vector_results, semantic_ratio);
 
-        let (merge_results, semantic_hit_count) =
-            ScoreWithRatioResult::merge(vector_results, keyword_results, self.offset, self.limit);
+        let (merge_results, semantic_hit_count) = ScoreWithRatioResult::merge(
+            vector_results,
+            keyword_results,
+            &self.pinned,
+            self.offset,
+            self.limit,
+            self.has_more,
+        );
         assert!(merge_results.documents_ids.len() <= self.limit);
         Ok((merge_results, Some(semantic_hit_count)))
     }
@@ -256,7 +345,16 @@ fn return_keyword_results(
         mut documents_ids,
         mut document_scores,
         degraded,
+        degradation_reasons,
         used_negative_operator,
+        has_more,
+        filter_funnel,
+        below_threshold,
+        page_boundary,
+        geo_distance_buckets,
+        query_stats,
+        execution_trace,
+        next_cursor: _,
     }: SearchResult,
 ) -> (SearchResult, Option<u32>) {
     let (documents_ids, document_scores) = if offset >= documents_ids.len() ||
@@ -282,7 +380,17 @@ fn return_keyword_results(
             documents_ids,
             document_scores,
             degraded,
+            degradation_reasons,
             used_negative_operator,
+            has_more,
+            filter_funnel,
+            below_threshold,
+            page_boundary,
+            geo_distance_buckets,
+            query_stats,
+            execution_trace,
+            // the cursor was built for the page starting at the first document
+            next_cursor: None,
         },
         Some(0),
     )
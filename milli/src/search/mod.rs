@@ -1,4 +1,7 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 
 use charabia::Language;
@@ -6,14 +9,27 @@ use levenshtein_automata::{LevenshteinAutomatonBuilder as LevBuilder, DFA};
 use once_cell::sync::Lazy;
 use roaring::bitmap::RoaringBitmap;
 
-pub use self::facet::{FacetDistribution, Filter, OrderBy, DEFAULT_VALUES_PER_FACET};
-pub use self::new::matches::{FormatOptions, MatchBounds, MatcherBuilder, MatchingWords};
-use self::new::{execute_vector_search, PartialSearchResult};
-use crate::score_details::{ScoreDetails, ScoringStrategy};
+pub use self::facet::{
+    FacetDistribution, Filter, FilterBuilder, OrderBy, DEFAULT_VALUES_PER_FACET,
+};
+pub use self::new::matches::{
+    FormatOptions, MatchBounds, MatchPosition, MatcherBuilder, MatchingWords,
+};
+use self::new::{
+    diversify_positions, effective_query_terms, execute_vector_search, geo_distance_buckets,
+    geo_distances, minimal_matching_query, ranking_rule_descriptors, term_contributions,
+    term_proximity, DegradationReason, GeoDecay, GeoDistanceBuckets, GeoScore, LocatedQueryTerm,
+    PageBoundary, PartialSearchResult, QueryGraphCache, QueryStats, ResumePoint, RuleStep,
+    SharedDatabaseCache,
+};
+pub use self::spec::SearchSpec;
+use crate::score_details::{Rank, ScoreDetails, ScoringStrategy};
 use crate::vector::Embedder;
 use crate::{
-    execute_search, filtered_universe, AscDesc, DefaultSearchLogger, DocumentId, Error, Index,
-    Result, SearchContext, TimeBudget, UserError,
+    analyze_criteria, any_exact_word_docids, execute_search, filtered_universe, AscDesc,
+    CriteriaWarning, DefaultSearchLogger, DocumentId, Error, FieldId, Index, Member,
+    NormalizerConfig, RankingRuleKind, Result, SearchContext, TimeBudget, UserError,
+    WordBloomFilter,
 };
 
 // Building these factories is not free.
@@ -21,11 +37,15 @@ static LEVDIST0: Lazy<LevBuilder> = Lazy::new(|| LevBuilder::new(0, true));
 static LEVDIST1: Lazy<LevBuilder> = Lazy::new(|| LevBuilder::new(1, true));
 static LEVDIST2: Lazy<LevBuilder> = Lazy::new(|| LevBuilder::new(2, true));
 
+mod cursor;
 pub mod facet;
+pub mod federated;
 mod fst_utils;
 pub mod hybrid;
+pub mod multi;
 pub mod new;
 pub mod similar;
+mod spec;
 
 #[derive(Debug, Clone)]
 pub struct SemanticSearch {
@@ -42,7 +62,7 @@ pub struct Search<'a> {
     limit: usize,
     sort_criteria: Option<Vec<AscDesc>>,
     distinct: Option<String>,
-    searchable_attributes: Option<&'a [String]>,
+    searchable_attributes: Option<Cow<'a, [String]>>,
     geo_strategy: new::GeoSortStrategy,
     terms_matching_strategy: TermsMatchingStrategy,
     scoring_strategy: ScoringStrategy,
@@ -54,6 +74,42 @@ pub struct Search<'a> {
     time_budget: TimeBudget,
     ranking_score_threshold: Option<f64>,
     locales: Option<Vec<Language>>,
+    ordered_proximity: bool,
+    external_sorts: HashMap<String, Arc<HashMap<DocumentId, f64>>>,
+    diversify_by: Option<(String, usize)>,
+    has_more: bool,
+    parallelism: usize,
+    query_synonyms: HashMap<String, Vec<String>>,
+    skip_ranking_when_under: Option<usize>,
+    proximity_ignores_stop_words: bool,
+    word_filter: Option<&'a WordBloomFilter>,
+    geo_fields: Option<(String, String)>,
+    filter_funnel: bool,
+    geo_distance_quantum: f64,
+    phrase_slop: u8,
+    page_boundary: bool,
+    normalizer: Option<NormalizerConfig>,
+    any_exact: Option<Vec<String>>,
+    max_total_typos: Option<u8>,
+    word_window: Option<u16>,
+    max_ranked_candidates: Option<usize>,
+    geo_distance_buckets: Option<Vec<f64>>,
+    cancel_token: Option<Arc<AtomicBool>>,
+    proximity_field_weights: Option<HashMap<String, f64>>,
+    disabled_ranking_rules: Vec<RankingRuleKind>,
+    geo_score: Option<GeoScore>,
+    pinned: Vec<DocumentId>,
+    bucket_limits: HashMap<String, usize>,
+    query_stats: bool,
+    min_word_match_ratio: Option<f64>,
+    keep_duplicate_tokens: bool,
+    execution_trace: bool,
+    min_prefix_len: Option<u8>,
+    cursor: Option<String>,
+    next_cursor: bool,
+    invert_proximity: bool,
+    proximity_rarest_first: bool,
+    max_values_per_facet: usize,
 }
 
 impl<'a> Search<'a> {
@@ -77,6 +133,42 @@ impl<'a> Search<'a> {
             locales: None,
             time_budget: TimeBudget::max(),
             ranking_score_threshold: None,
+            ordered_proximity: true,
+            external_sorts: HashMap::new(),
+            diversify_by: None,
+            has_more: false,
+            parallelism: 1,
+            query_synonyms: HashMap::new(),
+            skip_ranking_when_under: None,
+            proximity_ignores_stop_words: false,
+            word_filter: None,
+            geo_fields: None,
+            filter_funnel: false,
+            geo_distance_quantum: 0.0,
+            phrase_slop: 0,
+            page_boundary: false,
+            normalizer: None,
+            any_exact: None,
+            max_total_typos: None,
+            word_window: None,
+            max_ranked_candidates: None,
+            geo_distance_buckets: None,
+            cancel_token: None,
+            proximity_field_weights: None,
+            disabled_ranking_rules: Vec::new(),
+            geo_score: None,
+            pinned: Vec::new(),
+            bucket_limits: HashMap::new(),
+            query_stats: false,
+            min_word_match_ratio: None,
+            keep_duplicate_tokens: false,
+            execution_trace: false,
+            min_prefix_len: None,
+            cursor: None,
+            next_cursor: false,
+            invert_proximity: false,
+            proximity_rarest_first: false,
+            max_values_per_facet: DEFAULT_VALUES_PER_FACET,
         }
     }
 
@@ -110,13 +202,36 @@ impl<'a> Search<'a> {
         self
     }
 
+    /// Sort the documents by the values of the given map instead of the values of an indexed facet.
+    ///
+    /// The name of the `order` member identifies this sort key: the criterion is appended to the
+    /// sort criteria but can also be placed anywhere in the list given to [`Self::sort_criteria`].
+    /// The documents missing from the map are placed at the end of their bucket.
+    pub fn sort_by_external(
+        &mut self,
+        key: HashMap<DocumentId, f64>,
+        order: AscDesc,
+    ) -> &mut Search<'a> {
+        self.external_sorts.insert(order.member().to_string(), Arc::new(key));
+        self.sort_criteria.get_or_insert_with(Vec::new).push(order);
+        self
+    }
+
     pub fn distinct(&mut self, distinct: String) -> &mut Search<'a> {
         self.distinct = Some(distinct);
         self
     }
 
     pub fn searchable_attributes(&mut self, searchable: &'a [String]) -> &mut Search<'a> {
-        self.searchable_attributes = Some(searchable);
+        self.searchable_attributes = Some(Cow::Borrowed(searchable));
+        self
+    }
+
+    /// Restrict the search to the given fields, overriding the searchable attributes of the index.
+    ///
+    /// Query words that do not appear in any of these fields do not match any document.
+    pub fn searchable_fields(&mut self, fields: Vec<String>) -> &mut Search<'a> {
+        self.searchable_attributes = Some(Cow::Owned(fields));
         self
     }
 
@@ -168,6 +283,196 @@ impl<'a> Search<'a> {
         self
     }
 
+    /// Whether the proximity ranking rule takes the order of the query words into account.
+    ///
+    /// When disabled, `jean bob` is considered as close as `bob jean` to the query `bob jean`.
+    pub fn ordered_proximity(&mut self, ordered_proximity: bool) -> &mut Search<'a> {
+        self.ordered_proximity = ordered_proximity;
+        self
+    }
+
+    /// Limit the number of results sharing the same value for the given field.
+    ///
+    /// While preserving their relative ranking, the documents sharing their value with
+    /// `max_per_group` better ranked documents are moved after all the other results.
+    /// The values are read from the facet databases, so the field must be filterable or
+    /// sortable. The documents without a value for this field are never moved.
+    pub fn diversify_by(&mut self, field: String, max_per_group: usize) -> &mut Search<'a> {
+        self.diversify_by = Some((field, max_per_group));
+        self
+    }
+
+    /// Fetch one more document than the limit to tell whether other results follow this page.
+    ///
+    /// The extra document is not returned but sets [`SearchResult::has_more`], which is enough to
+    /// drive a "load more" button without requiring an exact number of candidates.
+    pub fn has_more(&mut self, has_more: bool) -> &mut Search<'a> {
+        self.has_more = has_more;
+        self
+    }
+
+    /// Shard the per-document computations of the ranking rules, like the distances of the
+    /// geo sort, into `parallelism` shards computed on the global rayon thread pool.
+    ///
+    /// The results are identical to the ones of a serial search. Disabled by default, or when
+    /// `parallelism` is `0` or `1`.
+    pub fn parallelism(&mut self, parallelism: usize) -> &mut Search<'a> {
+        self.parallelism = parallelism.max(1);
+        self
+    }
+
+    /// Expand the query with the given synonyms, in addition to the ones of the index settings.
+    ///
+    /// Each synonym of a query word, or of consecutive query words, becomes an alternative to
+    /// match like the synonyms of the settings, for this search only.
+    pub fn query_synonyms(&mut self, synonyms: HashMap<String, Vec<String>>) -> &mut Search<'a> {
+        self.query_synonyms = synonyms;
+        self
+    }
+
+    /// Don't penalize the proximity between two words of the query separated by stop words.
+    ///
+    /// With a query like "king of england", where "of" is a stop word, the documents containing
+    /// "king of england" are then as close as the ones containing "king england". The stop
+    /// words are the ones of the index settings.
+    pub fn proximity_ignores_stopwords(&mut self, ignore: bool) -> &mut Search<'a> {
+        self.proximity_ignores_stop_words = ignore;
+        self
+    }
+
+    /// Return the candidates in their internal order, without ranking them, when there are
+    /// at most `threshold` of them.
+    ///
+    /// All the candidates are cheap to return when they fit in a few pages, but their relevancy
+    /// order is lost: below the threshold the documents are neither ranked by the query nor
+    /// sorted, and they get no ranking score details. It is ignored when a ranking score
+    /// threshold is set.
+    pub fn skip_ranking_when_under(&mut self, threshold: usize) -> &mut Search<'a> {
+        self.skip_ranking_when_under = Some(threshold);
+        self
+    }
+
+    /// Skip the database lookups of the query words, typos, and prefixes that the given
+    /// filter knows are not indexed.
+    ///
+    /// The filter must be built from the current state of the index, with
+    /// [`WordBloomFilter::from_index`], and can be shared by all the searches until new
+    /// documents are indexed.
+    pub fn word_filter(&mut self, word_filter: &'a WordBloomFilter) -> &mut Search<'a> {
+        self.word_filter = Some(word_filter);
+        self
+    }
+
+    /// Read the coordinates of the documents sorted by `_geoPoint` from the given sortable
+    /// fields instead of the `_geo` object.
+    ///
+    /// Only the documents with a number in both fields are sorted, and the altitude of a 3D
+    /// geo point is ignored. The rtree is only built on `_geo`, thus the documents are always
+    /// sorted iteratively and the [`GeoSortStrategy::AlwaysRtree`](crate::GeoSortStrategy)
+    /// strategy is rejected.
+    pub fn geo_field(&mut self, lat_field: String, lng_field: String) -> &mut Search<'a> {
+        self.geo_fields = Some((lat_field, lng_field));
+        self
+    }
+
+    /// Make the geo sort group the documents into distance bands of the given width, in
+    /// meters, and leave the order of the documents of a band to the next ranking rules.
+    ///
+    /// The documents of a band share the ranking score of the closest one, `0` sorts the
+    /// documents by their exact distance.
+    pub fn geo_distance_quantum(&mut self, meters: f64) -> &mut Search<'a> {
+        self.geo_distance_quantum = meters;
+        self
+    }
+
+    /// Match the quoted phrases of the query when their words are up to `slop` positions
+    /// farther apart than in the phrase, like `"new york"` matching "new big york" with
+    /// a slop of 1.
+    ///
+    /// Like for the proximity ranking rule, two swapped words are one position farther apart,
+    /// and the proximity databases limit the distance between two words of a phrase to 3.
+    /// The documents containing the exact phrase are ranked higher by the exactness ranking
+    /// rule.
+    pub fn phrase_slop(&mut self, slop: u8) -> &mut Search<'a> {
+        self.phrase_slop = slop;
+        self
+    }
+
+    /// Inspect the ranking rules of the index, along with the sort criteria of this search,
+    /// and return the warnings about the ones that can't have the intended effect.
+    ///
+    /// The search is not executed.
+    pub fn analyze_criteria(&self) -> Result<Vec<CriteriaWarning>> {
+        let criteria = self.index.criteria(self.rtxn)?;
+        let sorting = self.sort_criteria.as_ref().is_some_and(|sort| !sort.is_empty());
+        Ok(analyze_criteria(&criteria, sorting))
+    }
+
+    /// Report, in the results, the number of documents remaining after each clause of the
+    /// filter, to find out which clauses eliminate the documents.
+    ///
+    /// Only the top-level `AND` clauses are reported, a filter made of a single clause
+    /// reports the documents matching it.
+    pub fn filter_funnel(&mut self, enabled: bool) -> &mut Search<'a> {
+        self.filter_funnel = enabled;
+        self
+    }
+
+    /// Report, in the results, the buckets of the ranking rules containing the last returned
+    /// document, so that the next page can be resumed from this boundary.
+    ///
+    /// When [`Search::has_more`] is enabled, the boundary is the one of the extra document
+    /// fetched after the page.
+    pub fn page_boundary(&mut self, enabled: bool) -> &mut Search<'a> {
+        self.page_boundary = enabled;
+        self
+    }
+
+    /// Normalize the words of the query with the given configuration instead of the
+    /// normalization of the index.
+    ///
+    /// The documents stay indexed with the normalization of the index, see
+    /// [`NormalizerConfig`] for what the query words can still match.
+    pub fn normalizer(&mut self, normalizer: NormalizerConfig) -> &mut Search<'a> {
+        self.normalizer = Some(normalizer);
+        self
+    }
+
+    /// Restrict the results to the documents containing any of the given words exactly.
+    ///
+    /// The docids of the words are read directly and unioned into the universe of the search,
+    /// without building the typo and prefix derivations of a query, which makes it much cheaper
+    /// than searching the words for tag-like matching. The words are not normalized and must
+    /// be given as they are indexed.
+    pub fn any_exact(&mut self, words: Vec<String>) -> &mut Search<'a> {
+        self.any_exact = Some(words);
+        self
+    }
+
+    /// Limit the sum of the typos of the words of the query, so that at most `max` typos are
+    /// accepted across the whole query whatever the typo tolerance of each word.
+    ///
+    /// The typo ranking rule doesn't build the edges nor explore the paths costing more typos,
+    /// and the documents only matching the query with more typos are removed from the
+    /// candidates. Without the typo ranking rule, they are removed from the universe.
+    pub fn max_total_typos(&mut self, max: u8) -> &mut Search<'a> {
+        self.max_total_typos = Some(max);
+        self
+    }
+
+    /// Only return the documents where all the words of the query are found within `positions`
+    /// positions of each other in the same attribute.
+    ///
+    /// It filters the documents instead of ranking them: the distance between the first and
+    /// the last matched words must be at most `positions`, a document matching the words far
+    /// apart is removed from the results whatever its proximity rank. The positions are read
+    /// from the position databases of the index, which bucket the positions after the 16th word
+    /// of a field, so the window is approximate for the words far in their field.
+    pub fn word_window(&mut self, positions: u16) -> &mut Search<'a> {
+        self.word_window = Some(positions);
+        self
+    }
+
     pub fn execute_for_candidates(&self, has_vector_search: bool) -> Result<RoaringBitmap> {
         if has_vector_search {
             let ctx = SearchContext::new(self.index, self.rtxn)?;
@@ -177,12 +482,533 @@ impl<'a> Search<'a> {
         }
     }
 
+    /// Computes, for each of the given facet fields, the number of documents of the
+    /// search universe containing each of its values.
+    ///
+    /// The facet values docids are read through a cache created for the call and only
+    /// intersected with the universe, values absent from the universe are not returned. All
+    /// the values are counted exactly, the numbers being keyed by their text form, and a number
+    /// and a string with the same text form are counted as the same value. Use
+    /// [`Search::facet_distribution_with_cache`] to keep the cache between the calls.
+    pub fn facet_distribution(
+        &self,
+        fields: &[String],
+    ) -> Result<HashMap<String, HashMap<String, u64>>> {
+        let mut cache = SharedDatabaseCache::default();
+        self.facet_values_in_universe(fields, &mut cache, facet_value_counts)
+    }
+
+    /// Computes, for each of the given facet fields, the number of documents of the
+    /// search universe containing each of its numbers and strings.
+    ///
+    /// The facet values docids are read through the given cache, keeping it between the
+    /// calls made on the same transaction avoids reading them again. At most
+    /// [`Search::max_values_per_facet`] values are returned by field.
+    pub fn facet_distribution_with_cache(
+        &self,
+        fields: &[String],
+        cache: &mut SharedDatabaseCache<'a>,
+    ) -> Result<HashMap<String, FacetCounts>> {
+        self.facet_values_in_universe(fields, cache, |ctx, fid, universe| {
+            facet_counts(ctx, fid, universe, self.max_values_per_facet)
+        })
+    }
+
+    /// Checks that the given fields are filterable, computes the universe of the search, and
+    /// reads the values of each field in the universe with `read_values`.
+    fn facet_values_in_universe<T>(
+        &self,
+        fields: &[String],
+        cache: &mut SharedDatabaseCache<'a>,
+        mut read_values: impl FnMut(&mut SearchContext<'a>, FieldId, &RoaringBitmap) -> Result<T>,
+    ) -> Result<HashMap<String, T>> {
+        let filterable_fields = self.index.filterable_fields(self.rtxn)?;
+        let invalid_fields: HashSet<_> =
+            fields.iter().filter(|field| !crate::is_faceted(field, &filterable_fields)).collect();
+        if !invalid_fields.is_empty() {
+            return Err(UserError::InvalidFacetsDistribution {
+                invalid_facets_name: invalid_fields.into_iter().cloned().collect(),
+                valid_facets_name: filterable_fields.into_iter().collect(),
+            }
+            .into());
+        }
+
+        let universe = self.execute_with_cache(Some(cache))?.candidates;
+
+        let fields_ids_map = self.index.fields_ids_map(self.rtxn)?;
+        let mut ctx = SearchContext::with_shared_cache(self.index, self.rtxn, cache)?;
+        let distribution = fields_ids_map
+            .iter()
+            .filter(|(_, name)| crate::is_faceted(name, fields))
+            .map(|(fid, name)| Ok((name.to_string(), read_values(&mut ctx, fid, &universe)?)))
+            .collect();
+        // the cache is given back to the caller even when reading the facets fails
+        *cache = ctx.take_shared_cache();
+
+        distribution
+    }
+
+    /// Sets the maximum number of values returned by field by
+    /// [`Search::facet_distribution_with_cache`], [`DEFAULT_VALUES_PER_FACET`] by default.
+    pub fn max_values_per_facet(&mut self, max: usize) -> &mut Search<'a> {
+        self.max_values_per_facet = max;
+        self
+    }
+
+    /// Computes the smallest and largest numbers and the distinct strings of the given
+    /// sortable field in the search universe, e.g. to build a sort dropdown or a range slider.
+    ///
+    /// The universe and the facet values docids are read through the given cache, like in
+    /// [`Search::facet_distribution_with_cache`], so the next calls made on the same transaction don't
+    /// read them again. At most [`DEFAULT_VALUES_PER_FACET`] strings are returned.
+    pub fn sortable_field_extent(
+        &self,
+        field: String,
+        cache: &mut SharedDatabaseCache<'a>,
+    ) -> Result<FieldExtent> {
+        let sortable_fields = self.index.sortable_fields(self.rtxn)?;
+        if !crate::is_faceted(&field, &sortable_fields) {
+            let (valid_fields, hidden_fields) =
+                self.index.remove_hidden_fields(self.rtxn, sortable_fields)?;
+            return Err(UserError::InvalidSortableAttribute { field, valid_fields, hidden_fields }
+                .into());
+        }
+
+        let universe = self.execute_with_cache(Some(cache))?.candidates;
+
+        let Some(fid) = self.index.fields_ids_map(self.rtxn)?.id(&field) else {
+            return Ok(FieldExtent::default());
+        };
+
+        let mut ctx = SearchContext::with_shared_cache(self.index, self.rtxn, cache)?;
+        let extent = field_extent(&mut ctx, fid, &universe);
+        // the cache is given back to the caller even when reading the facets fails
+        *cache = ctx.take_shared_cache();
+
+        extent
+    }
+
+    /// Returns, in query order, a minimal subset of the words of the query that still matches
+    /// the given document.
+    ///
+    /// The words are removed greedily, in query order, as long as the document is matched by
+    /// the remaining ones under the terms matching strategy of the search, so none of the
+    /// returned words can be removed without the document no longer matching. Returns an empty
+    /// list when the document is not part of the results of this search.
+    pub fn minimal_matching_query(&self, docid: DocumentId) -> Result<Vec<String>> {
+        let Some(query) = &self.query else { return Ok(Vec::new()) };
+        if !self.execute()?.candidates.contains(docid) {
+            return Ok(Vec::new());
+        }
+
+        let mut ctx = self.search_context()?;
+        minimal_matching_query(
+            &mut ctx,
+            query,
+            docid,
+            Some(self.words_limit),
+            self.locales.as_ref(),
+            self.terms_matching_strategy,
+        )
+    }
+
+    /// Rank at most `max` candidates with the expensive ranking rules, the proximity, typo and
+    /// iterative geo sort rules, to bound their cost on very large sets of candidates.
+    ///
+    /// This sacrifices completeness: when more documents match, only the first `max` in the
+    /// order of the ranking rules before the first expensive one, such as the words rule, are
+    /// ranked, and the others are neither returned nor counted in the candidates, whatever
+    /// their relevancy. When the first ranking rule is expensive, the `max` candidates with the
+    /// smallest internal ids are ranked. The capping is reported to the search logger.
+    pub fn max_ranked_candidates(&mut self, max: usize) -> &mut Search<'a> {
+        self.max_ranked_candidates = Some(max);
+        self
+    }
+
+    /// Returns the words that are searched for the query, in order, after it is normalized and
+    /// its stop words are removed, e.g. to show what the search is actually looking for.
+    ///
+    /// These are the words as typed once cleaned, not their typo or synonym derivations.
+    pub fn effective_query_terms(&self) -> Result<Vec<String>> {
+        let Some(query) = &self.query else { return Ok(Vec::new()) };
+        let mut ctx = self.search_context()?;
+        effective_query_terms(&mut ctx, query, Some(self.words_limit), self.locales.as_ref())
+    }
+
+    /// Returns the identifiers of the ranking rules this search applies, in order, e.g.
+    /// `words`, `price:asc` or `geo_sort`, once the sort criteria are merged into the ranking
+    /// rules of the index.
+    ///
+    /// The ranking rules of a semantic search are not described.
+    pub fn ranking_rule_descriptors(&self) -> Result<Vec<String>> {
+        let mut ctx = self.search_context()?;
+        ranking_rule_descriptors(
+            &mut ctx,
+            self.query.as_deref(),
+            Some(self.words_limit),
+            self.locales.as_ref(),
+            &self.sort_criteria,
+            self.geo_strategy,
+            self.terms_matching_strategy,
+        )
+    }
+
+    /// Group the documents sorted by distance into the distance bands between the given edges,
+    /// in meters, and report the documents of each band in [`SearchResult::geo_distance_buckets`].
+    ///
+    /// The geo sort returns the documents of a band together, ordered by the next ranking
+    /// rules, instead of ordering them by exact distance. The documents beyond the last edge
+    /// are grouped in an overflow bucket and the documents without coordinates in their own.
+    pub fn geo_distance_buckets(&mut self, mut edges_meters: Vec<f64>) -> &mut Search<'a> {
+        edges_meters.sort_by(f64::total_cmp);
+        self.geo_distance_buckets = Some(edges_meters);
+        self
+    }
+
+    /// Cancel the search when the given flag is set, e.g. when the client is disconnected.
+    ///
+    /// The flag is checked between the buckets of the ranking rules and regularly while the
+    /// geo sort reads the coordinates of the documents, the search then returns
+    /// [`Error::SearchCancelled`](crate::Error::SearchCancelled).
+    pub fn cancel_token(&mut self, token: Arc<AtomicBool>) -> &mut Search<'a> {
+        self.cancel_token = Some(token);
+        self
+    }
+
+    /// Sort the documents of each proximity bucket by the fields in which the words of the
+    /// query are close, e.g. to rank a document whose title contains the words next to each
+    /// other before one whose description does.
+    ///
+    /// The proximity of each pair of consecutive words is multiplied by the weight of the field,
+    /// the fields missing from the map weigh `1.0`. It has no effect without the proximity rule.
+    ///
+    /// Returns an error when a weight is negative, infinite or not a number.
+    pub fn proximity_field_weights(
+        &mut self,
+        weights: HashMap<String, f64>,
+    ) -> Result<&mut Search<'a>> {
+        let invalid = weights.iter().find(|(_, weight)| !(weight.is_finite() && **weight >= 0.0));
+        if let Some((field, &weight)) = invalid {
+            return Err(
+                UserError::InvalidProximityFieldWeight { field: field.clone(), weight }.into()
+            );
+        }
+        self.proximity_field_weights = Some(weights);
+        Ok(self)
+    }
+
+    /// Skip the given built-in ranking rules of the index for this search only, e.g. to speed
+    /// up a query by not computing the proximity, the other ranking rules keep their order.
+    ///
+    /// The words rule is still applied before the typo, proximity, attribute, and exactness
+    /// rules when they are kept, as they rank the documents matching the same query words.
+    /// The vector search ignores it.
+    pub fn disable_ranking_rules(&mut self, rules: &[RankingRuleKind]) -> &mut Search<'a> {
+        self.disabled_ranking_rules.extend(rules.iter().copied());
+        self
+    }
+
+    /// Order the documents by a blend of their relevancy and of their distance to the target of
+    /// the geo sort, instead of strictly following the ranking rules, e.g. to rank a document
+    /// 100 meters away with a slightly worse text match before one 5 kilometers away.
+    ///
+    /// The score of a document is `(1 - weight) * relevancy + weight * distance_score`, where the
+    /// distance score decays from `1` at the target following `decay` and `scale_meters`. The
+    /// relevancy is the global ranking score of the document. It has no effect when the
+    /// documents are not sorted by distance.
+    ///
+    /// Returns an error when the weight is not between `0` and `1`, or when the scale is not a
+    /// finite positive number.
+    pub fn geo_score(
+        &mut self,
+        weight: f64,
+        scale_meters: f64,
+        decay: GeoDecay,
+    ) -> Result<&mut Search<'a>> {
+        let valid_scale = scale_meters.is_finite() && scale_meters > 0.0;
+        if !((0.0..=1.0).contains(&weight) && valid_scale) {
+            return Err(UserError::InvalidGeoScore { weight, scale_meters }.into());
+        }
+        self.geo_score = Some(GeoScore { weight, scale_meters, decay });
+        Ok(self)
+    }
+
+    /// Return the given documents first, in this order, before the ranked documents which
+    /// follow without them.
+    ///
+    /// The documents that are not part of the universe of the search, because they are
+    /// filtered out or don't match the query, are ignored. In a hybrid search, they come
+    /// before the keyword and semantic hits merged by score.
+    pub fn pin(&mut self, ids: Vec<DocumentId>) -> &mut Search<'a> {
+        self.pinned = ids;
+        self
+    }
+
+    /// Limit the number of candidates of each bucket of the given ranking rules that are ranked
+    /// by the next ranking rules, by ranking rule id, e.g. `"words"` or `"proximity"`.
+    ///
+    /// The candidates of a bucket tie for its ranking rule, only the ones with the smallest ids
+    /// are kept and the others are not returned, which marks the search as degraded. They are
+    /// still counted in the candidates.
+    pub fn bucket_limits(&mut self, limits: HashMap<String, usize>) -> &mut Search<'a> {
+        self.bucket_limits = limits;
+        self
+    }
+
+    /// Report the number of interned words, query graph nodes, ranking rule graph edges and
+    /// cached database values of the search in [`SearchResult::query_stats`], to find out which queries
+    /// use the most memory.
+    pub fn query_stats(&mut self, enabled: bool) -> &mut Search<'a> {
+        self.query_stats = enabled;
+        self
+    }
+
+    /// Only return the documents matching at least `ceil(ratio * words)` distinct words of
+    /// the query, `words` being the number of distinct words of the query.
+    ///
+    /// It is applied on top of the terms matching strategy: with the `last` strategy, a ratio
+    /// of `0.5` keeps the documents matching at least half of the words instead of only the
+    /// first one. A typo, a prefix or a synonym of a word counts as a match of the word.
+    pub fn min_word_match_ratio(&mut self, ratio: f64) -> &mut Search<'a> {
+        self.min_word_match_ratio = Some(ratio);
+        self
+    }
+
+    /// Keep the words repeated in the query as distinct terms, `false` by default.
+    ///
+    /// By default, a word repeated in the query is merged with its first occurrence, so
+    /// that `red red shoes` is searched as `red shoes`. The words of a phrase are never merged.
+    pub fn keep_duplicate_tokens(&mut self, keep: bool) -> &mut Search<'a> {
+        self.keep_duplicate_tokens = keep;
+        self
+    }
+
+    /// Return, in [`SearchResult::execution_trace`], the ranking rules given a universe to
+    /// sort, in order, with the number of buckets they returned or whether they were skipped.
+    ///
+    /// Unlike the configured ranking rules, it shows the rules that actually ran for the query:
+    /// a rule given a single document or a bucket before the requested page is skipped.
+    pub fn execution_trace(&mut self, enabled: bool) -> &mut Search<'a> {
+        self.execution_trace = enabled;
+        self
+    }
+
+    /// Only match the last word of the query as a prefix when it is at least `len` characters
+    /// long, a shorter last word is matched as a complete word.
+    ///
+    /// A last word of a single character is a prefix of a large part of the vocabulary, it is
+    /// costly to look up and rarely what the user is looking for.
+    pub fn min_prefix_len(&mut self, len: u8) -> &mut Search<'a> {
+        self.min_prefix_len = Some(len);
+        self
+    }
+
+    /// Rank the documents whose query words are the farthest apart first with the proximity
+    /// ranking rule, to spread out the near-duplicate matches.
+    ///
+    /// The proximity buckets are the same, they are only returned in the reverse order.
+    pub fn invert_proximity(&mut self, invert: bool) -> &mut Search<'a> {
+        self.invert_proximity = invert;
+        self
+    }
+
+    /// Explore the proximity paths going through the query terms matching the fewest
+    /// documents first.
+    ///
+    /// It speeds up the queries mixing rare and common words, but counting the documents of
+    /// every term is wasted work on the other ones, so it is disabled by default.
+    pub fn proximity_rarest_first(&mut self, enabled: bool) -> &mut Search<'a> {
+        self.proximity_rarest_first = enabled;
+        self
+    }
+
+    /// Returns, for each word the terms of the query are resolved to, the number of documents
+    /// of the results of this search containing it, the words contributing the most first.
+    ///
+    /// The typo candidates, synonyms and split words of a term are counted separately, which
+    /// helps finding the derivations that pull in the most matches.
+    pub fn term_contributions(&self) -> Result<Vec<(String, u64)>> {
+        let Some(query) = &self.query else { return Ok(Vec::new()) };
+        let universe = self.execute()?.candidates;
+
+        let mut ctx = self.search_context()?;
+        term_contributions(
+            &mut ctx,
+            query,
+            &universe,
+            Some(self.words_limit),
+            self.locales.as_ref(),
+        )
+    }
+
+    /// Returns the cost of the best proximity at which the given document matches the words
+    /// of the query, `0` meaning that they are all next to each other and in order.
+    ///
+    /// Returns `None` for queries of a single word and for documents that are not part of the
+    /// results of this search or that don't contain all the words of the query.
+    pub fn term_proximity(&self, docid: DocumentId) -> Result<Option<u16>> {
+        let Some(query) = &self.query else { return Ok(None) };
+        if !self.execute()?.candidates.contains(docid) {
+            return Ok(None);
+        }
+
+        let mut ctx = self.search_context()?;
+        term_proximity(&mut ctx, query, docid, Some(self.words_limit), self.locales.as_ref())
+    }
+
     pub fn execute(&self) -> Result<SearchResult> {
+        self.execute_with_cache(None)
+    }
+
+    /// Insert the ids of the documents returned by this search into `out`, without allocating
+    /// the vector of [`SearchResult::documents_ids`].
+    ///
+    /// The order of the documents is lost, use [`Search::execute`] when it matters. When the page
+    /// is the first one, contains all the candidates and no document is removed from it by a
+    /// distinct attribute, a ranking score threshold, a diversification or a geo score, the
+    /// documents aren't ranked.
+    pub fn execute_into(&self, out: &mut RoaringBitmap) -> Result<()> {
+        let mut cache = SharedDatabaseCache::default();
+        let unranked = self.offset == 0
+            && self.cursor.is_none()
+            && self.distinct.is_none()
+            && self.ranking_score_threshold.is_none()
+            && self.diversify_by.is_none()
+            && self.geo_score.is_none()
+            && self.max_ranked_candidates.is_none()
+            && self.semantic.is_none()
+            && self.index.distinct_field(self.rtxn)?.is_none();
+        if unranked {
+            let SearchResult { candidates, degraded, .. } =
+                self.execute_page(Some(&mut cache), None, 0, 0)?;
+            if !degraded && candidates.len() <= self.limit as u64 {
+                *out |= candidates;
+                return Ok(());
+            }
+        }
+
+        let SearchResult { documents_ids, .. } = self.execute_with_cache(Some(&mut cache))?;
+        out.extend(documents_ids);
+        Ok(())
+    }
+
+    /// Execute the search, reading the databases through the given shared cache and
+    /// giving it back filled with the values read by this search.
+    ///
+    /// The cache must only be shared between searches on the same transaction.
+    pub fn execute_with_cache(
+        &self,
+        shared_cache: Option<&mut SharedDatabaseCache<'a>>,
+    ) -> Result<SearchResult> {
+        self.execute_with_cursor(shared_cache)
+    }
+
+    /// Execute the search, skipping `offset` documents after the given resume point and
+    /// returning at most `length` documents instead of the offset and limit.
+    fn execute_page(
+        &self,
+        mut shared_cache: Option<&mut SharedDatabaseCache<'a>>,
+        resume_after: Option<ResumePoint>,
+        offset: usize,
+        length: usize,
+    ) -> Result<SearchResult> {
+        let mut ctx = match shared_cache.as_deref_mut() {
+            Some(cache) => SearchContext::with_shared_cache(self.index, self.rtxn, cache)?,
+            None => SearchContext::new(self.index, self.rtxn)?,
+        };
+        let result = self.execute_in_context(&mut ctx, resume_after, offset, length);
+        match shared_cache {
+            Some(cache) => {
+                // the cache is given back to the caller even when the search fails
+                *cache = ctx.take_shared_cache();
+                let (mut result, located_query_terms) = result?;
+                if let Some(located_query_terms) = located_query_terms {
+                    // the matching words own a copy of the words and phrases kept by the cache
+                    result.matching_words = MatchingWords::from_interners(
+                        cache.word_interner.clone(),
+                        cache.phrase_interner.clone(),
+                        &cache.term_interner,
+                        located_query_terms,
+                    );
+                }
+                Ok(result)
+            }
+            None => {
+                let (mut result, located_query_terms) = result?;
+                // consume context and located_query_terms to build MatchingWords.
+                if let Some(located_query_terms) = located_query_terms {
+                    result.matching_words = MatchingWords::new(ctx, located_query_terms);
+                }
+                Ok(result)
+            }
+        }
+    }
+
+    /// Create a search context configured with the options of this search, for the methods
+    /// analyzing the query or the results outside of the execution of the search.
+    fn search_context(&self) -> Result<SearchContext<'a>> {
         let mut ctx = SearchContext::new(self.index, self.rtxn)?;
+        self.configure_context(&mut ctx)?;
+        Ok(ctx)
+    }
 
-        if let Some(searchable_attributes) = self.searchable_attributes {
+    /// Apply the options of this search that change how the query is interpreted and how
+    /// the documents are ranked to the given search context.
+    fn configure_context(&self, ctx: &mut SearchContext<'a>) -> Result<()> {
+        if let Some(searchable_attributes) = &self.searchable_attributes {
             ctx.attributes_to_search_on(searchable_attributes)?;
         }
+        ctx.ordered_proximity = self.ordered_proximity;
+        ctx.external_sorts = self.external_sorts.clone();
+        ctx.parallelism = self.parallelism;
+        ctx.skip_ranking_when_under = self.skip_ranking_when_under;
+        ctx.proximity_ignores_stop_words = self.proximity_ignores_stop_words;
+        ctx.invert_proximity = self.invert_proximity;
+        ctx.proximity_rarest_first = self.proximity_rarest_first;
+        ctx.word_filter = self.word_filter;
+        ctx.geo_fields = self.geo_fields.clone();
+        ctx.geo_distance_quantum = self.geo_distance_quantum;
+        ctx.phrase_slop = self.phrase_slop;
+        ctx.page_boundary = self.page_boundary;
+        ctx.normalizer = self.normalizer.clone();
+        ctx.max_total_typos = self.max_total_typos;
+        ctx.word_window = self.word_window;
+        ctx.max_ranked_candidates = self.max_ranked_candidates;
+        ctx.geo_distance_buckets.clone_from(&self.geo_distance_buckets);
+        ctx.cancel_token.clone_from(&self.cancel_token);
+        ctx.proximity_field_weights.clone_from(&self.proximity_field_weights);
+        ctx.disabled_ranking_rules.clone_from(&self.disabled_ranking_rules);
+        ctx.pinned.clone_from(&self.pinned);
+        ctx.bucket_limits.clone_from(&self.bucket_limits);
+        ctx.min_word_match_ratio = self.min_word_match_ratio;
+        ctx.keep_duplicate_tokens = self.keep_duplicate_tokens;
+        ctx.min_prefix_len = self.min_prefix_len;
+        if !self.query_synonyms.is_empty() {
+            ctx.query_synonyms(&self.query_synonyms)?;
+        }
+        Ok(())
+    }
+
+    /// Execute the search in the given context, without the matching words, which are
+    /// built from the interners of the context once its cache is given back.
+    fn execute_in_context(
+        &self,
+        ctx: &mut SearchContext<'a>,
+        resume_after: Option<ResumePoint>,
+        offset: usize,
+        length: usize,
+    ) -> Result<(SearchResult, Option<Vec<LocatedQueryTerm>>)> {
+        // the words and values cached by the previous searches sharing the cache are not counted
+        let previous_interned_words = ctx.word_interner.len();
+        let previous_cached_values = ctx.db_cache.cached_values();
+        self.configure_context(ctx)?;
+        ctx.resume_after = resume_after;
+        if self.query_stats {
+            ctx.query_stats = Some(QueryStats::default());
+        }
+        if self.execution_trace {
+            ctx.execution_trace = Some(Vec::new());
+        }
 
         if let Some(distinct) = &self.distinct {
             let filterable_fields = ctx.index.filterable_fields(ctx.txn)?;
@@ -197,67 +1023,253 @@ impl<'a> Search<'a> {
             }
         }
 
-        let universe = filtered_universe(ctx.index, ctx.txn, &self.filter)?;
+        let (universe, filter_funnel) = match &self.filter {
+            Some(filter) if self.filter_funnel => {
+                let (universe, funnel) = filter.evaluate_funnel(ctx.txn, ctx.index)?;
+                (universe, Some(funnel))
+            }
+            _ => (filtered_universe(ctx.index, ctx.txn, &self.filter)?, None),
+        };
+        let universe = match &self.any_exact {
+            Some(words) => any_exact_word_docids(ctx, &universe, words)?,
+            None => universe,
+        };
+        let limit = if self.has_more { length + 1 } else { length };
         let PartialSearchResult {
             located_query_terms,
             candidates,
-            documents_ids,
+            mut documents_ids,
+            mut document_scores,
+            below_threshold,
+            page_boundary,
+            degraded,
+            degradation_reasons,
+            used_negative_operator,
+        } = match &self.diversify_by {
+            Some((field, max_per_group)) => {
+                self.execute_diversified(ctx, universe, field, *max_per_group, offset, limit)?
+            }
+            None => self.execute_ranked(ctx, universe, offset, limit)?,
+        };
+
+        let has_more = self.has_more && documents_ids.len() > length;
+        documents_ids.truncate(length);
+        document_scores.truncate(length);
+
+        let query_stats = ctx.query_stats.take().map(|stats| QueryStats {
+            interned_words: ctx.word_interner.len() - previous_interned_words,
+            cached_database_values: ctx.db_cache.cached_values() - previous_cached_values,
+            ..stats
+        });
+        let execution_trace = ctx.execution_trace.take();
+
+        let geo_distance_buckets = match &self.geo_distance_buckets {
+            Some(edges) => {
+                geo_distance_buckets(ctx, &candidates, self.sort_criteria.as_ref(), edges)?
+            }
+            None => None,
+        };
+
+        let result = SearchResult {
+            matching_words: MatchingWords::default(),
+            candidates,
             document_scores,
+            documents_ids,
             degraded,
+            degradation_reasons: (!degradation_reasons.is_empty()).then_some(degradation_reasons),
             used_negative_operator,
-        } = match self.semantic.as_ref() {
+            has_more,
+            filter_funnel,
+            below_threshold,
+            page_boundary,
+            geo_distance_buckets,
+            query_stats,
+            execution_trace,
+            next_cursor: None,
+        };
+        Ok((result, located_query_terms))
+    }
+
+    /// Rank the documents with the ranking rules, then with the geo score if it is set.
+    fn execute_ranked(
+        &self,
+        ctx: &mut SearchContext<'_>,
+        universe: RoaringBitmap,
+        offset: usize,
+        limit: usize,
+    ) -> Result<PartialSearchResult> {
+        match &self.geo_score {
+            Some(geo_score) => self.execute_geo_scored(ctx, universe, geo_score, offset, limit),
+            None => self.execute_partial(ctx, universe, offset, limit, self.scoring_strategy),
+        }
+    }
+
+    fn execute_partial(
+        &self,
+        ctx: &mut SearchContext<'_>,
+        universe: RoaringBitmap,
+        offset: usize,
+        limit: usize,
+        scoring_strategy: ScoringStrategy,
+    ) -> Result<PartialSearchResult> {
+        match self.semantic.as_ref() {
             Some(SemanticSearch { vector: Some(vector), embedder_name, embedder }) => {
                 execute_vector_search(
-                    &mut ctx,
+                    ctx,
                     vector,
-                    self.scoring_strategy,
+                    scoring_strategy,
                     universe,
                     &self.sort_criteria,
                     &self.distinct,
                     self.geo_strategy,
-                    self.offset,
-                    self.limit,
+                    offset,
+                    limit,
                     embedder_name,
                     embedder,
                     self.time_budget.clone(),
                     self.ranking_score_threshold,
-                )?
+                )
             }
             _ => execute_search(
-                &mut ctx,
+                ctx,
                 self.query.as_deref(),
                 self.terms_matching_strategy,
-                self.scoring_strategy,
+                scoring_strategy,
                 self.exhaustive_number_hits,
                 universe,
                 &self.sort_criteria,
                 &self.distinct,
                 self.geo_strategy,
-                self.offset,
-                self.limit,
+                offset,
+                limit,
                 Some(self.words_limit),
                 &mut DefaultSearchLogger,
                 &mut DefaultSearchLogger,
                 self.time_budget.clone(),
                 self.ranking_score_threshold,
                 self.locales.as_ref(),
-            )?,
-        };
+            ),
+        }
+    }
 
-        // consume context and located_query_terms to build MatchingWords.
-        let matching_words = match located_query_terms {
-            Some(located_query_terms) => MatchingWords::new(ctx, located_query_terms),
-            None => MatchingWords::default(),
-        };
+    /// Fetch more and more ranked documents until no document after them can be blended
+    /// into the requested page by the geo score.
+    ///
+    /// The relevancy of the documents decreases in the order of the ranking rules, so a
+    /// document can't enter the page once even a distance score of `1` doesn't make up for it.
+    fn execute_geo_scored(
+        &self,
+        ctx: &mut SearchContext<'_>,
+        universe: RoaringBitmap,
+        geo_score: &GeoScore,
+        offset: usize,
+        limit: usize,
+    ) -> Result<PartialSearchResult> {
+        let sorted_by_distance = self
+            .sort_criteria
+            .iter()
+            .flatten()
+            .any(|criterion| !matches!(criterion.member(), Member::Field(_)));
+        if !sorted_by_distance {
+            return self.execute_partial(ctx, universe, offset, limit, self.scoring_strategy);
+        }
 
-        Ok(SearchResult {
-            matching_words,
-            candidates,
-            document_scores,
-            documents_ids,
-            degraded,
-            used_negative_operator,
-        })
+        let wanted = offset + limit;
+        let mut window = wanted.max(1);
+        // the same query is searched again every time the window grows
+        ctx.query_graph_cache.get_or_insert_with(|| QueryGraphCache::new(1));
+        loop {
+            let mut result =
+                self.execute_partial(ctx, universe.clone(), 0, window, ScoringStrategy::Detailed)?;
+            let docids: RoaringBitmap = result.documents_ids.iter().copied().collect();
+            let distances =
+                geo_distances(ctx, &docids, self.sort_criteria.as_ref())?.unwrap_or_default();
+
+            let relevancies: Vec<f64> = result
+                .document_scores
+                .iter()
+                .map(|scores| ScoreDetails::global_score(scores.iter()))
+                .collect();
+            let mut ranked: Vec<(usize, f64)> = result
+                .documents_ids
+                .iter()
+                .zip(&relevancies)
+                .map(|(docid, &relevancy)| {
+                    geo_score.blend(relevancy, distances.get(docid).copied())
+                })
+                .enumerate()
+                .collect();
+            ranked.sort_by(|(_, lhs), (_, rhs)| rhs.total_cmp(lhs));
+
+            // the next documents are at most as relevant as the last one and at best at the target
+            let exhausted = result.documents_ids.len() < window;
+            let best_next =
+                relevancies.last().map(|&relevancy| geo_score.blend(relevancy, Some(0.0)));
+            let last_kept = ranked.get(wanted.saturating_sub(1)).map(|&(_, score)| score);
+            let may_change = match (best_next, last_kept) {
+                (Some(best_next), Some(last_kept)) => best_next > last_kept,
+                _ => true,
+            };
+            if may_change && !exhausted && !result.degraded {
+                window *= 2;
+                continue;
+            }
+
+            let mut document_scores = std::mem::take(&mut result.document_scores);
+            let (documents_ids, document_scores) = ranked
+                .into_iter()
+                .skip(offset)
+                .take(limit)
+                .map(|(position, _)| {
+                    (result.documents_ids[position], std::mem::take(&mut document_scores[position]))
+                })
+                .unzip();
+            result.documents_ids = documents_ids;
+            result.document_scores = document_scores;
+            // the documents don't follow the buckets of the ranking rules anymore
+            result.page_boundary = None;
+            return Ok(result);
+        }
+    }
+
+    /// Fetch more and more ranked documents until enough of them are not pushed down
+    /// by the diversity rule to fill the requested page.
+    fn execute_diversified(
+        &self,
+        ctx: &mut SearchContext<'_>,
+        universe: RoaringBitmap,
+        field: &str,
+        max_per_group: usize,
+        offset: usize,
+        limit: usize,
+    ) -> Result<PartialSearchResult> {
+        let wanted = offset + limit;
+        let mut window = wanted;
+        // the same query is searched again every time the window grows
+        ctx.query_graph_cache.get_or_insert_with(|| QueryGraphCache::new(1));
+        loop {
+            let mut result = self.execute_ranked(ctx, universe.clone(), 0, window)?;
+            let exhausted = result.documents_ids.len() < window;
+            let (positions, kept) =
+                diversify_positions(ctx, field, max_per_group, &result.documents_ids)?;
+            if kept < wanted && !exhausted && !result.degraded {
+                window *= 2;
+                continue;
+            }
+
+            let mut document_scores = std::mem::take(&mut result.document_scores);
+            let (documents_ids, document_scores) = positions
+                .into_iter()
+                .skip(offset)
+                .take(limit)
+                .map(|position| {
+                    (result.documents_ids[position], std::mem::take(&mut document_scores[position]))
+                })
+                .unzip();
+            result.documents_ids = documents_ids;
+            result.document_scores = document_scores;
+            return Ok(result);
+        }
     }
 }
 
@@ -282,6 +1294,42 @@ impl fmt::Debug for Search<'_> {
             time_budget,
             ranking_score_threshold,
             locales,
+            ordered_proximity,
+            external_sorts,
+            diversify_by,
+            has_more,
+            parallelism,
+            query_synonyms,
+            skip_ranking_when_under,
+            proximity_ignores_stop_words,
+            word_filter,
+            geo_fields,
+            filter_funnel,
+            geo_distance_quantum,
+            phrase_slop,
+            page_boundary,
+            normalizer,
+            any_exact,
+            max_total_typos,
+            word_window,
+            max_ranked_candidates,
+            geo_distance_buckets,
+            cancel_token,
+            proximity_field_weights,
+            disabled_ranking_rules,
+            geo_score,
+            pinned,
+            bucket_limits,
+            query_stats,
+            min_word_match_ratio,
+            keep_duplicate_tokens,
+            execution_trace,
+            min_prefix_len,
+            cursor,
+            next_cursor,
+            invert_proximity,
+            proximity_rarest_first,
+            max_values_per_facet,
         } = self;
         f.debug_struct("Search")
             .field("query", query)
@@ -303,6 +1351,42 @@ impl fmt::Debug for Search<'_> {
             .field("time_budget", time_budget)
             .field("ranking_score_threshold", ranking_score_threshold)
             .field("locales", locales)
+            .field("ordered_proximity", ordered_proximity)
+            .field("external_sorts", &external_sorts.keys().collect::<Vec<_>>())
+            .field("diversify_by", diversify_by)
+            .field("has_more", has_more)
+            .field("parallelism", parallelism)
+            .field("query_synonyms", query_synonyms)
+            .field("skip_ranking_when_under", skip_ranking_when_under)
+            .field("proximity_ignores_stop_words", proximity_ignores_stop_words)
+            .field("word_filter", &word_filter.is_some())
+            .field("geo_fields", geo_fields)
+            .field("filter_funnel", filter_funnel)
+            .field("geo_distance_quantum", geo_distance_quantum)
+            .field("phrase_slop", phrase_slop)
+            .field("page_boundary", page_boundary)
+            .field("normalizer", normalizer)
+            .field("any_exact", any_exact)
+            .field("max_total_typos", max_total_typos)
+            .field("word_window", word_window)
+            .field("max_ranked_candidates", max_ranked_candidates)
+            .field("geo_distance_buckets", geo_distance_buckets)
+            .field("cancel_token", cancel_token)
+            .field("proximity_field_weights", proximity_field_weights)
+            .field("disabled_ranking_rules", disabled_ranking_rules)
+            .field("geo_score", geo_score)
+            .field("pinned", pinned)
+            .field("bucket_limits", bucket_limits)
+            .field("query_stats", query_stats)
+            .field("min_word_match_ratio", min_word_match_ratio)
+            .field("keep_duplicate_tokens", keep_duplicate_tokens)
+            .field("execution_trace", execution_trace)
+            .field("min_prefix_len", min_prefix_len)
+            .field("cursor", cursor)
+            .field("next_cursor", next_cursor)
+            .field("invert_proximity", invert_proximity)
+            .field("proximity_rarest_first", proximity_rarest_first)
+            .field("max_values_per_facet", max_values_per_facet)
             .finish()
     }
 }
@@ -314,7 +1398,254 @@ pub struct SearchResult {
     pub documents_ids: Vec<DocumentId>,
     pub document_scores: Vec<Vec<ScoreDetails>>,
     pub degraded: bool,
+    /// The reasons why the returned documents may be incomplete, e.g. to encourage the user
+    /// to type more, `None` when nothing was cut.
+    pub degradation_reasons: Option<Vec<DegradationReason>>,
     pub used_negative_operator: bool,
+    /// Whether other documents follow the returned ones, only computed when
+    /// [`Search::has_more`] is enabled.
+    pub has_more: bool,
+    /// The number of documents remaining after each clause of the filter, only computed when
+    /// [`Search::filter_funnel`] is enabled and a filter is set.
+    pub filter_funnel: Option<Vec<(String, u64)>>,
+    /// The number of candidates removed from the results because their ranking score was
+    /// below [`Search::ranking_score_threshold`].
+    pub below_threshold: u64,
+    /// The buckets containing the last returned document, only computed when
+    /// [`Search::page_boundary`] is enabled.
+    pub page_boundary: Option<PageBoundary>,
+    /// The candidates grouped by distance bands, only computed when
+    /// [`Search::geo_distance_buckets`] is set and the documents are sorted by distance.
+    pub geo_distance_buckets: Option<GeoDistanceBuckets>,
+    /// The sizes of the structures built by the search, only computed when
+    /// [`Search::query_stats`] is enabled.
+    pub query_stats: Option<QueryStats>,
+    /// The ranking rules given a universe to sort, in order, only computed when
+    /// [`Search::execution_trace`] is enabled.
+    pub execution_trace: Option<Vec<RuleStep>>,
+    /// An opaque cursor to give to [`Search::from_cursor`] to fetch the next page, only
+    /// computed when [`Search::next_cursor`] is enabled, `None` when the returned page isn't
+    /// full.
+    pub next_cursor: Option<String>,
+}
+
+impl SearchResult {
+    /// Return the primary keys of the returned documents, in the order of `documents_ids`.
+    ///
+    /// Numeric primary keys are returned as their string representation, the same way
+    /// they are used to identify the documents in the index.
+    pub fn resolve_primary_keys<'t>(
+        &self,
+        index: &Index,
+        rtxn: &'t heed::RoTxn<'t>,
+    ) -> Result<Vec<String>> {
+        // an empty index may not have a primary key yet
+        if self.documents_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        index.external_id_of(rtxn, self.documents_ids.iter().copied())?.into_iter().collect()
+    }
+
+    /// Return, for each returned document, in the order of `documents_ids`, the field and the
+    /// position of the words matching the query, along with the matching word.
+    ///
+    /// The positions are only computed when this method is called, by tokenizing the fields
+    /// of the returned documents again, so that the search itself doesn't pay for them.
+    pub fn match_positions<'t>(
+        &self,
+        index: &Index,
+        rtxn: &'t heed::RoTxn<'t>,
+    ) -> Result<Vec<Vec<MatchPosition>>> {
+        new::matches::match_positions(&self.matching_words, index, rtxn, &self.documents_ids)
+    }
+
+    /// Return a stable textual fingerprint of the results, to be stored and compared in
+    /// relevance regression tests.
+    ///
+    /// The fingerprint has one line per returned document, in order, with its primary key
+    /// and, if `with_ranks` is set, the rank of the document in the bucket of every ranking
+    /// rule, as `rank/max_rank`. The rules without a rank, like sort or vector sort, are
+    /// written as `-`. The ranks are only available when the search was executed with
+    /// [`ScoringStrategy::Detailed`].
+    ///
+    /// The order of the results only depends on the index and the search, the documents
+    /// with the same ranks being returned by increasing internal id, so the fingerprint of
+    /// a search only changes when its ranking changes.
+    pub fn fingerprint<'t>(
+        &self,
+        index: &Index,
+        rtxn: &'t heed::RoTxn<'t>,
+        with_ranks: bool,
+    ) -> Result<String> {
+        let primary_keys = self.resolve_primary_keys(index, rtxn)?;
+        let mut fingerprint = String::new();
+        for (position, primary_key) in primary_keys.iter().enumerate() {
+            fingerprint.push_str(primary_key);
+            let scores = self.document_scores.get(position).filter(|scores| !scores.is_empty());
+            if let Some(scores) = scores.filter(|_| with_ranks) {
+                let ranks = scores.iter().map(|details| match details.rank() {
+                    Some(Rank { rank, max_rank }) => format!("{rank}/{max_rank}"),
+                    None => String::from("-"),
+                });
+                fingerprint.push(' ');
+                fingerprint.push_str(&ranks.collect::<Vec<_>>().join(" "));
+            }
+            fingerprint.push('\n');
+        }
+        Ok(fingerprint)
+    }
+}
+
+/// The number of documents of the search universe containing each value of a facet
+/// field, as computed by [`Search::facet_distribution_with_cache`].
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FacetCounts {
+    /// The counts of the numbers of the field, in ascending order.
+    pub numbers: Vec<(f64, u64)>,
+    /// The counts of the strings of the field, keyed by one of their original forms.
+    pub strings: HashMap<String, u64>,
+    /// Whether other values than the returned ones are in the universe.
+    pub truncated: bool,
+}
+
+/// Counts the documents of the universe containing each value of the given field, keyed by
+/// the text form of the values.
+fn facet_value_counts(
+    ctx: &mut SearchContext<'_>,
+    fid: FieldId,
+    universe: &RoaringBitmap,
+) -> Result<HashMap<String, u64>> {
+    // a document containing both a number and a string with the same text form is counted once
+    let mut docids_by_value: HashMap<String, RoaringBitmap> = HashMap::new();
+    for (number, docids) in ctx.get_db_facet_number_docids(fid)? {
+        let docids = docids & universe;
+        if !docids.is_empty() {
+            *docids_by_value.entry(number.to_string()).or_default() |= docids;
+        }
+    }
+    for (string, docids) in ctx.get_db_facet_string_docids(fid)? {
+        let docids = docids & universe;
+        if !docids.is_empty() {
+            *docids_by_value.entry(string.clone()).or_default() |= docids;
+        }
+    }
+    Ok(docids_by_value.into_iter().map(|(value, docids)| (value, docids.len())).collect())
+}
+
+/// Counts the documents of the universe containing each value of the given field, the
+/// numbers first then the strings, until `max_values` values are found.
+fn facet_counts(
+    ctx: &mut SearchContext<'_>,
+    fid: FieldId,
+    universe: &RoaringBitmap,
+    max_values: usize,
+) -> Result<FacetCounts> {
+    let mut counts = FacetCounts::default();
+    for (number, docids) in ctx.get_db_facet_number_docids(fid)? {
+        let count = docids.intersection_len(universe);
+        if count == 0 {
+            continue;
+        }
+        if counts.numbers.len() == max_values {
+            counts.truncated = true;
+            return Ok(counts);
+        }
+        counts.numbers.push((*number, count));
+    }
+    for (string, docids) in ctx.get_db_facet_string_docids(fid)? {
+        let count = docids.intersection_len(universe);
+        if count == 0 {
+            continue;
+        }
+        if counts.numbers.len() + counts.strings.len() == max_values {
+            counts.truncated = true;
+            break;
+        }
+        counts.strings.insert(string.clone(), count);
+    }
+    Ok(counts)
+}
+
+/// Finds the extremes of the numbers and the first strings of the given field in the universe.
+fn field_extent(
+    ctx: &mut SearchContext<'_>,
+    fid: FieldId,
+    universe: &RoaringBitmap,
+) -> Result<FieldExtent> {
+    let mut extent = FieldExtent::default();
+
+    let numbers = ctx.get_db_facet_number_docids(fid)?;
+    let min = numbers.iter().find(|(_, docids)| !docids.is_disjoint(universe));
+    let max = numbers.iter().rev().find(|(_, docids)| !docids.is_disjoint(universe));
+    extent.numbers = min.zip(max).map(|((min, _), (max, _))| (*min, *max));
+
+    for (string, docids) in ctx.get_db_facet_string_docids(fid)? {
+        if docids.is_disjoint(universe) {
+            continue;
+        }
+        if extent.strings.len() == DEFAULT_VALUES_PER_FACET {
+            extent.strings_truncated = true;
+            break;
+        }
+        extent.strings.push(string.clone());
+    }
+
+    Ok(extent)
+}
+
+/// The values of a sortable field in the universe of a search, see
+/// [`Search::sortable_field_extent`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FieldExtent {
+    /// The smallest and largest numbers of the field, `None` when the documents of the
+    /// universe don't contain any number.
+    pub numbers: Option<(f64, f64)>,
+    /// The distinct strings of the field, in lexicographic order.
+    pub strings: Vec<String>,
+    /// Whether other distinct strings than the returned ones exist.
+    pub strings_truncated: bool,
+}
+
+/// The differences between the documents returned by two searches, identified by their
+/// internal ids, the ranks starting at `0`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResultDiff {
+    /// The documents only returned by the second search, with their rank in it.
+    pub added: Vec<(DocumentId, usize)>,
+    /// The documents only returned by the first search, with their rank in it.
+    pub removed: Vec<(DocumentId, usize)>,
+    /// The documents returned by both searches at different ranks, with their rank in the
+    /// second search minus their rank in the first one, negative when they moved up.
+    pub moved: Vec<(DocumentId, isize)>,
+}
+
+/// Compare the documents returned by two searches, e.g. before and after a change of the
+/// settings, to quantify its impact along with [`SearchResult::fingerprint`].
+///
+/// Only the `documents_ids` of the results are compared. The added and moved documents are
+/// listed in the order of the second search, the removed ones in the order of the first one.
+pub fn compare_results(a: &SearchResult, b: &SearchResult) -> ResultDiff {
+    let ranks_a: HashMap<DocumentId, usize> =
+        a.documents_ids.iter().enumerate().map(|(rank, &docid)| (docid, rank)).collect();
+    let ranks_b: HashMap<DocumentId, usize> =
+        b.documents_ids.iter().enumerate().map(|(rank, &docid)| (docid, rank)).collect();
+
+    let mut diff = ResultDiff::default();
+    for (rank_b, &docid) in b.documents_ids.iter().enumerate() {
+        match ranks_a.get(&docid) {
+            None => diff.added.push((docid, rank_b)),
+            Some(&rank_a) if rank_a != rank_b => {
+                diff.moved.push((docid, rank_b as isize - rank_a as isize))
+            }
+            Some(_) => (),
+        }
+    }
+    for (rank_a, &docid) in a.documents_ids.iter().enumerate() {
+        if !ranks_b.contains_key(&docid) {
+            diff.removed.push((docid, rank_a));
+        }
+    }
+    diff
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -406,4 +1737,49 @@ mod test {
 
         assert_eq!(documents_ids, vec![1]);
     }
+
+    #[test]
+    fn test_resolve_primary_keys() {
+        use crate::index::tests::TempIndex;
+
+        let index = TempIndex::new();
+        index
+            .add_documents(documents!([
+                { "id": 12, "title": "the red shoes" },
+                { "id": 3, "title": "the red hat" },
+                { "id": 7, "title": "the blue shoes" },
+            ]))
+            .unwrap();
+
+        let txn = index.read_txn().unwrap();
+        let mut search = Search::new(&txn, &index);
+        search.query("red shoes");
+        let result = search.execute().unwrap();
+        assert_eq!(result.resolve_primary_keys(&index, &txn).unwrap(), vec!["12", "3"]);
+
+        let index = TempIndex::new();
+        index
+            .add_documents(documents!([
+                { "id": "shoes-red", "title": "the red shoes" },
+                { "id": "hat-red", "title": "the red hat" },
+            ]))
+            .unwrap();
+
+        let txn = index.read_txn().unwrap();
+        let mut search = Search::new(&txn, &index);
+        search.query("red shoes");
+        let result = search.execute().unwrap();
+        assert_eq!(
+            result.resolve_primary_keys(&index, &txn).unwrap(),
+            vec!["shoes-red", "hat-red"]
+        );
+
+        // no primary key has been inferred for an index without documents
+        let index = TempIndex::new();
+        let txn = index.read_txn().unwrap();
+        let mut search = Search::new(&txn, &index);
+        search.query("red shoes");
+        let result = search.execute().unwrap();
+        assert!(result.resolve_primary_keys(&index, &txn).unwrap().is_empty());
+    }
 }
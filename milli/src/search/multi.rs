@@ -0,0 +1,50 @@
+use super::new::SharedDatabaseCache;
+use crate::{Index, Result, Search, SearchResult, SearchSpec};
+
+/// A set of searches executed on the same read transaction, so that all of them observe
+/// the same version of the index, and sharing the values they read from its databases.
+///
+/// The transaction is opened when the multi-search is created and owned by it, the searches
+/// are described by [`SearchSpec`]s and only created on it when they are executed.
+pub struct MultiSearch<'a> {
+    rtxn: heed::RoTxn<'a>,
+    index: &'a Index,
+    searches: Vec<SearchSpec>,
+}
+
+impl<'a> MultiSearch<'a> {
+    /// Open the read transaction all the searches of the multi-search will observe.
+    pub fn new(index: &'a Index) -> Result<Self> {
+        Ok(Self { rtxn: index.read_txn()?, index, searches: Vec::new() })
+    }
+
+    /// The transaction the searches are executed on, e.g. to read the documents they return.
+    pub fn rtxn(&self) -> &heed::RoTxn<'a> {
+        &self.rtxn
+    }
+
+    /// Add a search to execute with the other ones.
+    ///
+    /// Returns an error if the filter or the sort criteria of the search are invalid.
+    pub fn add(&mut self, search: SearchSpec) -> Result<&mut MultiSearch<'a>> {
+        Search::from_spec(&self.rtxn, self.index, &search)?;
+        self.searches.push(search);
+        Ok(self)
+    }
+
+    /// Execute the searches in the order they were added, returning their results in
+    /// the same order.
+    ///
+    /// The values read from the databases by a search are cached and reused by the next
+    /// ones, which is sound as they are all executed on the same transaction.
+    pub fn execute_all(&self) -> Result<Vec<SearchResult>> {
+        let mut cache = SharedDatabaseCache::default();
+        self.searches
+            .iter()
+            .map(|spec| {
+                let search = Search::from_spec(&self.rtxn, self.index, spec)?;
+                search.execute_with_cache(Some(&mut cache))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,132 @@
+use heed::RoTxn;
+use roaring::RoaringBitmap;
+
+use super::geo_sort::{self, GeoSort};
+use super::graph_based_ranking_rule::{Proximity, Typo, Words};
+use super::query_graph::QueryGraph;
+use super::ranking_rules::{BoxRankingRule, PlaceholderQuery, RankingRuleQueryTrait};
+use super::{cached_query, resolve_universe, DefaultSearchLogger, SearchContext};
+use crate::{GeoSortStrategy, Index, Result, TermsMatchingStrategy};
+
+enum BenchedRule<'ctx> {
+    Placeholder(BoxRankingRule<'ctx, PlaceholderQuery>),
+    QueryGraph(BoxRankingRule<'ctx, QueryGraph>, QueryGraph),
+}
+
+/// A single ranking rule and the search context it reads from, to measure the cost of
+/// iterating over its buckets without the other ranking rules of a search.
+pub struct RankingRuleBench<'ctx> {
+    ctx: SearchContext<'ctx>,
+    rule: BenchedRule<'ctx>,
+    universe: RoaringBitmap,
+}
+
+impl<'ctx> RankingRuleBench<'ctx> {
+    /// A geo sort around the given point, ranking the geo faceted documents.
+    pub fn geo_sort(
+        index: &'ctx Index,
+        rtxn: &'ctx RoTxn<'ctx>,
+        point: [f64; 2],
+        ascending: bool,
+        strategy: GeoSortStrategy,
+    ) -> Result<Self> {
+        let ctx = SearchContext::new(index, rtxn)?;
+        let universe = geo_sort::geo_faceted_docids(&ctx, strategy)?;
+        let rule = GeoSort::new(strategy, universe.clone(), point, None, ascending)?;
+        Ok(Self { ctx, rule: BenchedRule::Placeholder(Box::new(rule)), universe })
+    }
+
+    /// The words ranking rule of the given query, `None` if the query has no words.
+    pub fn words(index: &'ctx Index, rtxn: &'ctx RoTxn<'ctx>, query: &str) -> Result<Option<Self>> {
+        let rule = Box::new(Words::new(TermsMatchingStrategy::Last));
+        Self::query_graph(index, rtxn, query, rule)
+    }
+
+    /// The typo ranking rule of the given query, `None` if the query has no words.
+    pub fn typo(index: &'ctx Index, rtxn: &'ctx RoTxn<'ctx>, query: &str) -> Result<Option<Self>> {
+        let rule = Box::new(Typo::new(Some(TermsMatchingStrategy::Last)));
+        Self::query_graph(index, rtxn, query, rule)
+    }
+
+    /// The proximity ranking rule of the given query, `None` if the query has no words.
+    pub fn proximity(
+        index: &'ctx Index,
+        rtxn: &'ctx RoTxn<'ctx>,
+        query: &str,
+    ) -> Result<Option<Self>> {
+        let rule = Box::new(Proximity::new(Some(TermsMatchingStrategy::Last)));
+        Self::query_graph(index, rtxn, query, rule)
+    }
+
+    /// Build the query graph of the query and rank the documents matching it with the `last`
+    /// terms matching strategy.
+    fn query_graph(
+        index: &'ctx Index,
+        rtxn: &'ctx RoTxn<'ctx>,
+        query: &str,
+        rule: BoxRankingRule<'ctx, QueryGraph>,
+    ) -> Result<Option<Self>> {
+        let mut ctx = SearchContext::new(index, rtxn)?;
+        let Some((graph, _)) = cached_query(&mut ctx, query, None, None)?.graph else {
+            return Ok(None);
+        };
+        let documents_ids = index.documents_ids(rtxn)?;
+        let universe = resolve_universe(
+            &mut ctx,
+            &documents_ids,
+            &graph,
+            TermsMatchingStrategy::Last,
+            &mut DefaultSearchLogger,
+        )?;
+        Ok(Some(Self { ctx, rule: BenchedRule::QueryGraph(rule, graph), universe }))
+    }
+
+    /// The documents the ranking rule ranks.
+    pub fn universe(&self) -> &RoaringBitmap {
+        &self.universe
+    }
+
+    /// Only rank the documents of the given universe.
+    pub fn restrict_universe(&mut self, universe: &RoaringBitmap) {
+        self.universe &= universe;
+    }
+
+    /// Iterate over the buckets of the ranking rule, at most `max_buckets` of them, and return
+    /// the number of documents they contain.
+    pub fn run(&mut self, max_buckets: Option<usize>) -> Result<u64> {
+        match &mut self.rule {
+            BenchedRule::Placeholder(rule) => {
+                drain_buckets(&mut self.ctx, rule, &PlaceholderQuery, &self.universe, max_buckets)
+            }
+            BenchedRule::QueryGraph(rule, graph) => {
+                drain_buckets(&mut self.ctx, rule, graph, &self.universe, max_buckets)
+            }
+        }
+    }
+}
+
+fn drain_buckets<'ctx, Q: RankingRuleQueryTrait>(
+    ctx: &mut SearchContext<'ctx>,
+    rule: &mut BoxRankingRule<'ctx, Q>,
+    query: &Q,
+    universe: &RoaringBitmap,
+    max_buckets: Option<usize>,
+) -> Result<u64> {
+    let logger = &mut DefaultSearchLogger;
+    let mut universe = universe.clone();
+    rule.start_iteration(ctx, logger, &universe, query)?;
+
+    let mut ranked = 0;
+    let mut buckets = 0;
+    while !universe.is_empty() && max_buckets.map_or(true, |max| buckets < max) {
+        let Some(bucket) = rule.next_bucket(ctx, logger, &universe)? else {
+            break;
+        };
+        ranked += bucket.candidates.len();
+        universe -= &bucket.candidates;
+        buckets += 1;
+    }
+
+    rule.end_iteration(ctx, logger);
+    Ok(ranked)
+}
@@ -1,7 +1,10 @@
+use std::cmp::Ordering;
+
 use roaring::RoaringBitmap;
+use serde::{Deserialize, Serialize};
 
 use super::logger::SearchLogger;
-use super::ranking_rules::{BoxRankingRule, RankingRuleQueryTrait};
+use super::ranking_rules::{BoxRankingRule, RankingRule, RankingRuleQueryTrait};
 use super::SearchContext;
 use crate::score_details::{ScoreDetails, ScoringStrategy};
 use crate::search::new::distinct::{apply_distinct_rule, distinct_single_docid, DistinctOutput};
@@ -11,8 +14,163 @@ pub struct BucketSortOutput {
     pub docids: Vec<u32>,
     pub scores: Vec<Vec<ScoreDetails>>,
     pub all_candidates: RoaringBitmap,
+    /// The number of candidates removed because their score was below the ranking score threshold.
+    pub below_threshold: u64,
+    pub page_boundary: Option<PageBoundary>,
 
     pub degraded: bool,
+    /// Whether the candidates were reduced to the maximum number of candidates ranked.
+    pub capped: bool,
+    /// Whether the candidates of a bucket were reduced to the bucket limit of its ranking rule.
+    pub bucket_limited: bool,
+}
+
+/// A reason why the results of a search may be incomplete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DegradationReason {
+    /// A prefix of the query matched more words than the maximum number of words a prefix
+    /// is expanded to, the documents only containing the others are missing.
+    PrefixTruncated,
+    /// There were more candidates than the maximum number of candidates ranked, only the first
+    /// ones in the order of the cheap ranking rules were ranked by the expensive ones.
+    CandidateCapHit,
+    /// The time budget of the search was exhausted before all the candidates were ranked.
+    TimedOut,
+    /// A ranking rule returned a bucket with more candidates than its bucket limit, only the
+    /// ones with the smallest ids were ranked by the next ranking rules.
+    BucketLimitHit,
+}
+
+/// The buckets of the ranking rules containing the last document returned by a search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageBoundary {
+    /// The index of the last ranking rule that split the candidates to build the bucket
+    /// of the document.
+    pub ranking_rule: usize,
+    /// The id of this ranking rule.
+    pub ranking_rule_id: String,
+    /// For each ranking rule up to `ranking_rule`, the rank of the bucket containing the
+    /// document among the buckets returned by the rule for its parent bucket, starting at 0.
+    pub bucket_ranks: Vec<usize>,
+}
+
+/// The position of a document in the buckets of the ranking rules, to resume a search after it
+/// even when documents were added or deleted before it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumePoint {
+    /// The key of each bucket containing the document, in the order of the ranking rules that
+    /// sorted it.
+    pub keys: Vec<BucketKey>,
+    /// The document, the documents of a bucket are returned in the order of their ids.
+    pub docid: u32,
+}
+
+impl ResumePoint {
+    /// The position of a document given its scores, as returned by the bucket sort.
+    pub fn new(scores: &[ScoreDetails], docid: u32) -> Self {
+        Self { keys: scores.iter().map(BucketKey::new).collect(), docid }
+    }
+}
+
+/// What identifies a bucket among the buckets returned by a ranking rule.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BucketKey {
+    /// The rank of the bucket, for the ranking rules with a score.
+    Rank(u32),
+    /// The value of the field sorted by a sort rule.
+    Sort { value: serde_json::Value, ascending: bool },
+    /// The distance to the target point of the geo sort, `None` for the documents without
+    /// coordinates.
+    GeoDistance { distance: Option<f64>, ascending: bool },
+    /// The similarity of the vectors of the documents to the query vector.
+    Similarity(Option<f32>),
+    /// The time budget was exhausted before the ranking rule sorted the bucket.
+    Skipped,
+}
+
+impl BucketKey {
+    pub fn new(score: &ScoreDetails) -> Self {
+        match score {
+            ScoreDetails::Sort(sort) => {
+                BucketKey::Sort { value: sort.value.clone(), ascending: sort.ascending }
+            }
+            ScoreDetails::GeoSort(geo_sort) => BucketKey::GeoDistance {
+                distance: geo_sort.distance(),
+                ascending: geo_sort.ascending,
+            },
+            ScoreDetails::Vector(vector) => BucketKey::Similarity(vector.similarity),
+            ScoreDetails::Skipped => BucketKey::Skipped,
+            score => BucketKey::Rank(score.rank().map_or(0, |rank| rank.rank)),
+        }
+    }
+
+    /// The order of the bucket of `self` relative to the bucket of `other` returned by the same
+    /// ranking rule, `Less` when it is returned first, `None` when they can't be compared.
+    fn cmp_bucket(&self, other: &Self) -> Option<Ordering> {
+        use serde_json::Value;
+
+        match (self, other) {
+            // the best ranks are returned first
+            (BucketKey::Rank(left), BucketKey::Rank(right)) => Some(right.cmp(left)),
+            (
+                BucketKey::Sort { value: left, ascending },
+                BucketKey::Sort { value: right, ascending: right_ascending },
+            ) if ascending == right_ascending => {
+                let order = match (left, right) {
+                    (Value::Null, Value::Null) => return Some(Ordering::Equal),
+                    // the documents without a value are returned last
+                    (Value::Null, _) => return Some(Ordering::Greater),
+                    (_, Value::Null) => return Some(Ordering::Less),
+                    // numbers are always before strings
+                    (Value::Number(_), Value::String(_)) => return Some(Ordering::Less),
+                    (Value::String(_), Value::Number(_)) => return Some(Ordering::Greater),
+                    (Value::Number(left), Value::Number(right)) => {
+                        left.as_f64()?.partial_cmp(&right.as_f64()?)?
+                    }
+                    (Value::String(left), Value::String(right)) => left.cmp(right),
+                    _ => return None,
+                };
+                Some(if *ascending { order } else { order.reverse() })
+            }
+            (
+                BucketKey::GeoDistance { distance: left, ascending },
+                BucketKey::GeoDistance { distance: right, ascending: right_ascending },
+            ) if ascending == right_ascending => match (left, right) {
+                (None, None) => Some(Ordering::Equal),
+                // the documents without coordinates are returned last
+                (None, Some(_)) => Some(Ordering::Greater),
+                (Some(_), None) => Some(Ordering::Less),
+                (Some(left), Some(right)) => {
+                    let order = left.partial_cmp(right)?;
+                    Some(if *ascending { order } else { order.reverse() })
+                }
+            },
+            (BucketKey::Similarity(left), BucketKey::Similarity(right)) => match (left, right) {
+                (None, None) => Some(Ordering::Equal),
+                (None, Some(_)) => Some(Ordering::Greater),
+                (Some(_), None) => Some(Ordering::Less),
+                (Some(left), Some(right)) => right.partial_cmp(left),
+            },
+            (BucketKey::Skipped, BucketKey::Skipped) => Some(Ordering::Equal),
+            _ => None,
+        }
+    }
+}
+
+/// A ranking rule given a universe to sort by the bucket sort, in the order they are given.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleStep {
+    /// The index of the ranking rule.
+    pub ranking_rule: usize,
+    /// The id of this ranking rule.
+    pub ranking_rule_id: String,
+    /// Whether the ranking rule was skipped because its universe didn't need to be sorted:
+    /// it was empty, had a single document, or was before the requested page.
+    pub skipped: bool,
+    /// The number of buckets the ranking rule returned for this universe.
+    pub buckets: usize,
 }
 
 // TODO: would probably be good to regroup some of these inside of a struct?
@@ -51,14 +209,38 @@ pub fn bucket_sort<'ctx, Q: RankingRuleQueryTrait>(
             docids: vec![],
             scores: vec![],
             all_candidates: universe.clone(),
+            below_threshold: 0,
+            page_boundary: None,
             degraded: false,
+            capped: false,
+            bucket_limited: false,
         });
     }
-    if ranking_rules.is_empty() {
+    // below the threshold the caller prefers getting the candidates fast to ranking them
+    let skip_ranking = ranking_score_threshold.is_none()
+        && ctx.skip_ranking_when_under.is_some_and(|threshold| universe.len() <= threshold as u64);
+
+    if ranking_rules.is_empty() || skip_ranking {
+        for (index, rule) in ranking_rules.iter().enumerate() {
+            trace_step(ctx, index, rule.as_ref(), true);
+        }
+        let pinned = pinned_docids(ctx, universe);
+        let pinned_bitmap: RoaringBitmap = pinned.iter().copied().collect();
+        let unpinned = universe - &pinned_bitmap;
+        // the documents up to the one the search is resumed after were returned before
+        let resumed = match &ctx.resume_after {
+            Some(ResumePoint { docid, .. }) => match pinned.iter().position(|id| id == docid) {
+                Some(position) => position + 1,
+                None => pinned.len() + unpinned.rank(*docid) as usize,
+            },
+            None => 0,
+        };
+        let docids = pinned.iter().copied().chain(unpinned.iter());
         if let Some(distinct_fid) = distinct_fid {
             let mut excluded = RoaringBitmap::new();
+            let mut skipped = vec![];
             let mut results = vec![];
-            for docid in universe.iter() {
+            for (position, docid) in docids.enumerate() {
                 if results.len() >= from + length {
                     break;
                 }
@@ -67,10 +249,15 @@ pub fn bucket_sort<'ctx, Q: RankingRuleQueryTrait>(
                 }
 
                 distinct_single_docid(ctx.index, ctx.txn, distinct_fid, docid, &mut excluded)?;
-                results.push(docid);
+                if position < resumed {
+                    skipped.push(docid);
+                } else {
+                    results.push(docid);
+                }
             }
 
             let mut all_candidates = universe - excluded;
+            all_candidates.extend(skipped);
             all_candidates.extend(results.iter().copied());
             // drain the results of the skipped elements
             // this **must** be done **after** writing the entire results in `all_candidates` to ensure
@@ -85,24 +272,68 @@ pub fn bucket_sort<'ctx, Q: RankingRuleQueryTrait>(
                 scores: vec![Default::default(); results.len()],
                 docids: results,
                 all_candidates,
+                below_threshold: 0,
+                page_boundary: None,
                 degraded: false,
+                capped: false,
+                bucket_limited: false,
             });
         } else {
-            let docids: Vec<u32> = universe.iter().skip(from).take(length).collect();
+            let docids: Vec<u32> = docids.skip(resumed + from).take(length).collect();
             return Ok(BucketSortOutput {
                 scores: vec![Default::default(); docids.len()],
                 docids,
                 all_candidates: universe.clone(),
+                below_threshold: 0,
+                page_boundary: None,
                 degraded: false,
+                capped: false,
+                bucket_limited: false,
             });
         };
     }
 
+    // above the cap, only the first candidates in the order of the ranking rules before the
+    // first expensive one are given to it, the others are neither returned nor counted
+    let first_expensive = match ctx.max_ranked_candidates {
+        Some(max) if universe.len() > max as u64 => {
+            ranking_rules.iter().position(|rule| rule.is_expensive(universe))
+        }
+        _ => None,
+    };
+    let mut capped = false;
+    let mut remaining_ranked = None;
+    let capped_universe: RoaringBitmap;
+    let universe = match (first_expensive, ctx.max_ranked_candidates) {
+        // there is no cheaper order, the candidates with the smallest ids are ranked
+        (Some(0), Some(max)) => {
+            capped_universe = universe.iter().take(max).collect();
+            logger.capped_universe(universe, &capped_universe);
+            capped = true;
+            &capped_universe
+        }
+        (Some(_), Some(max)) => {
+            remaining_ranked = Some(max as u64);
+            universe
+        }
+        _ => universe,
+    };
+    let first_expensive = first_expensive.unwrap_or(0);
+
+    // when resuming a search, the buckets are compared to the ones of the document it is
+    // resumed after, as long as the universe of the ranking rule at this depth contained it
+    let resume_after = ctx.resume_after.clone();
+    let mut resume_depth = resume_after.as_ref().map(|_| 0);
+
     let ranking_rules_len = ranking_rules.len();
+    let bucket_limits: Vec<Option<usize>> =
+        ranking_rules.iter().map(|rule| ctx.bucket_limits.get(&rule.id()).copied()).collect();
+    let mut bucket_limited = false;
 
     logger.start_iteration_ranking_rule(0, ranking_rules[0].as_ref(), query, universe);
 
     ranking_rules[0].start_iteration(ctx, logger, universe, query)?;
+    trace_step(ctx, 0, ranking_rules[0].as_ref(), false);
 
     let mut ranking_rule_scores: Vec<ScoreDetails> = vec![];
 
@@ -129,6 +360,10 @@ pub fn bucket_sort<'ctx, Q: RankingRuleQueryTrait>(
             );
             ranking_rule_universes[cur_ranking_rule_index].clear();
             ranking_rules[cur_ranking_rule_index].end_iteration(ctx, logger);
+            // the next buckets of the parent ranking rule follow the resumed document
+            if resume_depth == Some(cur_ranking_rule_index) {
+                resume_depth = None;
+            }
             if cur_ranking_rule_index == 0 {
                 break;
             } else {
@@ -141,12 +376,43 @@ pub fn bucket_sort<'ctx, Q: RankingRuleQueryTrait>(
     }
 
     let mut all_candidates = universe.clone();
+    let mut below_threshold = 0;
+    // the number of buckets returned by each ranking rule since it started iterating
+    let mut bucket_counts = vec![0; ranking_rules_len];
+    let mut page_boundary = None;
     let mut valid_docids = vec![];
     let mut valid_scores = vec![];
     let mut cur_offset = 0usize;
 
-    macro_rules! maybe_add_to_results {
+    /// Keep the candidates of a bucket sorted by the ranking rules before the first expensive
+    /// one that fit in the number of candidates left to rank, and drop the others.
+    macro_rules! cap_ranked_candidates {
         ($candidates:expr) => {
+            if let Some(remaining) = remaining_ranked.as_mut() {
+                if $candidates.len() > *remaining {
+                    let ranked: RoaringBitmap =
+                        $candidates.iter().take(*remaining as usize).collect();
+                    all_candidates -= &$candidates - &ranked;
+                    logger.capped_universe(&$candidates, &ranked);
+                    $candidates = ranked;
+                    capped = true;
+                }
+                *remaining -= $candidates.len();
+                if *remaining == 0 {
+                    // the candidates left to sort can't be ranked anymore
+                    for universe in &mut ranking_rule_universes[..first_expensive] {
+                        all_candidates -= &*universe;
+                        universe.clear();
+                    }
+                }
+            }
+        };
+    }
+
+    /// Add the candidates of a bucket built by the first `depth` ranking rules to the results.
+    macro_rules! maybe_add_to_results {
+        ($candidates:expr, $depth:expr) => {
+            let results_len = valid_docids.len();
             maybe_add_to_results(
                 ctx,
                 from,
@@ -163,26 +429,130 @@ pub fn bucket_sort<'ctx, Q: RankingRuleQueryTrait>(
                 &ranking_rule_scores,
                 $candidates,
             )?;
+            if ctx.page_boundary && valid_docids.len() > results_len {
+                page_boundary = page_boundary_at($depth, &bucket_counts, &ranking_rules);
+            }
+        };
+    }
+
+    /// Skip the candidates of a bucket returned before the document the search is resumed
+    /// after, like the candidates before the offset.
+    macro_rules! skip_resumed {
+        ($candidates:expr) => {
+            let offset = cur_offset;
+            maybe_add_to_results(
+                ctx,
+                usize::MAX,
+                length,
+                logger,
+                &mut valid_docids,
+                &mut valid_scores,
+                &mut all_candidates,
+                &mut ranking_rule_universes,
+                &mut ranking_rules,
+                cur_ranking_rule_index,
+                &mut cur_offset,
+                distinct_fid,
+                &ranking_rule_scores,
+                $candidates,
+            )?;
+            cur_offset = offset;
         };
     }
 
+    /// Skip the candidates of a bucket containing the document the search is resumed after
+    /// whose ids are up to its id, the next buckets follow it.
+    macro_rules! skip_resumed_docids {
+        ($candidates:expr) => {
+            if let Some(ResumePoint { docid, .. }) = &resume_after {
+                let mut after = $candidates.clone();
+                after.remove_range(..=*docid);
+                let before = &$candidates - &after;
+                $candidates = after;
+                skip_resumed!(before);
+            }
+            resume_depth = None;
+        };
+    }
+
+    // the pinned documents are returned before the buckets of the ranking rules, which don't
+    // return them again
+    let pinned = pinned_docids(ctx, universe);
+    // when resuming after a pinned document, the pinned documents up to it were returned
+    // before, and when resuming after a ranked document, all of them were
+    let resumed_pinned = match &resume_after {
+        Some(ResumePoint { docid, .. }) => match pinned.iter().position(|id| id == docid) {
+            Some(position) => {
+                resume_depth = None;
+                position + 1
+            }
+            None => pinned.len(),
+        },
+        None => 0,
+    };
+    for (position, docid) in pinned.into_iter().enumerate() {
+        if valid_docids.len() >= length {
+            break;
+        }
+        // a pinned document can be excluded by the distinct value of a previous one
+        if !all_candidates.contains(docid) {
+            continue;
+        }
+        ranking_rule_universes[0].remove(docid);
+        if position < resumed_pinned {
+            skip_resumed!(RoaringBitmap::from_iter([docid]));
+            continue;
+        }
+        maybe_add_to_results(
+            ctx,
+            from,
+            length,
+            logger,
+            &mut valid_docids,
+            &mut valid_scores,
+            &mut all_candidates,
+            &mut ranking_rule_universes,
+            &mut ranking_rules,
+            0,
+            &mut cur_offset,
+            distinct_fid,
+            &[],
+            RoaringBitmap::from_iter([docid]),
+        )?;
+    }
+
+    // the document the search is resumed after was not sorted by the ranking rules
+    if resume_depth == Some(0) && resume_after.as_ref().is_some_and(|point| point.keys.is_empty()) {
+        skip_resumed_docids!(ranking_rule_universes[0]);
+    }
+
     while valid_docids.len() < length {
+        ctx.check_cancelled()?;
         if time_budget.exceeded() {
             loop {
-                let bucket = std::mem::take(&mut ranking_rule_universes[cur_ranking_rule_index]);
+                let mut bucket =
+                    std::mem::take(&mut ranking_rule_universes[cur_ranking_rule_index]);
                 ranking_rule_scores.push(ScoreDetails::Skipped);
 
                 // remove candidates from the universe without adding them to result if their score is below the threshold
                 if let Some(ranking_score_threshold) = ranking_score_threshold {
                     let current_score = ScoreDetails::global_score(ranking_rule_scores.iter());
                     if current_score < ranking_score_threshold {
+                        let len = all_candidates.len();
                         all_candidates -= bucket | &ranking_rule_universes[cur_ranking_rule_index];
+                        below_threshold += len - all_candidates.len();
                         back!();
                         continue;
                     }
                 }
 
-                maybe_add_to_results!(bucket);
+                if cur_ranking_rule_index < first_expensive {
+                    cap_ranked_candidates!(bucket);
+                }
+                if resume_depth == Some(cur_ranking_rule_index) {
+                    skip_resumed_docids!(bucket);
+                }
+                maybe_add_to_results!(bucket, cur_ranking_rule_index);
 
                 ranking_rule_scores.pop();
 
@@ -197,7 +567,11 @@ pub fn bucket_sort<'ctx, Q: RankingRuleQueryTrait>(
                 scores: valid_scores,
                 docids: valid_docids,
                 all_candidates,
+                below_threshold,
+                page_boundary,
                 degraded: true,
+                capped,
+                bucket_limited,
             });
         }
 
@@ -207,8 +581,14 @@ pub fn bucket_sort<'ctx, Q: RankingRuleQueryTrait>(
             || (scoring_strategy == ScoringStrategy::Skip
                 && ranking_rule_universes[cur_ranking_rule_index].len() == 1)
         {
-            let bucket = std::mem::take(&mut ranking_rule_universes[cur_ranking_rule_index]);
-            maybe_add_to_results!(bucket);
+            let mut bucket = std::mem::take(&mut ranking_rule_universes[cur_ranking_rule_index]);
+            if let Some(step) = last_trace_step(ctx, cur_ranking_rule_index) {
+                step.skipped |= step.buckets == 0;
+            }
+            if resume_depth == Some(cur_ranking_rule_index) {
+                skip_resumed_docids!(bucket);
+            }
+            maybe_add_to_results!(bucket, cur_ranking_rule_index);
             back!();
             continue;
         }
@@ -219,11 +599,19 @@ pub fn bucket_sort<'ctx, Q: RankingRuleQueryTrait>(
             &ranking_rule_universes[cur_ranking_rule_index],
         )?
         else {
+            // e.g. the documents over the typo limit of the query are not candidates anymore
+            if ranking_rules[cur_ranking_rule_index].excludes_unranked(ctx) {
+                all_candidates -= &ranking_rule_universes[cur_ranking_rule_index];
+            }
             back!();
             continue;
         };
 
         ranking_rule_scores.push(next_bucket.score);
+        bucket_counts[cur_ranking_rule_index] += 1;
+        if let Some(step) = last_trace_step(ctx, cur_ranking_rule_index) {
+            step.buckets += 1;
+        }
 
         logger.next_bucket_ranking_rule(
             cur_ranking_rule_index,
@@ -240,8 +628,10 @@ pub fn bucket_sort<'ctx, Q: RankingRuleQueryTrait>(
         if let Some(ranking_score_threshold) = ranking_score_threshold {
             let current_score = ScoreDetails::global_score(ranking_rule_scores.iter());
             if current_score < ranking_score_threshold {
+                let len = all_candidates.len();
                 all_candidates -=
                     next_bucket.candidates | &ranking_rule_universes[cur_ranking_rule_index];
+                below_threshold += len - all_candidates.len();
                 back!();
                 continue;
             }
@@ -249,16 +639,78 @@ pub fn bucket_sort<'ctx, Q: RankingRuleQueryTrait>(
 
         ranking_rule_universes[cur_ranking_rule_index] -= &next_bucket.candidates;
 
-        if cur_ranking_rule_index == ranking_rules_len - 1
-            || (scoring_strategy == ScoringStrategy::Skip && next_bucket.candidates.len() <= 1)
-            || cur_offset + (next_bucket.candidates.len() as usize) < from
+        // the candidates of the bucket tie for this ranking rule, only the ones with the
+        // smallest ids are given to the next ranking rules and the others are dropped
+        let mut next_bucket = next_bucket;
+        let limit = bucket_limits[cur_ranking_rule_index]
+            .filter(|_| cur_ranking_rule_index < ranking_rules_len - 1);
+        if let Some(limit) = limit {
+            if next_bucket.candidates.len() > limit as u64 {
+                let limited: RoaringBitmap = next_bucket.candidates.iter().take(limit).collect();
+                logger.capped_bucket(
+                    cur_ranking_rule_index,
+                    ranking_rules[cur_ranking_rule_index].as_ref(),
+                    &next_bucket.candidates,
+                    &limited,
+                );
+                next_bucket.candidates = limited;
+                bucket_limited = true;
+            }
+        }
+
+        let skips_next_rules = |candidates: &RoaringBitmap, cur_offset: usize| {
+            cur_ranking_rule_index == ranking_rules_len - 1
+                || (scoring_strategy == ScoringStrategy::Skip && candidates.len() <= 1)
+                || cur_offset + (candidates.len() as usize) < from
+        };
+        // the candidates are capped once the cheap ranking rules are done sorting them
+        if cur_ranking_rule_index < first_expensive
+            && (cur_ranking_rule_index + 1 == first_expensive
+                || skips_next_rules(&next_bucket.candidates, cur_offset))
         {
-            maybe_add_to_results!(next_bucket.candidates);
+            cap_ranked_candidates!(next_bucket.candidates);
+        }
+
+        if resume_depth == Some(cur_ranking_rule_index) {
+            let keys = resume_after.as_ref().map(|point| point.keys.as_slice()).unwrap_or_default();
+            let order = keys
+                .get(cur_ranking_rule_index)
+                .zip(ranking_rule_scores.last())
+                .and_then(|(key, score)| BucketKey::new(score).cmp_bucket(key));
+            match order {
+                // the bucket was returned before the document the search is resumed after
+                Some(Ordering::Less) => {
+                    skip_resumed!(next_bucket.candidates);
+                    ranking_rule_scores.pop();
+                    continue;
+                }
+                // the document is in this bucket, and in the bucket of the next ranking rule
+                // with its key if it was sorted by it
+                Some(Ordering::Equal) => {
+                    if cur_ranking_rule_index + 1 < keys.len()
+                        && !skips_next_rules(&next_bucket.candidates, cur_offset)
+                    {
+                        resume_depth = Some(cur_ranking_rule_index + 1);
+                    } else {
+                        skip_resumed_docids!(next_bucket.candidates);
+                    }
+                }
+                // the bucket follows the one of the document, and so do the next ones
+                _ => resume_depth = None,
+            }
+        }
+
+        if skips_next_rules(&next_bucket.candidates, cur_offset) {
+            if let Some(next_rule) = ranking_rules.get(cur_ranking_rule_index + 1) {
+                trace_step(ctx, cur_ranking_rule_index + 1, next_rule.as_ref(), true);
+            }
+            maybe_add_to_results!(next_bucket.candidates, cur_ranking_rule_index + 1);
             ranking_rule_scores.pop();
             continue;
         }
 
         cur_ranking_rule_index += 1;
+        bucket_counts[cur_ranking_rule_index] = 0;
         ranking_rule_universes[cur_ranking_rule_index].clone_from(&next_bucket.candidates);
         logger.start_iteration_ranking_rule(
             cur_ranking_rule_index,
@@ -272,16 +724,75 @@ pub fn bucket_sort<'ctx, Q: RankingRuleQueryTrait>(
             &next_bucket.candidates,
             &next_bucket.query,
         )?;
+        trace_step(
+            ctx,
+            cur_ranking_rule_index,
+            ranking_rules[cur_ranking_rule_index].as_ref(),
+            false,
+        );
     }
 
     Ok(BucketSortOutput {
         docids: valid_docids,
         scores: valid_scores,
         all_candidates,
+        below_threshold,
+        page_boundary,
         degraded: false,
+        capped,
+        bucket_limited,
     })
 }
 
+/// The pinned documents of the context that are part of the universe, in the order they were
+/// pinned and without duplicates.
+fn pinned_docids(ctx: &SearchContext<'_>, universe: &RoaringBitmap) -> Vec<u32> {
+    let mut seen = RoaringBitmap::new();
+    ctx.pinned
+        .iter()
+        .copied()
+        .filter(|&docid| universe.contains(docid) && seen.insert(docid))
+        .collect()
+}
+
+/// The boundary of a bucket built by the first `depth` ranking rules, `None` when no
+/// ranking rule split the candidates.
+fn page_boundary_at<Q: RankingRuleQueryTrait>(
+    depth: usize,
+    bucket_counts: &[usize],
+    ranking_rules: &[BoxRankingRule<'_, Q>],
+) -> Option<PageBoundary> {
+    let ranking_rule = depth.checked_sub(1)?;
+    Some(PageBoundary {
+        ranking_rule,
+        ranking_rule_id: ranking_rules[ranking_rule].id(),
+        bucket_ranks: bucket_counts[..depth].iter().map(|count| count - 1).collect(),
+    })
+}
+
+/// Record in the execution trace of the context, when it is collected, that the ranking rule
+/// was given a universe.
+fn trace_step<'ctx, Q: RankingRuleQueryTrait>(
+    ctx: &mut SearchContext<'ctx>,
+    ranking_rule: usize,
+    rule: &dyn RankingRule<'ctx, Q>,
+    skipped: bool,
+) {
+    if let Some(trace) = &mut ctx.execution_trace {
+        trace.push(RuleStep { ranking_rule, ranking_rule_id: rule.id(), skipped, buckets: 0 });
+    }
+}
+
+/// The step of the universe the ranking rule is currently sorting, if the execution trace is
+/// collected.
+fn last_trace_step<'a>(
+    ctx: &'a mut SearchContext<'_>,
+    ranking_rule: usize,
+) -> Option<&'a mut RuleStep> {
+    let trace = ctx.execution_trace.as_mut()?;
+    trace.iter_mut().rev().find(|step| step.ranking_rule == ranking_rule)
+}
+
 /// Add the candidates to the results. Take `distinct`, `from`, `length`, and `cur_offset`
 /// into account and inform the logger.
 #[allow(clippy::too_many_arguments)]
@@ -1,19 +1,24 @@
 use std::borrow::Cow;
 use std::collections::hash_map::Entry;
+use std::collections::BTreeMap;
 use std::hash::Hash;
 
 use fxhash::FxHashMap;
 use heed::types::Bytes;
-use heed::{BytesEncode, Database, RoTxn};
+use heed::{BytesDecode, BytesEncode, Database, RoTxn};
 use roaring::RoaringBitmap;
 
-use super::interner::Interned;
-use super::Word;
-use crate::heed_codec::{BytesDecodeOwned, StrBEU16Codec};
+use super::interner::{DedupInterner, Interned, Interner};
+use super::query_term::{Phrase, QueryTerm};
+use super::{limits, QueryGraphCache, Word};
+use crate::heed_codec::facet::{
+    FacetGroupKey, FacetGroupKeyCodec, FacetGroupValue, OrderedF64Codec,
+};
+use crate::heed_codec::{BytesDecodeOwned, BytesRefCodec, StrBEU16Codec};
 use crate::proximity::ProximityPrecision;
 use crate::update::{merge_cbo_roaring_bitmaps, MergeFn};
 use crate::{
-    CboRoaringBitmapCodec, CboRoaringBitmapLenCodec, Result, SearchContext, U8StrStrCodec,
+    CboRoaringBitmapCodec, CboRoaringBitmapLenCodec, FieldId, Result, SearchContext, U8StrStrCodec,
 };
 
 /// A cache storing pointers to values in the LMDB databases.
@@ -21,6 +26,9 @@ use crate::{
 /// Used for performance reasons only. By using this cache, we avoid performing a
 /// database lookup and instead get a direct reference to the value using a fast
 /// local HashMap lookup.
+///
+/// The values borrowed from the databases are only valid for the transaction the
+/// cache was filled with, which is the `'ctx` transaction of its search context.
 #[derive(Default)]
 pub struct DatabaseCache<'ctx> {
     pub word_pair_proximity_docids:
@@ -44,8 +52,69 @@ pub struct DatabaseCache<'ctx> {
     pub word_prefix_fid_docids: FxHashMap<(Interned<String>, u16), Option<Cow<'ctx, [u8]>>>,
     pub word_fids: FxHashMap<Interned<String>, Vec<u16>>,
     pub word_prefix_fids: FxHashMap<Interned<String>, Vec<u16>>,
+
+    pub facet_number_docids: FxHashMap<FieldId, Vec<(f64, RoaringBitmap)>>,
+    pub facet_string_docids: FxHashMap<FieldId, BTreeMap<String, RoaringBitmap>>,
+
+    /// The number of word lookups skipped because the word filter doesn't contain the word.
+    pub skipped_word_lookups: usize,
+}
+/// A database cache shared by the successive search contexts created on the same
+/// transaction, along with the query graphs of their queries and the interners of the
+/// words, phrases, and terms its keys and the graphs refer to.
+///
+/// It is held by the caller between the calls to [`crate::Search::execute_with_cache`],
+/// [`crate::Search::facet_distribution_with_cache`] or
+/// [`crate::Search::sortable_field_extent`], which must all be made on the same transaction.
+/// The interners only grow as the searches intern new words, so the cache is emptied before a
+/// search once they hold more than a bounded number of values.
+#[derive(Default)]
+pub struct SharedDatabaseCache<'ctx> {
+    pub(crate) word_interner: DedupInterner<String>,
+    pub(crate) phrase_interner: DedupInterner<Phrase>,
+    pub(crate) term_interner: Interner<QueryTerm>,
+    pub(crate) db_cache: DatabaseCache<'ctx>,
+    /// The query graphs built by the previous searches, `None` until a search is executed.
+    pub(crate) query_graph_cache: Option<QueryGraphCache>,
+}
+
+impl SharedDatabaseCache<'_> {
+    /// Whether one of the interners holds too many values to be given to the next search.
+    pub(crate) fn is_full(&self) -> bool {
+        let max = limits::SHARED_INTERNED_VALUE_COUNT;
+        self.word_interner.len() > max
+            || self.phrase_interner.len() > max
+            || self.term_interner.len() as usize > max
+    }
 }
+
 impl<'ctx> DatabaseCache<'ctx> {
+    /// The number of values read from the databases and kept in this cache, which is the
+    /// number of distinct lookups made through it, not the number of times they were made.
+    pub(crate) fn cached_values(&self) -> u64 {
+        let lens = [
+            self.word_pair_proximity_docids.len(),
+            self.word_prefix_pair_proximity_docids.len(),
+            self.prefix_word_pair_proximity_docids.len(),
+            self.word_docids.len(),
+            self.exact_word_docids.len(),
+            self.word_prefix_docids.len(),
+            self.exact_word_prefix_docids.len(),
+            self.words_fst.is_some() as usize,
+            self.word_position_docids.len(),
+            self.word_prefix_position_docids.len(),
+            self.word_positions.len(),
+            self.word_prefix_positions.len(),
+            self.word_fid_docids.len(),
+            self.word_prefix_fid_docids.len(),
+            self.word_fids.len(),
+            self.word_prefix_fids.len(),
+            self.facet_number_docids.len(),
+            self.facet_string_docids.len(),
+        ];
+        lens.iter().sum::<usize>() as u64
+    }
+
     fn get_value<'v, K1, KC>(
         txn: &'ctx RoTxn<'_>,
         cache_key: K1,
@@ -176,6 +245,13 @@ impl<'ctx> SearchContext<'ctx> {
         universe: Option<&RoaringBitmap>,
         word: Word,
     ) -> Result<Option<RoaringBitmap>> {
+        if let Some(word_filter) = self.word_filter {
+            if !word_filter.may_contain(self.word_interner.get(word.interned())) {
+                self.db_cache.skipped_word_lookups += 1;
+                return Ok(None);
+            }
+        }
+
         match word {
             Word::Original(word) => {
                 let exact = self.get_db_exact_word_docids(universe, word)?;
@@ -362,20 +438,28 @@ impl<'ctx> SearchContext<'ctx> {
                 let docids = if let Some(docids) =
                     self.db_cache.word_pair_proximity_docids.get(&(proximity, word1, word2))
                 {
-                    docids
-                        .as_ref()
-                        .map(|d| CboRoaringBitmapCodec::bytes_decode_owned(d))
-                        .transpose()
-                        .map_err(heed::Error::Decoding)?
+                    match (docids, universe) {
+                        // Only decode the containers of the bitmap that intersect with the universe.
+                        (Some(bytes), Some(universe)) => Some(
+                            CboRoaringBitmapCodec::intersection_with_serialized(bytes, universe)?,
+                        ),
+                        (Some(bytes), None) => Some(
+                            CboRoaringBitmapCodec::bytes_decode_owned(bytes)
+                                .map_err(heed::Error::Decoding)?,
+                        ),
+                        (None, _) => None,
+                    }
                 } else {
                     // Compute the distance at the attribute level and store it in the cache.
+                    // The cached bitmap must not depend on the universe as it will be reused
+                    // by the following calls with different universes.
                     let fids = self.index.searchable_fields_ids(self.txn)?;
                     let mut docids = RoaringBitmap::new();
                     for fid in fids {
                         // for each field, intersect left word bitmap and right word bitmap,
                         // then merge the result in a global bitmap before storing it in the cache.
-                        let word1_docids = self.get_db_word_fid_docids(universe, word1, fid)?;
-                        let word2_docids = self.get_db_word_fid_docids(universe, word2, fid)?;
+                        let word1_docids = self.get_db_word_fid_docids(None, word1, fid)?;
+                        let word2_docids = self.get_db_word_fid_docids(None, word2, fid)?;
                         if let (Some(word1_docids), Some(word2_docids)) =
                             (word1_docids, word2_docids)
                         {
@@ -390,6 +474,9 @@ impl<'ctx> SearchContext<'ctx> {
                     self.db_cache
                         .word_pair_proximity_docids
                         .insert((proximity, word1, word2), encoded);
+                    if let Some(universe) = universe {
+                        docids &= universe;
+                    }
                     Some(docids)
                 };
 
@@ -703,4 +790,60 @@ impl<'ctx> SearchContext<'ctx> {
         };
         Ok(positions)
     }
+
+    /// Retrieve the docids of every number facet value of the given field, in ascending
+    /// order, and store them in the cache.
+    pub fn get_db_facet_number_docids(
+        &mut self,
+        field_id: FieldId,
+    ) -> Result<&[(f64, RoaringBitmap)]> {
+        match self.db_cache.facet_number_docids.entry(field_id) {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => {
+                let mut values = Vec::new();
+                let prefix = FacetGroupKey { field_id, level: 0, left_bound: &[][..] };
+                let iter = self
+                    .index
+                    .facet_id_f64_docids
+                    .remap_key_type::<FacetGroupKeyCodec<BytesRefCodec>>()
+                    .prefix_iter(self.txn, &prefix)?;
+                for result in iter {
+                    let (FacetGroupKey { left_bound, .. }, FacetGroupValue { bitmap, .. }) =
+                        result?;
+                    let value =
+                        OrderedF64Codec::bytes_decode(left_bound).map_err(heed::Error::Decoding)?;
+                    values.push((value, bitmap));
+                }
+                Ok(entry.insert(values))
+            }
+        }
+    }
+
+    /// Retrieve the docids of every string facet value of the given field, keyed by their
+    /// original (non-normalized) value, and store them in the cache.
+    pub fn get_db_facet_string_docids(
+        &mut self,
+        field_id: FieldId,
+    ) -> Result<&BTreeMap<String, RoaringBitmap>> {
+        match self.db_cache.facet_string_docids.entry(field_id) {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => {
+                let mut values: BTreeMap<String, RoaringBitmap> = BTreeMap::new();
+                let prefix = FacetGroupKey { field_id, level: 0, left_bound: "" };
+                for result in self.index.facet_id_string_docids.prefix_iter(self.txn, &prefix)? {
+                    let (FacetGroupKey { left_bound, .. }, FacetGroupValue { bitmap, .. }) =
+                        result?;
+                    let Some(any_docid) = bitmap.min() else { continue };
+                    // the database only stores the normalized value, fetch an original one
+                    let original = self
+                        .index
+                        .field_id_docid_facet_strings
+                        .get(self.txn, &(field_id, any_docid, left_bound))?
+                        .unwrap_or(left_bound);
+                    *values.entry(original.to_owned()).or_default() |= bitmap;
+                }
+                Ok(entry.insert(values))
+            }
+        }
+    }
 }
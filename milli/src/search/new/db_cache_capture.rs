@@ -0,0 +1,221 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::hash::Hash;
+
+use fxhash::FxHashMap;
+use roaring::RoaringBitmap;
+use serde::{Deserialize, Serialize};
+
+use super::interner::{DedupInterner, Interned};
+use crate::{FieldId, Result, SearchContext};
+
+/// The values read from the databases by the searches of a [`SearchContext`], keyed by
+/// words instead of interned words, that can be serialized to reproduce a search without
+/// the databases it read from.
+///
+/// Only the reads served by the `DatabaseCache` of the context are
+/// captured: the settings, the fields ids map and the universe of the search are still read
+/// from the index the capture is replayed on, which must have the same settings.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DatabaseCacheCapture {
+    word_pair_proximity_docids: Vec<((u8, String, String), Option<Vec<u8>>)>,
+    word_prefix_pair_proximity_docids: Vec<((u8, String, String), Option<RoaringBitmap>)>,
+    prefix_word_pair_proximity_docids: Vec<((u8, String, String), Option<Vec<u8>>)>,
+    word_docids: Vec<(String, Option<Vec<u8>>)>,
+    exact_word_docids: Vec<(String, Option<Vec<u8>>)>,
+    word_prefix_docids: Vec<(String, Option<Vec<u8>>)>,
+    exact_word_prefix_docids: Vec<(String, Option<Vec<u8>>)>,
+    words_fst: Option<Vec<u8>>,
+    word_position_docids: Vec<((String, u16), Option<Vec<u8>>)>,
+    word_prefix_position_docids: Vec<((String, u16), Option<Vec<u8>>)>,
+    word_positions: Vec<(String, Vec<u16>)>,
+    word_prefix_positions: Vec<(String, Vec<u16>)>,
+    word_fid_docids: Vec<((String, u16), Option<Vec<u8>>)>,
+    word_prefix_fid_docids: Vec<((String, u16), Option<Vec<u8>>)>,
+    word_fids: Vec<(String, Vec<u16>)>,
+    word_prefix_fids: Vec<(String, Vec<u16>)>,
+    facet_number_docids: Vec<(FieldId, Vec<(f64, RoaringBitmap)>)>,
+    facet_string_docids: Vec<(FieldId, BTreeMap<String, RoaringBitmap>)>,
+}
+
+/// A key of the database cache, made of interned words.
+trait CacheKey: Copy + Eq + Hash {
+    type Words;
+
+    fn to_words(self, interner: &DedupInterner<String>) -> Self::Words;
+    fn from_words(words: &Self::Words, interner: &mut DedupInterner<String>) -> Self;
+}
+
+impl CacheKey for Interned<String> {
+    type Words = String;
+
+    fn to_words(self, interner: &DedupInterner<String>) -> String {
+        interner.get(self).clone()
+    }
+
+    fn from_words(word: &String, interner: &mut DedupInterner<String>) -> Self {
+        interner.insert(word.clone())
+    }
+}
+
+impl CacheKey for (Interned<String>, u16) {
+    type Words = (String, u16);
+
+    fn to_words(self, interner: &DedupInterner<String>) -> (String, u16) {
+        (self.0.to_words(interner), self.1)
+    }
+
+    fn from_words((word, n): &(String, u16), interner: &mut DedupInterner<String>) -> Self {
+        (Interned::from_words(word, interner), *n)
+    }
+}
+
+impl CacheKey for (u8, Interned<String>, Interned<String>) {
+    type Words = (u8, String, String);
+
+    fn to_words(self, interner: &DedupInterner<String>) -> (u8, String, String) {
+        (self.0, self.1.to_words(interner), self.2.to_words(interner))
+    }
+
+    fn from_words(
+        (proximity, left, right): &(u8, String, String),
+        interner: &mut DedupInterner<String>,
+    ) -> Self {
+        (*proximity, Interned::from_words(left, interner), Interned::from_words(right, interner))
+    }
+}
+
+fn capture_bytes<K: CacheKey>(
+    interner: &DedupInterner<String>,
+    cache: &FxHashMap<K, Option<Cow<'_, [u8]>>>,
+) -> Vec<(K::Words, Option<Vec<u8>>)> {
+    cache
+        .iter()
+        .map(|(key, value)| (key.to_words(interner), value.as_deref().map(<[u8]>::to_vec)))
+        .collect()
+}
+
+fn capture_values<K: CacheKey, V: Clone>(
+    interner: &DedupInterner<String>,
+    cache: &FxHashMap<K, V>,
+) -> Vec<(K::Words, V)> {
+    cache.iter().map(|(key, value)| (key.to_words(interner), value.clone())).collect()
+}
+
+fn replay_bytes<'ctx, K: CacheKey>(
+    interner: &mut DedupInterner<String>,
+    captured: &[(K::Words, Option<Vec<u8>>)],
+    cache: &mut FxHashMap<K, Option<Cow<'ctx, [u8]>>>,
+) {
+    for (words, value) in captured {
+        let key = K::from_words(words, interner);
+        cache.insert(key, value.clone().map(Cow::Owned));
+    }
+}
+
+fn replay_values<K: CacheKey, V: Clone>(
+    interner: &mut DedupInterner<String>,
+    captured: &[(K::Words, V)],
+    cache: &mut FxHashMap<K, V>,
+) {
+    for (words, value) in captured {
+        let key = K::from_words(words, interner);
+        cache.insert(key, value.clone());
+    }
+}
+
+impl<'ctx> SearchContext<'ctx> {
+    /// Capture the values read from the databases by the searches executed with this context.
+    pub fn capture_db_cache(&self) -> DatabaseCacheCapture {
+        let interner = &self.word_interner;
+        let cache = &self.db_cache;
+        DatabaseCacheCapture {
+            word_pair_proximity_docids: capture_bytes(interner, &cache.word_pair_proximity_docids),
+            word_prefix_pair_proximity_docids: capture_values(
+                interner,
+                &cache.word_prefix_pair_proximity_docids,
+            ),
+            prefix_word_pair_proximity_docids: capture_bytes(
+                interner,
+                &cache.prefix_word_pair_proximity_docids,
+            ),
+            word_docids: capture_bytes(interner, &cache.word_docids),
+            exact_word_docids: capture_bytes(interner, &cache.exact_word_docids),
+            word_prefix_docids: capture_bytes(interner, &cache.word_prefix_docids),
+            exact_word_prefix_docids: capture_bytes(interner, &cache.exact_word_prefix_docids),
+            words_fst: cache.words_fst.as_ref().map(|fst| fst.as_fst().as_bytes().to_vec()),
+            word_position_docids: capture_bytes(interner, &cache.word_position_docids),
+            word_prefix_position_docids: capture_bytes(
+                interner,
+                &cache.word_prefix_position_docids,
+            ),
+            word_positions: capture_values(interner, &cache.word_positions),
+            word_prefix_positions: capture_values(interner, &cache.word_prefix_positions),
+            word_fid_docids: capture_bytes(interner, &cache.word_fid_docids),
+            word_prefix_fid_docids: capture_bytes(interner, &cache.word_prefix_fid_docids),
+            word_fids: capture_values(interner, &cache.word_fids),
+            word_prefix_fids: capture_values(interner, &cache.word_prefix_fids),
+            facet_number_docids: cache
+                .facet_number_docids
+                .iter()
+                .map(|(fid, values)| (*fid, values.clone()))
+                .collect(),
+            facet_string_docids: cache
+                .facet_string_docids
+                .iter()
+                .map(|(fid, values)| (*fid, values.clone()))
+                .collect(),
+        }
+    }
+
+    /// Fill the database cache of this context with the captured values, which are then
+    /// returned instead of the values of the databases.
+    ///
+    /// A search executed with this context returns the same results as the captured one,
+    /// as long as it is executed on the same universe with the same parameters.
+    pub fn replay_db_cache(&mut self, capture: &DatabaseCacheCapture) -> Result<()> {
+        let interner = &mut self.word_interner;
+        let cache = &mut self.db_cache;
+        replay_bytes(
+            interner,
+            &capture.word_pair_proximity_docids,
+            &mut cache.word_pair_proximity_docids,
+        );
+        replay_values(
+            interner,
+            &capture.word_prefix_pair_proximity_docids,
+            &mut cache.word_prefix_pair_proximity_docids,
+        );
+        replay_bytes(
+            interner,
+            &capture.prefix_word_pair_proximity_docids,
+            &mut cache.prefix_word_pair_proximity_docids,
+        );
+        replay_bytes(interner, &capture.word_docids, &mut cache.word_docids);
+        replay_bytes(interner, &capture.exact_word_docids, &mut cache.exact_word_docids);
+        replay_bytes(interner, &capture.word_prefix_docids, &mut cache.word_prefix_docids);
+        replay_bytes(
+            interner,
+            &capture.exact_word_prefix_docids,
+            &mut cache.exact_word_prefix_docids,
+        );
+        if let Some(bytes) = &capture.words_fst {
+            cache.words_fst = Some(fst::Set::new(Cow::Owned(bytes.clone()))?);
+        }
+        replay_bytes(interner, &capture.word_position_docids, &mut cache.word_position_docids);
+        replay_bytes(
+            interner,
+            &capture.word_prefix_position_docids,
+            &mut cache.word_prefix_position_docids,
+        );
+        replay_values(interner, &capture.word_positions, &mut cache.word_positions);
+        replay_values(interner, &capture.word_prefix_positions, &mut cache.word_prefix_positions);
+        replay_bytes(interner, &capture.word_fid_docids, &mut cache.word_fid_docids);
+        replay_bytes(interner, &capture.word_prefix_fid_docids, &mut cache.word_prefix_fid_docids);
+        replay_values(interner, &capture.word_fids, &mut cache.word_fids);
+        replay_values(interner, &capture.word_prefix_fids, &mut cache.word_prefix_fids);
+        cache.facet_number_docids.extend(capture.facet_number_docids.iter().cloned());
+        cache.facet_string_docids.extend(capture.facet_string_docids.iter().cloned());
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+
+use super::distinct::{facet_number_values, facet_string_values};
+use crate::{DocumentId, FieldId, Result, SearchContext};
+
+/// The normalized facet value documents are grouped by.
+#[derive(PartialEq, Eq, Hash)]
+enum GroupKey {
+    Number(Vec<u8>),
+    String(Vec<u8>),
+}
+
+/// Return the order in which the given ranked documents must be returned so that at most
+/// `max_per_group` of them share the same value for the given field before all the others,
+/// along with the number of documents that were not pushed down.
+///
+/// The relative order of the documents is preserved: the documents that exceed the limit of
+/// their group are moved, in order, after all the documents that don't. The documents are
+/// grouped by the normalized value of the field in the facet databases, its smallest one when
+/// the field has several values, so only filterable or sortable fields can group documents.
+/// The documents that don't have a value for the field are never considered as part of a group.
+pub fn diversify_positions(
+    ctx: &SearchContext<'_>,
+    field: &str,
+    max_per_group: usize,
+    docids: &[DocumentId],
+) -> Result<(Vec<usize>, usize)> {
+    let Some(fid) = ctx.index.fields_ids_map(ctx.txn)?.id(field) else {
+        return Ok(((0..docids.len()).collect(), docids.len()));
+    };
+
+    let mut group_sizes: HashMap<GroupKey, usize> = HashMap::new();
+    let mut positions = Vec::with_capacity(docids.len());
+    let mut pushed_down = Vec::new();
+    for (position, &docid) in docids.iter().enumerate() {
+        match group_key(ctx, fid, docid)? {
+            Some(key) => {
+                let group_size = group_sizes.entry(key).or_default();
+                if *group_size < max_per_group {
+                    *group_size += 1;
+                    positions.push(position);
+                } else {
+                    pushed_down.push(position);
+                }
+            }
+            None => positions.push(position),
+        }
+    }
+
+    let kept = positions.len();
+    positions.extend(pushed_down);
+    Ok((positions, kept))
+}
+
+/// Return the smallest normalized number value of the field of the document, or else its
+/// smallest normalized string value.
+fn group_key(ctx: &SearchContext<'_>, fid: FieldId, docid: DocumentId) -> Result<Option<GroupKey>> {
+    if let Some(entry) = facet_number_values(docid, fid, ctx.index, ctx.txn)?.next() {
+        let ((_, _, value), _) = entry?;
+        return Ok(Some(GroupKey::Number(value.to_vec())));
+    }
+    if let Some(entry) = facet_string_values(docid, fid, ctx.index, ctx.txn)?.next() {
+        let ((_, _, value), _) = entry?;
+        return Ok(Some(GroupKey::String(value.to_vec())));
+    }
+    Ok(None)
+}
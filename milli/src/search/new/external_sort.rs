@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use roaring::RoaringBitmap;
+
+use super::logger::SearchLogger;
+use super::{RankingRule, RankingRuleOutput, RankingRuleQueryTrait, SearchContext};
+use crate::score_details::{self, ScoreDetails};
+use crate::{DocumentId, Result};
+
+/// A sort ranking rule reading the value of each document from a map provided
+/// by the caller instead of the facet databases.
+///
+/// The documents missing from the map are returned in the last bucket.
+pub struct ExternalSort<Query> {
+    field_name: String,
+    values: Arc<HashMap<DocumentId, f64>>,
+    is_ascending: bool,
+    original_query: Option<Query>,
+    /// The remaining buckets of the current iteration, the next one being the last.
+    buckets: Vec<(f64, RoaringBitmap)>,
+}
+
+impl<Query> ExternalSort<Query> {
+    pub fn new(
+        field_name: String,
+        values: Arc<HashMap<DocumentId, f64>>,
+        is_ascending: bool,
+    ) -> Self {
+        Self { field_name, values, is_ascending, original_query: None, buckets: Vec::new() }
+    }
+
+    fn score(&self, value: Option<f64>) -> ScoreDetails {
+        let value = value
+            .and_then(serde_json::Number::from_f64)
+            .map_or(serde_json::Value::Null, serde_json::Value::Number);
+        ScoreDetails::Sort(score_details::Sort {
+            field_name: self.field_name.clone(),
+            ascending: self.is_ascending,
+            redacted: false,
+            value,
+        })
+    }
+}
+
+impl<'ctx, Query: RankingRuleQueryTrait> RankingRule<'ctx, Query> for ExternalSort<Query> {
+    fn id(&self) -> String {
+        let Self { field_name, is_ascending, .. } = self;
+        format!("{field_name}:{}", if *is_ascending { "asc" } else { "desc" })
+    }
+
+    #[tracing::instrument(level = "trace", skip_all, target = "search::sort")]
+    fn start_iteration(
+        &mut self,
+        _ctx: &mut SearchContext<'ctx>,
+        _logger: &mut dyn SearchLogger<Query>,
+        parent_candidates: &RoaringBitmap,
+        parent_query: &Query,
+    ) -> Result<()> {
+        let mut values: Vec<_> = parent_candidates
+            .iter()
+            .filter_map(|docid| self.values.get(&docid).map(|value| (*value, docid)))
+            .collect();
+        // the buckets are popped from the end of the list
+        if self.is_ascending {
+            values.sort_unstable_by(|(left, _), (right, _)| right.total_cmp(left));
+        } else {
+            values.sort_unstable_by(|(left, _), (right, _)| left.total_cmp(right));
+        }
+
+        let mut buckets: Vec<(f64, RoaringBitmap)> = Vec::new();
+        for (value, docid) in values {
+            match buckets.last_mut() {
+                Some((last_value, docids)) if last_value.total_cmp(&value).is_eq() => {
+                    docids.insert(docid);
+                }
+                _ => buckets.push((value, RoaringBitmap::from_iter([docid]))),
+            }
+        }
+
+        self.original_query = Some(parent_query.clone());
+        self.buckets = buckets;
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "trace", skip_all, target = "search::sort")]
+    fn next_bucket(
+        &mut self,
+        _ctx: &mut SearchContext<'ctx>,
+        _logger: &mut dyn SearchLogger<Query>,
+        universe: &RoaringBitmap,
+    ) -> Result<Option<RankingRuleOutput<Query>>> {
+        let query = self.original_query.as_ref().unwrap().clone();
+        while let Some((value, mut candidates)) = self.buckets.pop() {
+            candidates &= universe;
+            if !candidates.is_empty() {
+                return Ok(Some(RankingRuleOutput {
+                    query,
+                    candidates,
+                    score: self.score(Some(value)),
+                }));
+            }
+        }
+
+        // the documents without any value are all returned in the last bucket
+        Ok(Some(RankingRuleOutput { query, candidates: universe.clone(), score: self.score(None) }))
+    }
+
+    #[tracing::instrument(level = "trace", skip_all, target = "search::sort")]
+    fn end_iteration(
+        &mut self,
+        _ctx: &mut SearchContext<'ctx>,
+        _logger: &mut dyn SearchLogger<Query>,
+    ) {
+        self.original_query = None;
+        self.buckets.clear();
+    }
+}
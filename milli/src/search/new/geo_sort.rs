@@ -1,22 +1,49 @@
-use std::collections::VecDeque;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::f64::consts::{FRAC_PI_2, PI};
 use std::iter::FromIterator;
 
 use heed::types::{Bytes, Unit};
 use heed::{RoPrefix, RoTxn};
+use rayon::iter::ParallelIterator;
+use rayon::slice::ParallelSlice;
 use roaring::RoaringBitmap;
-use rstar::RTree;
+use rstar::{RTree, AABB};
+use serde::{Deserialize, Serialize};
 
 use super::facet_string_values;
 use super::ranking_rules::{RankingRule, RankingRuleOutput, RankingRuleQueryTrait};
-use crate::heed_codec::facet::{FieldDocIdFacetCodec, OrderedF64Codec};
+use crate::heed_codec::facet::{
+    FacetGroupKey, FacetGroupKeyCodec, FacetGroupValue, FieldDocIdFacetCodec, OrderedF64Codec,
+};
+use crate::heed_codec::BytesRefCodec;
 use crate::score_details::{self, ScoreDetails};
+use crate::search::facet::get_highest_level;
 use crate::{
-    distance_between_two_points, lat_lng_to_xyz, GeoPoint, Index, Result, SearchContext,
-    SearchLogger,
+    distance_between_two_points, lat_lng_to_xyz, AscDesc, DocumentId, GeoPoint, Index, Member,
+    Result, SearchContext, SearchLogger, UserError,
 };
 
 const FID_SIZE: usize = 2;
 const DOCID_SIZE: usize = 4;
+/// The number of documents read by the iterative strategy between two checks of the
+/// cancellation of the search.
+const CANCEL_CHECK_INTERVAL: usize = 1024;
+/// The distance, in meters, around the sorted shape in which the rtree is first searched.
+const SHAPE_ENVELOPE_INITIAL_RADIUS: f64 = 1_000.0;
+/// The polar radius of the earth, lower than the one of the haversine distance, to convert
+/// the distances in meters to angles that are never too small.
+const EARTH_RADIUS_LOWER_BOUND: f64 = 6_356_752.0;
+/// The rounding errors the envelopes of the rtree are widened by.
+const ENVELOPE_EPSILON: f64 = 1e-9;
+
+// The rtree is deserialized from the index by each geo sort and only traversed through shared
+// references, thus the searches running in parallel on the same index don't wait for each
+// other to read it. It must stay possible to share it between threads.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<RTree<GeoPoint>>();
+};
 
 #[allow(clippy::drop_non_drop)]
 fn facet_values_prefix_key(distinct: u16, id: u32) -> [u8; FID_SIZE + DOCID_SIZE] {
@@ -44,7 +71,8 @@ fn facet_number_values<'a>(
 /// Define the strategy used by the geo sort.
 /// The parameter represents the cache size, and, in the case of the Dynamic strategy,
 /// the point where we move from using the iterative strategy to the rtree.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum Strategy {
     AlwaysIterative(usize),
     AlwaysRtree(usize),
@@ -73,16 +101,60 @@ impl Strategy {
     }
 }
 
+/// Return the documents the geo sort can sort, the ones with a `_geo` field or with a
+/// number in both of the geo fields of the context.
+///
+/// The rtree is only built on `_geo`, thus a strategy always using it is rejected when
+/// the coordinates are read from other fields.
+pub(super) fn geo_faceted_docids(
+    ctx: &SearchContext<'_>,
+    strategy: Strategy,
+) -> Result<RoaringBitmap> {
+    let Some((lat, lng)) = &ctx.geo_fields else {
+        return Ok(ctx.index.geo_faceted_documents_ids(ctx.txn)?);
+    };
+    if let Strategy::AlwaysRtree(_) = strategy {
+        return Err(UserError::GeoRtreeOnCustomFields(lat.clone(), lng.clone()).into());
+    }
+
+    let fid_map = ctx.index.fields_ids_map(ctx.txn)?;
+    let (Some(lat), Some(lng)) = (fid_map.id(lat), fid_map.id(lng)) else {
+        return Ok(RoaringBitmap::new());
+    };
+    Ok(number_faceted_docids(ctx.index, ctx.txn, lat)?
+        & number_faceted_docids(ctx.index, ctx.txn, lng)?)
+}
+
+/// Return the documents having at least one number in the given field.
+fn number_faceted_docids(index: &Index, rtxn: &RoTxn<'_>, field_id: u16) -> Result<RoaringBitmap> {
+    let db = index.facet_id_f64_docids.remap_key_type::<FacetGroupKeyCodec<BytesRefCodec>>();
+    // the groups of the highest level cover all the documents of the field
+    let level = get_highest_level(rtxn, db, field_id)?;
+    let prefix = FacetGroupKey { field_id, level, left_bound: &[][..] };
+    let mut docids = RoaringBitmap::new();
+    for result in db.prefix_iter(rtxn, &prefix)? {
+        let (_, FacetGroupValue { bitmap, .. }) = result?;
+        docids |= bitmap;
+    }
+    Ok(docids)
+}
+
 pub struct GeoSort<Q: RankingRuleQueryTrait> {
     query: Option<Q>,
 
     strategy: Strategy,
     ascending: bool,
     point: [f64; 2],
+    altitude: Option<f64>,
+    /// The shape the documents are sorted by their distance to, instead of `point`.
+    shape: Option<Shape>,
     field_ids: Option<[u16; 2]>,
+    /// The field id of `_geo.alt`, only set if the sort is in 3D and some documents have an altitude.
+    altitude_field_id: Option<u16>,
+    /// The rtree of the index, owned by this geo sort and never modified once read.
     rtree: Option<RTree<GeoPoint>>,
 
-    cached_sorted_docids: VecDeque<(u32, [f64; 2])>,
+    cached_sorted_docids: VecDeque<(u32, [f64; 2], Option<f64>)>,
     geo_candidates: RoaringBitmap,
 }
 
@@ -91,6 +163,7 @@ impl<Q: RankingRuleQueryTrait> GeoSort<Q> {
         strategy: Strategy,
         geo_faceted_docids: RoaringBitmap,
         point: [f64; 2],
+        altitude: Option<f64>,
         ascending: bool,
     ) -> Result<Self> {
         Ok(Self {
@@ -98,13 +171,56 @@ impl<Q: RankingRuleQueryTrait> GeoSort<Q> {
             strategy,
             ascending,
             point,
+            altitude,
+            shape: None,
             geo_candidates: geo_faceted_docids,
             field_ids: None,
+            altitude_field_id: None,
             rtree: None,
             cached_sorted_docids: VecDeque::new(),
         })
     }
 
+    /// Create a geo sort ordering the documents by their distance to the nearest segment
+    /// of the given route, which must contain at least two points.
+    pub fn new_route(
+        strategy: Strategy,
+        geo_faceted_docids: RoaringBitmap,
+        route: Vec<[f64; 2]>,
+        ascending: bool,
+    ) -> Result<Self> {
+        let mut geo_sort = Self::new(strategy, geo_faceted_docids, route[0], None, ascending)?;
+        geo_sort.shape = Some(Shape::Route(route));
+        Ok(geo_sort)
+    }
+
+    /// Create a geo sort ordering the documents by their distance to the nearest vertex
+    /// of the given polygon, which must contain at least one vertex.
+    pub fn new_polygon_vertices(
+        strategy: Strategy,
+        geo_faceted_docids: RoaringBitmap,
+        vertices: Vec<[f64; 2]>,
+        ascending: bool,
+    ) -> Result<Self> {
+        let mut geo_sort = Self::new(strategy, geo_faceted_docids, vertices[0], None, ascending)?;
+        geo_sort.shape = Some(Shape::Vertices(vertices));
+        Ok(geo_sort)
+    }
+
+    /// Return the distance between the target of the sort and the given point.
+    fn distance_to(&self, point: &[f64; 2], altitude: Option<f64>) -> f64 {
+        distance_to_target(&self.point, self.altitude, self.shape.as_ref(), point, altitude)
+    }
+
+    /// Return the point of the target the given point is compared with: the sorted point,
+    /// or the nearest point of the shape.
+    fn target_of(&self, point: &[f64; 2]) -> [f64; 2] {
+        match &self.shape {
+            Some(shape) => shape.nearest_point(point).0,
+            None => self.point,
+        }
+    }
+
     /// Refill the internal buffer of cached docids based on the strategy.
     /// Drop the rtree if we don't need it anymore.
     fn fill_buffer(
@@ -115,8 +231,15 @@ impl<Q: RankingRuleQueryTrait> GeoSort<Q> {
         debug_assert!(self.field_ids.is_some(), "fill_buffer can't be called without the lat&lng");
         debug_assert!(self.cached_sorted_docids.is_empty());
 
+        // the rtree only knows about the horizontal position of the documents, which is enough
+        // to find the closest documents in 3D but not the farthest ones, and it is only built
+        // on the `_geo` field.
+        let use_rtree = self.strategy.use_rtree(geo_candidates.len() as usize)
+            && (self.ascending || self.altitude_field_id.is_none())
+            && ctx.geo_fields.is_none();
+
         // lazily initialize the rtree if needed by the strategy, and cache it in `self.rtree`
-        let rtree = if self.strategy.use_rtree(geo_candidates.len() as usize) {
+        let rtree = if use_rtree {
             if let Some(rtree) = self.rtree.as_ref() {
                 // get rtree from cache
                 Some(rtree)
@@ -132,11 +255,96 @@ impl<Q: RankingRuleQueryTrait> GeoSort<Q> {
 
         let cache_size = self.strategy.cache_size();
         if let Some(rtree) = rtree {
-            if self.ascending {
+            if let Some(shape) = &self.shape {
+                // the rtree gives the coordinates of the documents without reading them. The
+                // documents in an envelope around the shape are sorted, and the envelope grows
+                // until enough documents are closer to the shape than its margin, the documents
+                // outside of it being farther. The farthest documents can be anywhere.
+                let mut radius = SHAPE_ENVELOPE_INITIAL_RADIUS;
+                loop {
+                    let envelope = if self.ascending { shape.envelope(radius) } else { None };
+                    let points: Box<dyn Iterator<Item = &GeoPoint>> = match &envelope {
+                        Some(envelope) => Box::new(rtree.locate_in_envelope(envelope)),
+                        None => Box::new(rtree.iter()),
+                    };
+                    let mut documents: Vec<_> = points
+                        .map(|point| point.data)
+                        .filter(|(docid, _)| geo_candidates.contains(*docid))
+                        .map(|(docid, lat_lng)| (shape.distance(&lat_lng), docid, lat_lng))
+                        .collect();
+                    // ties are broken by docid, like in the iterative strategy
+                    documents.sort_unstable_by_key(|&(distance, docid, _)| (distance, docid));
+
+                    // the distances are truncated, a document outside of the envelope can be
+                    // as far as its margin but not closer
+                    let within = match envelope {
+                        Some(_) => documents
+                            .partition_point(|&(distance, _, _)| (distance as f64) < radius),
+                        None => documents.len(),
+                    };
+                    if envelope.is_some() && within < cache_size.max(1) {
+                        radius *= 2.0;
+                        continue;
+                    }
+
+                    // the documents farther than the last one to cache can't be part of the cache
+                    let threshold = match documents[..within].get(cache_size.saturating_sub(1)) {
+                        Some(&(distance, _, _)) if self.ascending => distance,
+                        _ => usize::MAX,
+                    };
+                    self.cached_sorted_docids.extend(
+                        documents
+                            .into_iter()
+                            .take_while(|&(distance, _, _)| distance <= threshold)
+                            .map(|(_, docid, lat_lng)| (docid, lat_lng, None)),
+                    );
+                    break;
+                }
+            } else if let Some(alt) = self.altitude_field_id {
+                // the horizontal distance of a document is a lower bound of its 3D distance, thus
+                // the documents closer in 3D than the last one visited horizontally are sorted
+                let point = lat_lng_to_xyz(&self.point);
+                let mut visited = BinaryHeap::new();
+                let mut points = HashMap::new();
+                let mut exhausted = true;
+                for point in rtree.nearest_neighbor_iter(&point) {
+                    let (docid, lat_lng) = point.data;
+                    if !geo_candidates.contains(docid) {
+                        continue;
+                    }
+
+                    let lower_bound = distance_between_two_points(&self.point, &lat_lng) as usize;
+                    while let Some(&Reverse((distance, closest))) = visited.peek() {
+                        if distance >= lower_bound {
+                            break;
+                        }
+                        visited.pop();
+                        let (lat_lng, altitude) = points[&closest];
+                        self.cached_sorted_docids.push_back((closest, lat_lng, altitude));
+                    }
+                    if self.cached_sorted_docids.len() >= cache_size {
+                        exhausted = false;
+                        break;
+                    }
+
+                    let altitude = geo_field_value(docid, alt, ctx.index, ctx.txn)?;
+                    let distance = self.distance_to(&lat_lng, altitude);
+                    visited.push(Reverse((distance as usize, docid)));
+                    points.insert(docid, (lat_lng, altitude));
+                }
+
+                // all the candidates were visited, the remaining ones can be sorted
+                if exhausted {
+                    while let Some(Reverse((_, docid))) = visited.pop() {
+                        let (lat_lng, altitude) = points[&docid];
+                        self.cached_sorted_docids.push_back((docid, lat_lng, altitude));
+                    }
+                }
+            } else if self.ascending {
                 let point = lat_lng_to_xyz(&self.point);
                 for point in rtree.nearest_neighbor_iter(&point) {
                     if geo_candidates.contains(point.data.0) {
-                        self.cached_sorted_docids.push_back(point.data);
+                        self.cached_sorted_docids.push_back((point.data.0, point.data.1, None));
                         if self.cached_sorted_docids.len() >= cache_size {
                             break;
                         }
@@ -148,7 +356,7 @@ impl<Q: RankingRuleQueryTrait> GeoSort<Q> {
                 let point = lat_lng_to_xyz(&opposite_of(self.point));
                 for point in rtree.nearest_neighbor_iter(&point) {
                     if geo_candidates.contains(point.data.0) {
-                        self.cached_sorted_docids.push_front(point.data);
+                        self.cached_sorted_docids.push_front((point.data.0, point.data.1, None));
                         if self.cached_sorted_docids.len() >= cache_size {
                             break;
                         }
@@ -159,49 +367,328 @@ impl<Q: RankingRuleQueryTrait> GeoSort<Q> {
             // the iterative version
             let [lat, lng] = self.field_ids.unwrap();
 
-            let mut documents = geo_candidates
-                .iter()
-                .map(|id| -> Result<_> { Ok((id, geo_value(id, lat, lng, ctx.index, ctx.txn)?)) })
-                .collect::<Result<Vec<(u32, [f64; 2])>>>()?;
-            // computing the distance between two points is expensive thus we cache the result
-            documents
-                .sort_by_cached_key(|(_, p)| distance_between_two_points(&self.point, p) as usize);
-            self.cached_sorted_docids.extend(documents);
+            let mut documents: Vec<(u32, [f64; 2], Option<f64>)> = Vec::new();
+            let mut unreadable = RoaringBitmap::new();
+            for (i, id) in geo_candidates.iter().enumerate() {
+                if i % CANCEL_CHECK_INTERVAL == 0 {
+                    ctx.check_cancelled()?;
+                }
+                let Some(point) = geo_value(id, lat, lng, ctx.index, ctx.txn)? else {
+                    unreadable.insert(id);
+                    continue;
+                };
+                let altitude = match self.altitude_field_id {
+                    Some(alt) => geo_field_value(id, alt, ctx.index, ctx.txn)?,
+                    None => None,
+                };
+                documents.push((id, point, altitude));
+            }
+            // the documents whose coordinates can't be read are sorted with the documents
+            // without coordinates, they would otherwise never be cached
+            self.geo_candidates -= unreadable;
+
+            if ctx.parallelism > 1 {
+                // the database is read by this thread only, each shard only computes distances
+                // on the global thread pool
+                let (point, altitude, shape) = (self.point, self.altitude, self.shape.as_ref());
+                let shard_len = documents.len().div_ceil(ctx.parallelism).max(1);
+                let distances: Vec<usize> = documents
+                    .par_chunks(shard_len)
+                    .flat_map_iter(|shard| {
+                        shard.iter().map(|(_, p, alt)| {
+                            distance_to_target(&point, altitude, shape, p, *alt) as usize
+                        })
+                    })
+                    .collect();
+
+                // ties are broken by position, like `sort_by_cached_key` does
+                let mut positions: Vec<(usize, usize)> =
+                    distances.into_iter().enumerate().map(|(i, distance)| (distance, i)).collect();
+                positions.sort_unstable();
+                self.cached_sorted_docids.extend(positions.into_iter().map(|(_, i)| documents[i]));
+            } else {
+                // computing the distance between two points is expensive thus we cache the result
+                documents.sort_by_cached_key(|(_, p, alt)| self.distance_to(p, *alt) as usize);
+                self.cached_sorted_docids.extend(documents);
+            }
         };
 
         Ok(())
     }
 }
 
-/// Extracts the lat and long values from a single document.
+/// How the distances are grouped into the bands whose documents the geo sort returns together.
+enum DistanceBands {
+    /// Bands of the given width, in meters.
+    Quantum(f64),
+    /// Bands between the given edges, in meters and in increasing order.
+    Edges(Vec<f64>),
+}
+
+impl DistanceBands {
+    /// Return the bands of the context, `None` when the documents are sorted by exact distance.
+    fn from_ctx(ctx: &SearchContext<'_>) -> Option<Self> {
+        let quantum = ctx.geo_distance_quantum;
+        match &ctx.geo_distance_buckets {
+            Some(edges) => Some(Self::Edges(edges.clone())),
+            None if quantum > 0.0 && quantum.is_finite() => Some(Self::Quantum(quantum)),
+            None => None,
+        }
+    }
+
+    fn band_of(&self, distance: f64) -> u64 {
+        match self {
+            Self::Quantum(quantum) => (distance / quantum) as u64,
+            Self::Edges(edges) => edges_band(edges, distance) as u64,
+        }
+    }
+}
+
+/// Return the index of the band between the given edges containing the distance, the bands
+/// including their lower edge, or `edges.len()` when it is beyond the last edge.
+fn edges_band(edges: &[f64], distance: f64) -> usize {
+    edges.partition_point(|edge| *edge <= distance)
+}
+
+impl<Q: RankingRuleQueryTrait> GeoSort<Q> {
+    /// Return the index of the distance band of the given point.
+    fn distance_band(&self, point: &[f64; 2], altitude: Option<f64>, bands: &DistanceBands) -> u64 {
+        bands.band_of(self.distance_to(point, altitude))
+    }
+
+    /// Return the given document along with the following cached documents in the same
+    /// distance band, refilling the cache until a document of another band is found.
+    fn band_candidates(
+        &mut self,
+        ctx: &mut SearchContext<'_>,
+        geo_candidates: &RoaringBitmap,
+        docid: u32,
+        band: u64,
+        bands: &DistanceBands,
+    ) -> Result<RoaringBitmap> {
+        let mut candidates = RoaringBitmap::from_iter([docid]);
+        loop {
+            loop {
+                let next = if self.ascending {
+                    self.cached_sorted_docids.front()
+                } else {
+                    self.cached_sorted_docids.back()
+                };
+                let Some(&(id, point, altitude)) = next else { break };
+                if geo_candidates.contains(id) {
+                    if self.distance_band(&point, altitude, bands) != band {
+                        return Ok(candidates);
+                    }
+                    candidates.insert(id);
+                }
+                if self.ascending {
+                    self.cached_sorted_docids.pop_front();
+                } else {
+                    self.cached_sorted_docids.pop_back();
+                }
+            }
+
+            // the cache is exhausted but the band may continue with the other documents
+            let remaining = geo_candidates - &candidates;
+            if remaining.is_empty() {
+                return Ok(candidates);
+            }
+            self.fill_buffer(ctx, &remaining)?;
+            if self.cached_sorted_docids.is_empty() {
+                return Ok(candidates);
+            }
+        }
+    }
+}
+
+/// Return the distance between two points, in 3D when both of them have an altitude
+/// and on the horizontal plane otherwise.
+fn distance_between(a: &[f64; 2], a_alt: Option<f64>, b: &[f64; 2], b_alt: Option<f64>) -> f64 {
+    let distance = distance_between_two_points(a, b);
+    match a_alt.zip(b_alt) {
+        Some((a_alt, b_alt)) => distance.hypot(a_alt - b_alt),
+        None => distance,
+    }
+}
+
+/// Return the distance between the given point and the sorted point, or the given shape
+/// if any.
+fn distance_to_target(
+    point: &[f64; 2],
+    altitude: Option<f64>,
+    shape: Option<&Shape>,
+    other: &[f64; 2],
+    other_altitude: Option<f64>,
+) -> f64 {
+    match shape {
+        Some(shape) => shape.nearest_point(other).1,
+        None => distance_between(point, altitude, other, other_altitude),
+    }
+}
+
+/// A shape made of several points the documents can be sorted by their distance to.
+#[derive(Debug, Clone)]
+enum Shape {
+    /// A path, the documents are compared with its nearest segment.
+    Route(Vec<[f64; 2]>),
+    /// The vertices of a polygon, the documents are compared with the nearest one.
+    Vertices(Vec<[f64; 2]>),
+}
+
+impl Shape {
+    /// Return the point of the shape nearest to the given point, with its distance to it.
+    fn nearest_point(&self, point: &[f64; 2]) -> ([f64; 2], f64) {
+        match self {
+            Shape::Route(route) => nearest_point_of_route(route, point),
+            Shape::Vertices(vertices) => vertices
+                .iter()
+                .map(|vertex| (*vertex, distance_between_two_points(point, vertex)))
+                .min_by(|(_, left), (_, right)| left.total_cmp(right))
+                .unwrap_or((*point, 0.0)),
+        }
+    }
+
+    /// Return the sort member the shape was created from.
+    fn to_member(&self) -> Member {
+        match self {
+            Shape::Route(route) => Member::GeoRoute(route.clone()),
+            Shape::Vertices(vertices) => Member::GeoPolygonVertices(vertices.clone()),
+        }
+    }
+
+    /// Return the distance between the given point and the shape, truncated to the meter
+    /// like the distances the documents are sorted by.
+    fn distance(&self, point: &[f64; 2]) -> usize {
+        self.nearest_point(point).1 as usize
+    }
+
+    /// Return the envelope, in the coordinates of the rtree, of the points closer than
+    /// `radius` meters to the shape, or `None` when it reaches a pole and covers all the
+    /// longitudes.
+    ///
+    /// The nearest point of a shape is always between the latitudes and longitudes of its
+    /// points, thus the envelope is computed around their bounding box.
+    fn envelope(&self, radius: f64) -> Option<AABB<[f64; 3]>> {
+        let (Shape::Route(points) | Shape::Vertices(points)) = self;
+        let ([min_lat, min_lng], [max_lat, max_lng]) = points.iter().fold(
+            ([f64::INFINITY; 2], [f64::NEG_INFINITY; 2]),
+            |([min_lat, min_lng], [max_lat, max_lng]), [lat, lng]| {
+                ([min_lat.min(*lat), min_lng.min(*lng)], [max_lat.max(*lat), max_lng.max(*lng)])
+            },
+        );
+
+        // the angle seen from the center of the earth between a point and the bounding box
+        let margin = radius / EARTH_RADIUS_LOWER_BOUND;
+        let highest_lat = min_lat.abs().max(max_lat.abs()).to_radians();
+        if margin >= FRAC_PI_2 - highest_lat {
+            return None;
+        }
+        // a degree of longitude is shorter when going away from the equator
+        let lng_margin = (margin.sin() / highest_lat.cos()).asin();
+        let (min_lat, max_lat) = (min_lat.to_radians() - margin, max_lat.to_radians() + margin);
+        let (min_lng, max_lng) =
+            (min_lng.to_radians() - lng_margin, max_lng.to_radians() + lng_margin);
+
+        // the cosine of the latitudes is positive, it scales the cosine and sine of the longitudes
+        let (low_cos_lat, high_cos_lat) = cos_range(min_lat, max_lat);
+        let (low_cos_lng, high_cos_lng) = cos_range(min_lng, max_lng);
+        let (low_sin_lng, high_sin_lng) = cos_range(min_lng - FRAC_PI_2, max_lng - FRAC_PI_2);
+        let x = (
+            (low_cos_lat * low_cos_lng).min(high_cos_lat * low_cos_lng),
+            (low_cos_lat * high_cos_lng).max(high_cos_lat * high_cos_lng),
+        );
+        let y = (
+            (low_cos_lat * low_sin_lng).min(high_cos_lat * low_sin_lng),
+            (low_cos_lat * high_sin_lng).max(high_cos_lat * high_sin_lng),
+        );
+        let z = (min_lat.sin(), max_lat.sin());
+
+        Some(AABB::from_corners(
+            [x.0 - ENVELOPE_EPSILON, y.0 - ENVELOPE_EPSILON, z.0 - ENVELOPE_EPSILON],
+            [x.1 + ENVELOPE_EPSILON, y.1 + ENVELOPE_EPSILON, z.1 + ENVELOPE_EPSILON],
+        ))
+    }
+}
+
+/// Return the lowest and highest cosines of the angles between `min` and `max` radians.
+fn cos_range(min: f64, max: f64) -> (f64, f64) {
+    if max - min >= 2.0 * PI {
+        return (-1.0, 1.0);
+    }
+    let (mut low, mut high) = (min.cos().min(max.cos()), min.cos().max(max.cos()));
+    // the cosine is the highest on the even multiples of π and the lowest on the odd ones
+    let mut multiple = (min / PI).ceil();
+    while multiple * PI <= max {
+        if multiple.rem_euclid(2.0) == 0.0 {
+            high = 1.0;
+        } else {
+            low = -1.0;
+        }
+        multiple += 1.0;
+    }
+    (low, high)
+}
+
+/// Return the point of the route nearest to the given point, with its distance to it.
+///
+/// The segments of the route are interpolated linearly between their latitudes and
+/// longitudes, which is close to the great circle for the short segments of a route.
+fn nearest_point_of_route(route: &[[f64; 2]], point: &[f64; 2]) -> ([f64; 2], f64) {
+    // the projection is computed on a plane tangent to the point, where a degree of
+    // longitude is shorter than a degree of latitude
+    let scale = point[0].to_radians().cos();
+    let to_plane = |[lat, lng]: [f64; 2]| [lat - point[0], (lng - point[1]) * scale];
+
+    let nearest_of_segment = |a: [f64; 2], b: [f64; 2]| {
+        let ([ay, ax], [by, bx]) = (to_plane(a), to_plane(b));
+        let (dy, dx) = (by - ay, bx - ax);
+        let length = dy * dy + dx * dx;
+        let t = if length == 0.0 { 0.0 } else { (-(ay * dy + ax * dx) / length).clamp(0.0, 1.0) };
+        let nearest = [a[0] + t * (b[0] - a[0]), a[1] + t * (b[1] - a[1])];
+        (nearest, distance_between_two_points(point, &nearest))
+    };
+
+    route
+        .windows(2)
+        .map(|segment| nearest_of_segment(segment[0], segment[1]))
+        .min_by(|(_, left), (_, right)| left.total_cmp(right))
+        .unwrap_or_else(|| (route[0], distance_between_two_points(point, &route[0])))
+}
+
+/// Extracts the value of a geo field from a single document, if any.
 ///
 /// If it is not able to find it in the facet number index it will extract it
 /// from the facet string index and parse it as f64 (as the geo extraction behaves).
+fn geo_field_value(
+    docid: u32,
+    geo_field: u16,
+    index: &Index,
+    rtxn: &RoTxn<'_>,
+) -> Result<Option<f64>> {
+    match facet_number_values(docid, geo_field, index, rtxn)?.next() {
+        Some(Ok(((_, _, geo), ()))) => Ok(Some(geo)),
+        Some(Err(e)) => Err(e.into()),
+        None => match facet_string_values(docid, geo_field, index, rtxn)?.next() {
+            Some(Ok((_, geo))) => Ok(geo.parse::<f64>().ok()),
+            Some(Err(e)) => Err(e.into()),
+            None => Ok(None),
+        },
+    }
+}
+
+/// Extracts the lat and long values from a single document, `None` if one of them is missing
+/// or can't be parsed.
 fn geo_value(
     docid: u32,
     field_lat: u16,
     field_lng: u16,
     index: &Index,
     rtxn: &RoTxn<'_>,
-) -> Result<[f64; 2]> {
-    let extract_geo = |geo_field: u16| -> Result<f64> {
-        match facet_number_values(docid, geo_field, index, rtxn)?.next() {
-            Some(Ok(((_, _, geo), ()))) => Ok(geo),
-            Some(Err(e)) => Err(e.into()),
-            None => match facet_string_values(docid, geo_field, index, rtxn)?.next() {
-                Some(Ok((_, geo))) => {
-                    Ok(geo.parse::<f64>().expect("cannot parse geo field as f64"))
-                }
-                Some(Err(e)) => Err(e.into()),
-                None => panic!("A geo faceted document doesn't contain any lat or lng"),
-            },
-        }
-    };
-
-    let lat = extract_geo(field_lat)?;
-    let lng = extract_geo(field_lng)?;
+) -> Result<Option<[f64; 2]>> {
+    let lat = geo_field_value(docid, field_lat, index, rtxn)?;
+    let lng = geo_field_value(docid, field_lng, index, rtxn)?;
 
-    Ok([lat, lng])
+    Ok(lat.zip(lng).map(|(lat, lng)| [lat, lng]))
 }
 
 impl<'ctx, Q: RankingRuleQueryTrait> RankingRule<'ctx, Q> for GeoSort<Q> {
@@ -209,6 +696,11 @@ impl<'ctx, Q: RankingRuleQueryTrait> RankingRule<'ctx, Q> for GeoSort<Q> {
         "geo_sort".to_owned()
     }
 
+    fn is_expensive(&self, universe: &RoaringBitmap) -> bool {
+        // only the iterative strategy reads the coordinates of every candidate
+        !self.strategy.use_rtree(universe.len() as usize)
+    }
+
     #[tracing::instrument(level = "trace", skip_all, target = "search::geo_sort")]
     fn start_iteration(
         &mut self,
@@ -228,9 +720,14 @@ impl<'ctx, Q: RankingRuleQueryTrait> RankingRule<'ctx, Q> for GeoSort<Q> {
         }
 
         let fid_map = ctx.index.fields_ids_map(ctx.txn)?;
-        let lat = fid_map.id("_geo.lat").expect("geo candidates but no fid for lat");
-        let lng = fid_map.id("_geo.lng").expect("geo candidates but no fid for lng");
+        let (lat, lng, alt) = match &ctx.geo_fields {
+            Some((lat, lng)) => (lat.as_str(), lng.as_str(), None),
+            None => ("_geo.lat", "_geo.lng", fid_map.id("_geo.alt")),
+        };
+        let lat = fid_map.id(lat).expect("geo candidates but no fid for lat");
+        let lng = fid_map.id(lng).expect("geo candidates but no fid for lng");
         self.field_ids = Some([lat, lng]);
+        self.altitude_field_id = self.altitude.and(alt);
         self.fill_buffer(ctx, &geo_candidates)?;
         Ok(())
     }
@@ -253,8 +750,11 @@ impl<'ctx, Q: RankingRuleQueryTrait> RankingRule<'ctx, Q> for GeoSort<Q> {
                 candidates: universe.clone(),
                 score: ScoreDetails::GeoSort(score_details::GeoSort {
                     target_point: self.point,
+                    target_shape: self.shape.as_ref().map(Shape::to_member),
                     ascending: self.ascending,
                     value: None,
+                    target_altitude: self.altitude,
+                    altitude: None,
                 }),
             }));
         }
@@ -267,15 +767,25 @@ impl<'ctx, Q: RankingRuleQueryTrait> RankingRule<'ctx, Q> for GeoSort<Q> {
                 cache.pop_back()
             }
         };
-        while let Some((id, point)) = next(&mut self.cached_sorted_docids) {
+        while let Some((id, point, altitude)) = next(&mut self.cached_sorted_docids) {
             if geo_candidates.contains(id) {
+                let candidates = match DistanceBands::from_ctx(ctx) {
+                    Some(bands) => {
+                        let band = self.distance_band(&point, altitude, &bands);
+                        self.band_candidates(ctx, &geo_candidates, id, band, &bands)?
+                    }
+                    None => RoaringBitmap::from_iter([id]),
+                };
                 return Ok(Some(RankingRuleOutput {
                     query,
-                    candidates: RoaringBitmap::from_iter([id]),
+                    candidates,
                     score: ScoreDetails::GeoSort(score_details::GeoSort {
-                        target_point: self.point,
+                        target_point: self.target_of(&point),
+                        target_shape: self.shape.as_ref().map(Shape::to_member),
                         ascending: self.ascending,
                         value: Some(point),
+                        target_altitude: self.altitude,
+                        altitude,
                     }),
                 }));
             }
@@ -295,6 +805,139 @@ impl<'ctx, Q: RankingRuleQueryTrait> RankingRule<'ctx, Q> for GeoSort<Q> {
     }
 }
 
+/// The documents of a search grouped by their distance to the target of its geo sort.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GeoDistanceBuckets {
+    /// The edges of the bands, in meters and in increasing order.
+    pub edges: Vec<f64>,
+    /// For each edge, the documents closer than it and not closer than the previous edge.
+    pub bands: Vec<RoaringBitmap>,
+    /// The documents not closer than the last edge.
+    pub overflow: RoaringBitmap,
+    /// The documents without coordinates.
+    pub without_geo: RoaringBitmap,
+}
+
+/// How the distance to the target of the geo sort is turned into a score between `0` and `1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GeoDecay {
+    /// The score is `1` at the target and halves every `scale_meters`.
+    #[default]
+    Exponential,
+    /// The score decreases from `1` at the target to `0` at `scale_meters` and farther.
+    Linear,
+}
+
+/// The share of the distance to the target of the geo sort in the score used to order the
+/// documents, blended with the relevancy of the documents.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoScore {
+    /// The weight of the distance, between `0` and `1`, the relevancy weighing the rest.
+    pub weight: f64,
+    /// The distance, in meters, at which the score of the distance decays.
+    pub scale_meters: f64,
+    pub decay: GeoDecay,
+}
+
+impl GeoScore {
+    /// Return the score, between `0` and `1`, of the given distance in meters.
+    pub fn distance_score(&self, distance: f64) -> f64 {
+        let scaled = distance / self.scale_meters;
+        match self.decay {
+            GeoDecay::Exponential => 0.5f64.powf(scaled),
+            GeoDecay::Linear => (1.0 - scaled).max(0.0),
+        }
+    }
+
+    /// Blend the relevancy score of a document with the score of its distance, the documents
+    /// without coordinates having a distance score of `0`.
+    pub fn blend(&self, relevancy: f64, distance: Option<f64>) -> f64 {
+        let distance_score = distance.map_or(0.0, |distance| self.distance_score(distance));
+        (1.0 - self.weight) * relevancy + self.weight * distance_score
+    }
+}
+
+/// Group the candidates by their distance to the target of the first geo sort of the sort
+/// criteria, between the given edges, the same way the geo sort computes the distances.
+///
+/// Returns `None` when the documents are not sorted by distance.
+pub(crate) fn geo_distance_buckets(
+    ctx: &SearchContext<'_>,
+    candidates: &RoaringBitmap,
+    sort_criteria: Option<&Vec<AscDesc>>,
+    edges: &[f64],
+) -> Result<Option<GeoDistanceBuckets>> {
+    let Some(distances) = geo_distances(ctx, candidates, sort_criteria)? else {
+        return Ok(None);
+    };
+
+    let mut buckets = GeoDistanceBuckets {
+        edges: edges.to_vec(),
+        bands: vec![RoaringBitmap::new(); edges.len()],
+        ..Default::default()
+    };
+    for docid in candidates {
+        let Some(&distance) = distances.get(&docid) else {
+            buckets.without_geo.insert(docid);
+            continue;
+        };
+        match buckets.bands.get_mut(edges_band(edges, distance)) {
+            Some(band) => band.insert(docid),
+            None => buckets.overflow.insert(docid),
+        };
+    }
+    Ok(Some(buckets))
+}
+
+/// Return the distance, in meters, between the given documents and the target of the first
+/// geo sort of the sort criteria, the same way the geo sort computes it. The documents without
+/// coordinates are missing from the map.
+///
+/// Returns `None` when the documents are not sorted by distance.
+pub(crate) fn geo_distances(
+    ctx: &SearchContext<'_>,
+    docids: &RoaringBitmap,
+    sort_criteria: Option<&Vec<AscDesc>>,
+) -> Result<Option<HashMap<DocumentId, f64>>> {
+    let target =
+        sort_criteria.into_iter().flatten().map(AscDesc::member).find(|member| member.is_geo());
+    let (point, altitude, shape) = match target {
+        Some(Member::Geo(point)) => (*point, None, None),
+        Some(Member::Geo3D([lat, lng, alt])) => ([*lat, *lng], Some(*alt), None),
+        Some(Member::GeoRoute(route)) => (route[0], None, Some(Shape::Route(route.clone()))),
+        Some(Member::GeoPolygonVertices(vertices)) => {
+            (vertices[0], None, Some(Shape::Vertices(vertices.clone())))
+        }
+        Some(Member::Field(_)) | None => return Ok(None),
+    };
+
+    let mut distances = HashMap::new();
+    let geo_candidates = geo_faceted_docids(ctx, Strategy::default())? & docids;
+    if geo_candidates.is_empty() {
+        return Ok(Some(distances));
+    }
+
+    let fid_map = ctx.index.fields_ids_map(ctx.txn)?;
+    let (lat, lng, alt) = match &ctx.geo_fields {
+        Some((lat, lng)) => (lat.as_str(), lng.as_str(), None),
+        None => ("_geo.lat", "_geo.lng", fid_map.id("_geo.alt")),
+    };
+    let lat = fid_map.id(lat).expect("geo candidates but no fid for lat");
+    let lng = fid_map.id(lng).expect("geo candidates but no fid for lng");
+    let alt = altitude.and(alt);
+
+    for docid in geo_candidates {
+        let Some(value) = geo_value(docid, lat, lng, ctx.index, ctx.txn)? else { continue };
+        let value_altitude = match alt {
+            Some(alt) => geo_field_value(docid, alt, ctx.index, ctx.txn)?,
+            None => None,
+        };
+        let distance = distance_to_target(&point, altitude, shape.as_ref(), &value, value_altitude);
+        distances.insert(docid, distance);
+    }
+    Ok(Some(distances))
+}
+
 /// Compute the antipodal coordinate of `coord`
 fn opposite_of(mut coord: [f64; 2]) -> [f64; 2] {
     coord[0] *= -1.;
@@ -43,11 +43,12 @@ use roaring::RoaringBitmap;
 
 use super::interner::{Interned, MappedInterner};
 use super::logger::SearchLogger;
-use super::query_graph::QueryNode;
+use super::query_graph::{QueryNode, QueryNodeData};
 use super::ranking_rule_graph::{
     ConditionDocIdsCache, DeadEndsCache, ExactnessGraph, FidGraph, PositionGraph, ProximityGraph,
     RankingRuleGraph, RankingRuleGraphTrait, TypoGraph, WordsGraph,
 };
+use super::resolve_query_graph::compute_query_term_subset_docids;
 use super::small_bitmap::SmallBitmap;
 use super::{QueryGraph, RankingRule, RankingRuleOutput, SearchContext};
 use crate::score_details::Rank;
@@ -99,11 +100,24 @@ pub struct GraphBasedRankingRule<G: RankingRuleGraphTrait> {
     // When the ranking rule is not iterating over its buckets,
     // its state is `None`.
     state: Option<GraphBasedRankingRuleState<G>>,
+    /// Whether the buckets are returned from the most costly one.
+    inverted: bool,
+    /// When the rule is inverted, the remaining buckets, computed in the order of their cost.
+    inverted_buckets: Option<Vec<RankingRuleOutput<QueryGraph>>>,
 }
 impl<G: RankingRuleGraphTrait> GraphBasedRankingRule<G> {
     /// Creates the ranking rule with the given identifier
     pub fn new_with_id(id: String, terms_matching_strategy: Option<TermsMatchingStrategy>) -> Self {
-        Self { id, terms_matching_strategy, state: None }
+        Self { id, terms_matching_strategy, state: None, inverted: false, inverted_buckets: None }
+    }
+
+    /// Return the buckets from the most costly one to the cheapest one.
+    ///
+    /// The documents are still put in the bucket of their cheapest path, then the buckets are
+    /// returned in reverse order with a rank growing with their cost.
+    pub fn inverted(mut self, inverted: bool) -> Self {
+        self.inverted = inverted;
+        self
     }
 }
 
@@ -121,6 +135,9 @@ pub struct GraphBasedRankingRuleState<G: RankingRuleGraphTrait> {
     cur_cost: u64,
     /// One above the highest possible cost for this rule
     next_max_cost: u64,
+    /// The number of documents of the universe matched by each node, when the paths are
+    /// explored through the rarest nodes first
+    nodes_docids_len: Option<MappedInterner<QueryNode, u64>>,
 }
 
 impl<'ctx, G: RankingRuleGraphTrait> RankingRule<'ctx, QueryGraph> for GraphBasedRankingRule<G> {
@@ -128,12 +145,20 @@ impl<'ctx, G: RankingRuleGraphTrait> RankingRule<'ctx, QueryGraph> for GraphBase
         self.id.clone()
     }
 
+    fn is_expensive(&self, _universe: &RoaringBitmap) -> bool {
+        G::EXPENSIVE
+    }
+
+    fn excludes_unranked(&self, ctx: &SearchContext<'ctx>) -> bool {
+        G::max_cost(ctx).is_some()
+    }
+
     #[tracing::instrument(level = "trace", skip_all, target = "search::graph_based")]
     fn start_iteration(
         &mut self,
         ctx: &mut SearchContext<'ctx>,
         _logger: &mut dyn SearchLogger<QueryGraph>,
-        _universe: &RoaringBitmap,
+        universe: &RoaringBitmap,
         query_graph: &QueryGraph,
     ) -> Result<()> {
         // the `next_max_cost` is the successor integer to the maximum cost of the paths in the graph.
@@ -188,6 +213,14 @@ impl<'ctx, G: RankingRuleGraphTrait> RankingRule<'ctx, QueryGraph> for GraphBase
         };
 
         let graph = RankingRuleGraph::build(ctx, query_graph.clone(), removal_cost)?;
+        if let Some(stats) = &mut ctx.query_stats {
+            let edges = graph.edges_store.iter().filter(|(_, edge)| edge.is_some()).count();
+            match self.id.as_str() {
+                "proximity" => stats.proximity_edges += edges,
+                "typo" => stats.typo_edges += edges,
+                _ => (),
+            }
+        }
         let condition_docids_cache = ConditionDocIdsCache::default();
         let dead_ends_cache = DeadEndsCache::new(&graph.conditions_interner);
 
@@ -197,6 +230,20 @@ impl<'ctx, G: RankingRuleGraphTrait> RankingRule<'ctx, QueryGraph> for GraphBase
         next_max_cost +=
             all_costs.get(graph.query_graph.root_node).iter().copied().max().unwrap_or(0);
 
+        let nodes_docids_len = if G::EXPLORE_RAREST_FIRST && ctx.proximity_rarest_first {
+            let mut nodes_docids_len = graph.query_graph.nodes.map(|_| 0);
+            for (node_id, node) in graph.query_graph.nodes.iter() {
+                if let QueryNodeData::Term(term) = &node.data {
+                    let docids =
+                        compute_query_term_subset_docids(ctx, Some(universe), &term.term_subset)?;
+                    *nodes_docids_len.get_mut(node_id) = docids.len();
+                }
+            }
+            Some(nodes_docids_len)
+        } else {
+            None
+        };
+
         let state = GraphBasedRankingRuleState {
             graph,
             conditions_cache: condition_docids_cache,
@@ -204,9 +251,11 @@ impl<'ctx, G: RankingRuleGraphTrait> RankingRule<'ctx, QueryGraph> for GraphBase
             all_costs,
             cur_cost: 0,
             next_max_cost,
+            nodes_docids_len,
         };
 
         self.state = Some(state);
+        self.inverted_buckets = None;
 
         Ok(())
     }
@@ -218,13 +267,76 @@ impl<'ctx, G: RankingRuleGraphTrait> RankingRule<'ctx, QueryGraph> for GraphBase
         logger: &mut dyn SearchLogger<QueryGraph>,
         universe: &RoaringBitmap,
     ) -> Result<Option<RankingRuleOutput<QueryGraph>>> {
+        if self.inverted {
+            return self.next_inverted_bucket(ctx, logger, universe);
+        }
+        Ok(self.next_cost_bucket(ctx, logger, universe)?.map(|(_, bucket)| bucket))
+    }
+
+    #[tracing::instrument(level = "trace", skip_all, target = "search::graph_based")]
+    fn end_iteration(
+        &mut self,
+        _ctx: &mut SearchContext<'ctx>,
+        _logger: &mut dyn SearchLogger<QueryGraph>,
+    ) {
+        self.state = None;
+        self.inverted_buckets = None;
+    }
+}
+
+impl<G: RankingRuleGraphTrait> GraphBasedRankingRule<G> {
+    /// Compute all the buckets of the universe in the order of their cost, then return them
+    /// from the last one.
+    fn next_inverted_bucket(
+        &mut self,
+        ctx: &mut SearchContext<'_>,
+        logger: &mut dyn SearchLogger<QueryGraph>,
+        universe: &RoaringBitmap,
+    ) -> Result<Option<RankingRuleOutput<QueryGraph>>> {
+        if self.inverted_buckets.is_none() {
+            let max_rank = self.state.as_ref().map_or(0, |state| state.next_max_cost) as u32;
+            let mut remaining = universe.clone();
+            let mut buckets = Vec::new();
+            while !remaining.is_empty() {
+                let Some((cost, mut bucket)) = self.next_cost_bucket(ctx, logger, &remaining)?
+                else {
+                    break;
+                };
+                remaining -= &bucket.candidates;
+                bucket.score = G::rank_to_score(Rank { rank: cost as u32 + 1, max_rank });
+                buckets.push(bucket);
+            }
+            self.inverted_buckets = Some(buckets);
+        }
+
+        // the universe shrinks as the documents of the previous buckets are ranked
+        let buckets = self.inverted_buckets.as_mut().unwrap();
+        while let Some(mut bucket) = buckets.pop() {
+            bucket.candidates &= universe;
+            if !bucket.candidates.is_empty() {
+                return Ok(Some(bucket));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Compute the next bucket and return it with the cost of its paths.
+    fn next_cost_bucket(
+        &mut self,
+        ctx: &mut SearchContext<'_>,
+        logger: &mut dyn SearchLogger<QueryGraph>,
+        universe: &RoaringBitmap,
+    ) -> Result<Option<(u64, RankingRuleOutput<QueryGraph>)>> {
         // Will crash if `next_bucket` is called before `start_iteration` or after `end_iteration`,
         // should never happen
         let mut state = self.state.take().unwrap();
 
         let all_costs = state.all_costs.get(state.graph.query_graph.root_node);
-        // Retrieve the cost of the paths to compute
-        let Some(&cost) = all_costs.iter().find(|c| **c >= state.cur_cost) else {
+        // Retrieve the cost of the paths to compute, the paths over the maximum cost are never explored
+        let max_cost = G::max_cost(ctx).unwrap_or(u64::MAX);
+        let Some(&cost) =
+            all_costs.iter().find(|c| **c >= state.cur_cost).filter(|c| **c <= max_cost)
+        else {
             self.state = None;
             return Ok(None);
         };
@@ -239,6 +351,7 @@ impl<'ctx, G: RankingRuleGraphTrait> RankingRule<'ctx, QueryGraph> for GraphBase
             all_costs,
             cur_cost: _,
             next_max_cost,
+            nodes_docids_len,
         } = &mut state;
 
         let rank = *next_max_cost - cost;
@@ -260,7 +373,10 @@ impl<'ctx, G: RankingRuleGraphTrait> RankingRule<'ctx, QueryGraph> for GraphBase
         let mut subpaths_docids: Vec<(Interned<G::Condition>, RoaringBitmap)> = vec![];
 
         let mut nodes_with_removed_outgoing_conditions = BTreeSet::new();
-        let visitor = PathVisitor::new(cost, graph, all_costs, dead_ends_cache);
+        let mut visitor = PathVisitor::new(cost, graph, all_costs, dead_ends_cache);
+        if let Some(nodes_docids_len) = nodes_docids_len {
+            visitor = visitor.rarest_first(nodes_docids_len);
+        }
 
         visitor.visit_paths(&mut |path, graph, dead_ends_cache| {
             considered_paths.push(path.to_vec());
@@ -327,6 +443,10 @@ impl<'ctx, G: RankingRuleGraphTrait> RankingRule<'ctx, QueryGraph> for GraphBase
         })?;
         logger.log_internal_state(graph);
         logger.log_internal_state(&good_paths);
+        logger.dead_ends(&mut dead_ends_cache.iter().map(|(prefix, forbidden)| {
+            let prefix = prefix.into_iter().map(Interned::into_raw).collect();
+            (prefix, forbidden.iter().map(Interned::into_raw).collect())
+        }));
 
         // We modify the next query graph so that it only contains the subgraph
         // that was used to compute this bucket
@@ -358,16 +478,8 @@ impl<'ctx, G: RankingRuleGraphTrait> RankingRule<'ctx, QueryGraph> for GraphBase
 
         self.state = Some(state);
 
-        Ok(Some(RankingRuleOutput { query: next_query_graph, candidates: bucket, score }))
-    }
-
-    #[tracing::instrument(level = "trace", skip_all, target = "search::graph_based")]
-    fn end_iteration(
-        &mut self,
-        _ctx: &mut SearchContext<'ctx>,
-        _logger: &mut dyn SearchLogger<QueryGraph>,
-    ) {
-        self.state = None;
+        let bucket = RankingRuleOutput { query: next_query_graph, candidates: bucket, score };
+        Ok(Some((cost, bucket)))
     }
 }
 
@@ -45,6 +45,39 @@ impl<T> DedupInterner<T> {
     pub fn freeze(self) -> FixedSizeInterner<T> {
         FixedSizeInterner { stable_store: self.stable_store }
     }
+    /// Remove all the values from the dedup-interner, keeping its allocated memory.
+    pub fn clear(&mut self) {
+        self.stable_store.clear();
+        self.lookup.clear();
+    }
+    /// Remove the values inserted after the first `len` ones, keeping the indexes of
+    /// the remaining values unchanged.
+    pub fn truncate(&mut self, len: usize) {
+        self.stable_store.truncate(len);
+        self.lookup.retain(|_, interned| (interned.idx as usize) < len);
+    }
+    /// Get the number of values in the dedup-interner.
+    pub fn len(&self) -> usize {
+        self.stable_store.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.stable_store.is_empty()
+    }
+}
+
+impl DedupInterner<String> {
+    /// Create a dedup-interner containing the given words, inserted in order.
+    ///
+    /// The indexes of an interner are only meaningful within this interner: to share a
+    /// vocabulary between several searches, give it to an [`InternerPool`](super::InternerPool)
+    /// with [`InternerPool::with_vocabulary`](super::InternerPool::with_vocabulary).
+    pub fn from_words(words: &[&str]) -> Self {
+        let mut interner = Self::default();
+        for word in words {
+            interner.insert(word.to_string());
+        }
+        interner
+    }
 }
 
 impl<T> DedupInterner<T>
@@ -68,6 +101,10 @@ where
     pub fn get(&self, interned: Interned<T>) -> &T {
         &self.stable_store[interned.idx as usize]
     }
+    /// Get the index of the given value, if it was inserted into the dedup-interner.
+    pub fn lookup(&self, s: &T) -> Option<Interned<T>> {
+        self.lookup.get(s).copied()
+    }
 }
 
 /// A fixed-length store for values of type `T`, where each value is identified
@@ -179,6 +216,10 @@ impl<T> Interner<T> {
     pub fn freeze(self) -> FixedSizeInterner<T> {
         FixedSizeInterner { stable_store: self.stable_store }
     }
+    /// Remove all the values from the interner, keeping its allocated memory.
+    pub fn clear(&mut self) {
+        self.stable_store.clear();
+    }
 }
 
 /// A store of values of type `T`, each linked to a value of type `From`
@@ -0,0 +1,56 @@
+use super::interner::{DedupInterner, Interner};
+use super::query_term::{Phrase, QueryTerm, TypoDerivationsCache};
+use super::resolve_query_graph::PhraseDocIdsCache;
+
+/// A pool of the interners and caches of a [`SearchContext`](super::SearchContext), which
+/// store the words, phrases, and query terms of a search, that can be reused by the next ones.
+///
+/// Creating a search context with
+/// [`SearchContext::with_interner_pool`](super::SearchContext::with_interner_pool) and taking
+/// the pool back with
+/// [`SearchContext::into_interner_pool`](super::SearchContext::into_interner_pool) once the
+/// search is executed allows the following searches to reuse the capacity of the previous ones
+/// instead of growing their stores from scratch. Only the capacity is reused: the values of the
+/// interners, and the nodes and edges of the query graph, are still allocated for every search.
+#[derive(Default)]
+pub struct InternerPool {
+    pub(super) word_interner: DedupInterner<String>,
+    pub(super) phrase_interner: DedupInterner<Phrase>,
+    pub(super) term_interner: Interner<QueryTerm>,
+    pub(super) phrase_docids: PhraseDocIdsCache,
+    pub(super) typo_derivations: TypoDerivationsCache,
+    /// The number of words of the vocabulary, which are kept when the pool is cleared.
+    pub(super) vocabulary_len: usize,
+}
+
+impl InternerPool {
+    /// Create a pool whose word interner already contains the given vocabulary.
+    ///
+    /// The words of the vocabulary keep the same [`Interned`](super::Interned) index
+    /// in every search context created from this pool, and when the pool is given back:
+    /// only the words interned by a search on top of the vocabulary are forgotten. The pool
+    /// must only be reused by searches on the index the vocabulary was built for.
+    pub fn with_vocabulary(word_interner: DedupInterner<String>) -> Self {
+        let vocabulary_len = word_interner.len();
+        Self { word_interner, vocabulary_len, ..Default::default() }
+    }
+
+    /// Forget the values of the previous search while keeping the allocated memory.
+    pub(super) fn clear(&mut self) {
+        let Self {
+            word_interner,
+            phrase_interner,
+            term_interner,
+            phrase_docids,
+            typo_derivations,
+            vocabulary_len,
+        } = self;
+        word_interner.truncate(*vocabulary_len);
+        phrase_interner.clear();
+        term_interner.clear();
+        phrase_docids.cache.clear();
+        phrase_docids.exact_cache.clear();
+        typo_derivations.one_typo.clear();
+        typo_derivations.one_and_two_typos.clear();
+    }
+}
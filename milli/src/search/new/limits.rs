@@ -15,3 +15,10 @@ pub const MAX_SYNONYM_PHRASE_COUNT: usize = 50;
 ///
 /// This limit is meant to gracefully handle the case where a word would have very long phrases as synonyms.
 pub const MAX_SYNONYM_WORD_COUNT: usize = 100;
+
+/// Maximum number of query graphs kept by a shared database cache for the next searches.
+pub const SHARED_QUERY_GRAPH_COUNT: usize = 8;
+
+/// Maximum number of words, phrases, or query terms kept by a shared database cache for the
+/// next searches, which can't intern more than `u16::MAX` values of each.
+pub const SHARED_INTERNED_VALUE_COUNT: usize = u16::MAX as usize / 2;
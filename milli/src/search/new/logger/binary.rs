@@ -0,0 +1,535 @@
+use std::any::Any;
+use std::io::{self, Read, Write};
+
+use roaring::RoaringBitmap;
+
+use crate::search::new::ranking_rules::BoxRankingRule;
+use crate::search::new::{RankingRule, RankingRuleQueryTrait, SearchLogger};
+
+const INITIAL_QUERY: u8 = 0;
+const QUERY_FOR_INITIAL_UNIVERSE: u8 = 1;
+const INITIAL_UNIVERSE: u8 = 2;
+const RANKING_RULES: u8 = 3;
+const RANKING_RULE_START_ITERATION: u8 = 4;
+const RANKING_RULE_NEXT_BUCKET: u8 = 5;
+const RANKING_RULE_SKIP_BUCKET: u8 = 6;
+const RANKING_RULE_END_ITERATION: u8 = 7;
+const EXTEND_RESULTS: u8 = 8;
+const INTERNAL_STATE: u8 = 9;
+const CAPPED_UNIVERSE: u8 = 10;
+const CAPPED_BUCKET: u8 = 11;
+const DEAD_ENDS: u8 = 12;
+
+/// An event of the execution of a search query, as written by the [`BinarySearchLogger`].
+///
+/// Only the sizes of the sets of documents are kept, and the ranking rules are referred
+/// to by their index in the [`BinarySearchEvent::RankingRules`] event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BinarySearchEvent {
+    InitialQuery,
+    QueryForInitialUniverse,
+    InitialUniverse { universe_len: u64 },
+    CappedUniverse { universe_len: u64, capped_len: u64 },
+    CappedBucket { ranking_rule_idx: u8, bucket_len: u64, capped_len: u64 },
+    RankingRules { ids: Vec<String> },
+    RankingRuleStartIteration { ranking_rule_idx: u8, universe_len: u64 },
+    RankingRuleNextBucket { ranking_rule_idx: u8, universe_len: u64, bucket_len: u64 },
+    RankingRuleSkipBucket { ranking_rule_idx: u8, bucket_len: u64 },
+    RankingRuleEndIteration { ranking_rule_idx: u8, universe_len: u64 },
+    ExtendResults { new: Vec<u32> },
+    InternalState,
+    DeadEnds { dead_ends: Vec<(Vec<u16>, Vec<u16>)> },
+}
+
+impl BinarySearchEvent {
+    /// Write the event, prefixed by its length, to the given writer.
+    pub fn encode<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut buffer = Vec::new();
+        match self {
+            Self::InitialQuery => buffer.push(INITIAL_QUERY),
+            Self::QueryForInitialUniverse => buffer.push(QUERY_FOR_INITIAL_UNIVERSE),
+            Self::InitialUniverse { universe_len } => {
+                buffer.push(INITIAL_UNIVERSE);
+                write_varint(&mut buffer, *universe_len);
+            }
+            Self::CappedUniverse { universe_len, capped_len } => {
+                buffer.push(CAPPED_UNIVERSE);
+                write_varint(&mut buffer, *universe_len);
+                write_varint(&mut buffer, *capped_len);
+            }
+            Self::CappedBucket { ranking_rule_idx, bucket_len, capped_len } => {
+                buffer.extend_from_slice(&[CAPPED_BUCKET, *ranking_rule_idx]);
+                write_varint(&mut buffer, *bucket_len);
+                write_varint(&mut buffer, *capped_len);
+            }
+            Self::RankingRules { ids } => {
+                buffer.push(RANKING_RULES);
+                write_varint(&mut buffer, ids.len() as u64);
+                for id in ids {
+                    write_varint(&mut buffer, id.len() as u64);
+                    buffer.extend_from_slice(id.as_bytes());
+                }
+            }
+            Self::RankingRuleStartIteration { ranking_rule_idx, universe_len } => {
+                buffer.extend_from_slice(&[RANKING_RULE_START_ITERATION, *ranking_rule_idx]);
+                write_varint(&mut buffer, *universe_len);
+            }
+            Self::RankingRuleNextBucket { ranking_rule_idx, universe_len, bucket_len } => {
+                buffer.extend_from_slice(&[RANKING_RULE_NEXT_BUCKET, *ranking_rule_idx]);
+                write_varint(&mut buffer, *universe_len);
+                write_varint(&mut buffer, *bucket_len);
+            }
+            Self::RankingRuleSkipBucket { ranking_rule_idx, bucket_len } => {
+                buffer.extend_from_slice(&[RANKING_RULE_SKIP_BUCKET, *ranking_rule_idx]);
+                write_varint(&mut buffer, *bucket_len);
+            }
+            Self::RankingRuleEndIteration { ranking_rule_idx, universe_len } => {
+                buffer.extend_from_slice(&[RANKING_RULE_END_ITERATION, *ranking_rule_idx]);
+                write_varint(&mut buffer, *universe_len);
+            }
+            Self::ExtendResults { new } => {
+                buffer.push(EXTEND_RESULTS);
+                write_varint(&mut buffer, new.len() as u64);
+                for docid in new {
+                    write_varint(&mut buffer, *docid as u64);
+                }
+            }
+            Self::InternalState => buffer.push(INTERNAL_STATE),
+            Self::DeadEnds { dead_ends } => {
+                buffer.push(DEAD_ENDS);
+                write_varint(&mut buffer, dead_ends.len() as u64);
+                for (prefix, forbidden) in dead_ends {
+                    for conditions in [prefix, forbidden] {
+                        write_varint(&mut buffer, conditions.len() as u64);
+                        for condition in conditions {
+                            write_varint(&mut buffer, *condition as u64);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut length = Vec::new();
+        write_varint(&mut length, buffer.len() as u64);
+        writer.write_all(&length)?;
+        writer.write_all(&buffer)
+    }
+
+    /// Read the next event from the given reader, returns `None` at the end of the stream.
+    pub fn decode<R: Read>(reader: &mut R) -> io::Result<Option<Self>> {
+        let mut first_byte = [0];
+        if reader.read(&mut first_byte)? == 0 {
+            return Ok(None);
+        }
+        let length = read_varint(&mut (&first_byte[..]).chain(&mut *reader))?;
+        let buffer = read_bytes(reader, length)?;
+
+        let mut payload = buffer.as_slice();
+        let mut tag = [0];
+        payload.read_exact(&mut tag)?;
+        let event = match tag[0] {
+            INITIAL_QUERY => Self::InitialQuery,
+            QUERY_FOR_INITIAL_UNIVERSE => Self::QueryForInitialUniverse,
+            INITIAL_UNIVERSE => Self::InitialUniverse { universe_len: read_varint(&mut payload)? },
+            CAPPED_UNIVERSE => Self::CappedUniverse {
+                universe_len: read_varint(&mut payload)?,
+                capped_len: read_varint(&mut payload)?,
+            },
+            CAPPED_BUCKET => Self::CappedBucket {
+                ranking_rule_idx: read_u8(&mut payload)?,
+                bucket_len: read_varint(&mut payload)?,
+                capped_len: read_varint(&mut payload)?,
+            },
+            RANKING_RULES => {
+                let count = read_varint(&mut payload)?;
+                let mut ids = Vec::new();
+                for _ in 0..count {
+                    let length = read_varint(&mut payload)?;
+                    let id = read_bytes(&mut payload, length)?;
+                    let id = String::from_utf8(id)
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                    ids.push(id);
+                }
+                Self::RankingRules { ids }
+            }
+            RANKING_RULE_START_ITERATION => Self::RankingRuleStartIteration {
+                ranking_rule_idx: read_u8(&mut payload)?,
+                universe_len: read_varint(&mut payload)?,
+            },
+            RANKING_RULE_NEXT_BUCKET => Self::RankingRuleNextBucket {
+                ranking_rule_idx: read_u8(&mut payload)?,
+                universe_len: read_varint(&mut payload)?,
+                bucket_len: read_varint(&mut payload)?,
+            },
+            RANKING_RULE_SKIP_BUCKET => Self::RankingRuleSkipBucket {
+                ranking_rule_idx: read_u8(&mut payload)?,
+                bucket_len: read_varint(&mut payload)?,
+            },
+            RANKING_RULE_END_ITERATION => Self::RankingRuleEndIteration {
+                ranking_rule_idx: read_u8(&mut payload)?,
+                universe_len: read_varint(&mut payload)?,
+            },
+            EXTEND_RESULTS => {
+                let count = read_varint(&mut payload)?;
+                let new = (0..count)
+                    .map(|_| {
+                        let docid = read_varint(&mut payload)?;
+                        u32::try_from(docid)
+                            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+                    })
+                    .collect::<io::Result<_>>()?;
+                Self::ExtendResults { new }
+            }
+            INTERNAL_STATE => Self::InternalState,
+            DEAD_ENDS => {
+                let count = read_varint(&mut payload)?;
+                let mut dead_ends = Vec::new();
+                for _ in 0..count {
+                    let prefix = read_conditions(&mut payload)?;
+                    let forbidden = read_conditions(&mut payload)?;
+                    dead_ends.push((prefix, forbidden));
+                }
+                Self::DeadEnds { dead_ends }
+            }
+            tag => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unknown search event tag {tag}"),
+                ))
+            }
+        };
+        Ok(Some(event))
+    }
+
+    /// Read all the events of the given reader.
+    pub fn decode_all<R: Read>(mut reader: R) -> io::Result<Vec<Self>> {
+        let mut events = Vec::new();
+        while let Some(event) = Self::decode(&mut reader)? {
+            events.push(event);
+        }
+        Ok(events)
+    }
+}
+
+fn write_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+fn read_varint<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = read_u8(reader)?;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::InvalidData, "varint is too long"))
+}
+
+fn read_conditions<R: Read>(reader: &mut R) -> io::Result<Vec<u16>> {
+    let count = read_varint(reader)?;
+    (0..count)
+        .map(|_| {
+            let condition = read_varint(reader)?;
+            u16::try_from(condition).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        })
+        .collect()
+}
+
+/// Read `length` bytes, which are only allocated as they are read since the length comes
+/// from the stream and can be corrupted.
+fn read_bytes<R: Read>(reader: &mut R, length: u64) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.take(length).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != length {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated search event"));
+    }
+    Ok(bytes)
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> io::Result<u8> {
+    let mut byte = [0];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+/// A [`SearchLogger`] writing a compact stream of [`BinarySearchEvent`]s to a writer,
+/// lighter than the [`VisualSearchLogger`](super::visual::VisualSearchLogger) for capturing
+/// the execution of queries in production.
+///
+/// The logger methods cannot fail, the first write error is kept and returned by
+/// [`BinarySearchLogger::finish`], after which nothing is written anymore.
+pub struct BinarySearchLogger<W: Write> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> BinarySearchLogger<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer, error: None }
+    }
+
+    /// Return the writer, or the first error encountered while writing to it.
+    pub fn finish(mut self) -> io::Result<W> {
+        match self.error {
+            Some(error) => Err(error),
+            None => {
+                self.writer.flush()?;
+                Ok(self.writer)
+            }
+        }
+    }
+
+    fn write(&mut self, event: BinarySearchEvent) {
+        if self.error.is_none() {
+            if let Err(error) = event.encode(&mut self.writer) {
+                self.error = Some(error);
+            }
+        }
+    }
+
+    /// Return the index of the ranking rule as written in the events, or keep an error if
+    /// there are too many ranking rules for it to be written.
+    fn ranking_rule_idx(&mut self, ranking_rule_idx: usize) -> Option<u8> {
+        match u8::try_from(ranking_rule_idx) {
+            Ok(ranking_rule_idx) => Some(ranking_rule_idx),
+            Err(e) => {
+                if self.error.is_none() {
+                    self.error = Some(io::Error::new(io::ErrorKind::InvalidInput, e));
+                }
+                None
+            }
+        }
+    }
+}
+
+impl<W: Write, Q: RankingRuleQueryTrait> SearchLogger<Q> for BinarySearchLogger<W> {
+    fn initial_query(&mut self, _query: &Q) {
+        self.write(BinarySearchEvent::InitialQuery);
+    }
+
+    fn query_for_initial_universe(&mut self, _query: &Q) {
+        self.write(BinarySearchEvent::QueryForInitialUniverse);
+    }
+
+    fn initial_universe(&mut self, universe: &RoaringBitmap) {
+        self.write(BinarySearchEvent::InitialUniverse { universe_len: universe.len() });
+    }
+
+    fn capped_universe(&mut self, universe: &RoaringBitmap, capped: &RoaringBitmap) {
+        self.write(BinarySearchEvent::CappedUniverse {
+            universe_len: universe.len(),
+            capped_len: capped.len(),
+        });
+    }
+
+    fn capped_bucket(
+        &mut self,
+        ranking_rule_idx: usize,
+        _ranking_rule: &dyn RankingRule<'_, Q>,
+        bucket: &RoaringBitmap,
+        capped: &RoaringBitmap,
+    ) {
+        let Some(ranking_rule_idx) = self.ranking_rule_idx(ranking_rule_idx) else { return };
+        self.write(BinarySearchEvent::CappedBucket {
+            ranking_rule_idx,
+            bucket_len: bucket.len(),
+            capped_len: capped.len(),
+        });
+    }
+
+    fn ranking_rules(&mut self, rr: &[BoxRankingRule<'_, Q>]) {
+        self.write(BinarySearchEvent::RankingRules { ids: rr.iter().map(|rr| rr.id()).collect() });
+    }
+
+    fn start_iteration_ranking_rule(
+        &mut self,
+        ranking_rule_idx: usize,
+        _ranking_rule: &dyn RankingRule<'_, Q>,
+        _query: &Q,
+        universe: &RoaringBitmap,
+    ) {
+        let Some(ranking_rule_idx) = self.ranking_rule_idx(ranking_rule_idx) else { return };
+        self.write(BinarySearchEvent::RankingRuleStartIteration {
+            ranking_rule_idx,
+            universe_len: universe.len(),
+        });
+    }
+
+    fn next_bucket_ranking_rule(
+        &mut self,
+        ranking_rule_idx: usize,
+        _ranking_rule: &dyn RankingRule<'_, Q>,
+        universe: &RoaringBitmap,
+        candidates: &RoaringBitmap,
+    ) {
+        let Some(ranking_rule_idx) = self.ranking_rule_idx(ranking_rule_idx) else { return };
+        self.write(BinarySearchEvent::RankingRuleNextBucket {
+            ranking_rule_idx,
+            universe_len: universe.len(),
+            bucket_len: candidates.len(),
+        });
+    }
+
+    fn skip_bucket_ranking_rule(
+        &mut self,
+        ranking_rule_idx: usize,
+        _ranking_rule: &dyn RankingRule<'_, Q>,
+        candidates: &RoaringBitmap,
+    ) {
+        let Some(ranking_rule_idx) = self.ranking_rule_idx(ranking_rule_idx) else { return };
+        self.write(BinarySearchEvent::RankingRuleSkipBucket {
+            ranking_rule_idx,
+            bucket_len: candidates.len(),
+        });
+    }
+
+    fn end_iteration_ranking_rule(
+        &mut self,
+        ranking_rule_idx: usize,
+        _ranking_rule: &dyn RankingRule<'_, Q>,
+        universe: &RoaringBitmap,
+    ) {
+        let Some(ranking_rule_idx) = self.ranking_rule_idx(ranking_rule_idx) else { return };
+        self.write(BinarySearchEvent::RankingRuleEndIteration {
+            ranking_rule_idx,
+            universe_len: universe.len(),
+        });
+    }
+
+    fn add_to_results(&mut self, docids: &[u32]) {
+        self.write(BinarySearchEvent::ExtendResults { new: docids.to_vec() });
+    }
+
+    fn log_internal_state(&mut self, _rr: &dyn Any) {
+        self.write(BinarySearchEvent::InternalState);
+    }
+
+    fn dead_ends(&mut self, dead_ends: &mut dyn Iterator<Item = (Vec<u16>, Vec<u16>)>) {
+        self.write(BinarySearchEvent::DeadEnds { dead_ends: dead_ends.collect() });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::tests::TempIndex;
+    use crate::score_details::ScoringStrategy;
+    use crate::{
+        execute_search, DefaultSearchLogger, GeoSortStrategy, SearchContext, TermsMatchingStrategy,
+        TimeBudget,
+    };
+
+    #[test]
+    fn events_round_trip() {
+        let events = vec![
+            BinarySearchEvent::InitialQuery,
+            BinarySearchEvent::QueryForInitialUniverse,
+            BinarySearchEvent::InitialUniverse { universe_len: 1_000_000 },
+            BinarySearchEvent::CappedUniverse { universe_len: 1_000_000, capped_len: 1000 },
+            BinarySearchEvent::RankingRules { ids: vec!["words".to_owned(), "typo".to_owned()] },
+            BinarySearchEvent::RankingRuleStartIteration { ranking_rule_idx: 0, universe_len: 300 },
+            BinarySearchEvent::RankingRuleNextBucket {
+                ranking_rule_idx: 1,
+                universe_len: 300,
+                bucket_len: 127,
+            },
+            BinarySearchEvent::RankingRuleSkipBucket { ranking_rule_idx: 1, bucket_len: 128 },
+            BinarySearchEvent::CappedBucket {
+                ranking_rule_idx: 1,
+                bucket_len: 300,
+                capped_len: 10,
+            },
+            BinarySearchEvent::ExtendResults { new: vec![0, 127, 128, u32::MAX] },
+            BinarySearchEvent::RankingRuleEndIteration { ranking_rule_idx: 0, universe_len: 0 },
+            BinarySearchEvent::InternalState,
+            BinarySearchEvent::DeadEnds {
+                dead_ends: vec![(vec![], vec![3]), (vec![0, 1], vec![2, u16::MAX])],
+            },
+        ];
+
+        let mut bytes = Vec::new();
+        for event in &events {
+            event.encode(&mut bytes).unwrap();
+        }
+        assert_eq!(BinarySearchEvent::decode_all(bytes.as_slice()).unwrap(), events);
+    }
+
+    #[test]
+    fn truncated_event() {
+        // the length prefix announces more bytes than the stream contains
+        let mut bytes = Vec::new();
+        write_varint(&mut bytes, u64::MAX >> 1);
+        bytes.push(INITIAL_QUERY);
+        let error = BinarySearchEvent::decode_all(bytes.as_slice()).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn query_events_round_trip() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|s| {
+                s.set_primary_key("id".to_owned());
+                s.set_searchable_fields(vec!["text".to_owned()]);
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "text": "the quick brown fox" },
+                { "id": 1, "text": "the quick fox" },
+                { "id": 2, "text": "a brown dog" },
+                { "id": 3, "text": "quick quick" },
+            ]))
+            .unwrap();
+
+        let txn = index.read_txn().unwrap();
+        let mut ctx = SearchContext::new(&index, &txn).unwrap();
+        let universe = index.documents_ids(&txn).unwrap();
+        let mut logger = BinarySearchLogger::new(Vec::new());
+        let result = execute_search(
+            &mut ctx,
+            Some("quick brown"),
+            TermsMatchingStrategy::Last,
+            ScoringStrategy::Skip,
+            false,
+            universe,
+            &None,
+            &None,
+            GeoSortStrategy::default(),
+            0,
+            20,
+            None,
+            &mut DefaultSearchLogger,
+            &mut logger,
+            TimeBudget::max(),
+            None,
+            None,
+        )
+        .unwrap();
+        let bytes = logger.finish().unwrap();
+
+        let events = BinarySearchEvent::decode_all(bytes.as_slice()).unwrap();
+        assert_eq!(events.first(), Some(&BinarySearchEvent::InitialQuery));
+        let results: Vec<u32> = events
+            .iter()
+            .filter_map(|event| match event {
+                BinarySearchEvent::ExtendResults { new } => Some(new.clone()),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+        assert_eq!(results, result.documents_ids);
+
+        let mut encoded = Vec::new();
+        for event in &events {
+            event.encode(&mut encoded).unwrap();
+        }
+        assert_eq!(encoded, bytes);
+    }
+}
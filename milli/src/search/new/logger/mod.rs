@@ -1,3 +1,4 @@
+pub mod binary;
 // #[cfg(test)]
 pub mod visual;
 
@@ -19,6 +20,20 @@ pub trait SearchLogger<Q: RankingRuleQueryTrait> {
     /// Logs the query that was used to compute the set of all candidates
     fn query_for_initial_universe(&mut self, _query: &Q);
 
+    /// Logs the reduction of the candidates given to the expensive ranking rules to the
+    /// maximum number of candidates they rank
+    fn capped_universe(&mut self, _universe: &RoaringBitmap, _capped: &RoaringBitmap) {}
+
+    /// Logs the reduction of a bucket of a ranking rule to the bucket limit of the rule
+    fn capped_bucket(
+        &mut self,
+        _ranking_rule_idx: usize,
+        _ranking_rule: &dyn RankingRule<'_, Q>,
+        _bucket: &RoaringBitmap,
+        _capped: &RoaringBitmap,
+    ) {
+    }
+
     /// Logs the ranking rules used to perform the search query
     fn ranking_rules(&mut self, _rr: &[BoxRankingRule<'_, Q>]);
 
@@ -61,6 +76,10 @@ pub trait SearchLogger<Q: RankingRuleQueryTrait> {
 
     /// Logs an internal state in the search algorithms
     fn log_internal_state(&mut self, _rr: &dyn Any);
+
+    /// Logs the path prefixes leading to dead ends found so far by a graph-based ranking rule,
+    /// as the raw ids of their conditions, along with the conditions forbidden after them
+    fn dead_ends(&mut self, _dead_ends: &mut dyn Iterator<Item = (Vec<u16>, Vec<u16>)>) {}
 }
 
 /// A dummy [`SearchLogger`] which does nothing.
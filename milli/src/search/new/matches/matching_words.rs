@@ -4,8 +4,8 @@ use std::ops::RangeInclusive;
 
 use charabia::Token;
 
-use super::super::interner::Interned;
-use super::super::query_term::LocatedQueryTerm;
+use super::super::interner::{Interned, Interner};
+use super::super::query_term::{LocatedQueryTerm, QueryTerm};
 use super::super::{DedupInterner, Phrase};
 use crate::SearchContext;
 
@@ -18,6 +18,7 @@ pub struct LocatedMatchingWords {
     pub value: Vec<Interned<String>>,
     pub positions: RangeInclusive<WordId>,
     pub is_prefix: bool,
+    pub original: Interned<String>,
     pub original_char_count: usize,
 }
 
@@ -33,13 +34,24 @@ pub struct MatchingWords {
 
 impl MatchingWords {
     pub fn new(ctx: SearchContext<'_>, located_terms: Vec<LocatedQueryTerm>) -> Self {
+        let SearchContext { word_interner, phrase_interner, term_interner, .. } = ctx;
+        Self::from_interners(word_interner, phrase_interner, &term_interner, located_terms)
+    }
+
+    /// Build the matching words from the interners the located terms were interned in.
+    pub(crate) fn from_interners(
+        word_interner: DedupInterner<String>,
+        phrase_interner: DedupInterner<Phrase>,
+        term_interner: &Interner<QueryTerm>,
+        located_terms: Vec<LocatedQueryTerm>,
+    ) -> Self {
         let mut phrases = Vec::new();
         let mut words = Vec::new();
 
         // Extract and centralize the different phrases and words to match stored in a QueryTerm
         // and wrap them in dedicated structures.
         for located_term in located_terms {
-            let term = ctx.term_interner.get(located_term.value);
+            let term = term_interner.get(located_term.value);
             let (matching_words, matching_phrases) = term.all_computed_derivations();
 
             for matching_phrase in matching_phrases {
@@ -53,19 +65,15 @@ impl MatchingWords {
                 value: matching_words,
                 positions: located_term.positions.clone(),
                 is_prefix: term.is_prefix(),
-                original_char_count: term.original_word(&ctx).chars().count(),
+                original: term.original_word_id(),
+                original_char_count: word_interner.get(term.original_word_id()).chars().count(),
             });
         }
 
         // Sort word to put prefixes at the bottom prioritizing the exact matches.
         words.sort_unstable_by_key(|lmw| (lmw.is_prefix, Reverse(lmw.positions.len())));
 
-        Self {
-            phrases,
-            words,
-            word_interner: ctx.word_interner,
-            phrase_interner: ctx.phrase_interner,
-        }
+        Self { phrases, words, word_interner, phrase_interner }
     }
 
     /// Returns an iterator over terms that match or partially match the given token.
@@ -73,6 +81,14 @@ impl MatchingWords {
         MatchesIter { matching_words: self, phrases: Box::new(self.phrases.iter()), token }
     }
 
+    /// Returns the word or phrase written by the end user at the given query positions.
+    pub(crate) fn query_word(&self, ids: &RangeInclusive<WordId>) -> Option<&str> {
+        self.words
+            .iter()
+            .find(|located_words| located_words.positions == *ids)
+            .map(|located_words| self.word_interner.get(located_words.original).as_str())
+    }
+
     /// Try to match the token with one of the located_words.
     fn match_unique_words<'a>(&'a self, token: &Token<'_>) -> Option<MatchType<'a>> {
         for located_words in &self.words {
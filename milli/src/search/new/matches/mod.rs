@@ -1,10 +1,14 @@
 use std::borrow::Cow;
+use std::ops::RangeInclusive;
 
 use charabia::{Language, SeparatorKind, Token, Tokenizer};
 pub use matching_words::MatchingWords;
 use matching_words::{MatchType, PartialMatch, WordId};
 use serde::Serialize;
 
+use crate::update::visit_indexed_words;
+use crate::{DocumentId, FieldId, Index, Result};
+
 pub mod matching_words;
 
 const DEFAULT_CROP_MARKER: &str = "…";
@@ -110,6 +114,73 @@ pub struct MatchBounds {
     pub length: usize,
 }
 
+/// Where a word of a document matched a term of the query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchPosition {
+    pub field_id: FieldId,
+    /// The position of the word in its field, as it is indexed.
+    pub word_position: u16,
+    /// The term of the query the word matched, as it was written in the query, e.g. the
+    /// original word of a typo or prefix derivation, or the whole phrase.
+    pub term: String,
+}
+
+/// Return, for each of the given documents, where its searchable fields match the words
+/// of the query.
+///
+/// The documents are flattened and their fields tokenized again like when they are indexed,
+/// so the positions are the exact ones the position-aware databases are built from, before
+/// they are bucketed.
+pub(crate) fn match_positions(
+    matching_words: &MatchingWords,
+    index: &Index,
+    rtxn: &heed::RoTxn<'_>,
+    docids: &[DocumentId],
+) -> Result<Vec<Vec<MatchPosition>>> {
+    let mut positions = vec![Vec::new(); docids.len()];
+    visit_indexed_words(index, rtxn, docids, |document, field_id, words| {
+        let mut i = 0;
+        while i < words.len() {
+            let matched =
+                matching_words.match_token(&words[i].1).find_map(|match_type| match match_type {
+                    MatchType::Full { ids, .. } => Some((1, ids)),
+                    MatchType::Partial(partial) => complete_partial_match(partial, &words[i + 1..]),
+                });
+            match matched {
+                Some((len, ids)) => {
+                    let term = matching_words.query_word(ids).unwrap_or_default();
+                    positions[document].extend(words[i..i + len].iter().map(|(position, _)| {
+                        MatchPosition {
+                            field_id,
+                            word_position: *position as u16,
+                            term: term.to_string(),
+                        }
+                    }));
+                    i += len;
+                }
+                None => i += 1,
+            }
+        }
+    })?;
+    Ok(positions)
+}
+
+/// Return the number of words matching a phrase or a multi-word synonym, its first word
+/// included, and the ids of the query words they match, if the following words complete
+/// the partial match.
+fn complete_partial_match<'a>(
+    mut partial: PartialMatch<'a>,
+    following: &[(usize, Token<'_>)],
+) -> Option<(usize, &'a RangeInclusive<WordId>)> {
+    for (len, (_, token)) in following.iter().enumerate() {
+        match partial.match_token(token)? {
+            MatchType::Full { ids, .. } => return Some((len + 2, ids)),
+            MatchType::Partial(next) => partial = next,
+        }
+    }
+    None
+}
+
 /// Structure used to analyze a string, compute words that match,
 /// and format the source string, returning a highlighted and cropped sub-string.
 pub struct Matcher<'t, 'tokenizer, 'b, 'lang> {
@@ -0,0 +1,63 @@
+use roaring::RoaringBitmap;
+
+use super::query_term::{LocatedQueryTerm, QueryTermSubset};
+use super::resolve_query_graph::compute_query_term_subset_docids;
+use super::SearchContext;
+use crate::Result;
+
+/// Return the documents of the universe matching the terms of the query with at most
+/// `max_typos` typos in total, when the typo ranking rule, which enforces the limit by not
+/// exploring the paths costing more typos, is not used.
+///
+/// A document costs the typos of the closest derivation of each term it contains, the terms
+/// it doesn't contain are removed by the terms matching strategy and cost nothing. The n-th
+/// bitmap contains the documents with at most n typos so far, and each term moves the documents
+/// it matches with typos to the bitmaps of the additional typos.
+pub(crate) fn max_total_typos_docids(
+    ctx: &mut SearchContext<'_>,
+    located_terms: &[LocatedQueryTerm],
+    universe: &RoaringBitmap,
+    max_typos: u8,
+) -> Result<RoaringBitmap> {
+    let mut within = vec![universe.clone(); max_typos as usize + 1];
+    for term in located_terms {
+        let full = QueryTermSubset::full(term.value);
+        let mut matched = RoaringBitmap::new();
+        let mut typo_docids = Vec::new();
+        for nbr_typos in 0..=full.max_typo_cost(ctx) {
+            let mut subset = full.clone();
+            match nbr_typos {
+                0 => {
+                    subset.clear_one_typo_subset();
+                    subset.clear_two_typo_subset();
+                }
+                1 => {
+                    subset.clear_zero_typo_subset();
+                    subset.clear_two_typo_subset();
+                }
+                _ => {
+                    subset.clear_zero_typo_subset();
+                    subset.clear_one_typo_subset();
+                }
+            }
+            let docids = compute_query_term_subset_docids(ctx, Some(universe), &subset)? - &matched;
+            matched |= &docids;
+            typo_docids.push(docids);
+        }
+
+        let without_typos = universe - (&matched - &typo_docids[0]);
+        within = (0..within.len())
+            .map(|total| {
+                let mut docids = &within[total] & &without_typos;
+                for (nbr_typos, typo_docids) in typo_docids.iter().enumerate().skip(1) {
+                    if let Some(before) = total.checked_sub(nbr_typos) {
+                        docids |= &within[before] & typo_docids;
+                    }
+                }
+                docids
+            })
+            .collect();
+    }
+
+    Ok(within.pop().unwrap_or_default())
+}
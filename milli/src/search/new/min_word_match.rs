@@ -0,0 +1,45 @@
+use std::collections::HashSet;
+
+use roaring::RoaringBitmap;
+
+use super::query_term::{LocatedQueryTerm, QueryTermSubset};
+use super::resolve_query_graph::compute_query_term_subset_docids;
+use super::SearchContext;
+use crate::Result;
+
+/// Return the documents of the universe matching at least `ceil(ratio * words)` of the
+/// distinct words of the query.
+///
+/// The documents are counted level by level: the n-th bitmap contains the documents matching
+/// at least n + 1 words, and each word promotes the documents of a level containing it to the
+/// next one.
+pub(crate) fn min_word_match_docids(
+    ctx: &mut SearchContext<'_>,
+    located_terms: &[LocatedQueryTerm],
+    universe: &RoaringBitmap,
+    ratio: f64,
+) -> Result<RoaringBitmap> {
+    let mut words = HashSet::new();
+    let terms: Vec<_> = located_terms
+        .iter()
+        .filter(|term| words.insert(ctx.term_interner.get(term.value).original_word(ctx)))
+        .collect();
+
+    let min_words = (ratio.clamp(0., 1.) * terms.len() as f64).ceil() as usize;
+    if min_words == 0 {
+        return Ok(universe.clone());
+    }
+
+    let mut levels = vec![RoaringBitmap::new(); min_words];
+    for term in terms {
+        let subset = QueryTermSubset::full(term.value);
+        let docids = compute_query_term_subset_docids(ctx, Some(universe), &subset)?;
+        for level in (1..min_words).rev() {
+            let promoted = &levels[level - 1] & &docids;
+            levels[level] |= promoted;
+        }
+        levels[0] |= docids;
+    }
+
+    Ok(levels.pop().unwrap_or_default() & universe)
+}
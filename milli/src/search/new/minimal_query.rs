@@ -0,0 +1,60 @@
+use charabia::Language;
+use roaring::RoaringBitmap;
+
+use super::logger::DefaultSearchLogger;
+use super::query_graph::QueryGraph;
+use super::query_term::{ExtractedTokens, LocatedQueryTerm};
+use super::{extract_tokens, resolve_maximally_reduced_query_graph, SearchContext};
+use crate::{DocumentId, Result, TermsMatchingStrategy};
+
+/// Return, in query order, the original words of a minimal set of query terms matching the
+/// given document, which must be matched by the whole query.
+///
+/// Starting from the whole query, the terms are removed greedily, in query order, as long as
+/// the remaining terms still match the document under the given terms matching strategy.
+/// None of the returned terms can be removed without the document no longer matching.
+pub fn minimal_matching_query(
+    ctx: &mut SearchContext<'_>,
+    query: &str,
+    docid: DocumentId,
+    words_limit: Option<usize>,
+    locales: Option<&Vec<Language>>,
+    matching_strategy: TermsMatchingStrategy,
+) -> Result<Vec<String>> {
+    let ExtractedTokens { mut query_terms, .. } = extract_tokens(ctx, query, words_limit, locales)?;
+
+    let document = RoaringBitmap::from_iter([docid]);
+    let mut i = 0;
+    while i < query_terms.len() && query_terms.len() > 1 {
+        let mut remaining = query_terms.clone();
+        remaining.remove(i);
+        if matches_document(ctx, &document, &remaining, matching_strategy)? {
+            query_terms = remaining;
+        } else {
+            i += 1;
+        }
+    }
+
+    Ok(query_terms
+        .iter()
+        .map(|located_term| ctx.term_interner.get(located_term.value).original_word(ctx))
+        .collect())
+}
+
+/// Whether the query made of the given terms matches the only document of `document`.
+fn matches_document(
+    ctx: &mut SearchContext<'_>,
+    document: &RoaringBitmap,
+    query_terms: &[LocatedQueryTerm],
+    matching_strategy: TermsMatchingStrategy,
+) -> Result<bool> {
+    let (graph, _) = QueryGraph::from_query(ctx, query_terms)?;
+    let docids = resolve_maximally_reduced_query_graph(
+        ctx,
+        document,
+        &graph,
+        matching_strategy,
+        &mut DefaultSearchLogger,
+    )?;
+    Ok(!docids.is_empty())
+}
@@ -1,40 +1,78 @@
+#[cfg(feature = "bench-harness")]
+mod bench_harness;
 mod bucket_sort;
 mod db_cache;
+#[cfg(feature = "search-capture")]
+mod db_cache_capture;
 mod distinct;
+mod diversify;
 mod geo_sort;
 mod graph_based_ranking_rule;
 mod interner;
+mod interner_pool;
 mod limits;
 mod logger;
 pub mod matches;
+mod max_total_typos;
+mod min_word_match;
+mod minimal_query;
+mod normalizer;
+mod proximity_fields;
 mod query_graph;
+mod query_graph_cache;
 mod query_term;
 mod ranking_rule_graph;
 mod ranking_rules;
 mod resolve_query_graph;
 mod small_bitmap;
+mod term_contributions;
+mod term_proximity;
+mod word_filter;
+mod word_window;
 
 mod exact_attribute;
+mod external_sort;
 mod sort;
 mod vector_sort;
 
 #[cfg(test)]
 mod tests;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
+#[cfg(feature = "bench-harness")]
+pub use bench_harness::RankingRuleBench;
 use bucket_sort::{bucket_sort, BucketSortOutput};
-use charabia::{Language, TokenizerBuilder};
+pub use bucket_sort::{BucketKey, DegradationReason, PageBoundary, ResumePoint, RuleStep};
+use charabia::{Language, Tokenizer, TokenizerBuilder};
 use db_cache::DatabaseCache;
+pub use db_cache::SharedDatabaseCache;
+#[cfg(feature = "search-capture")]
+pub use db_cache_capture::DatabaseCacheCapture;
+pub(crate) use diversify::diversify_positions;
 use exact_attribute::ExactAttribute;
 use graph_based_ranking_rule::{Exactness, Fid, Position, Proximity, Typo};
 use heed::RoTxn;
-use interner::{DedupInterner, Interner};
+use interner::Interner;
+pub use interner::{DedupInterner, Interned};
+pub use interner_pool::InternerPool;
+pub use logger::binary::{BinarySearchEvent, BinarySearchLogger};
 pub use logger::visual::VisualSearchLogger;
 pub use logger::{DefaultSearchLogger, SearchLogger};
+use max_total_typos::max_total_typos_docids;
+use min_word_match::min_word_match_docids;
+pub(crate) use minimal_query::minimal_matching_query;
+pub use normalizer::NormalizerConfig;
+use proximity_fields::ProximityFields;
 use query_graph::{QueryGraph, QueryNode};
+pub(crate) use query_graph_cache::QueryGraphCache;
+use query_graph_cache::{CachedQuery, QueryGraphCacheKey};
+pub(crate) use query_term::LocatedQueryTerm;
 use query_term::{
-    located_query_terms_from_tokens, ExtractedTokens, LocatedQueryTerm, Phrase, QueryTerm,
+    dedup_located_query_terms, located_query_terms_from_tokens, ExtractedTokens, Phrase, QueryTerm,
+    TypoDerivationsCache,
 };
 use ranking_rules::{
     BoxRankingRule, PlaceholderQuery, RankingRule, RankingRuleOutput, RankingRuleQueryTrait,
@@ -42,19 +80,24 @@ use ranking_rules::{
 use resolve_query_graph::{compute_query_graph_docids, PhraseDocIdsCache};
 use roaring::RoaringBitmap;
 use sort::Sort;
+pub(crate) use term_contributions::term_contributions;
+pub(crate) use term_proximity::term_proximity;
+pub use word_filter::WordBloomFilter;
+use word_window::word_window_docids;
 
 use self::distinct::facet_string_values;
+use self::external_sort::ExternalSort;
 use self::geo_sort::GeoSort;
-pub use self::geo_sort::Strategy as GeoSortStrategy;
+pub(crate) use self::geo_sort::{geo_distance_buckets, geo_distances};
+pub use self::geo_sort::{GeoDecay, GeoDistanceBuckets, GeoScore, Strategy as GeoSortStrategy};
 use self::graph_based_ranking_rule::Words;
-use self::interner::Interned;
 use self::vector_sort::VectorSort;
 use crate::score_details::{ScoreDetails, ScoringStrategy};
 use crate::search::new::distinct::apply_distinct_rule;
 use crate::vector::Embedder;
 use crate::{
-    AscDesc, DocumentId, FieldId, Filter, Index, Member, Result, TermsMatchingStrategy, TimeBudget,
-    UserError, Weight,
+    AscDesc, Criterion, DocumentId, Error, FieldId, Filter, Index, Member, RankingRuleKind, Result,
+    TermsMatchingStrategy, TimeBudget, UserError, Weight,
 };
 
 /// A structure used throughout the execution of a search query.
@@ -66,11 +109,109 @@ pub struct SearchContext<'ctx> {
     pub phrase_interner: DedupInterner<Phrase>,
     pub term_interner: Interner<QueryTerm>,
     pub phrase_docids: PhraseDocIdsCache,
+    pub typo_derivations: TypoDerivationsCache,
     pub restricted_fids: Option<RestrictedFids>,
+    /// Whether the proximity between two words depends on the order in which they appear.
+    pub ordered_proximity: bool,
+    /// The sort keys provided at search time, by name, that are read instead of the facets.
+    pub external_sorts: HashMap<String, Arc<HashMap<DocumentId, f64>>>,
+    /// The number of threads the ranking rules can use to compute their buckets, `1` disables it.
+    pub parallelism: usize,
+    /// The normalized synonyms provided at search time, added to the ones of the index.
+    pub query_synonyms: HashMap<Vec<String>, Vec<Vec<String>>>,
+    /// The number of candidates under which they are returned without being ranked.
+    pub skip_ranking_when_under: Option<usize>,
+    /// Whether the stop words between two words of the query are ignored by the proximity.
+    pub proximity_ignores_stop_words: bool,
+    /// Whether the proximity ranking rule returns the documents whose query words are the
+    /// farthest apart first.
+    pub invert_proximity: bool,
+    /// Whether the proximity paths going through the rarest query terms are explored first.
+    pub proximity_rarest_first: bool,
+    /// The positions of the stop words of the query, which were not turned into query terms.
+    pub(crate) query_stop_word_positions: Vec<u16>,
+    /// The filter of the indexed words, skipping the lookups of the words it doesn't contain.
+    pub word_filter: Option<&'ctx WordBloomFilter>,
+    /// The latitude and longitude fields read by the geo sort instead of `_geo`.
+    pub geo_fields: Option<(String, String)>,
+    /// The width, in meters, of the distance bands whose documents the geo sort returns
+    /// together, `0` sorts by exact distance.
+    pub geo_distance_quantum: f64,
+    /// The number of positions the words of a phrase can be farther apart than in the query.
+    pub phrase_slop: u8,
+    /// The query graphs built by the previous searches of this context, reused when the same
+    /// query is searched again, `None` disables the cache.
+    pub(crate) query_graph_cache: Option<QueryGraphCache>,
+    /// Whether the buckets containing the last returned document are reported.
+    pub page_boundary: bool,
+    /// How the words of the query are normalized, `None` uses the normalization of the index.
+    pub normalizer: Option<NormalizerConfig>,
+    /// The highest sum of the typos of the words of the query, `None` doesn't limit it.
+    pub max_total_typos: Option<u8>,
+    /// The highest number of positions between the first and last words of the query matched
+    /// in a field of a document, `None` doesn't filter the documents on it.
+    pub word_window: Option<u16>,
+    /// The lowest ratio of the distinct words of the query a document must match to be a
+    /// candidate, `None` doesn't filter the documents on it.
+    pub min_word_match_ratio: Option<f64>,
+    /// Whether the words repeated in the query are kept as distinct terms of the query graph
+    /// instead of being merged with their first occurrence.
+    pub keep_duplicate_tokens: bool,
+    /// The lowest number of characters of the last word of the query for it to be matched as
+    /// a prefix, `None` always matches it as a prefix.
+    pub min_prefix_len: Option<u8>,
+    /// The highest number of candidates given to the first expensive ranking rule, in the
+    /// order of the ranking rules before it, the candidates over it are dropped, `None` ranks
+    /// all of them.
+    pub max_ranked_candidates: Option<usize>,
+    /// The edges, in meters and in increasing order, of the distance bands whose documents the
+    /// geo sort returns together, overriding `geo_distance_quantum`.
+    pub geo_distance_buckets: Option<Vec<f64>>,
+    /// A flag set from outside of the search to cancel it, checked between the buckets of the
+    /// ranking rules and while scanning the documents to sort.
+    pub cancel_token: Option<Arc<AtomicBool>>,
+    /// The weights, by field name, multiplying the proximity of the words of the query in the
+    /// documents of a proximity bucket to sort them, `None` doesn't sort them.
+    pub proximity_field_weights: Option<HashMap<String, f64>>,
+    /// The built-in ranking rules of the index skipped by the keyword search.
+    pub disabled_ranking_rules: Vec<RankingRuleKind>,
+    /// The highest number of candidates of a bucket of a ranking rule, by ranking rule id, given
+    /// to the next ranking rules, the other candidates of the bucket are dropped.
+    pub bucket_limits: HashMap<String, usize>,
+    /// The statistics of the search, only collected when set.
+    pub query_stats: Option<QueryStats>,
+    /// The ranking rules given a universe to sort by the bucket sort, only collected when set.
+    pub execution_trace: Option<Vec<RuleStep>>,
+    /// The documents returned before the ranked documents, in this order, when they are part
+    /// of the universe.
+    pub pinned: Vec<DocumentId>,
+    /// The document after which the bucket sort resumes returning documents, `None` starts
+    /// from the first one.
+    pub resume_after: Option<ResumePoint>,
+    /// The number of words of the vocabulary of the pool this context was created in.
+    vocabulary_len: usize,
 }
 
 impl<'ctx> SearchContext<'ctx> {
     pub fn new(index: &'ctx Index, txn: &'ctx RoTxn<'ctx>) -> Result<Self> {
+        Self::with_interner_pool(index, txn, InternerPool::default())
+    }
+
+    /// Create a search context whose words, phrases, and query terms are stored in the
+    /// interners of the given pool, previously returned by [`Self::into_interner_pool`].
+    pub fn with_interner_pool(
+        index: &'ctx Index,
+        txn: &'ctx RoTxn<'ctx>,
+        pool: InternerPool,
+    ) -> Result<Self> {
+        let InternerPool {
+            word_interner,
+            phrase_interner,
+            term_interner,
+            phrase_docids,
+            typo_derivations,
+            vocabulary_len,
+        } = pool;
         let searchable_fids = index.searchable_fields_and_weights(txn)?;
         let exact_attributes_ids = index.exact_attributes_ids(txn)?;
 
@@ -88,18 +229,117 @@ impl<'ctx> SearchContext<'ctx> {
             index,
             txn,
             db_cache: <_>::default(),
-            word_interner: <_>::default(),
-            phrase_interner: <_>::default(),
-            term_interner: <_>::default(),
-            phrase_docids: <_>::default(),
+            word_interner,
+            phrase_interner,
+            term_interner,
+            phrase_docids,
+            typo_derivations,
             restricted_fids: None,
+            ordered_proximity: true,
+            external_sorts: HashMap::new(),
+            parallelism: 1,
+            query_synonyms: HashMap::new(),
+            skip_ranking_when_under: None,
+            proximity_ignores_stop_words: false,
+            invert_proximity: false,
+            proximity_rarest_first: false,
+            query_stop_word_positions: Vec::new(),
+            word_filter: None,
+            geo_fields: None,
+            geo_distance_quantum: 0.0,
+            phrase_slop: 0,
+            query_graph_cache: None,
+            page_boundary: false,
+            normalizer: None,
+            max_total_typos: None,
+            word_window: None,
+            min_word_match_ratio: None,
+            keep_duplicate_tokens: false,
+            min_prefix_len: None,
+            max_ranked_candidates: None,
+            geo_distance_buckets: None,
+            cancel_token: None,
+            proximity_field_weights: None,
+            disabled_ranking_rules: Vec::new(),
+            bucket_limits: HashMap::new(),
+            query_stats: None,
+            execution_trace: None,
+            pinned: Vec::new(),
+            resume_after: None,
+            vocabulary_len,
         })
     }
 
-    pub fn attributes_to_search_on(
-        &mut self,
-        attributes_to_search_on: &'ctx [String],
-    ) -> Result<()> {
+    /// Give back the interners and caches of this search context, cleared, so that their
+    /// capacity can be reused by the next one, see [`Self::with_interner_pool`].
+    pub fn into_interner_pool(self) -> InternerPool {
+        let mut pool = InternerPool {
+            word_interner: self.word_interner,
+            phrase_interner: self.phrase_interner,
+            term_interner: self.term_interner,
+            phrase_docids: self.phrase_docids,
+            typo_derivations: self.typo_derivations,
+            vocabulary_len: self.vocabulary_len,
+        };
+        pool.clear();
+        pool
+    }
+
+    /// Create a search context reading the databases through the given shared cache,
+    /// which must have been filled on the same transaction, and reusing its query graphs.
+    ///
+    /// The cache is only taken once the context is created, it is left to the caller if
+    /// reading the settings of the index fails. A cache whose interners are full is dropped
+    /// and the context starts from an empty one.
+    pub(crate) fn with_shared_cache(
+        index: &'ctx Index,
+        txn: &'ctx RoTxn<'ctx>,
+        shared: &mut SharedDatabaseCache<'ctx>,
+    ) -> Result<Self> {
+        let mut ctx = Self::new(index, txn)?;
+        let mut shared = std::mem::take(shared);
+        if shared.is_full() {
+            shared = SharedDatabaseCache::default();
+        }
+        let SharedDatabaseCache {
+            word_interner,
+            phrase_interner,
+            term_interner,
+            db_cache,
+            query_graph_cache,
+        } = shared;
+        ctx.vocabulary_len = word_interner.len();
+        ctx.word_interner = word_interner;
+        ctx.phrase_interner = phrase_interner;
+        ctx.term_interner = term_interner;
+        ctx.db_cache = db_cache;
+        ctx.query_graph_cache = Some(
+            query_graph_cache
+                .unwrap_or_else(|| QueryGraphCache::new(limits::SHARED_QUERY_GRAPH_COUNT)),
+        );
+        Ok(ctx)
+    }
+
+    /// Take the database cache and the query graphs of this context, along with the words,
+    /// phrases, and terms they refer to, to share them with the next search context on the
+    /// same transaction. The context must not be used to search anymore.
+    pub(crate) fn take_shared_cache(&mut self) -> SharedDatabaseCache<'ctx> {
+        SharedDatabaseCache {
+            word_interner: std::mem::take(&mut self.word_interner),
+            phrase_interner: std::mem::take(&mut self.phrase_interner),
+            term_interner: std::mem::take(&mut self.term_interner),
+            db_cache: std::mem::take(&mut self.db_cache),
+            query_graph_cache: self.query_graph_cache.take(),
+        }
+    }
+
+    /// Keep the query graphs of the `capacity` most recently searched queries in this context,
+    /// to reuse them when the same query is searched again with it.
+    pub fn enable_query_graph_cache(&mut self, capacity: usize) {
+        self.query_graph_cache = Some(QueryGraphCache::new(capacity));
+    }
+
+    pub fn attributes_to_search_on(&mut self, attributes_to_search_on: &[String]) -> Result<()> {
         let user_defined_searchable = self.index.user_defined_searchable_fields(self.txn)?;
         let searchable_fields_weights = self.index.searchable_fields_and_weights(self.txn)?;
         let exact_attributes_ids = self.index.exact_attributes_ids(self.txn)?;
@@ -152,6 +392,82 @@ impl<'ctx> SearchContext<'ctx> {
 
         Ok(())
     }
+
+    /// Add synonyms to the ones defined in the settings of the index, for this search only.
+    ///
+    /// The words and their synonyms are normalized the same way the synonyms of the settings are.
+    pub fn query_synonyms(&mut self, synonyms: &HashMap<String, Vec<String>>) -> Result<()> {
+        fn normalize(tokenizer: &Tokenizer<'_>, text: &str) -> Vec<String> {
+            tokenizer
+                .tokenize(text)
+                .filter_map(|token| {
+                    if token.is_word() && !token.lemma().is_empty() {
+                        Some(token.lemma().to_string())
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<_>>()
+        }
+
+        let mut builder = TokenizerBuilder::new();
+        let stop_words = self.index.stop_words(self.txn)?;
+        if let Some(ref stop_words) = stop_words {
+            builder.stop_words(stop_words);
+        }
+
+        let separators = self.index.allowed_separators(self.txn)?;
+        let separators: Option<Vec<_>> =
+            separators.as_ref().map(|x| x.iter().map(String::as_str).collect());
+        if let Some(ref separators) = separators {
+            builder.separators(separators);
+        }
+
+        let dictionary = self.index.dictionary(self.txn)?;
+        let dictionary: Option<Vec<_>> =
+            dictionary.as_ref().map(|x| x.iter().map(String::as_str).collect());
+        if let Some(ref dictionary) = dictionary {
+            builder.words_dict(dictionary);
+        }
+
+        let tokenizer = builder.build();
+
+        for (word, word_synonyms) in synonyms {
+            let normalized_word = normalize(&tokenizer, word);
+            if normalized_word.is_empty() {
+                continue;
+            }
+            let entry = self.query_synonyms.entry(normalized_word).or_default();
+            for synonym in word_synonyms {
+                let synonym = normalize(&tokenizer, synonym);
+                if !synonym.is_empty() && !entry.contains(&synonym) {
+                    entry.push(synonym);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the synonyms of the given normalized words, the ones defined in the settings
+    /// of the index followed by the ones provided for this search.
+    pub fn words_synonyms(&self, words: &[String]) -> Result<Vec<Vec<String>>> {
+        let mut synonyms = self.index.words_synonyms(self.txn, words)?.unwrap_or_default();
+        for synonym in self.query_synonyms.get(words).into_iter().flatten() {
+            if !synonyms.contains(synonym) {
+                synonyms.push(synonym.clone());
+            }
+        }
+        Ok(synonyms)
+    }
+
+    /// Returns an error if the search was cancelled through its cancel token.
+    pub fn check_cancelled(&self) -> Result<()> {
+        match &self.cancel_token {
+            Some(token) if token.load(Ordering::Relaxed) => Err(Error::SearchCancelled),
+            _ => Ok(()),
+        }
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, PartialOrd, Ord, Eq)]
@@ -269,7 +585,7 @@ fn get_ranking_rules_for_placeholder_search<'ctx>(
     let mut sorted_fields = HashSet::new();
     let mut geo_sorted = false;
     let mut ranking_rules: Vec<BoxRankingRule<'ctx, PlaceholderQuery>> = vec![];
-    let settings_ranking_rules = ctx.index.criteria(ctx.txn)?;
+    let settings_ranking_rules = enabled_criteria(ctx)?;
     for rr in settings_ranking_rules {
         match rr {
             // These rules need a query to have an effect; ignore them in placeholder search
@@ -385,6 +701,15 @@ fn get_ranking_rules_for_vector<'ctx>(
     Ok(ranking_rules)
 }
 
+/// Return the ranking rules of the index, without the ones disabled for this search.
+fn enabled_criteria(ctx: &SearchContext<'_>) -> Result<Vec<Criterion>> {
+    let mut criteria = ctx.index.criteria(ctx.txn)?;
+    criteria.retain(|criterion| {
+        !criterion.kind().is_some_and(|kind| ctx.disabled_ranking_rules.contains(&kind))
+    });
+    Ok(criteria)
+}
+
 /// Return the list of initialised ranking rules to be used for a query graph search.
 fn get_ranking_rules_for_query_graph_search<'ctx>(
     ctx: &SearchContext<'ctx>,
@@ -408,7 +733,7 @@ fn get_ranking_rules_for_query_graph_search<'ctx>(
     }
 
     let mut ranking_rules: Vec<BoxRankingRule<'ctx, QueryGraph>> = vec![];
-    let settings_ranking_rules = ctx.index.criteria(ctx.txn)?;
+    let settings_ranking_rules = enabled_criteria(ctx)?;
     for rr in settings_ranking_rules {
         // Add Words before any of: typo, proximity, attribute
         match rr {
@@ -443,7 +768,10 @@ fn get_ranking_rules_for_query_graph_search<'ctx>(
                     continue;
                 }
                 proximity = true;
-                ranking_rules.push(Box::new(Proximity::new(None)));
+                ranking_rules.push(Box::new(Proximity::new(None).inverted(ctx.invert_proximity)));
+                if let Some(weights) = &ctx.proximity_field_weights {
+                    ranking_rules.push(Box::new(ProximityFields::new(weights.clone())));
+                }
             }
             crate::Criterion::Attribute => {
                 if attribute {
@@ -505,50 +833,69 @@ fn resolve_sort_criteria<'ctx, Query: RankingRuleQueryTrait>(
     let sort_criteria = sort_criteria.clone().unwrap_or_default();
     ranking_rules.reserve(sort_criteria.len());
     for criterion in sort_criteria {
-        match criterion {
-            AscDesc::Asc(Member::Field(field_name)) => {
-                if sorted_fields.contains(&field_name) {
-                    continue;
-                }
-                sorted_fields.insert(field_name.clone());
-                ranking_rules.push(Box::new(Sort::new(ctx.index, ctx.txn, field_name, true)?));
-            }
-            AscDesc::Desc(Member::Field(field_name)) => {
+        let (member, is_ascending) = match criterion {
+            AscDesc::Asc(member) => (member, true),
+            AscDesc::Desc(member) => (member, false),
+        };
+        match member {
+            Member::Field(field_name) => {
                 if sorted_fields.contains(&field_name) {
                     continue;
                 }
                 sorted_fields.insert(field_name.clone());
-                ranking_rules.push(Box::new(Sort::new(ctx.index, ctx.txn, field_name, false)?));
-            }
-            AscDesc::Asc(Member::Geo(point)) => {
-                if *geo_sorted {
-                    continue;
-                }
-                let geo_faceted_docids = ctx.index.geo_faceted_documents_ids(ctx.txn)?;
-                ranking_rules.push(Box::new(GeoSort::new(
-                    geo_strategy,
-                    geo_faceted_docids,
-                    point,
-                    true,
-                )?));
+                ranking_rules.push(field_sort_rule(ctx, field_name, is_ascending)?);
             }
-            AscDesc::Desc(Member::Geo(point)) => {
+            member => {
                 if *geo_sorted {
                     continue;
                 }
-                let geo_faceted_docids = ctx.index.geo_faceted_documents_ids(ctx.txn)?;
-                ranking_rules.push(Box::new(GeoSort::new(
-                    geo_strategy,
-                    geo_faceted_docids,
-                    point,
-                    false,
-                )?));
+                ranking_rules.push(geo_sort_rule(ctx, geo_strategy, member, is_ascending)?);
             }
         };
     }
     Ok(())
 }
 
+/// Return the geo sort ranking rule of the given geo member, sorting the documents by their
+/// distance to its point or its shape, a field being sorted by [`field_sort_rule`].
+fn geo_sort_rule<'ctx, Query: RankingRuleQueryTrait>(
+    ctx: &SearchContext<'ctx>,
+    geo_strategy: geo_sort::Strategy,
+    member: Member,
+    is_ascending: bool,
+) -> Result<BoxRankingRule<'ctx, Query>> {
+    let geo_faceted_docids = geo_sort::geo_faceted_docids(ctx, geo_strategy)?;
+    let geo_sort = match member {
+        Member::Geo(point) => {
+            GeoSort::new(geo_strategy, geo_faceted_docids, point, None, is_ascending)?
+        }
+        Member::Geo3D([lat, lng, alt]) => {
+            GeoSort::new(geo_strategy, geo_faceted_docids, [lat, lng], Some(alt), is_ascending)?
+        }
+        Member::GeoRoute(route) => {
+            GeoSort::new_route(geo_strategy, geo_faceted_docids, route, is_ascending)?
+        }
+        Member::GeoPolygonVertices(vertices) => {
+            GeoSort::new_polygon_vertices(geo_strategy, geo_faceted_docids, vertices, is_ascending)?
+        }
+        Member::Field(field_name) => return field_sort_rule(ctx, field_name, is_ascending),
+    };
+    Ok(Box::new(geo_sort))
+}
+
+/// Return the sort ranking rule of the given field, reading the values from the
+/// caller-provided map instead of the facets if the field is an external sort key.
+fn field_sort_rule<'ctx, Query: RankingRuleQueryTrait>(
+    ctx: &SearchContext<'ctx>,
+    field_name: String,
+    is_ascending: bool,
+) -> Result<BoxRankingRule<'ctx, Query>> {
+    match ctx.external_sorts.get(&field_name) {
+        Some(values) => Ok(Box::new(ExternalSort::new(field_name, values.clone(), is_ascending))),
+        None => Ok(Box::new(Sort::new(ctx.index, ctx.txn, field_name, is_ascending)?)),
+    }
+}
+
 #[tracing::instrument(level = "trace", skip_all, target = "search::universe")]
 pub fn filtered_universe(
     index: &Index,
@@ -562,6 +909,26 @@ pub fn filtered_universe(
     })
 }
 
+/// Return the documents of the universe containing any of the given words, as they are,
+/// without deriving their typos nor their prefixes.
+///
+/// The words are read directly from the word docids databases, they must therefore be
+/// normalized the same way as the indexed words.
+pub fn any_exact_word_docids(
+    ctx: &mut SearchContext<'_>,
+    universe: &RoaringBitmap,
+    words: &[String],
+) -> Result<RoaringBitmap> {
+    let mut docids = RoaringBitmap::new();
+    for word in words {
+        let word = ctx.word_interner.insert(word.clone());
+        if let Some(word_docids) = ctx.word_docids(Some(universe), Word::Original(word))? {
+            docids |= word_docids;
+        }
+    }
+    Ok(docids & universe)
+}
+
 #[allow(clippy::too_many_arguments)]
 pub fn execute_vector_search(
     ctx: &mut SearchContext<'_>,
@@ -596,7 +963,16 @@ pub fn execute_vector_search(
     let placeholder_search_logger: &mut dyn SearchLogger<PlaceholderQuery> =
         &mut placeholder_search_logger;
 
-    let BucketSortOutput { docids, scores, all_candidates, degraded } = bucket_sort(
+    let BucketSortOutput {
+        docids,
+        scores,
+        all_candidates,
+        below_threshold,
+        page_boundary,
+        degraded,
+        capped,
+        bucket_limited,
+    } = bucket_sort(
         ctx,
         ranking_rules,
         &PlaceholderQuery,
@@ -615,11 +991,180 @@ pub fn execute_vector_search(
         document_scores: scores,
         documents_ids: docids,
         located_query_terms: None,
-        degraded,
+        below_threshold,
+        page_boundary,
+        degraded: degraded || bucket_limited,
+        degradation_reasons: degradation_reasons(ctx, None, degraded, capped, bucket_limited),
         used_negative_operator: false,
     })
 }
 
+/// Tokenize the query with the settings of the index and build its query terms, without the
+/// duplicated words unless the context keeps them.
+fn extract_tokens(
+    ctx: &mut SearchContext<'_>,
+    query: &str,
+    words_limit: Option<usize>,
+    locales: Option<&Vec<Language>>,
+) -> Result<ExtractedTokens> {
+    let span = tracing::trace_span!(target: "search::tokens", "tokenizer_builder");
+    let entered = span.enter();
+
+    // We make sure that the analyzer is aware of the stop words
+    // this ensures that the query builder is able to properly remove them.
+    let mut tokbuilder = TokenizerBuilder::new();
+    let stop_words = ctx.index.stop_words(ctx.txn)?;
+    if let Some(ref stop_words) = stop_words {
+        tokbuilder.stop_words(stop_words);
+    }
+
+    let normalizer = ctx.normalizer.clone();
+    let separators = match normalizer.as_ref().and_then(|n| n.separators.as_ref()) {
+        Some(separators) => Some(separators.iter().cloned().collect()),
+        None => ctx.index.allowed_separators(ctx.txn)?,
+    };
+    let separators: Option<Vec<_>> =
+        separators.as_ref().map(|x| x.iter().map(String::as_str).collect());
+    if let Some(ref separators) = separators {
+        tokbuilder.separators(separators);
+    }
+
+    if let Some(normalizer) = &normalizer {
+        tokbuilder.lossy_normalization(normalizer.fold_diacritics);
+    }
+
+    let dictionary = ctx.index.dictionary(ctx.txn)?;
+    let dictionary: Option<Vec<_>> =
+        dictionary.as_ref().map(|x| x.iter().map(String::as_str).collect());
+    if let Some(ref dictionary) = dictionary {
+        tokbuilder.words_dict(dictionary);
+    }
+
+    if let Some(locales) = locales {
+        tokbuilder.allow_list(locales);
+    }
+
+    let tokenizer = tokbuilder.build();
+    drop(entered);
+
+    let span = tracing::trace_span!(target: "search::tokens", "tokenize");
+    let entered = span.enter();
+    let tokens = tokenizer.tokenize(query);
+    drop(entered);
+
+    let mut extracted = match normalizer {
+        Some(normalizer) => {
+            let tokens = tokens.map(|token| normalizer.normalize(token));
+            located_query_terms_from_tokens(ctx, tokens, words_limit)?
+        }
+        None => located_query_terms_from_tokens(ctx, tokens, words_limit)?,
+    };
+    if !ctx.keep_duplicate_tokens {
+        dedup_located_query_terms(ctx, &mut extracted.query_terms);
+    }
+    Ok(extracted)
+}
+
+/// Return the words of the query that are searched, in order, once the query is normalized and
+/// its stop words are removed. The phrases are returned between double quotes.
+pub(crate) fn effective_query_terms(
+    ctx: &mut SearchContext<'_>,
+    query: &str,
+    words_limit: Option<usize>,
+    locales: Option<&Vec<Language>>,
+) -> Result<Vec<String>> {
+    let ExtractedTokens { query_terms, .. } = extract_tokens(ctx, query, words_limit, locales)?;
+    Ok(query_terms
+        .into_iter()
+        .map(|located_term| {
+            let term = ctx.term_interner.get(located_term.value);
+            match term.original_phrase() {
+                Some(phrase) => format!("\"{}\"", phrase.description(ctx)),
+                None => term.original_word(ctx),
+            }
+        })
+        .collect())
+}
+
+/// Return the identifiers of the ranking rules a search of the query applies, in order, e.g.
+/// `words`, `price:asc` or `geo_sort`. The rules of a placeholder search are returned when
+/// there is no query or when it has no words to search for.
+pub(crate) fn ranking_rule_descriptors<'ctx>(
+    ctx: &mut SearchContext<'ctx>,
+    query: Option<&str>,
+    words_limit: Option<usize>,
+    locales: Option<&Vec<Language>>,
+    sort_criteria: &Option<Vec<AscDesc>>,
+    geo_strategy: geo_sort::Strategy,
+    terms_matching_strategy: TermsMatchingStrategy,
+) -> Result<Vec<String>> {
+    check_sort_criteria(ctx, sort_criteria.as_ref())?;
+
+    let graph = match query {
+        Some(query) => cached_query(ctx, query, words_limit, locales)?.graph,
+        None => None,
+    };
+    let descriptors = if graph.is_some() {
+        get_ranking_rules_for_query_graph_search(
+            ctx,
+            sort_criteria,
+            geo_strategy,
+            terms_matching_strategy,
+        )?
+        .iter()
+        .map(|rule| rule.id())
+        .collect()
+    } else {
+        get_ranking_rules_for_placeholder_search(ctx, sort_criteria, geo_strategy)?
+            .iter()
+            .map(|rule| rule.id())
+            .collect()
+    };
+    Ok(descriptors)
+}
+
+/// Tokenize the query and build its query graph, or reuse the ones built by a previous
+/// search of the same query when the query graph cache of the context is enabled.
+fn cached_query(
+    ctx: &mut SearchContext<'_>,
+    query: &str,
+    words_limit: Option<usize>,
+    locales: Option<&Vec<Language>>,
+) -> Result<CachedQuery> {
+    let key = QueryGraphCacheKey::new(
+        query,
+        words_limit,
+        locales,
+        ctx.normalizer.as_ref(),
+        ctx.keep_duplicate_tokens,
+        ctx.min_prefix_len,
+        &ctx.query_synonyms,
+    );
+    if let Some(cached) = ctx.query_graph_cache.as_mut().and_then(|cache| cache.get(&key)) {
+        ctx.query_stop_word_positions.clone_from(&cached.stop_word_positions);
+        return Ok(cached);
+    }
+
+    let ExtractedTokens { query_terms, negative_words, negative_phrases } =
+        extract_tokens(ctx, query, words_limit, locales)?;
+    let graph = if query_terms.is_empty() {
+        // Do a placeholder search instead
+        None
+    } else {
+        Some(QueryGraph::from_query(ctx, &query_terms)?)
+    };
+    let cached = CachedQuery {
+        negative_words,
+        negative_phrases,
+        graph,
+        stop_word_positions: ctx.query_stop_word_positions.clone(),
+    };
+    if let Some(cache) = &mut ctx.query_graph_cache {
+        cache.insert(key, cached.clone());
+    }
+    Ok(cached)
+}
+
 #[allow(clippy::too_many_arguments)]
 #[tracing::instrument(level = "trace", skip_all, target = "search::main")]
 pub fn execute_search(
@@ -644,47 +1189,11 @@ pub fn execute_search(
     check_sort_criteria(ctx, sort_criteria.as_ref())?;
 
     let mut used_negative_operator = false;
+    let mut window_degraded = false;
     let mut located_query_terms = None;
-    let query_terms = if let Some(query) = query {
-        let span = tracing::trace_span!(target: "search::tokens", "tokenizer_builder");
-        let entered = span.enter();
-
-        // We make sure that the analyzer is aware of the stop words
-        // this ensures that the query builder is able to properly remove them.
-        let mut tokbuilder = TokenizerBuilder::new();
-        let stop_words = ctx.index.stop_words(ctx.txn)?;
-        if let Some(ref stop_words) = stop_words {
-            tokbuilder.stop_words(stop_words);
-        }
-
-        let separators = ctx.index.allowed_separators(ctx.txn)?;
-        let separators: Option<Vec<_>> =
-            separators.as_ref().map(|x| x.iter().map(String::as_str).collect());
-        if let Some(ref separators) = separators {
-            tokbuilder.separators(separators);
-        }
-
-        let dictionary = ctx.index.dictionary(ctx.txn)?;
-        let dictionary: Option<Vec<_>> =
-            dictionary.as_ref().map(|x| x.iter().map(String::as_str).collect());
-        if let Some(ref dictionary) = dictionary {
-            tokbuilder.words_dict(dictionary);
-        }
-
-        if let Some(locales) = locales {
-            tokbuilder.allow_list(locales);
-        }
-
-        let tokenizer = tokbuilder.build();
-        drop(entered);
-
-        let span = tracing::trace_span!(target: "search::tokens", "tokenize");
-        let entered = span.enter();
-        let tokens = tokenizer.tokenize(query);
-        drop(entered);
-
-        let ExtractedTokens { query_terms, negative_words, negative_phrases } =
-            located_query_terms_from_tokens(ctx, tokens, words_limit)?;
+    let query_graph = if let Some(query) = query {
+        let CachedQuery { negative_words, negative_phrases, graph, .. } =
+            cached_query(ctx, query, words_limit, locales)?;
         used_negative_operator = !negative_words.is_empty() || !negative_phrases.is_empty();
 
         let ignored_documents = resolve_negative_words(ctx, Some(&universe), &negative_words)?;
@@ -693,18 +1202,15 @@ pub fn execute_search(
         universe -= ignored_documents;
         universe -= ignored_phrases;
 
-        if query_terms.is_empty() {
-            // Do a placeholder search instead
-            None
-        } else {
-            Some(query_terms)
+        if let (Some(stats), Some((graph, _))) = (&mut ctx.query_stats, &graph) {
+            stats.query_graph_nodes = graph.nodes.len() as usize;
         }
+        graph
     } else {
         None
     };
 
-    let bucket_sort_output = if let Some(query_terms) = query_terms {
-        let (graph, new_located_query_terms) = QueryGraph::from_query(ctx, &query_terms)?;
+    let bucket_sort_output = if let Some((graph, new_located_query_terms)) = query_graph {
         located_query_terms = Some(new_located_query_terms);
 
         let ranking_rules = get_ranking_rules_for_query_graph_search(
@@ -716,6 +1222,22 @@ pub fn execute_search(
 
         universe &=
             resolve_universe(ctx, &universe, &graph, terms_matching_strategy, query_graph_logger)?;
+        if let (Some(max), Some(terms)) = (ctx.max_total_typos, &located_query_terms) {
+            // the typo ranking rule doesn't explore the paths over the typo limit, the universe
+            // only has to be filtered when it isn't used
+            if !ranking_rules.iter().any(|rule| rule.id() == "typo") {
+                universe = max_total_typos_docids(ctx, terms, &universe, max)?;
+            }
+        }
+        if let (Some(ratio), Some(terms)) = (ctx.min_word_match_ratio, &located_query_terms) {
+            universe = min_word_match_docids(ctx, terms, &universe, ratio)?;
+        }
+        if let (Some(window), Some(terms)) = (ctx.word_window, &located_query_terms) {
+            let (in_window, degraded) =
+                word_window_docids(ctx, terms, &universe, window, &time_budget)?;
+            universe = in_window;
+            window_degraded = degraded;
+        }
 
         bucket_sort(
             ctx,
@@ -748,7 +1270,17 @@ pub fn execute_search(
         )?
     };
 
-    let BucketSortOutput { docids, scores, mut all_candidates, degraded } = bucket_sort_output;
+    let BucketSortOutput {
+        docids,
+        scores,
+        mut all_candidates,
+        below_threshold,
+        page_boundary,
+        degraded,
+        capped,
+        bucket_limited,
+    } = bucket_sort_output;
+    let degraded = degraded || window_degraded;
     let fields_ids_map = ctx.index.fields_ids_map(ctx.txn)?;
 
     // The candidates is the universe unless the exhaustive number of hits
@@ -766,16 +1298,49 @@ pub fn execute_search(
         }
     }
 
+    let degradation_reasons =
+        degradation_reasons(ctx, located_query_terms.as_deref(), degraded, capped, bucket_limited);
     Ok(PartialSearchResult {
         candidates: all_candidates,
         document_scores: scores,
         documents_ids: docids,
         located_query_terms,
-        degraded,
+        below_threshold,
+        page_boundary,
+        degraded: degraded || bucket_limited,
+        degradation_reasons,
         used_negative_operator,
     })
 }
 
+/// Return the reasons why the results of a search may be incomplete.
+fn degradation_reasons(
+    ctx: &SearchContext<'_>,
+    located_query_terms: Option<&[LocatedQueryTerm]>,
+    degraded: bool,
+    capped: bool,
+    bucket_limited: bool,
+) -> Vec<DegradationReason> {
+    let mut reasons = Vec::new();
+    let prefix_truncated = located_query_terms
+        .into_iter()
+        .flatten()
+        .any(|term| ctx.term_interner.get(term.value).is_prefix_truncated());
+    if prefix_truncated {
+        reasons.push(DegradationReason::PrefixTruncated);
+    }
+    if capped {
+        reasons.push(DegradationReason::CandidateCapHit);
+    }
+    if degraded {
+        reasons.push(DegradationReason::TimedOut);
+    }
+    if bucket_limited {
+        reasons.push(DegradationReason::BucketLimitHit);
+    }
+    reasons
+}
+
 fn check_sort_criteria(
     ctx: &SearchContext<'_>,
     sort_criteria: Option<&Vec<AscDesc>>,
@@ -802,7 +1367,10 @@ fn check_sort_criteria(
     let sortable_fields = ctx.index.sortable_fields(ctx.txn)?;
     for asc_desc in sort_criteria {
         match asc_desc.member() {
-            Member::Field(ref field) if !crate::is_faceted(field, &sortable_fields) => {
+            Member::Field(ref field)
+                if !ctx.external_sorts.contains_key(field)
+                    && !crate::is_faceted(field, &sortable_fields) =>
+            {
                 let (valid_fields, hidden_fields) =
                     ctx.index.remove_hidden_fields(ctx.txn, sortable_fields)?;
 
@@ -813,30 +1381,76 @@ fn check_sort_criteria(
                 }
                 .into());
             }
-            Member::Geo(_) if !sortable_fields.contains("_geo") => {
-                let (valid_fields, hidden_fields) =
-                    ctx.index.remove_hidden_fields(ctx.txn, sortable_fields)?;
-
-                return Err(UserError::InvalidSortableAttribute {
-                    field: "_geo".to_string(),
-                    valid_fields,
-                    hidden_fields,
+            member if member.is_geo() => {
+                let unsortable = match &ctx.geo_fields {
+                    Some((lat, lng)) => [lat, lng]
+                        .into_iter()
+                        .find(|field| !crate::is_faceted(field, &sortable_fields))
+                        .cloned(),
+                    None => (!sortable_fields.contains("_geo")).then(|| "_geo".to_string()),
+                };
+                if let Some(field) = unsortable {
+                    let (valid_fields, hidden_fields) =
+                        ctx.index.remove_hidden_fields(ctx.txn, sortable_fields)?;
+
+                    return Err(UserError::InvalidSortableAttribute {
+                        field,
+                        valid_fields,
+                        hidden_fields,
+                    }
+                    .into());
                 }
-                .into());
             }
             _ => (),
         }
     }
 
+    // We check that there is at most one geo sort, the precedence between
+    // multiple geo points being undefined.
+    let geo_sorts: Vec<_> = sort_criteria
+        .iter()
+        .filter_map(|asc_desc| match asc_desc {
+            AscDesc::Asc(member) if member.is_geo() => Some(format!("{member}:asc")),
+            AscDesc::Desc(member) if member.is_geo() => Some(format!("{member}:desc")),
+            _ => None,
+        })
+        .collect();
+    if geo_sorts.len() > 1 {
+        return Err(UserError::MultipleGeoSort(geo_sorts).into());
+    }
+
     Ok(())
 }
 
+/// The sizes of the structures built by a search, to find out which queries use the most memory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryStats {
+    /// The number of words interned by the search, the ones interned by the previous searches
+    /// sharing its cache are not counted.
+    pub interned_words: usize,
+    /// The number of nodes of the query graph.
+    pub query_graph_nodes: usize,
+    /// The number of edges of the proximity graphs built by the proximity ranking rule, summed
+    /// over all the buckets it sorted.
+    pub proximity_edges: usize,
+    /// The number of edges of the typo graphs built by the typo ranking rule, summed over all
+    /// the buckets it sorted.
+    pub typo_edges: usize,
+    /// The number of distinct values read from the databases and added to the database cache
+    /// by the search, a value read again from the cache is only counted once.
+    pub cached_database_values: u64,
+}
+
 pub struct PartialSearchResult {
     pub located_query_terms: Option<Vec<LocatedQueryTerm>>,
     pub candidates: RoaringBitmap,
     pub documents_ids: Vec<DocumentId>,
     pub document_scores: Vec<Vec<ScoreDetails>>,
+    pub below_threshold: u64,
+    pub page_boundary: Option<PageBoundary>,
 
     pub degraded: bool,
+    /// The reasons why the results may be incomplete, empty when they are complete.
+    pub degradation_reasons: Vec<DegradationReason>,
     pub used_negative_operator: bool,
 }
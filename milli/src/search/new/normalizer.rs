@@ -0,0 +1,38 @@
+use std::borrow::Cow;
+
+use charabia::Token;
+
+/// How the words of a query are normalized, instead of the normalization of the index.
+///
+/// Only the query is normalized differently, the documents stay indexed with the
+/// normalization of the index: a query word can only match the documents containing the
+/// same word once normalized by the index. For example, keeping the diacritics of `café`
+/// doesn't match anything in an index that folds them and stores `cafe`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizerConfig {
+    /// Whether the words are lowercased.
+    pub lowercase: bool,
+    /// Whether the diacritics are removed, e.g. `é` becomes `e`, along with the other lossy
+    /// normalizations of the tokenizer. Folding the diacritics also lowercases the words.
+    pub fold_diacritics: bool,
+    /// The separators splitting the query into words, instead of the ones of the index.
+    pub separators: Option<Vec<String>>,
+}
+
+impl Default for NormalizerConfig {
+    /// The normalization used by the index.
+    fn default() -> Self {
+        Self { lowercase: true, fold_diacritics: true, separators: None }
+    }
+}
+
+impl NormalizerConfig {
+    /// Apply the normalizations that the tokenizer doesn't do on its own to the token,
+    /// which was produced by a tokenizer with the lossy normalization set to `fold_diacritics`.
+    pub(crate) fn normalize<'o>(&self, mut token: Token<'o>) -> Token<'o> {
+        if self.lowercase && !self.fold_diacritics {
+            token.lemma = Cow::Owned(token.lemma().to_lowercase());
+        }
+        token
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+
+use roaring::RoaringBitmap;
+
+use super::query_graph::{QueryGraph, QueryNodeData};
+use super::ranking_rule_graph::{ProximityCondition, ProximityGraph, RankingRuleGraphTrait};
+use super::ranking_rules::{RankingRule, RankingRuleOutput};
+use super::resolve_query_graph::compute_query_term_subset_docids_within_field_id;
+use super::word_window::term_subset_fids;
+use crate::proximity::MAX_DISTANCE;
+use crate::score_details::{self, ScoreDetails};
+use crate::{DocumentId, FieldId, Result, SearchContext, SearchLogger};
+
+/// The rank of the documents whose consecutive query words are all next to each other in the
+/// field of the highest weight.
+const MAX_RANK: u32 = 100;
+
+/// A ranking rule that sorts the documents of a proximity bucket by the fields in which the
+/// consecutive words of the query are close to each other.
+///
+/// Each pair of consecutive words contributes `MAX_DISTANCE - proximity` multiplied by the weight
+/// of the field in which they are the closest, the fields without a weight weighing `1.0`. The
+/// documents with the same sum of contributions are returned in the same bucket.
+///
+/// The proximity of a pair is read from the proximity databases and its fields from the word fid
+/// databases: a pair is considered close in a field when both words are in this field and close
+/// in the document.
+pub struct ProximityFields {
+    weights: HashMap<String, f64>,
+    state: Option<State>,
+}
+
+struct State {
+    query: QueryGraph,
+    /// The buckets of the universe with their rank, by decreasing score.
+    buckets: Vec<(u32, RoaringBitmap)>,
+    next: usize,
+}
+
+impl ProximityFields {
+    pub fn new(weights: HashMap<String, f64>) -> Self {
+        Self { weights, state: None }
+    }
+}
+
+impl<'ctx> RankingRule<'ctx, QueryGraph> for ProximityFields {
+    fn id(&self) -> String {
+        "proximity_fields".to_owned()
+    }
+
+    #[tracing::instrument(level = "trace", skip_all, target = "search::proximity_fields")]
+    fn start_iteration(
+        &mut self,
+        ctx: &mut SearchContext<'ctx>,
+        _logger: &mut dyn SearchLogger<QueryGraph>,
+        universe: &RoaringBitmap,
+        query: &QueryGraph,
+    ) -> Result<()> {
+        let buckets = proximity_fields_buckets(ctx, &self.weights, universe, query)?;
+        self.state = Some(State { query: query.clone(), buckets, next: 0 });
+        Ok(())
+    }
+
+    #[tracing::instrument(level = "trace", skip_all, target = "search::proximity_fields")]
+    fn next_bucket(
+        &mut self,
+        _ctx: &mut SearchContext<'ctx>,
+        _logger: &mut dyn SearchLogger<QueryGraph>,
+        universe: &RoaringBitmap,
+    ) -> Result<Option<RankingRuleOutput<QueryGraph>>> {
+        let Some(state) = &mut self.state else { return Ok(None) };
+        while let Some((rank, bucket)) = state.buckets.get(state.next) {
+            state.next += 1;
+            let candidates = bucket & universe;
+            if candidates.is_empty() {
+                continue;
+            }
+            return Ok(Some(RankingRuleOutput {
+                query: state.query.clone(),
+                candidates,
+                score: ScoreDetails::ProximityFields(score_details::Rank {
+                    rank: *rank,
+                    max_rank: MAX_RANK,
+                }),
+            }));
+        }
+        Ok(None)
+    }
+
+    #[tracing::instrument(level = "trace", skip_all, target = "search::proximity_fields")]
+    fn end_iteration(
+        &mut self,
+        _ctx: &mut SearchContext<'ctx>,
+        _logger: &mut dyn SearchLogger<QueryGraph>,
+    ) {
+        self.state = None;
+    }
+}
+
+/// Split the universe into buckets of documents with the same weighted proximity, by decreasing
+/// weighted proximity, along with their rank.
+fn proximity_fields_buckets(
+    ctx: &mut SearchContext<'_>,
+    weights: &HashMap<String, f64>,
+    universe: &RoaringBitmap,
+    query: &QueryGraph,
+) -> Result<Vec<(u32, RoaringBitmap)>> {
+    // the terms of the query which are not ngrams, in the order of the query
+    let mut terms: Vec<_> = query
+        .nodes
+        .iter()
+        .filter_map(|(_, node)| match &node.data {
+            QueryNodeData::Term(term) if term.term_ids.start() == term.term_ids.end() => Some(term),
+            _ => None,
+        })
+        .collect();
+    terms.sort_by_key(|term| *term.positions.start());
+    terms.dedup_by_key(|term| *term.term_ids.start());
+
+    // the consecutive terms, which can be separated by the stop words ignored by the proximity
+    let mut pairs = Vec::new();
+    for pair in terms.windows(2) {
+        let (left, right) = (pair[0], pair[1]);
+        let gap = (left.positions.end() + 1)..*right.positions.start();
+        let stop_words = if gap.is_empty() {
+            0
+        } else if ctx.proximity_ignores_stop_words
+            && gap.clone().all(|position| ctx.query_stop_word_positions.contains(&position))
+        {
+            gap.len() as u8
+        } else {
+            continue;
+        };
+        pairs.push((left.clone(), right.clone(), stop_words));
+    }
+    if pairs.is_empty() || universe.is_empty() {
+        return Ok(vec![(MAX_RANK, universe.clone())]);
+    }
+
+    let fid_map = ctx.index.fields_ids_map(ctx.txn)?;
+    let weights: HashMap<FieldId, f64> = weights
+        .iter()
+        .filter_map(|(name, weight)| fid_map.id(name).map(|fid| (fid, *weight)))
+        .collect();
+    let max_weight = weights.values().copied().fold(1.0, f64::max);
+    let max_score = pairs.len() as f64 * max_weight * (MAX_DISTANCE - 1) as f64;
+
+    let mut scores: HashMap<DocumentId, f64> = HashMap::new();
+    for (left, right, stop_words) in pairs {
+        ctx.check_cancelled()?;
+
+        // the documents containing both terms in each of their common fields
+        let right_fids = term_subset_fids(ctx, &right.term_subset)?;
+        let mut fids_docids = Vec::new();
+        for fid in term_subset_fids(ctx, &left.term_subset)?.intersection(&right_fids) {
+            let mut docids = compute_query_term_subset_docids_within_field_id(
+                ctx,
+                Some(universe),
+                &left.term_subset,
+                *fid,
+            )?;
+            docids &= compute_query_term_subset_docids_within_field_id(
+                ctx,
+                Some(&docids),
+                &right.term_subset,
+                *fid,
+            )?;
+            if !docids.is_empty() {
+                fids_docids.push((*fid, docids));
+            }
+        }
+
+        // a document only counts the best contribution of the pair
+        let mut contributions = Vec::new();
+        for proximity in 1..MAX_DISTANCE as u8 {
+            let condition = ProximityCondition::Uninit {
+                left_term: left.clone(),
+                right_term: right.clone(),
+                cost: proximity,
+                stop_words,
+            };
+            let proximity_docids =
+                ProximityGraph::resolve_condition(ctx, &condition, universe)?.docids;
+            for (fid, fid_docids) in &fids_docids {
+                let docids = &proximity_docids & fid_docids;
+                if !docids.is_empty() {
+                    let weight = weights.get(fid).copied().unwrap_or(1.0);
+                    let contribution = weight * (MAX_DISTANCE - proximity as u32) as f64;
+                    contributions.push((contribution, docids));
+                }
+            }
+        }
+        contributions.sort_by(|(lhs, _), (rhs, _)| rhs.total_cmp(lhs));
+        let mut contributed = RoaringBitmap::new();
+        for (contribution, docids) in contributions {
+            let docids = docids - &contributed;
+            for docid in &docids {
+                *scores.entry(docid).or_default() += contribution;
+            }
+            contributed |= docids;
+        }
+    }
+
+    let unscored = universe - scores.keys().copied().collect::<RoaringBitmap>();
+    let mut scores: Vec<_> = scores.into_iter().map(|(docid, score)| (score, docid)).collect();
+    scores.sort_by(|(lhs, _), (rhs, _)| rhs.total_cmp(lhs));
+
+    let mut buckets: Vec<(u32, RoaringBitmap)> = Vec::new();
+    let mut last_score = None;
+    for (score, docid) in scores {
+        if last_score != Some(score) {
+            buckets.push((score_rank(score, max_score), RoaringBitmap::new()));
+            last_score = Some(score);
+        }
+        if let Some((_, bucket)) = buckets.last_mut() {
+            bucket.insert(docid);
+        }
+    }
+    if !unscored.is_empty() {
+        match buckets.last_mut() {
+            Some((_, bucket)) if last_score == Some(0.0) => *bucket |= unscored,
+            _ => buckets.push((score_rank(0.0, max_score), unscored)),
+        }
+    }
+    Ok(buckets)
+}
+
+/// The rank of a weighted proximity out of [`MAX_RANK`], the scores being between `0` and
+/// the score of a query whose words are all next to each other in the field of highest weight.
+fn score_rank(score: f64, max_score: f64) -> u32 {
+    let rank = 1 + (score / max_score * (MAX_RANK - 1) as f64).round() as u32;
+    rank.min(MAX_RANK)
+}
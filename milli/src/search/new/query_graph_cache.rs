@@ -0,0 +1,95 @@
+use std::collections::{HashMap, VecDeque};
+
+use charabia::Language;
+
+use super::query_graph::QueryGraph;
+use super::query_term::LocatedQueryTerm;
+use super::{NormalizerConfig, Word};
+
+/// What identifies the query graph built for a query: the query, stripped of its leading
+/// and trailing whitespace, the parameters of its tokenization and normalization, and the
+/// synonyms given at search time, which are part of its terms.
+#[derive(Clone, PartialEq)]
+pub(crate) struct QueryGraphCacheKey {
+    query: String,
+    words_limit: Option<usize>,
+    locales: Option<Vec<Language>>,
+    normalizer: Option<NormalizerConfig>,
+    keep_duplicate_tokens: bool,
+    min_prefix_len: Option<u8>,
+    query_synonyms: HashMap<Vec<String>, Vec<Vec<String>>>,
+}
+
+impl QueryGraphCacheKey {
+    pub(crate) fn new(
+        query: &str,
+        words_limit: Option<usize>,
+        locales: Option<&Vec<Language>>,
+        normalizer: Option<&NormalizerConfig>,
+        keep_duplicate_tokens: bool,
+        min_prefix_len: Option<u8>,
+        query_synonyms: &HashMap<Vec<String>, Vec<Vec<String>>>,
+    ) -> Self {
+        Self {
+            query: query.trim().to_owned(),
+            words_limit,
+            locales: locales.cloned(),
+            normalizer: normalizer.cloned(),
+            keep_duplicate_tokens,
+            min_prefix_len,
+            query_synonyms: query_synonyms.clone(),
+        }
+    }
+}
+
+/// The result of the tokenization of a query and of the construction of its query graph.
+#[derive(Clone)]
+pub(crate) struct CachedQuery {
+    pub negative_words: Vec<Word>,
+    pub negative_phrases: Vec<LocatedQueryTerm>,
+    /// The query graph and the located query terms, `None` for a placeholder search.
+    pub graph: Option<(QueryGraph, Vec<LocatedQueryTerm>)>,
+    pub stop_word_positions: Vec<u16>,
+}
+
+/// A least-recently-used cache of the query graphs built by a [`SearchContext`](super::SearchContext),
+/// avoiding to tokenize the same query and to build its graph again when it is searched
+/// several times with the same context.
+///
+/// The graphs only refer to the words and terms interned in the context, the documents
+/// of their nodes are still resolved on every search. The cache is kept, along with these
+/// words and terms, by the [`SharedDatabaseCache`](super::SharedDatabaseCache) of the context,
+/// so that the searches sharing it on the same transaction reuse the graphs of each other.
+pub(crate) struct QueryGraphCache {
+    capacity: usize,
+    /// The cached queries, from the most to the least recently used.
+    entries: VecDeque<(QueryGraphCacheKey, CachedQuery)>,
+    /// The number of searches that reused a cached query graph.
+    pub(crate) hits: usize,
+}
+
+impl QueryGraphCache {
+    /// Create a cache keeping the query graphs of the `capacity` most recently searched queries.
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self { capacity, entries: VecDeque::with_capacity(capacity), hits: 0 }
+    }
+
+    pub(crate) fn get(&mut self, key: &QueryGraphCacheKey) -> Option<CachedQuery> {
+        let position = self.entries.iter().position(|(k, _)| k == key)?;
+        let entry = self.entries.remove(position)?;
+        let cached = entry.1.clone();
+        self.entries.push_front(entry);
+        self.hits += 1;
+        Some(cached)
+    }
+
+    pub(crate) fn insert(&mut self, key: QueryGraphCacheKey, cached: CachedQuery) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_back();
+        }
+        self.entries.push_front((key, cached));
+    }
+}
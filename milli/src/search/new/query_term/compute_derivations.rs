@@ -4,26 +4,31 @@ use std::ops::ControlFlow;
 
 use fst::automaton::Str;
 use fst::{Automaton, IntoStreamer, Streamer};
+use fxhash::FxHashMap;
 use heed::types::DecodeIgnore;
 
 use super::{OneTypoTerm, Phrase, QueryTerm, ZeroTypoTerm};
-use crate::search::fst_utils::{Complement, Intersection, StartsWith, Union};
+use crate::search::fst_utils::{Complement, Intersection, StartsWith, Union, UnionAll};
 use crate::search::new::interner::{DedupInterner, Interned};
 use crate::search::new::query_term::{Lazy, TwoTypoTerm};
 use crate::search::new::{limits, SearchContext};
 use crate::search::{build_dfa, get_first};
 use crate::{Result, MAX_WORD_LENGTH};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum NumberOfTypos {
-    Zero,
-    One,
-    Two,
-}
-
-pub enum ZeroOrOneTypo {
-    Zero,
-    One,
+/// A cache storing the typo derivations of the words of a query.
+///
+/// The derivations of a word only depend on the word itself and on whether it is a prefix.
+/// They are computed in a single traversal of the words FST for all the words of the query
+/// sharing the same first letter, see [`typo_derivations_group`], and then shared between all
+/// the terms built from the same word.
+#[derive(Default)]
+pub struct TypoDerivationsCache {
+    pub one_typo: FxHashMap<(Interned<String>, bool), BTreeSet<Interned<String>>>,
+    #[allow(clippy::type_complexity)]
+    pub one_and_two_typos: FxHashMap<
+        (Interned<String>, bool),
+        (BTreeSet<Interned<String>>, BTreeSet<Interned<String>>),
+    >,
 }
 
 impl Interned<QueryTerm> {
@@ -70,105 +75,194 @@ fn find_zero_typo_prefix_derivations(
     Ok(())
 }
 
-fn find_zero_one_typo_derivations(
-    ctx: &mut SearchContext<'_>,
+/// The words whose typo derivations are computed in the same traversal of the words FST as
+/// the given word: the other words of the query with the same first letter, prefix-ness and
+/// maximum number of typos, whose derivations are not cached yet.
+///
+/// Since a typo on the first letter costs two typos, the derivations of these words are all
+/// found below the same first letter of the FST, and words with a common stem share most of
+/// their traversal.
+fn typo_derivations_group(
+    ctx: &SearchContext<'_>,
     word_interned: Interned<String>,
     is_prefix: bool,
-    mut visit: impl FnMut(Interned<String>, ZeroOrOneTypo) -> Result<ControlFlow<()>>,
-) -> Result<()> {
+    two_typos: bool,
+) -> Vec<Interned<String>> {
+    let first = get_first(ctx.word_interner.get(word_interned));
+    let mut group = vec![word_interned];
+    for (_, term) in ctx.term_interner.iter() {
+        let QueryTerm {
+            original, ngram_words, max_levenshtein_distance, one_typo, two_typo, ..
+        } = term;
+        // the terms of the previous queries of a shared cache are already computed
+        let (same_max_typos, cached) = if two_typos {
+            let cached = two_typo.is_init()
+                || ctx.typo_derivations.one_and_two_typos.contains_key(&(*original, is_prefix));
+            (*max_levenshtein_distance > 1, cached)
+        } else {
+            let cached = one_typo.is_init()
+                || ctx.typo_derivations.one_typo.contains_key(&(*original, is_prefix));
+            (*max_levenshtein_distance == 1, cached)
+        };
+        if term.is_prefix != is_prefix
+            || !same_max_typos
+            || cached
+            || ngram_words.is_some()
+            || group.contains(original)
+            || get_first(ctx.word_interner.get(*original)) != first
+        {
+            continue;
+        }
+        group.push(*original);
+    }
+    group
+}
+
+/// Find the one-typo derivations of each of the given words, which must share the same first
+/// letter, in a single traversal of the words FST.
+///
+/// Each word keeps the first [`limits::MAX_ONE_TYPO_COUNT`] derivations in the order of the
+/// FST, exactly as if its derivations were computed on their own.
+fn find_one_typo_derivations(
+    ctx: &mut SearchContext<'_>,
+    words: &[Interned<String>],
+    is_prefix: bool,
+) -> Result<Vec<BTreeSet<Interned<String>>>> {
     let fst = ctx.get_words_fst()?;
-    let word = ctx.word_interner.get(word_interned).to_owned();
-    let word = word.as_str();
+    let words_str: Vec<_> =
+        words.iter().map(|word| ctx.word_interner.get(*word).to_owned()).collect();
 
-    let dfa = build_dfa(word, 1, is_prefix);
-    let starts = StartsWith(Str::new(get_first(word)));
-    let mut stream = fst.search_with_state(Intersection(starts, &dfa)).into_stream();
+    let dfas: Vec<_> = words_str.iter().map(|word| build_dfa(word, 1, is_prefix)).collect();
+    let starts = StartsWith(Str::new(get_first(&words_str[0])));
+    let automaton = Intersection(starts, UnionAll(dfas.iter().collect()));
+    let mut stream = fst.search_with_state(automaton).into_stream();
 
+    let mut derivations = vec![BTreeSet::new(); words.len()];
     while let Some((derived_word, state)) = stream.next() {
+        if derivations
+            .iter()
+            .all(|one_typo_words| one_typo_words.len() >= limits::MAX_ONE_TYPO_COUNT)
+        {
+            // No chance we will add derivations anymore, stop iterating.
+            break;
+        }
         let derived_word = std::str::from_utf8(derived_word)?;
         let derived_word = ctx.word_interner.insert(derived_word.to_owned());
-        let d = dfa.distance(state.1);
-        match d.to_u8() {
-            0 => {
-                if derived_word != word_interned {
-                    let cf = visit(derived_word, ZeroOrOneTypo::Zero)?;
-                    if cf.is_break() {
-                        break;
-                    }
-                }
-            }
-            1 => {
-                let cf = visit(derived_word, ZeroOrOneTypo::One)?;
-                if cf.is_break() {
-                    break;
-                }
-            }
-            _ => {
-                unreachable!("One typo dfa produced multiple typos")
+        for ((dfa, state), one_typo_words) in dfas.iter().zip(&state.1).zip(&mut derivations) {
+            if dfa.distance(*state).to_u8() == 1
+                && one_typo_words.len() < limits::MAX_ONE_TYPO_COUNT
+            {
+                one_typo_words.insert(derived_word);
             }
         }
     }
-    Ok(())
+    Ok(derivations)
 }
 
-fn find_zero_one_two_typo_derivations(
+/// Return the one-typo derivations of the given word, computing them only if
+/// no previous term of the query shares the same word.
+fn cached_one_typo_derivations(
+    ctx: &mut SearchContext<'_>,
     word_interned: Interned<String>,
     is_prefix: bool,
-    fst: fst::Set<Cow<'_, [u8]>>,
-    word_interner: &mut DedupInterner<String>,
-    mut visit: impl FnMut(Interned<String>, NumberOfTypos) -> Result<ControlFlow<()>>,
-) -> Result<()> {
-    let word = word_interner.get(word_interned).to_owned();
-    let word = word.as_str();
+) -> Result<BTreeSet<Interned<String>>> {
+    if let Some(one_typo_words) = ctx.typo_derivations.one_typo.get(&(word_interned, is_prefix)) {
+        return Ok(one_typo_words.clone());
+    }
+
+    let group = typo_derivations_group(ctx, word_interned, is_prefix, false);
+    let derivations = find_one_typo_derivations(ctx, &group, is_prefix)?;
+    let one_typo_words = derivations[0].clone();
+    for (word, one_typo_words) in group.into_iter().zip(derivations) {
+        ctx.typo_derivations.one_typo.insert((word, is_prefix), one_typo_words);
+    }
+    Ok(one_typo_words)
+}
+
+/// Return the one- and two-typo derivations of the given word, computing them only if
+/// no previous term of the query shares the same word.
+fn cached_one_two_typo_derivations(
+    ctx: &mut SearchContext<'_>,
+    word_interned: Interned<String>,
+    is_prefix: bool,
+) -> Result<(BTreeSet<Interned<String>>, BTreeSet<Interned<String>>)> {
+    if let Some(derivations) =
+        ctx.typo_derivations.one_and_two_typos.get(&(word_interned, is_prefix))
+    {
+        return Ok(derivations.clone());
+    }
 
-    let starts = StartsWith(Str::new(get_first(word)));
-    let first = Intersection(build_dfa(word, 1, is_prefix), Complement(&starts));
-    let second_dfa = build_dfa(word, 2, is_prefix);
-    let second = Intersection(&second_dfa, &starts);
-    let automaton = Union(first, &second);
+    let group = typo_derivations_group(ctx, word_interned, is_prefix, true);
+    let derivations = find_one_two_typo_derivations(ctx, &group, is_prefix)?;
+    let one_two_typo_words = derivations[0].clone();
+    for (word, derivations) in group.into_iter().zip(derivations) {
+        ctx.typo_derivations.one_and_two_typos.insert((word, is_prefix), derivations);
+    }
+    Ok(one_two_typo_words)
+}
+
+/// Find the one- and two-typo derivations of each of the given words, which must share the
+/// same first letter, in a single traversal of the words FST.
+///
+/// Each word keeps the first [`limits::MAX_ONE_TYPO_COUNT`] and [`limits::MAX_TWO_TYPOS_COUNT`]
+/// derivations in the order of the FST, exactly as if its derivations were computed on their own.
+#[allow(clippy::type_complexity)]
+fn find_one_two_typo_derivations(
+    ctx: &mut SearchContext<'_>,
+    words: &[Interned<String>],
+    is_prefix: bool,
+) -> Result<Vec<(BTreeSet<Interned<String>>, BTreeSet<Interned<String>>)>> {
+    let fst = ctx.get_words_fst()?;
+    let words_str: Vec<_> =
+        words.iter().map(|word| ctx.word_interner.get(*word).to_owned()).collect();
+    let first_letter = get_first(&words_str[0]);
+
+    let first_dfas: Vec<_> = words_str.iter().map(|word| build_dfa(word, 1, is_prefix)).collect();
+    let second_dfas: Vec<_> = words_str.iter().map(|word| build_dfa(word, 2, is_prefix)).collect();
+    let starts = StartsWith(Str::new(first_letter));
+    let first = Intersection(UnionAll(first_dfas.iter().collect()), Complement(&starts));
+    let second = Intersection(UnionAll(second_dfas.iter().collect()), &starts);
+    let automaton = Union(first, second);
 
     let mut stream = fst.search_with_state(automaton).into_stream();
 
+    let mut derivations = vec![(BTreeSet::new(), BTreeSet::new()); words.len()];
     while let Some((derived_word, state)) = stream.next() {
-        let derived_word = std::str::from_utf8(derived_word)?;
-        let derived_word_interned = word_interner.insert(derived_word.to_owned());
+        if derivations.iter().all(|(one_typo_words, two_typo_words)| {
+            one_typo_words.len() >= limits::MAX_ONE_TYPO_COUNT
+                && two_typo_words.len() >= limits::MAX_TWO_TYPOS_COUNT
+        }) {
+            // No chance we will add either one- or two-typo derivations anymore, stop iterating.
+            break;
+        }
+        let derived_word_str = std::str::from_utf8(derived_word)?;
+        let derived_word = ctx.word_interner.insert(derived_word_str.to_owned());
         // in the case the typo is on the first letter, we know the number of typo
-        // is two
-        if get_first(derived_word) != get_first(word) {
-            let cf = visit(derived_word_interned, NumberOfTypos::Two)?;
-            if cf.is_break() {
-                break;
-            }
-        } else {
-            // Else, we know that it is the second dfa that matched and compute the
-            // correct distance
-            let d = second_dfa.distance((state.1).0);
-            match d.to_u8() {
-                0 => {
-                    if derived_word_interned != word_interned {
-                        let cf = visit(derived_word_interned, NumberOfTypos::Zero)?;
-                        if cf.is_break() {
-                            break;
-                        }
-                    }
-                }
-                1 => {
-                    let cf = visit(derived_word_interned, NumberOfTypos::One)?;
-                    if cf.is_break() {
-                        break;
+        // is two, for the words whose first dfa matched
+        let first_letter_typo = get_first(derived_word_str) != first_letter;
+        let dfas = if first_letter_typo { &first_dfas } else { &second_dfas };
+        let states = if first_letter_typo { &(state.0).0 } else { &(state.1).0 };
+
+        for ((dfa, state), (one_typo_words, two_typo_words)) in
+            dfas.iter().zip(states).zip(&mut derivations)
+        {
+            match (first_letter_typo, dfa.distance(*state).to_u8()) {
+                (false, 1) => {
+                    if one_typo_words.len() < limits::MAX_ONE_TYPO_COUNT {
+                        one_typo_words.insert(derived_word);
                     }
                 }
-                2 => {
-                    let cf = visit(derived_word_interned, NumberOfTypos::Two)?;
-                    if cf.is_break() {
-                        break;
+                (true, 0 | 1) | (false, 2) => {
+                    if two_typo_words.len() < limits::MAX_TWO_TYPOS_COUNT {
+                        two_typo_words.insert(derived_word);
                     }
                 }
-                _ => unreachable!("2 typos DFA produced a distance greater than 2"),
+                // either the word itself, or a word that only matches other words of the group
+                _ => (),
             }
         }
     }
-    Ok(())
+    Ok(derivations)
 }
 
 pub fn partially_initialized_term_from_word(
@@ -194,7 +288,7 @@ pub fn partially_initialized_term_from_word(
         });
     }
 
-    let fst = ctx.index.words_fst(ctx.txn)?;
+    let fst = ctx.get_words_fst()?;
 
     let use_prefix_db = is_prefix
         && (ctx
@@ -214,6 +308,7 @@ pub fn partially_initialized_term_from_word(
 
     let mut zero_typo = None;
     let mut prefix_of = BTreeSet::new();
+    let mut prefix_of_truncated = false;
 
     if fst.contains(word) {
         zero_typo = Some(word_interned);
@@ -229,17 +324,16 @@ pub fn partially_initialized_term_from_word(
                     prefix_of.insert(derived_word);
                     Ok(ControlFlow::Continue(()))
                 } else {
+                    // a word is left out only when there is one more than the maximum
+                    prefix_of_truncated = true;
                     Ok(ControlFlow::Break(()))
                 }
             },
         )?;
     }
-    let synonyms = ctx.index.synonyms(ctx.txn)?;
+    let synonyms = ctx.words_synonyms(&[word.to_owned()])?;
     let mut synonym_word_count = 0;
     let synonyms = synonyms
-        .get(&vec![word.to_owned()])
-        .cloned()
-        .unwrap_or_default()
         .into_iter()
         .take(limits::MAX_SYNONYM_PHRASE_COUNT)
         .filter_map(|words| {
@@ -251,8 +345,14 @@ pub fn partially_initialized_term_from_word(
             Some(ctx.phrase_interner.insert(Phrase { words }))
         })
         .collect();
-    let zero_typo =
-        ZeroTypoTerm { phrase: None, exact: zero_typo, prefix_of, synonyms, use_prefix_db };
+    let zero_typo = ZeroTypoTerm {
+        phrase: None,
+        exact: zero_typo,
+        prefix_of,
+        prefix_of_truncated,
+        synonyms,
+        use_prefix_db,
+    };
 
     Ok(QueryTerm {
         original: word_interned,
@@ -292,23 +392,11 @@ impl Interned<QueryTerm> {
         if one_typo.is_init() {
             return Ok(());
         }
-        let mut one_typo_words = BTreeSet::new();
-
-        if *max_nbr_typos > 0 {
-            find_zero_one_typo_derivations(ctx, original, is_prefix, |derived_word, nbr_typos| {
-                match nbr_typos {
-                    ZeroOrOneTypo::Zero => {}
-                    ZeroOrOneTypo::One => {
-                        if one_typo_words.len() < limits::MAX_ONE_TYPO_COUNT {
-                            one_typo_words.insert(derived_word);
-                        } else {
-                            return Ok(ControlFlow::Break(()));
-                        }
-                    }
-                }
-                Ok(ControlFlow::Continue(()))
-            })?;
-        }
+        let one_typo_words = if *max_nbr_typos > 0 {
+            cached_one_typo_derivations(ctx, original, is_prefix)?
+        } else {
+            BTreeSet::new()
+        };
 
         let split_words = if allows_split_words {
             let original_str = ctx.word_interner.get(original).to_owned();
@@ -349,43 +437,18 @@ impl Interned<QueryTerm> {
             max_levenshtein_distance: max_nbr_typos,
             ..
         } = self_mut;
-        let original_str = ctx.word_interner.get(*original).to_owned();
+        let original = *original;
+        let is_prefix = *is_prefix;
+        let original_str = ctx.word_interner.get(original).to_owned();
         if two_typo.is_init() {
             return Ok(());
         }
-        let mut one_typo_words = BTreeSet::new();
-        let mut two_typo_words = BTreeSet::new();
-
-        if *max_nbr_typos > 0 {
-            find_zero_one_two_typo_derivations(
-                *original,
-                *is_prefix,
-                ctx.index.words_fst(ctx.txn)?,
-                &mut ctx.word_interner,
-                |derived_word, nbr_typos| {
-                    if one_typo_words.len() >= limits::MAX_ONE_TYPO_COUNT
-                        && two_typo_words.len() >= limits::MAX_TWO_TYPOS_COUNT
-                    {
-                        // No chance we will add either one- or two-typo derivations anymore, stop iterating.
-                        return Ok(ControlFlow::Break(()));
-                    }
-                    match nbr_typos {
-                        NumberOfTypos::Zero => {}
-                        NumberOfTypos::One => {
-                            if one_typo_words.len() < limits::MAX_ONE_TYPO_COUNT {
-                                one_typo_words.insert(derived_word);
-                            }
-                        }
-                        NumberOfTypos::Two => {
-                            if two_typo_words.len() < limits::MAX_TWO_TYPOS_COUNT {
-                                two_typo_words.insert(derived_word);
-                            }
-                        }
-                    }
-                    Ok(ControlFlow::Continue(()))
-                },
-            )?;
-        }
+
+        let (one_typo_words, two_typo_words) = if *max_nbr_typos > 0 {
+            cached_one_two_typo_derivations(ctx, original, is_prefix)?
+        } else {
+            (BTreeSet::new(), BTreeSet::new())
+        };
 
         let split_words = find_split_words(ctx, original_str.as_str())?;
         let self_mut = ctx.term_interner.get_mut(self);
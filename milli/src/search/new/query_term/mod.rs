@@ -7,15 +7,17 @@ use std::collections::BTreeSet;
 use std::iter::FromIterator;
 use std::ops::RangeInclusive;
 
+pub use compute_derivations::TypoDerivationsCache;
 use either::Either;
 pub use ntypo_subset::NTypoTermSubset;
 pub use parse_query::{
-    located_query_terms_from_tokens, make_ngram, number_of_typos_allowed, ExtractedTokens,
+    dedup_located_query_terms, located_query_terms_from_tokens, make_ngram,
+    number_of_typos_allowed, ExtractedTokens,
 };
 pub use phrase::Phrase;
 
 use super::interner::{DedupInterner, Interned};
-use super::{limits, SearchContext, Word};
+use super::{SearchContext, Word};
 use crate::Result;
 
 /// A set of word derivations attached to a location in the search query.
@@ -62,6 +64,8 @@ struct ZeroTypoTerm {
     exact: Option<Interned<String>>,
     /// All the words that contain the original word as prefix
     prefix_of: BTreeSet<Interned<String>>,
+    /// Whether some words containing the original word as prefix were left out of `prefix_of`
+    prefix_of_truncated: bool,
     /// All the synonyms of the original word or phrase
     synonyms: BTreeSet<Interned<Phrase>>,
     /// A prefix in the prefix databases matching the original word
@@ -212,6 +216,7 @@ impl QueryTermSubset {
                     phrase: _,
                     exact: zero_typo,
                     prefix_of,
+                    prefix_of_truncated: _,
                     synonyms: _,
                     use_prefix_db: _,
                 } = &original.zero_typo;
@@ -235,6 +240,7 @@ impl QueryTermSubset {
                     phrase: _,
                     exact: zero_typo,
                     prefix_of,
+                    prefix_of_truncated: _,
                     synonyms: _,
                     use_prefix_db: _,
                 } = &original.zero_typo;
@@ -298,8 +304,14 @@ impl QueryTermSubset {
         }
         let original = ctx.term_interner.get_mut(self.original);
 
-        let ZeroTypoTerm { phrase, exact: _, prefix_of: _, synonyms, use_prefix_db: _ } =
-            &original.zero_typo;
+        let ZeroTypoTerm {
+            phrase,
+            exact: _,
+            prefix_of: _,
+            prefix_of_truncated: _,
+            synonyms,
+            use_prefix_db: _,
+        } = &original.zero_typo;
         result.extend(phrase.iter().copied());
         result.extend(synonyms.iter().copied());
 
@@ -407,7 +419,14 @@ impl QueryTermSubset {
 
 impl ZeroTypoTerm {
     fn is_empty(&self) -> bool {
-        let ZeroTypoTerm { phrase, exact: zero_typo, prefix_of, synonyms, use_prefix_db } = self;
+        let ZeroTypoTerm {
+            phrase,
+            exact: zero_typo,
+            prefix_of,
+            prefix_of_truncated: _,
+            synonyms,
+            use_prefix_db,
+        } = self;
         phrase.is_none()
             && zero_typo.is_none()
             && prefix_of.is_empty()
@@ -477,10 +496,20 @@ impl QueryTerm {
     pub fn is_prefix(&self) -> bool {
         self.is_prefix
     }
+    /// Whether the words starting with this prefix were cut to the maximum number of words a
+    /// prefix is expanded to.
+    pub fn is_prefix_truncated(&self) -> bool {
+        self.zero_typo.prefix_of_truncated
+    }
     pub fn original_word(&self, ctx: &SearchContext<'_>) -> String {
         ctx.word_interner.get(self.original).clone()
     }
 
+    /// Return the interned word the term was built from.
+    pub fn original_word_id(&self) -> Interned<String> {
+        self.original
+    }
+
     pub fn original_phrase(&self) -> Option<Interned<Phrase>> {
         self.zero_typo.phrase
     }
@@ -489,8 +518,14 @@ impl QueryTerm {
         let mut words = BTreeSet::new();
         let mut phrases = BTreeSet::new();
 
-        let ZeroTypoTerm { phrase, exact: zero_typo, prefix_of, synonyms, use_prefix_db: _ } =
-            &self.zero_typo;
+        let ZeroTypoTerm {
+            phrase,
+            exact: zero_typo,
+            prefix_of,
+            prefix_of_truncated: _,
+            synonyms,
+            use_prefix_db: _,
+        } = &self.zero_typo;
         words.extend(zero_typo.iter().copied());
         words.extend(prefix_of.iter().copied());
         phrases.extend(phrase.iter().copied());
@@ -1,7 +1,6 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashSet};
 
-use charabia::normalizer::NormalizedTokenIter;
-use charabia::{SeparatorKind, TokenKind};
+use charabia::{SeparatorKind, Token, TokenKind};
 
 use super::compute_derivations::partially_initialized_term_from_word;
 use super::{LocatedQueryTerm, ZeroTypoTerm};
@@ -24,12 +23,13 @@ pub struct ExtractedTokens {
 #[tracing::instrument(level = "trace", skip_all, target = "search::query")]
 pub fn located_query_terms_from_tokens(
     ctx: &mut SearchContext<'_>,
-    query: NormalizedTokenIter<'_, '_, '_, '_>,
+    query: impl Iterator<Item = Token<'_>>,
     words_limit: Option<usize>,
 ) -> Result<ExtractedTokens> {
     let nbr_typos = number_of_typos_allowed(ctx)?;
 
     let mut query_terms = Vec::new();
+    ctx.query_stop_word_positions.clear();
 
     let mut negative_phrase = false;
     let mut phrase: Option<PhraseBuilder> = None;
@@ -86,15 +86,18 @@ pub fn located_query_terms_from_tokens(
                             };
                             query_terms.push(located_term);
                         }
-                        TokenKind::StopWord | TokenKind::Separator(_) | TokenKind::Unknown => (),
+                        TokenKind::StopWord => ctx.query_stop_word_positions.push(position),
+                        TokenKind::Separator(_) | TokenKind::Unknown => (),
                     }
                 } else {
                     let word = token.lemma();
+                    let is_prefix =
+                        ctx.min_prefix_len.map_or(true, |min| word.chars().count() >= min as usize);
                     let term = partially_initialized_term_from_word(
                         ctx,
                         word,
                         nbr_typos(word),
-                        true,
+                        is_prefix,
                         false,
                     )?;
                     let located_term = LocatedQueryTerm {
@@ -190,6 +193,44 @@ pub fn located_query_terms_from_tokens(
     Ok(ExtractedTokens { query_terms, negative_words, negative_phrases })
 }
 
+/// Remove the words of the query repeating an earlier word, with the same prefix-ness, and
+/// move the terms and stop words following them back so that no gap is left in their place.
+///
+/// Phrases are never removed, nor are the words matching a word of a phrase.
+pub fn dedup_located_query_terms(ctx: &mut SearchContext<'_>, terms: &mut Vec<LocatedQueryTerm>) {
+    let mut seen = HashSet::new();
+    let mut removed = Vec::new();
+    for term in terms.iter() {
+        let query_term = ctx.term_interner.get(term.value);
+        if query_term.original_phrase().is_some() {
+            continue;
+        }
+        if !seen.insert((query_term.original_word(ctx), query_term.is_prefix())) {
+            removed.push(term.positions.clone());
+        }
+    }
+    if removed.is_empty() {
+        return;
+    }
+
+    // the number of positions removed before the given one
+    let shift = |position: u16| -> u16 {
+        removed
+            .iter()
+            .filter(|range| *range.end() < position)
+            .map(|range| range.end() - range.start() + 1)
+            .sum()
+    };
+    terms.retain(|term| !removed.contains(&term.positions));
+    for term in terms.iter_mut() {
+        let (start, end) = (*term.positions.start(), *term.positions.end());
+        term.positions = (start - shift(start))..=(end - shift(end));
+    }
+    for position in ctx.query_stop_word_positions.iter_mut() {
+        *position -= shift(*position);
+    }
+}
+
 pub fn number_of_typos_allowed<'ctx>(
     ctx: &SearchContext<'ctx>,
 ) -> Result<impl Fn(&str) -> u8 + 'ctx> {
@@ -257,14 +298,12 @@ pub fn make_ngram(
         partially_initialized_term_from_word(ctx, &ngram_str, max_nbr_typos, is_prefix, true)?;
 
     // Now add the synonyms
-    let index_synonyms = ctx.index.synonyms(ctx.txn)?;
+    let synonyms = ctx.words_synonyms(&words)?;
 
-    term.zero_typo.synonyms.extend(
-        index_synonyms.get(&words).cloned().unwrap_or_default().into_iter().map(|words| {
-            let words = words.into_iter().map(|w| Some(ctx.word_interner.insert(w))).collect();
-            ctx.phrase_interner.insert(Phrase { words })
-        }),
-    );
+    term.zero_typo.synonyms.extend(synonyms.into_iter().map(|words| {
+        let words = words.into_iter().map(|w| Some(ctx.word_interner.insert(w))).collect();
+        ctx.phrase_interner.insert(Phrase { words })
+    }));
 
     let term = QueryTerm {
         original: ngram_str_interned,
@@ -333,6 +372,7 @@ impl PhraseBuilder {
                         phrase: Some(phrase),
                         exact: None,
                         prefix_of: BTreeSet::default(),
+                        prefix_of_truncated: false,
                         synonyms: BTreeSet::default(),
                         use_prefix_db: None,
                     },
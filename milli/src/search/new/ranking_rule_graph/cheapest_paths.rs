@@ -71,6 +71,8 @@ struct VisitorContext<'a, G: RankingRuleGraphTrait> {
     graph: &'a mut RankingRuleGraph<G>,
     all_costs_from_node: &'a MappedInterner<QueryNode, Vec<u64>>,
     dead_ends_cache: &'a mut DeadEndsCache<G::Condition>,
+    /// The number of documents matched by each node, to visit the rarest ones first.
+    nodes_docids_len: Option<&'a MappedInterner<QueryNode, u64>>,
 }
 
 /// The internal state of the traversal algorithm
@@ -112,10 +114,22 @@ impl<'a, G: RankingRuleGraphTrait> PathVisitor<'a, G> {
                 ),
                 nodes_to_skip: SmallBitmap::for_interned_values_in(&graph.query_graph.nodes),
             },
-            ctx: VisitorContext { graph, all_costs_from_node, dead_ends_cache },
+            ctx: VisitorContext {
+                graph,
+                all_costs_from_node,
+                dead_ends_cache,
+                nodes_docids_len: None,
+            },
         }
     }
 
+    /// Visit the edges of each node in the ascending order of the number of documents
+    /// matched by their destination node, instead of the order in which they were built.
+    pub fn rarest_first(mut self, nodes_docids_len: &'a MappedInterner<QueryNode, u64>) -> Self {
+        self.ctx.nodes_docids_len = Some(nodes_docids_len);
+        self
+    }
+
     /// See module documentation
     pub fn visit_paths(mut self, visit: VisitFn<'_, G>) -> Result<()> {
         let _ =
@@ -140,8 +154,15 @@ impl<G: RankingRuleGraphTrait> VisitorState<G> {
         // and we will need to do more work to potentially backtrack
         let mut any_valid = false;
 
-        let edges = ctx.graph.edges_of_node.get(from_node).clone();
-        for edge_idx in edges.iter() {
+        let mut edges: Vec<_> = ctx.graph.edges_of_node.get(from_node).iter().collect();
+        if let Some(nodes_docids_len) = ctx.nodes_docids_len {
+            // the sort is stable, the edges to equally rare nodes keep their order
+            edges.sort_by_cached_key(|&edge_idx| match ctx.graph.edges_store.get(edge_idx) {
+                Some(edge) => *nodes_docids_len.get(edge.dest_node),
+                None => 0,
+            });
+        }
+        for edge_idx in edges {
             // could be none if the edge was deleted
             let Some(edge) = ctx.graph.edges_store.get(edge_idx).clone() else { continue };
 
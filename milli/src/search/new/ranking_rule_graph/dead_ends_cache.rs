@@ -90,6 +90,28 @@ impl<T> DeadEndsCache<T> {
         }
     }
 
+    /// Return an iterator over the path prefixes leading to dead ends, in depth-first order,
+    /// along with the conditions that are forbidden after them.
+    ///
+    /// The empty prefix holds the conditions forbidden in every path, and the prefixes
+    /// without forbidden conditions of their own are skipped.
+    pub fn iter(&self) -> impl Iterator<Item = (Vec<Interned<T>>, &SmallBitmap<T>)> + '_ {
+        let mut stack = vec![(vec![], self)];
+        std::iter::from_fn(move || {
+            while let Some((prefix, cursor)) = stack.pop() {
+                for (condition, next) in cursor.conditions.iter().zip(&cursor.next).rev() {
+                    let mut next_prefix = prefix.clone();
+                    next_prefix.push(*condition);
+                    stack.push((next_prefix, next));
+                }
+                if !cursor.forbidden.is_empty() {
+                    return Some((prefix, &cursor.forbidden));
+                }
+            }
+            None
+        })
+    }
+
     // pub fn debug_print(&self, indent: usize) {
     //     println!("{} {:?}", " ".repeat(indent), self.forbidden.iter().collect::<Vec<_>>());
     //     for (condition, next) in self.conditions.iter().zip(self.next.iter()) {
@@ -114,6 +114,24 @@ pub trait RankingRuleGraphTrait: Sized + 'static {
 
     /// Convert the rank of a path to its corresponding score for the ranking rule
     fn rank_to_score(rank: Rank) -> ScoreDetails;
+
+    /// Whether the paths of a given cost are explored through the nodes matching the fewest
+    /// documents first, which shrinks the universe faster when the query contains very
+    /// frequent words, when enabled with [`crate::Search::proximity_rarest_first`].
+    /// The resulting buckets are the same.
+    const EXPLORE_RAREST_FIRST: bool = false;
+
+    /// Whether exploring the paths of the graph is costly on large universes, in which case
+    /// the candidates given to the ranking rule are capped by the maximum number of candidates
+    /// ranked of the search.
+    const EXPENSIVE: bool = false;
+
+    /// The highest cost of the paths explored by the ranking rule, the edges costing more are
+    /// not built and the documents only matched by costlier paths are excluded from the
+    /// candidates.
+    fn max_cost(_ctx: &SearchContext<'_>) -> Option<u64> {
+        None
+    }
 }
 
 /// The graph used by graph-based ranking rules.
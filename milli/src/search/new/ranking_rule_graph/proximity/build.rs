@@ -8,7 +8,7 @@ use crate::search::new::SearchContext;
 use crate::Result;
 
 pub fn build_edges(
-    _ctx: &mut SearchContext<'_>,
+    ctx: &mut SearchContext<'_>,
     conditions_interner: &mut DedupInterner<ProximityCondition>,
     left_term: Option<&LocatedQueryTermSubset>,
     right_term: &LocatedQueryTermSubset,
@@ -22,7 +22,13 @@ pub fn build_edges(
         )]);
     };
 
-    if left_term.positions.end() + 1 != *right_term.positions.start() {
+    // the positions between the two terms, which can be ignored if they are all stop words
+    let gap = (left_term.positions.end() + 1)..*right_term.positions.start();
+    let ignored_stop_words = ctx.proximity_ignores_stop_words
+        && !gap.is_empty()
+        && gap.clone().all(|position| ctx.query_stop_word_positions.contains(&position));
+
+    if left_term.positions.end() + 1 != *right_term.positions.start() && !ignored_stop_words {
         // We want to ignore this pair of terms
         // Unconditionally walk through the edge without computing the docids
         // This can happen when, in a query like `the sun flowers are beautiful`, the term
@@ -35,6 +41,7 @@ pub fn build_edges(
         )]);
     }
 
+    let stop_words = if ignored_stop_words { gap.len() as u8 } else { 0 };
     let mut conditions = vec![];
     for cost in right_ngram_max..(((MAX_DISTANCE as usize) - 1) + right_ngram_max) {
         conditions.push((
@@ -43,6 +50,7 @@ pub fn build_edges(
                 left_term: left_term.clone(),
                 right_term: right_term.clone(),
                 cost: (cost + 1) as u8,
+                stop_words,
             }),
         ))
     }
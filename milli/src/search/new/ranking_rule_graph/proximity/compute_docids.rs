@@ -5,6 +5,7 @@ use std::collections::BTreeSet;
 use roaring::RoaringBitmap;
 
 use super::ProximityCondition;
+use crate::proximity::MAX_DISTANCE;
 use crate::search::new::interner::Interned;
 use crate::search::new::query_term::{Phrase, QueryTermSubset};
 use crate::search::new::ranking_rule_graph::ComputedCondition;
@@ -17,9 +18,9 @@ pub fn compute_docids(
     condition: &ProximityCondition,
     universe: &RoaringBitmap,
 ) -> Result<ComputedCondition> {
-    let (left_term, right_term, cost) = match condition {
-        ProximityCondition::Uninit { left_term, right_term, cost } => {
-            (left_term, right_term, *cost)
+    let (left_term, right_term, cost, stop_words) = match condition {
+        ProximityCondition::Uninit { left_term, right_term, cost, stop_words } => {
+            (left_term, right_term, *cost, *stop_words)
         }
         ProximityCondition::Term { term } => {
             return Ok(ComputedCondition {
@@ -45,57 +46,67 @@ pub fn compute_docids(
     let forward_proximity = 1 + cost - right_term_ngram_len;
     let backward_proximity = cost - right_term_ngram_len;
 
+    // the documents can contain the stop words of the query between the two terms for free
+    let mut proximities = vec![(forward_proximity, backward_proximity)];
+    if stop_words > 0 && forward_proximity + stop_words < MAX_DISTANCE as u8 {
+        proximities.push((forward_proximity + stop_words, 0));
+    }
+
     let mut docids = RoaringBitmap::new();
 
-    if let Some(right_prefix) = right_term.term_subset.use_prefix_db(ctx) {
-        for (left_phrase, left_word) in last_words_of_term_derivations(ctx, &left_term.term_subset)?
-        {
-            compute_prefix_edges(
-                ctx,
-                left_word.interned(),
-                right_prefix.interned(),
-                left_phrase,
-                forward_proximity,
-                backward_proximity,
-                &mut docids,
-                universe,
-            )?;
+    for (forward_proximity, backward_proximity) in proximities {
+        if let Some(right_prefix) = right_term.term_subset.use_prefix_db(ctx) {
+            for (left_phrase, left_word) in
+                last_words_of_term_derivations(ctx, &left_term.term_subset)?
+            {
+                compute_prefix_edges(
+                    ctx,
+                    left_word.interned(),
+                    right_prefix.interned(),
+                    left_phrase,
+                    forward_proximity,
+                    backward_proximity,
+                    &mut docids,
+                    universe,
+                )?;
+            }
         }
-    }
 
-    for (left_phrase, left_word) in last_words_of_term_derivations(ctx, &left_term.term_subset)? {
-        // Before computing the edges, check that the left word and left phrase
-        // aren't disjoint with the universe, but only do it if there is more than
-        // one word derivation to the right.
-        //
-        // This is an optimisation to avoid checking for an excessive number of
-        // pairs.
-        let right_derivs = first_word_of_term_iter(ctx, &right_term.term_subset)?;
-        if right_derivs.len() > 1 {
-            let universe = &universe;
-            if let Some(left_phrase) = left_phrase {
-                if universe.is_disjoint(ctx.get_phrase_docids(left_phrase)?) {
-                    continue;
-                }
-            } else if let Some(left_word_docids) = ctx.word_docids(Some(universe), left_word)? {
-                if left_word_docids.is_empty() {
-                    continue;
+        for (left_phrase, left_word) in last_words_of_term_derivations(ctx, &left_term.term_subset)?
+        {
+            // Before computing the edges, check that the left word and left phrase
+            // aren't disjoint with the universe, but only do it if there is more than
+            // one word derivation to the right.
+            //
+            // This is an optimisation to avoid checking for an excessive number of
+            // pairs.
+            let right_derivs = first_word_of_term_iter(ctx, &right_term.term_subset)?;
+            if right_derivs.len() > 1 {
+                let universe = &universe;
+                if let Some(left_phrase) = left_phrase {
+                    if universe.is_disjoint(ctx.get_phrase_docids(left_phrase)?) {
+                        continue;
+                    }
+                } else if let Some(left_word_docids) = ctx.word_docids(Some(universe), left_word)? {
+                    if left_word_docids.is_empty() {
+                        continue;
+                    }
                 }
             }
-        }
 
-        for (right_word, right_phrase) in right_derivs {
-            compute_non_prefix_edges(
-                ctx,
-                left_word.interned(),
-                right_word,
-                left_phrase,
-                right_phrase,
-                forward_proximity,
-                backward_proximity,
-                &mut docids,
-                universe,
-            )?;
+            for (right_word, right_phrase) in right_derivs {
+                compute_non_prefix_edges(
+                    ctx,
+                    left_word.interned(),
+                    right_word,
+                    left_phrase,
+                    right_phrase,
+                    forward_proximity,
+                    backward_proximity,
+                    &mut docids,
+                    universe,
+                )?;
+            }
         }
     }
 
@@ -151,16 +162,24 @@ fn compute_prefix_edges(
 
     // No swapping when computing the proximity between a phrase and a word
     if left_phrase.is_none() {
-        if let Some(new_docids) = ctx.get_db_prefix_word_pair_proximity_docids(
-            Some(&universe),
-            right_prefix,
-            left_word,
-            backward_proximity,
-        )? {
-            if !new_docids.is_empty() {
-                used_left_words.insert(left_word);
-                used_right_prefix.insert(right_prefix);
-                *docids |= new_docids;
+        // When the order doesn't matter, the swapped pair costs as much as the original one
+        let swapped_proximities: &[u8] = if ctx.ordered_proximity {
+            &[backward_proximity]
+        } else {
+            &[forward_proximity, backward_proximity]
+        };
+        for &proximity in swapped_proximities {
+            if let Some(new_docids) = ctx.get_db_prefix_word_pair_proximity_docids(
+                Some(&universe),
+                right_prefix,
+                left_word,
+                proximity,
+            )? {
+                if !new_docids.is_empty() {
+                    used_left_words.insert(left_word);
+                    used_right_prefix.insert(right_prefix);
+                    *docids |= new_docids;
+                }
             }
         }
     }
@@ -195,6 +214,16 @@ fn compute_non_prefix_edges(
             *docids |= new_docids;
         }
     }
+    // When the order doesn't matter, the swapped pair costs as much as the original one
+    if !ctx.ordered_proximity && left_phrase.is_none() && right_phrase.is_none() {
+        if let Some(new_docids) =
+            ctx.get_db_word_pair_proximity_docids(Some(&universe), word2, word1, forward_proximity)?
+        {
+            if !new_docids.is_empty() {
+                *docids |= new_docids;
+            }
+        }
+    }
     if backward_proximity >= 1 && left_phrase.is_none() && right_phrase.is_none() {
         if let Some(new_docids) = ctx.get_db_word_pair_proximity_docids(
             Some(&universe),
@@ -12,8 +12,17 @@ use crate::Result;
 
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub enum ProximityCondition {
-    Uninit { left_term: LocatedQueryTermSubset, right_term: LocatedQueryTermSubset, cost: u8 },
-    Term { term: LocatedQueryTermSubset },
+    Uninit {
+        left_term: LocatedQueryTermSubset,
+        right_term: LocatedQueryTermSubset,
+        cost: u8,
+        /// The number of stop words of the query between the two terms, that the documents
+        /// can contain between them without increasing the cost.
+        stop_words: u8,
+    },
+    Term {
+        term: LocatedQueryTermSubset,
+    },
 }
 
 pub enum ProximityGraph {}
@@ -21,6 +30,9 @@ pub enum ProximityGraph {}
 impl RankingRuleGraphTrait for ProximityGraph {
     type Condition = ProximityCondition;
 
+    const EXPLORE_RAREST_FIRST: bool = true;
+    const EXPENSIVE: bool = true;
+
     #[tracing::instrument(level = "trace", skip_all, target = "search::proximity")]
     fn resolve_condition(
         ctx: &mut SearchContext<'_>,
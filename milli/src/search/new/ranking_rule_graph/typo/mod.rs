@@ -19,6 +19,8 @@ pub enum TypoGraph {}
 impl RankingRuleGraphTrait for TypoGraph {
     type Condition = TypoCondition;
 
+    const EXPENSIVE: bool = true;
+
     #[tracing::instrument(level = "trace", skip_all, target = "search::typo")]
     fn resolve_condition(
         ctx: &mut SearchContext<'_>,
@@ -53,6 +55,10 @@ impl RankingRuleGraphTrait for TypoGraph {
         let base_cost = if term.term_ids.len() == 1 { 0 } else { term.term_ids.len() as u32 };
 
         for nbr_typos in 0..=term.term_subset.max_typo_cost(ctx) {
+            // the edges costing more than the typos allowed for the whole query are never taken
+            if Self::max_cost(ctx).is_some_and(|max| (nbr_typos as u32 + base_cost) as u64 > max) {
+                break;
+            }
             let mut term = term.clone();
             match nbr_typos {
                 0 => {
@@ -82,4 +88,8 @@ impl RankingRuleGraphTrait for TypoGraph {
     fn rank_to_score(rank: Rank) -> ScoreDetails {
         ScoreDetails::Typo(score_details::Typo::from_rank(rank))
     }
+
+    fn max_cost(ctx: &SearchContext<'_>) -> Option<u64> {
+        ctx.max_total_typos.map(u64::from)
+    }
 }
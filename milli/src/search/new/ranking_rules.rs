@@ -24,6 +24,19 @@ pub type BoxRankingRule<'ctx, Query> = Box<dyn RankingRule<'ctx, Query> + 'ctx>;
 pub trait RankingRule<'ctx, Query: RankingRuleQueryTrait> {
     fn id(&self) -> String;
 
+    /// Whether sorting the given universe is costly, in which case the candidates given to
+    /// the first such ranking rule of the search are capped by
+    /// [`max_ranked_candidates`](SearchContext::max_ranked_candidates).
+    fn is_expensive(&self, _universe: &RoaringBitmap) -> bool {
+        false
+    }
+
+    /// Whether the documents of its universe that the ranking rule doesn't return in any bucket
+    /// are excluded from the candidates, instead of being kept as unranked candidates.
+    fn excludes_unranked(&self, _ctx: &SearchContext<'ctx>) -> bool {
+        false
+    }
+
     /// Prepare the ranking rule such that it can start iterating over its
     /// buckets using [`next_bucket`](RankingRule::next_bucket).
     ///
@@ -10,12 +10,16 @@ use super::query_graph::QueryNodeData;
 use super::query_term::{Phrase, QueryTermSubset};
 use super::small_bitmap::SmallBitmap;
 use super::{QueryGraph, SearchContext, Word};
+use crate::proximity::MAX_DISTANCE;
 use crate::search::new::query_term::LocatedQueryTermSubset;
 use crate::Result;
 
 #[derive(Default)]
 pub struct PhraseDocIdsCache {
     pub cache: FxHashMap<Interned<Phrase>, RoaringBitmap>,
+    /// The documents containing the words of the phrase next to each other, only filled
+    /// when the phrases are matched with a slop.
+    pub exact_cache: FxHashMap<Interned<Phrase>, RoaringBitmap>,
 }
 impl<'ctx> SearchContext<'ctx> {
     /// Get the document ids associated with the given phrase
@@ -23,12 +27,27 @@ impl<'ctx> SearchContext<'ctx> {
         if self.phrase_docids.cache.contains_key(&phrase) {
             return Ok(&self.phrase_docids.cache[&phrase]);
         };
-        let docids = compute_phrase_docids(self, phrase)?;
+        let slop = self.phrase_slop;
+        let docids = compute_phrase_docids(self, phrase, slop)?;
         // TODO can we improve that? Because there is an issue, we keep that in cache...
         let _ = self.phrase_docids.cache.insert(phrase, docids);
         let docids = &self.phrase_docids.cache[&phrase];
         Ok(docids)
     }
+
+    /// Get the document ids containing the words of the given phrase next to each other,
+    /// whatever the phrase slop.
+    pub fn get_exact_phrase_docids(&mut self, phrase: Interned<Phrase>) -> Result<&RoaringBitmap> {
+        if self.phrase_slop == 0 {
+            return self.get_phrase_docids(phrase);
+        }
+        if self.phrase_docids.exact_cache.contains_key(&phrase) {
+            return Ok(&self.phrase_docids.exact_cache[&phrase]);
+        };
+        let docids = compute_phrase_docids(self, phrase, 0)?;
+        let _ = self.phrase_docids.exact_cache.insert(phrase, docids);
+        Ok(&self.phrase_docids.exact_cache[&phrase])
+    }
 }
 pub fn compute_query_term_subset_docids(
     ctx: &mut SearchContext<'_>,
@@ -184,9 +203,12 @@ pub fn compute_query_graph_docids(
     panic!()
 }
 
+/// Return the documents containing the words of the phrase in order, each pair of words
+/// being at most `slop` positions farther apart than in the phrase.
 pub fn compute_phrase_docids(
     ctx: &mut SearchContext<'_>,
     phrase: Interned<Phrase>,
+    slop: u8,
 ) -> Result<RoaringBitmap> {
     let Phrase { words } = ctx.phrase_interner.get(phrase).clone();
 
@@ -218,7 +240,7 @@ pub fn compute_phrase_docids(
                 .enumerate()
                 .filter_map(|(index, word)| word.as_ref().map(|word| (index, word)))
             {
-                if dist == 0 {
+                if dist == 0 && slop == 0 {
                     match ctx.get_db_word_pair_proximity_docids(None, s1, s2, 1)? {
                         Some(m) => bitmaps.push(m),
                         // If there are no documents for this pair, there will be no
@@ -226,10 +248,13 @@ pub fn compute_phrase_docids(
                         None => return Ok(RoaringBitmap::new()),
                     }
                 } else {
+                    // the pair databases only store the proximities lower than the max distance
+                    let max_proximity = (dist as u8 + 1).saturating_add(slop);
+                    let max_proximity = max_proximity.min(MAX_DISTANCE as u8 - 1);
                     let mut bitmap = RoaringBitmap::new();
-                    for dist in 0..=dist {
+                    for proximity in 1..=max_proximity {
                         if let Some(m) =
-                            ctx.get_db_word_pair_proximity_docids(None, s1, s2, dist as u8 + 1)?
+                            ctx.get_db_word_pair_proximity_docids(None, s1, s2, proximity)?
                         {
                             bitmap |= m;
                         }
@@ -0,0 +1,47 @@
+use std::collections::HashMap;
+
+use charabia::Language;
+use roaring::RoaringBitmap;
+
+use super::query_term::{ExtractedTokens, QueryTermSubset};
+use super::{extract_tokens, SearchContext};
+use crate::Result;
+
+/// Return, for each word and phrase the terms of the query are resolved to, the number of
+/// documents of the universe containing it, sorted by decreasing number of documents.
+///
+/// The words derived from a term, such as its typo candidates, its synonyms, or its split
+/// words, are counted on their own. A prefix searched in the prefix database is reported
+/// followed by a `*`.
+pub fn term_contributions(
+    ctx: &mut SearchContext<'_>,
+    query: &str,
+    universe: &RoaringBitmap,
+    words_limit: Option<usize>,
+    locales: Option<&Vec<Language>>,
+) -> Result<Vec<(String, u64)>> {
+    let ExtractedTokens { query_terms, .. } = extract_tokens(ctx, query, words_limit, locales)?;
+
+    let mut contributions = HashMap::new();
+    for located_term in query_terms {
+        let subset = QueryTermSubset::full(located_term.value);
+        for word in subset.all_single_words_except_prefix_db(ctx)? {
+            let count = ctx.word_docids(Some(universe), word)?;
+            let count = count.map_or(0, |docids| docids.intersection_len(universe));
+            contributions.insert(ctx.word_interner.get(word.interned()).clone(), count);
+        }
+        for phrase in subset.all_phrases(ctx)? {
+            let count = ctx.get_phrase_docids(phrase)?.intersection_len(universe);
+            contributions.insert(phrase.description(ctx), count);
+        }
+        if let Some(prefix) = subset.use_prefix_db(ctx) {
+            let count = ctx.word_prefix_docids(Some(universe), prefix)?;
+            let count = count.map_or(0, |docids| docids.intersection_len(universe));
+            contributions.insert(format!("{}*", ctx.word_interner.get(prefix.interned())), count);
+        }
+    }
+
+    let mut contributions: Vec<_> = contributions.into_iter().collect();
+    contributions.sort_unstable_by(|(la, ca), (lb, cb)| cb.cmp(ca).then_with(|| la.cmp(lb)));
+    Ok(contributions)
+}
@@ -0,0 +1,49 @@
+use charabia::Language;
+use roaring::RoaringBitmap;
+
+use super::graph_based_ranking_rule::Proximity;
+use super::query_term::ExtractedTokens;
+use super::ranking_rules::{RankingRule, RankingRuleOutput};
+use super::{extract_tokens, DefaultSearchLogger, QueryGraph, SearchContext};
+use crate::score_details::{Rank, ScoreDetails};
+use crate::{DocumentId, Result};
+
+/// Return the cost of the cheapest proximity bucket containing the given document, i.e. how
+/// close to each other the words of the query are in the document.
+///
+/// Returns `None` when the query has less than two terms, or when the document doesn't
+/// contain all the terms of the query and thus is never returned by the proximity rule.
+pub fn term_proximity(
+    ctx: &mut SearchContext<'_>,
+    query: &str,
+    docid: DocumentId,
+    words_limit: Option<usize>,
+    locales: Option<&Vec<Language>>,
+) -> Result<Option<u16>> {
+    let ExtractedTokens { query_terms, .. } = extract_tokens(ctx, query, words_limit, locales)?;
+    if query_terms.len() < 2 {
+        return Ok(None);
+    }
+    let (graph, _) = QueryGraph::from_query(ctx, &query_terms)?;
+
+    // the buckets are computed for this document only
+    let universe = RoaringBitmap::from_iter([docid]);
+    let logger = &mut DefaultSearchLogger;
+    let mut proximity = Proximity::new(None);
+    proximity.start_iteration(ctx, logger, &universe, &graph)?;
+
+    let mut cost = None;
+    while let Some(RankingRuleOutput { candidates, score, .. }) =
+        proximity.next_bucket(ctx, logger, &universe)?
+    {
+        if candidates.contains(docid) {
+            if let ScoreDetails::Proximity(Rank { rank, max_rank }) = score {
+                cost = Some((max_rank - rank) as u16);
+            }
+            break;
+        }
+    }
+    proximity.end_iteration(ctx, logger);
+
+    Ok(cost)
+}
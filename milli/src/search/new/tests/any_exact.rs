@@ -0,0 +1,77 @@
+/*!
+This module tests the `any_exact` search option:
+
+1. the results are the documents containing any of the words exactly
+2. it matches the union of the exact searches of each word
+3. the words are read without any typo, prefix, or proximity database lookup
+*/
+
+use roaring::RoaringBitmap;
+
+use crate::index::tests::TempIndex;
+use crate::search::new::tests::collect_field_values;
+use crate::{any_exact_word_docids, Search, SearchContext, SearchResult};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["tags".to_owned()]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "tags": "rust search engine" },
+            { "id": 1, "tags": "rusty nail" },
+            { "id": 2, "tags": "search" },
+            { "id": 3, "tags": "database engine" },
+            { "id": 4, "tags": "python" },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_any_exact() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+    let words = vec!["rust".to_owned(), "engine".to_owned()];
+
+    let mut s = Search::new(&txn, &index);
+    s.any_exact(words.clone());
+    let SearchResult { documents_ids, candidates, .. } = s.execute().unwrap();
+    let mut ids = collect_field_values(&index, &txn, "id", &documents_ids);
+    ids.sort();
+    // `rusty` is neither a typo nor a prefix match of `rust`
+    assert_eq!(ids, vec!["0", "3"]);
+
+    let mut expected = RoaringBitmap::new();
+    for word in &words {
+        let mut s = Search::new(&txn, &index);
+        s.query(format!("\"{word}\""));
+        expected |= s.execute().unwrap().candidates;
+    }
+    assert_eq!(candidates, expected);
+}
+
+#[test]
+fn test_any_exact_database_reads() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut ctx = SearchContext::new(&index, &txn).unwrap();
+    let universe = index.documents_ids(&txn).unwrap();
+    let words = ["search".to_owned(), "python".to_owned()];
+    let docids = any_exact_word_docids(&mut ctx, &universe, &words).unwrap();
+    assert_eq!(docids, RoaringBitmap::from_iter([0, 2, 4]));
+
+    assert!(ctx.db_cache.word_prefix_docids.is_empty());
+    assert!(ctx.db_cache.exact_word_prefix_docids.is_empty());
+    assert!(ctx.db_cache.word_pair_proximity_docids.is_empty());
+    assert!(ctx.db_cache.word_prefix_pair_proximity_docids.is_empty());
+    assert!(ctx.db_cache.prefix_word_pair_proximity_docids.is_empty());
+    assert!(ctx.db_cache.words_fst.is_none());
+}
@@ -152,6 +152,7 @@ fn test_attribute_position_repeated() {
 
     let mut s = Search::new(&txn, &index);
     s.terms_matching_strategy(TermsMatchingStrategy::All);
+    s.keep_duplicate_tokens(true);
     s.query("a a a a a");
     s.scoring_strategy(crate::score_details::ScoringStrategy::Detailed);
 
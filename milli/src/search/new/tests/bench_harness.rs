@@ -0,0 +1,64 @@
+/*!
+This module tests the harness used to benchmark the ranking rules one by one:
+
+1. the geo sort ranks the same documents with the iterative and rtree strategies
+2. the number of buckets iterated over can be limited
+3. the universe of a query graph ranking rule are the documents matching the query
+4. the field values of the fixtures are read back as JSON
+*/
+
+use crate::{collect_field_values, GeoSortStrategy, RankingRuleBench, TempIndex};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_sortable_fields(["_geo".to_owned()].into_iter().collect());
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "the quick brown fox", "_geo": { "lat": 0, "lng": 0 } },
+            { "id": 1, "text": "the quick dog", "_geo": { "lat": 1, "lng": 1 } },
+            { "id": 2, "text": "a lazy fox", "_geo": { "lat": 2, "lng": 2 } },
+            { "id": 3, "text": "nothing to see here" },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_bench_geo_sort() {
+    let index = create_index();
+    let rtxn = index.read_txn().unwrap();
+
+    for strategy in [GeoSortStrategy::AlwaysIterative(2), GeoSortStrategy::AlwaysRtree(2)] {
+        let mut bench =
+            RankingRuleBench::geo_sort(&index, &rtxn, [0., 0.], true, strategy).unwrap();
+        assert_eq!(bench.universe().len(), 3);
+        assert_eq!(bench.run(None).unwrap(), 3);
+        assert_eq!(bench.run(Some(1)).unwrap(), 1);
+    }
+}
+
+#[test]
+fn test_bench_query_graph_rules() {
+    let index = create_index();
+    let rtxn = index.read_txn().unwrap();
+
+    let mut bench = RankingRuleBench::words(&index, &rtxn, "quick fox").unwrap().unwrap();
+    insta::assert_snapshot!(format!("{:?}", bench.universe()), @"RoaringBitmap<[0, 1]>");
+    assert_eq!(bench.run(None).unwrap(), 2);
+
+    let mut bench = RankingRuleBench::proximity(&index, &rtxn, "fox").unwrap().unwrap();
+    bench.restrict_universe(&[2].into_iter().collect());
+    assert_eq!(bench.run(None).unwrap(), 1);
+
+    assert!(RankingRuleBench::typo(&index, &rtxn, "").unwrap().is_none());
+
+    let values = collect_field_values(&index, &rtxn, "text", &[2, 3]);
+    insta::assert_snapshot!(format!("{values:?}"), @r###"["\"a lazy fox\"", "\"nothing to see here\""]"###);
+}
@@ -0,0 +1,80 @@
+/*!
+This module tests the `bucket_limits` search option:
+
+1. the candidates of a bucket over the limit of its ranking rule are not ranked by the next
+   ranking rules and are not returned
+2. the search is reported as degraded when a bucket is limited
+3. the limit of the last ranking rule has no effect
+*/
+
+use std::collections::HashMap;
+
+use big_s::S;
+use maplit::hashset;
+
+use crate::index::tests::TempIndex;
+use crate::{Criterion, DegradationReason, Search, SearchResult};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_sortable_fields(hashset! { S("rank") });
+            s.set_criteria(vec![Criterion::Words, Criterion::Asc("rank".to_owned())]);
+        })
+        .unwrap();
+
+    // all the documents tie for the words ranking rule
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "hello", "rank": 9 },
+            { "id": 1, "text": "hello", "rank": 8 },
+            { "id": 2, "text": "hello", "rank": 7 },
+            { "id": 3, "text": "hello", "rank": 6 },
+            { "id": 4, "text": "hello", "rank": 5 },
+            { "id": 5, "text": "hello", "rank": 4 },
+            { "id": 6, "text": "hello", "rank": 3 },
+            { "id": 7, "text": "hello", "rank": 2 },
+            { "id": 8, "text": "hello", "rank": 1 },
+            { "id": 9, "text": "hello", "rank": 0 },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_bucket_limits() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello");
+    let SearchResult { documents_ids, degraded, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[9, 8, 7, 6, 5, 4, 3, 2, 1, 0]");
+    assert!(!degraded);
+
+    s.bucket_limits(HashMap::from([(S("words"), 3)]));
+    let SearchResult { documents_ids, candidates, degraded, degradation_reasons, .. } =
+        s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[2, 1, 0]");
+    assert_eq!(candidates.len(), 10);
+    assert!(degraded);
+    assert_eq!(degradation_reasons, Some(vec![DegradationReason::BucketLimitHit]));
+}
+
+#[test]
+fn test_bucket_limits_of_the_last_ranking_rule() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello");
+    s.limit(4);
+    s.bucket_limits(HashMap::from([(S("rank:asc"), 1), (S("words"), 10)]));
+    let SearchResult { documents_ids, degraded, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[9, 8, 7, 6]");
+    assert!(!degraded);
+}
@@ -0,0 +1,117 @@
+/*!
+This module tests the `cancel_token` search option:
+
+1. a search whose token is set returns `Error::SearchCancelled`
+2. a token set during the search stops it before its next bucket
+*/
+
+use std::any::Any;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use roaring::RoaringBitmap;
+
+use crate::index::tests::TempIndex;
+use crate::score_details::ScoringStrategy;
+use crate::search::new::ranking_rules::BoxRankingRule;
+use crate::search::new::QueryGraph;
+use crate::{
+    execute_search, Criterion, DefaultSearchLogger, Error, GeoSortStrategy, Search, SearchContext,
+    SearchLogger, TermsMatchingStrategy, TimeBudget,
+};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_criteria(vec![Criterion::Words, Criterion::Typo]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "hello" },
+            { "id": 1, "text": "hallo" },
+            { "id": 2, "text": "hello world" },
+            { "id": 3, "text": "hullo" },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_cancel_token() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let token = Arc::new(AtomicBool::new(false));
+    let mut s = Search::new(&txn, &index);
+    s.query("hello");
+    s.cancel_token(token.clone());
+    assert_eq!(s.execute().unwrap().documents_ids.len(), 4);
+
+    let setter = token.clone();
+    std::thread::spawn(move || setter.store(true, Ordering::Relaxed)).join().unwrap();
+    assert!(matches!(s.execute(), Err(Error::SearchCancelled)));
+}
+
+/// A logger cancelling the search from another thread once the first documents are found.
+struct CancelOnResults {
+    token: Arc<AtomicBool>,
+    results: usize,
+}
+
+impl SearchLogger<QueryGraph> for CancelOnResults {
+    fn initial_query(&mut self, _query: &QueryGraph) {}
+
+    fn initial_universe(&mut self, _universe: &RoaringBitmap) {}
+
+    fn query_for_initial_universe(&mut self, _query: &QueryGraph) {}
+
+    fn ranking_rules(&mut self, _rr: &[BoxRankingRule<'_, QueryGraph>]) {}
+
+    fn add_to_results(&mut self, docids: &[u32]) {
+        self.results += docids.len();
+        let token = self.token.clone();
+        std::thread::spawn(move || token.store(true, Ordering::Relaxed)).join().unwrap();
+    }
+
+    fn log_internal_state(&mut self, _rr: &dyn Any) {}
+}
+
+#[test]
+fn test_cancel_token_during_search() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let token = Arc::new(AtomicBool::new(false));
+    let mut ctx = SearchContext::new(&index, &txn).unwrap();
+    ctx.cancel_token = Some(token.clone());
+    let universe = index.documents_ids(&txn).unwrap();
+    let mut logger = CancelOnResults { token, results: 0 };
+    let result = execute_search(
+        &mut ctx,
+        Some("hello"),
+        TermsMatchingStrategy::Last,
+        ScoringStrategy::Skip,
+        false,
+        universe,
+        &None,
+        &None,
+        GeoSortStrategy::default(),
+        0,
+        20,
+        None,
+        &mut DefaultSearchLogger,
+        &mut logger,
+        TimeBudget::max(),
+        None,
+        None,
+    );
+    assert!(matches!(result, Err(Error::SearchCancelled)));
+    // only the documents without typos were found before the search was cancelled
+    assert_eq!(logger.results, 2);
+}
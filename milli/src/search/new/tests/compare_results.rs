@@ -0,0 +1,38 @@
+/*!
+This module tests the comparison of the documents returned by two searches:
+
+1. the documents only returned by one of the searches are added or removed
+2. the documents returned at different ranks are moved by the difference of their ranks
+*/
+
+use crate::{compare_results, ResultDiff, SearchResult};
+
+fn result(documents_ids: Vec<u32>) -> SearchResult {
+    SearchResult { documents_ids, ..Default::default() }
+}
+
+#[test]
+fn test_compare_results() {
+    let a = result(vec![10, 11, 12, 13, 14]);
+    let b = result(vec![12, 10, 15, 14, 11]);
+
+    let diff = compare_results(&a, &b);
+    assert_eq!(
+        diff,
+        ResultDiff {
+            added: vec![(15, 2)],
+            removed: vec![(13, 3)],
+            moved: vec![(12, -2), (10, 1), (14, -1), (11, 3)],
+        }
+    );
+}
+
+#[test]
+fn test_compare_same_results() {
+    let a = result(vec![1, 2, 3]);
+
+    assert_eq!(compare_results(&a, &a), ResultDiff::default());
+    let diff = compare_results(&result(vec![]), &a);
+    assert_eq!(diff.added, vec![(1, 0), (2, 1), (3, 2)]);
+    assert!(diff.removed.is_empty() && diff.moved.is_empty());
+}
@@ -0,0 +1,312 @@
+/*!
+This module tests the cursors returned by a search to fetch the next page:
+
+1. following the cursors returns the same pages as the offsets, and the last page has no cursor
+2. a document added or deleted before the cursor doesn't make the next page return a document
+again or miss one
+3. a cursor built by another search, or that isn't a cursor, is rejected
+4. a cursor built by a search with other options changing the order of the documents is rejected
+5. the documents inserted by `execute_into` follow the cursor too
+6. the cursor is only returned when it is requested
+*/
+
+use big_s::S;
+use charabia::Language;
+use maplit::hashset;
+use meili_snap::insta;
+use roaring::RoaringBitmap;
+
+use crate::index::tests::TempIndex;
+use crate::{AscDesc, Criterion, Error, GeoSortStrategy, Member, Search, SearchResult, UserError};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_sortable_fields(hashset! { S("rank") });
+            s.set_criteria(vec![Criterion::Words, Criterion::Sort]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "hello", "rank": 9 },
+            { "id": 1, "text": "hello", "rank": 8 },
+            { "id": 2, "text": "hello", "rank": 7 },
+            { "id": 3, "text": "hello", "rank": 6 },
+            { "id": 4, "text": "hello", "rank": 5 },
+            { "id": 5, "text": "hello", "rank": 4 },
+            { "id": 6, "text": "hello", "rank": 3 },
+            { "id": 7, "text": "hello", "rank": 2 },
+            { "id": 8, "text": "hello", "rank": 1 },
+            { "id": 9, "text": "hello", "rank": 0 },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_cursor_round_trip() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let search = || {
+        let mut s = Search::new(&txn, &index);
+        s.query("hello");
+        s.sort_criteria(vec![AscDesc::Asc(Member::Field(S("rank")))]);
+        s.limit(3);
+        s.next_cursor(true);
+        s
+    };
+
+    let mut pages = Vec::new();
+    let mut offset_pages = Vec::new();
+    let mut cursor = None;
+    loop {
+        let mut s = search();
+        if let Some(cursor) = &cursor {
+            s.from_cursor(cursor);
+        }
+        let SearchResult { documents_ids, next_cursor, .. } = s.execute().unwrap();
+        pages.push(documents_ids);
+
+        let mut s = search();
+        s.offset(3 * offset_pages.len());
+        offset_pages.push(s.execute().unwrap().documents_ids);
+
+        match next_cursor {
+            Some(next_cursor) => cursor = Some(next_cursor),
+            None => break,
+        }
+    }
+    insta::assert_snapshot!(format!("{pages:?}"), @"[[9, 8, 7], [6, 5, 4], [3, 2, 1], [0]]");
+    assert_eq!(pages, offset_pages);
+}
+
+#[test]
+fn test_cursor_document_added_before() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello");
+    s.sort_criteria(vec![AscDesc::Asc(Member::Field(S("rank")))]);
+    s.limit(3);
+    s.next_cursor(true);
+    let SearchResult { documents_ids, next_cursor, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[9, 8, 7]");
+    let cursor = next_cursor.unwrap();
+    drop(txn);
+
+    index.add_documents(documents!([{ "id": 10, "text": "hello", "rank": -1 }])).unwrap();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello");
+    s.sort_criteria(vec![AscDesc::Asc(Member::Field(S("rank")))]);
+    s.limit(3);
+    s.from_cursor(&cursor);
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    // the last document of the previous page is not returned again
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[6, 5, 4]");
+}
+
+#[test]
+fn test_cursor_invalid() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello");
+    s.sort_criteria(vec![AscDesc::Asc(Member::Field(S("rank")))]);
+    s.limit(3);
+    s.next_cursor(true);
+    let cursor = s.execute().unwrap().next_cursor.unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello");
+    s.sort_criteria(vec![AscDesc::Desc(Member::Field(S("rank")))]);
+    s.limit(3);
+    s.from_cursor(&cursor);
+    let error = s.execute().unwrap_err();
+    assert!(matches!(error, Error::UserError(UserError::InvalidSearchCursor(_))));
+    insta::assert_snapshot!(error, @"Invalid search cursor: the cursor was built by a search with other parameters.");
+
+    s.from_cursor("hello");
+    let error = s.execute().unwrap_err();
+    insta::assert_snapshot!(error, @"Invalid search cursor: `hello` is not a cursor.");
+}
+
+#[test]
+fn test_cursor_other_options() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let search = || {
+        let mut s = Search::new(&txn, &index);
+        s.query("hello");
+        s.sort_criteria(vec![AscDesc::Asc(Member::Field(S("rank")))]);
+        s.limit(3);
+        s.next_cursor(true);
+        s
+    };
+    let cursor = search().execute().unwrap().next_cursor.unwrap();
+
+    let options: [&dyn Fn(&mut Search); 4] = [
+        &|s| {
+            s.locales(vec![Language::Eng]);
+        },
+        &|s| {
+            s.geo_sort_strategy(GeoSortStrategy::AlwaysRtree(10));
+        },
+        &|s| {
+            s.ranking_score_threshold(0.5);
+        },
+        &|s| {
+            s.pin(vec![0]);
+        },
+    ];
+    for option in options {
+        let mut s = search();
+        option(&mut s);
+        s.from_cursor(&cursor);
+        let error = s.execute().unwrap_err();
+        assert!(matches!(error, Error::UserError(UserError::InvalidSearchCursor(_))));
+    }
+
+    // the limit doesn't change the order of the documents
+    let mut s = search();
+    s.limit(2);
+    s.from_cursor(&cursor);
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[6, 5]");
+}
+
+#[test]
+fn test_cursor_execute_into() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello");
+    s.sort_criteria(vec![AscDesc::Asc(Member::Field(S("rank")))]);
+    s.limit(3);
+    s.next_cursor(true);
+    let cursor = s.execute().unwrap().next_cursor.unwrap();
+
+    // all the remaining candidates fit in the page, but the first page must not be returned
+    s.limit(10);
+    s.from_cursor(&cursor);
+    let mut documents = RoaringBitmap::new();
+    s.execute_into(&mut documents).unwrap();
+    insta::assert_snapshot!(format!("{documents:?}"), @"RoaringBitmap<[0, 1, 2, 3, 4, 5, 6]>");
+}
+
+#[test]
+fn test_cursor_document_deleted_before() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello");
+    s.sort_criteria(vec![AscDesc::Asc(Member::Field(S("rank")))]);
+    s.limit(3);
+    s.next_cursor(true);
+    let SearchResult { documents_ids, next_cursor, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[9, 8, 7]");
+    let cursor = next_cursor.unwrap();
+    drop(txn);
+
+    // the last document of the page is deleted too
+    index.delete_documents(vec![S("9"), S("7")]);
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello");
+    s.sort_criteria(vec![AscDesc::Asc(Member::Field(S("rank")))]);
+    s.limit(3);
+    s.from_cursor(&cursor);
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    // no document is skipped
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[6, 5, 4]");
+}
+
+#[test]
+fn test_cursor_diversify_document_deleted_before() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello");
+    s.sort_criteria(vec![AscDesc::Asc(Member::Field(S("rank")))]);
+    // the documents are skipped by their number when they are diversified
+    s.diversify_by(S("rank"), 1);
+    s.limit(3);
+    s.next_cursor(true);
+    let SearchResult { documents_ids, next_cursor, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[9, 8, 7]");
+    let cursor = next_cursor.unwrap();
+    drop(txn);
+
+    index.delete_documents(vec![S("9")]);
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello");
+    s.sort_criteria(vec![AscDesc::Asc(Member::Field(S("rank")))]);
+    s.diversify_by(S("rank"), 1);
+    s.limit(3);
+    s.next_cursor(true);
+    s.from_cursor(&cursor);
+    let SearchResult { documents_ids, next_cursor, .. } = s.execute().unwrap();
+    // the page resumes after the last document of the previous page, no document is skipped
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[6, 5, 4]");
+
+    s.from_cursor(&next_cursor.unwrap());
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[3, 2, 1]");
+}
+
+#[test]
+fn test_cursor_pinned() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut pages = Vec::new();
+    let mut cursor = None;
+    loop {
+        let mut s = Search::new(&txn, &index);
+        s.query("hello");
+        s.sort_criteria(vec![AscDesc::Asc(Member::Field(S("rank")))]);
+        s.pin(vec![5, 3]);
+        s.limit(2);
+        s.next_cursor(true);
+        if let Some(cursor) = &cursor {
+            s.from_cursor(cursor);
+        }
+        let SearchResult { documents_ids, next_cursor, .. } = s.execute().unwrap();
+        pages.push(documents_ids);
+        match next_cursor {
+            Some(next_cursor) => cursor = Some(next_cursor),
+            None => break,
+        }
+    }
+    // the second page is resumed after a pinned document
+    insta::assert_snapshot!(format!("{pages:?}"), @"[[5, 3], [9, 8], [7, 6], [4, 2], [1, 0], []]");
+}
+
+#[test]
+fn test_cursor_not_requested() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello");
+    s.limit(3);
+    let SearchResult { documents_ids, next_cursor, .. } = s.execute().unwrap();
+    assert_eq!(documents_ids.len(), 3);
+    assert_eq!(next_cursor, None);
+}
@@ -0,0 +1,86 @@
+/*!
+This module tests the capture of the values read by a search, with
+`SearchContext::capture_db_cache`, and their replay with `SearchContext::replay_db_cache`:
+
+1. the capture survives a serialization roundtrip
+2. replaying the capture on an index with the same settings but without any document
+returns the same results as the captured search
+*/
+
+use roaring::RoaringBitmap;
+
+use crate::index::tests::TempIndex;
+use crate::score_details::ScoringStrategy;
+use crate::{
+    execute_search, Criterion, DatabaseCacheCapture, DefaultSearchLogger, GeoSortStrategy,
+    SearchContext, TermsMatchingStrategy, TimeBudget,
+};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_criteria(vec![Criterion::Words, Criterion::Typo, Criterion::Proximity]);
+        })
+        .unwrap();
+    index
+}
+
+fn search(ctx: &mut SearchContext, query: &str, universe: RoaringBitmap) -> Vec<u32> {
+    execute_search(
+        ctx,
+        Some(query),
+        TermsMatchingStrategy::Last,
+        ScoringStrategy::Skip,
+        false,
+        universe,
+        &None,
+        &None,
+        GeoSortStrategy::default(),
+        0,
+        20,
+        None,
+        &mut DefaultSearchLogger,
+        &mut DefaultSearchLogger,
+        TimeBudget::max(),
+        None,
+        None,
+    )
+    .unwrap()
+    .documents_ids
+}
+
+#[test]
+fn test_replay_db_cache_capture() {
+    let index = create_index();
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "the summer of love" },
+            { "id": 1, "text": "a summer song about love" },
+            { "id": 2, "text": "love songs from the sumer" },
+            { "id": 3, "text": "winter is coming" },
+            { "id": 4, "text": "the love of summer songs" },
+        ]))
+        .unwrap();
+    let txn = index.read_txn().unwrap();
+    let universe = index.documents_ids(&txn).unwrap();
+
+    let query = "sumer love song";
+    let mut ctx = SearchContext::new(&index, &txn).unwrap();
+    let expected = search(&mut ctx, query, universe.clone());
+    assert!(!expected.is_empty());
+    let capture = ctx.capture_db_cache();
+
+    let serialized = serde_json::to_string(&capture).unwrap();
+    let capture: DatabaseCacheCapture = serde_json::from_str(&serialized).unwrap();
+
+    // the replay index only shares the settings of the captured one
+    let replay_index = create_index();
+    let replay_txn = replay_index.read_txn().unwrap();
+    let mut ctx = SearchContext::new(&replay_index, &replay_txn).unwrap();
+    ctx.replay_db_cache(&capture).unwrap();
+    assert_eq!(search(&mut ctx, query, universe), expected);
+}
@@ -0,0 +1,98 @@
+/*!
+This module tests the iteration over the path prefixes leading to dead ends
+stored in a `DeadEndsCache`, with `DeadEndsCache::iter`, and their reporting
+to the search logger by the graph-based ranking rules.
+*/
+
+use crate::index::tests::TempIndex;
+use crate::score_details::ScoringStrategy;
+use crate::search::new::interner::{FixedSizeInterner, Interned};
+use crate::search::new::ranking_rule_graph::DeadEndsCache;
+use crate::{
+    execute_search, BinarySearchEvent, BinarySearchLogger, Criterion, DefaultSearchLogger,
+    GeoSortStrategy, SearchContext, TermsMatchingStrategy, TimeBudget,
+};
+
+#[test]
+fn test_dead_ends_cache_iter() {
+    let conditions = FixedSizeInterner::new(4, ());
+    let c = |i| Interned::<()>::from_raw(i);
+    let mut cache = DeadEndsCache::new(&conditions);
+    assert_eq!(cache.iter().count(), 0);
+
+    cache.forbid_condition(c(3));
+    cache.forbid_condition_after_prefix([c(0)].into_iter(), c(1));
+    cache.forbid_condition_after_prefix([c(0), c(1)].into_iter(), c(2));
+    cache.forbid_condition_after_prefix([c(0), c(1)].into_iter(), c(3));
+    cache.forbid_condition_after_prefix([c(2), c(1)].into_iter(), c(0));
+
+    let dead_ends: Vec<_> = cache
+        .iter()
+        .map(|(prefix, forbidden)| {
+            let prefix: Vec<_> = prefix.into_iter().map(Interned::into_raw).collect();
+            let forbidden: Vec<_> = forbidden.iter().map(Interned::into_raw).collect();
+            (prefix, forbidden)
+        })
+        .collect();
+    assert_eq!(
+        dead_ends,
+        vec![
+            (vec![], vec![3]),
+            (vec![0], vec![1]),
+            (vec![0, 1], vec![2, 3]),
+            (vec![2, 1], vec![0]),
+        ]
+    );
+}
+
+#[test]
+fn test_dead_ends_logged() {
+    let index = TempIndex::new();
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_criteria(vec![Criterion::Words, Criterion::Proximity]);
+        })
+        .unwrap();
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "hello world" },
+            { "id": 1, "text": "hello a b c world" },
+        ]))
+        .unwrap();
+
+    let txn = index.read_txn().unwrap();
+    let mut ctx = SearchContext::new(&index, &txn).unwrap();
+    let universe = index.documents_ids(&txn).unwrap();
+    let mut logger = BinarySearchLogger::new(Vec::new());
+    execute_search(
+        &mut ctx,
+        Some("hello world"),
+        TermsMatchingStrategy::Last,
+        ScoringStrategy::Skip,
+        false,
+        universe,
+        &None,
+        &None,
+        GeoSortStrategy::default(),
+        0,
+        20,
+        None,
+        &mut DefaultSearchLogger,
+        &mut logger,
+        TimeBudget::max(),
+        None,
+        None,
+    )
+    .unwrap();
+    let bytes = logger.finish().unwrap();
+
+    // the proximity conditions between the two words that match no document are dead ends
+    let events = BinarySearchEvent::decode_all(bytes.as_slice()).unwrap();
+    assert!(events.iter().any(|event| matches!(
+        event,
+        BinarySearchEvent::DeadEnds { dead_ends }
+            if dead_ends.iter().any(|(prefix, forbidden)| prefix.is_empty() && !forbidden.is_empty())
+    )));
+}
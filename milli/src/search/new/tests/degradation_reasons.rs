@@ -0,0 +1,109 @@
+/*!
+This module tests the reasons reported in `SearchResult::degradation_reasons`:
+
+1. nothing is reported when the results are complete
+2. a prefix matching too many words is reported, but not one matching exactly the maximum
+   number of words a prefix is expanded to
+3. reaching the maximum number of ranked candidates is reported
+4. exhausting the time budget is reported
+*/
+
+use std::time::Duration;
+
+use crate::index::tests::TempIndex;
+use crate::{Criterion, DegradationReason, Search, SearchResult, TimeBudget};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_criteria(vec![Criterion::Words, Criterion::Proximity]);
+        })
+        .unwrap();
+
+    let letters = b'a'..=b'z';
+    let words_starting_with = |prefix: &str, count: usize| {
+        let words: Vec<String> = letters
+            .clone()
+            .flat_map(|a| letters.clone().map(move |b| (a, b)))
+            .flat_map(|(a, b)| letters.clone().map(move |c| [a, b, c]))
+            .take(count)
+            .map(|suffix| format!("{prefix}{}", String::from_utf8_lossy(&suffix)))
+            .collect();
+        words.join(" ")
+    };
+    // more words starting with `abcde` than a prefix is expanded to
+    let text = words_starting_with("abcde", 1_100);
+    // exactly as many words starting with `fghij` as a prefix is expanded to
+    let other_text = words_starting_with("fghij", 1_000);
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "hello world" },
+            { "id": 1, "text": "hello big world" },
+            { "id": 2, "text": "hello the whole world" },
+            { "id": 3, "text": text },
+            { "id": 4, "text": other_text },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_no_degradation() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello world");
+    let SearchResult { documents_ids, degradation_reasons, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0, 1, 2]");
+    assert_eq!(degradation_reasons, None);
+}
+
+#[test]
+fn test_prefix_truncated() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("abcde");
+    let SearchResult { documents_ids, degradation_reasons, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[3]");
+    assert_eq!(degradation_reasons, Some(vec![DegradationReason::PrefixTruncated]));
+
+    // the word isn't a prefix anymore
+    s.query("abcde ");
+    let SearchResult { degradation_reasons, .. } = s.execute().unwrap();
+    assert_eq!(degradation_reasons, None);
+
+    // all the words starting with the prefix are kept
+    s.query("fghij");
+    let SearchResult { documents_ids, degradation_reasons, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[4]");
+    assert_eq!(degradation_reasons, None);
+}
+
+#[test]
+fn test_candidate_cap_and_timeout() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello world");
+    s.max_ranked_candidates(2);
+    let SearchResult { documents_ids, degradation_reasons, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0, 1]");
+    assert_eq!(degradation_reasons, Some(vec![DegradationReason::CandidateCapHit]));
+
+    s.time_budget(TimeBudget::new(Duration::from_millis(0)));
+    let SearchResult { degraded, degradation_reasons, .. } = s.execute().unwrap();
+    assert!(degraded);
+    assert_eq!(
+        degradation_reasons,
+        Some(vec![DegradationReason::CandidateCapHit, DegradationReason::TimedOut])
+    );
+}
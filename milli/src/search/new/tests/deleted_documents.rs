@@ -0,0 +1,87 @@
+/*!
+This module tests that the deleted documents are never returned by the search.
+
+Documents are deleted from all the databases of the index, the geo rtree included, in the same
+transaction as they are removed from the documents ids, so no deleted document must be returned
+by the words, sort, or geo sort ranking rules, nor counted in the candidates.
+*/
+
+use big_s::S;
+use maplit::hashset;
+
+use crate::index::tests::TempIndex;
+use crate::search::new::tests::collect_field_values;
+use crate::{AscDesc, Criterion, GeoSortStrategy, Member, Search, SearchResult};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec![S("text")]);
+            s.set_sortable_fields(hashset! { S("price"), S("_geo") });
+            s.set_criteria(vec![Criterion::Words, Criterion::Sort]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "hello world", "price": 4, "_geo": { "lat": 4, "lng": 0 } },
+            { "id": 1, "text": "hello", "price": 3, "_geo": { "lat": 3, "lng": 0 } },
+            { "id": 2, "text": "hello world", "price": 2, "_geo": { "lat": 2, "lng": 0 } },
+            { "id": 3, "text": "hello", "price": 1, "_geo": { "lat": 1, "lng": 0 } },
+            { "id": 4, "text": "hello world", "price": 0, "_geo": { "lat": 0, "lng": 0 } },
+        ]))
+        .unwrap();
+    index.delete_documents(vec![S("1"), S("3")]);
+    index
+}
+
+#[test]
+fn test_deleted_documents_words() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello");
+    let SearchResult { documents_ids, candidates, .. } = s.execute().unwrap();
+    let ids = collect_field_values(&index, &txn, "id", &documents_ids);
+    insta::assert_snapshot!(format!("{ids:?}"), @r###"["0", "2", "4"]"###);
+    assert_eq!(candidates.len(), 3);
+
+    let s = Search::new(&txn, &index);
+    let SearchResult { documents_ids, candidates, .. } = s.execute().unwrap();
+    let ids = collect_field_values(&index, &txn, "id", &documents_ids);
+    insta::assert_snapshot!(format!("{ids:?}"), @r###"["0", "2", "4"]"###);
+    assert_eq!(candidates.len(), 3);
+}
+
+#[test]
+fn test_deleted_documents_sort() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello");
+    s.sort_criteria(vec![AscDesc::Asc(Member::Field(S("price")))]);
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    let ids = collect_field_values(&index, &txn, "id", &documents_ids);
+    insta::assert_snapshot!(format!("{ids:?}"), @r###"["4", "2", "0"]"###);
+}
+
+#[test]
+fn test_deleted_documents_geo_sort() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.sort_criteria(vec![AscDesc::Asc(Member::Geo([0., 0.]))]);
+    for strategy in [GeoSortStrategy::AlwaysIterative(2), GeoSortStrategy::AlwaysRtree(2)] {
+        s.geo_sort_strategy(strategy);
+        let SearchResult { documents_ids, candidates, .. } = s.execute().unwrap();
+        let ids = collect_field_values(&index, &txn, "id", &documents_ids);
+        insta::assert_snapshot!(format!("{ids:?}"), @r###"["4", "2", "0"]"###);
+        assert_eq!(candidates.len(), 3);
+    }
+}
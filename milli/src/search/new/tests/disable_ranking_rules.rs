@@ -0,0 +1,76 @@
+/*!
+This module tests the `disable_ranking_rules` search option:
+
+1. the disabled ranking rules don't change the candidates, only their order
+2. the other ranking rules, including the custom ones, keep their order
+3. the ranking rules are disabled for a single search, not for the index
+*/
+
+use big_s::S;
+use maplit::hashset;
+
+use crate::index::tests::TempIndex;
+use crate::{Criterion, RankingRuleKind, Search, SearchResult};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_sortable_fields(hashset! { S("rank") });
+            s.set_criteria(vec![
+                Criterion::Words,
+                Criterion::Proximity,
+                Criterion::Asc("rank".to_owned()),
+            ]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "hello there big world", "rank": 2 },
+            { "id": 1, "text": "hello world", "rank": 3 },
+            { "id": 2, "text": "hello world again", "rank": 1 },
+            { "id": 3, "text": "hello", "rank": 0 },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_disable_proximity() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello world");
+    let SearchResult { documents_ids, candidates, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[2, 1, 0, 3]");
+    let all_candidates = candidates;
+
+    s.disable_ranking_rules(&[RankingRuleKind::Proximity]);
+    let SearchResult { documents_ids, candidates, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[2, 0, 1, 3]");
+    assert_eq!(candidates, all_candidates);
+
+    // the next searches still use all the ranking rules of the index
+    let mut s = Search::new(&txn, &index);
+    s.query("hello world");
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[2, 1, 0, 3]");
+}
+
+#[test]
+fn test_disable_words_and_proximity() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello world");
+    s.disable_ranking_rules(&[RankingRuleKind::Words, RankingRuleKind::Proximity]);
+    let SearchResult { documents_ids, candidates, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[3, 2, 0, 1]");
+    assert_eq!(candidates.len(), 4);
+}
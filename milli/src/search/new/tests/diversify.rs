@@ -0,0 +1,114 @@
+/*!
+This module tests the `diversify_by` search option:
+
+1. at most `max_per_group` documents sharing the same brand are returned before the other documents
+2. the documents exceeding the limit of their brand are pushed down, in ranking order
+3. the documents without a brand are never collapsed
+4. the pagination is applied after the diversification
+5. the documents are grouped by the normalized facet value of the field
+*/
+
+use big_s::S;
+use maplit::hashset;
+
+use crate::index::tests::TempIndex;
+use crate::search::new::tests::collect_field_values;
+use crate::{Criterion, Search, SearchResult, TermsMatchingStrategy};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["title".to_owned()]);
+            s.set_filterable_fields(hashset! { S("brand"), S("size") });
+            s.set_criteria(vec![Criterion::Words, Criterion::Exactness]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "title": "red running shoes", "brand": "acme", "size": 42 },
+            { "id": 1, "title": "red running shoes light", "brand": "Acme", "size": 42.0 },
+            { "id": 2, "title": "red running shoes wide", "brand": "ACME" },
+            { "id": 3, "title": "red running shoes trail", "brand": "acme" },
+            { "id": 4, "title": "the red running shoes", "brand": "globex" },
+            { "id": 5, "title": "running shoes in red" },
+            { "id": 6, "title": "running shoes red edition" },
+            { "id": 7, "title": "running red", "brand": "initech" },
+        ]))
+        .unwrap();
+    index
+}
+
+fn search(
+    index: &TempIndex,
+    diversify: Option<(&str, usize)>,
+    offset: usize,
+    limit: usize,
+) -> Vec<String> {
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, index);
+    s.terms_matching_strategy(TermsMatchingStrategy::Last);
+    s.query("red running shoes");
+    s.offset(offset);
+    s.limit(limit);
+    if let Some((field, max_per_group)) = diversify {
+        s.diversify_by(field.to_owned(), max_per_group);
+    }
+    let SearchResult { documents_ids, document_scores, .. } = s.execute().unwrap();
+    assert_eq!(documents_ids.len(), document_scores.len());
+    collect_field_values(index, &txn, "id", &documents_ids)
+}
+
+#[test]
+fn test_diversify_by_brand() {
+    let index = create_index();
+
+    assert_eq!(search(&index, None, 0, 8), vec!["0", "1", "2", "3", "4", "5", "6", "7"]);
+    assert_eq!(
+        search(&index, Some(("brand", 2)), 0, 8),
+        vec!["0", "1", "4", "5", "6", "7", "2", "3"]
+    );
+    assert_eq!(
+        search(&index, Some(("brand", 1)), 0, 8),
+        vec!["0", "4", "5", "6", "7", "1", "2", "3"]
+    );
+}
+
+#[test]
+fn test_diversify_by_brand_paginated() {
+    let index = create_index();
+
+    assert_eq!(search(&index, Some(("brand", 1)), 0, 3), vec!["0", "4", "5"]);
+    assert_eq!(search(&index, Some(("brand", 1)), 3, 3), vec!["6", "7", "1"]);
+    assert_eq!(search(&index, Some(("brand", 1)), 6, 3), vec!["2", "3"]);
+}
+
+#[test]
+fn test_diversify_by_normalized_number() {
+    let index = create_index();
+
+    // `42` and `42.0` are the same facet value
+    assert_eq!(
+        search(&index, Some(("size", 1)), 0, 8),
+        vec!["0", "2", "3", "4", "5", "6", "7", "1"]
+    );
+}
+
+#[test]
+fn test_diversify_by_unknown_field() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("red running shoes");
+    s.diversify_by("color".to_owned(), 1);
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    assert_eq!(
+        collect_field_values(&index, &txn, "id", &documents_ids),
+        vec!["0", "1", "2", "3", "4", "5", "6", "7"]
+    );
+}
@@ -0,0 +1,72 @@
+/*!
+This module tests the merging of the words repeated in a query:
+
+1. a repeated word is merged with its first occurrence, the universe and the ranking are
+the ones of the query without the repetition
+2. `keep_duplicate_tokens` keeps the repeated words as distinct terms
+3. the words of a phrase are not merged
+*/
+
+use crate::index::tests::TempIndex;
+use crate::score_details::ScoringStrategy;
+use crate::{Criterion, Search, SearchResult, TermsMatchingStrategy};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_criteria(vec![Criterion::Words, Criterion::Proximity]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "red shoes" },
+            { "id": 1, "text": "red leather shoes" },
+            { "id": 2, "text": "shoes that are red" },
+            { "id": 3, "text": "red red shoes" },
+            { "id": 4, "text": "blue shoes" },
+            { "id": 5, "text": "a red hat" },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_duplicate_tokens_are_merged() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.scoring_strategy(ScoringStrategy::Detailed);
+
+    s.query("red shoes");
+    let expected = s.execute().unwrap();
+
+    s.query("red red shoes");
+    let SearchResult { documents_ids, document_scores, candidates, .. } = s.execute().unwrap();
+    assert_eq!(candidates, expected.candidates);
+    assert_eq!(documents_ids, expected.documents_ids);
+    assert_eq!(document_scores, expected.document_scores);
+
+    // the repeated word is a term of its own, matched by the same documents
+    s.keep_duplicate_tokens(true);
+    let SearchResult { document_scores, candidates, .. } = s.execute().unwrap();
+    assert_eq!(candidates, expected.candidates);
+    assert_ne!(document_scores, expected.document_scores);
+}
+
+#[test]
+fn test_duplicate_tokens_in_phrase() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.terms_matching_strategy(TermsMatchingStrategy::All);
+    s.query("\"red red\" shoes");
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[3]");
+}
@@ -0,0 +1,50 @@
+/*!
+This module tests the `effective_query_terms` method of the search:
+
+1. the words are returned normalized, in the order of the query
+2. the stop words are removed, even inside phrases
+3. the phrases are returned between double quotes
+4. a stop word is kept when it is the last word of the query, as it is searched as a prefix
+*/
+
+use std::collections::BTreeSet;
+
+use crate::index::tests::TempIndex;
+use crate::Search;
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_stop_words(BTreeSet::from_iter(["the".to_owned(), "a".to_owned()]));
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "the quick brown fox jumps over the lazy dog" },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_effective_query_terms() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("the Quick BROWN \"over the dog\" fox");
+    let terms = s.effective_query_terms().unwrap();
+    insta::assert_snapshot!(format!("{terms:?}"), @r###"["quick", "brown", "\"over dog\"", "fox"]"###);
+
+    s.query("quick fox a");
+    let terms = s.effective_query_terms().unwrap();
+    insta::assert_snapshot!(format!("{terms:?}"), @r###"["quick", "fox", "a"]"###);
+
+    let s = Search::new(&txn, &index);
+    assert!(s.effective_query_terms().unwrap().is_empty());
+}
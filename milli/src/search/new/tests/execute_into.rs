@@ -0,0 +1,98 @@
+/*!
+This module tests the `execute_into` search method:
+
+1. the documents inserted in the bitmap are the documents returned by `execute`
+2. when the page contains all the candidates, they are inserted without being ranked
+3. the offset, the limit and the distinct attribute are applied like in `execute`
+*/
+
+use big_s::S;
+use maplit::hashset;
+use roaring::RoaringBitmap;
+
+use crate::index::tests::TempIndex;
+use crate::{Criterion, Search, SearchResult, TermsMatchingStrategy};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_filterable_fields(hashset! { S("group") });
+            s.set_criteria(vec![Criterion::Words, Criterion::Proximity]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "hello there big world", "group": 0 },
+            { "id": 1, "text": "hello world", "group": 0 },
+            { "id": 2, "text": "hello world again", "group": 1 },
+            { "id": 3, "text": "hello", "group": 1 },
+            { "id": 4, "text": "goodbye", "group": 2 },
+            { "id": 5, "text": "world", "group": 2 },
+        ]))
+        .unwrap();
+    index
+}
+
+#[track_caller]
+fn assert_same_set(search: &Search<'_>) -> RoaringBitmap {
+    let SearchResult { documents_ids, .. } = search.execute().unwrap();
+    let mut out = RoaringBitmap::new();
+    search.execute_into(&mut out).unwrap();
+    assert_eq!(out, documents_ids.into_iter().collect::<RoaringBitmap>());
+    out
+}
+
+#[test]
+fn test_execute_into_all_candidates() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.limit(100);
+    let out = assert_same_set(&s);
+    insta::assert_snapshot!(format!("{out:?}"), @"RoaringBitmap<[0, 1, 2, 3, 4, 5]>");
+
+    s.query("hello world");
+    s.terms_matching_strategy(TermsMatchingStrategy::Last);
+    let out = assert_same_set(&s);
+    insta::assert_snapshot!(format!("{out:?}"), @"RoaringBitmap<[0, 1, 2, 3]>");
+}
+
+#[test]
+fn test_execute_into_page() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello world");
+    s.terms_matching_strategy(TermsMatchingStrategy::Last);
+    s.limit(2);
+    let out = assert_same_set(&s);
+    insta::assert_snapshot!(format!("{out:?}"), @"RoaringBitmap<[1, 2]>");
+
+    s.offset(2);
+    let out = assert_same_set(&s);
+    insta::assert_snapshot!(format!("{out:?}"), @"RoaringBitmap<[0, 3]>");
+}
+
+#[test]
+fn test_execute_into_distinct() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.limit(100);
+    s.distinct(S("group"));
+    let out = assert_same_set(&s);
+    insta::assert_snapshot!(format!("{out:?}"), @"RoaringBitmap<[0, 2, 4]>");
+
+    // the documents are accumulated in the bitmap
+    let mut out = RoaringBitmap::from_iter([42]);
+    s.execute_into(&mut out).unwrap();
+    insta::assert_snapshot!(format!("{out:?}"), @"RoaringBitmap<[0, 2, 4, 42]>");
+}
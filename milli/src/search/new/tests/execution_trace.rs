@@ -0,0 +1,103 @@
+/*!
+This module tests the `execution_trace` search option:
+
+1. the trace is only returned when requested
+2. each ranking rule given a universe adds a step with the number of buckets it returned
+3. a ranking rule given a bucket of a single document or a bucket before the requested page
+is recorded as skipped
+*/
+
+use crate::index::tests::TempIndex;
+use crate::score_details::ScoringStrategy;
+use crate::{Criterion, RuleStep, Search, SearchResult};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_criteria(vec![Criterion::Words, Criterion::Proximity]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "hello world" },
+            { "id": 1, "text": "hello you" },
+            { "id": 2, "text": "hello" },
+        ]))
+        .unwrap();
+    index
+}
+
+fn describe(trace: Option<Vec<RuleStep>>) -> Vec<String> {
+    trace
+        .unwrap()
+        .into_iter()
+        .map(|step| {
+            let RuleStep { ranking_rule, ranking_rule_id, skipped, buckets } = step;
+            if skipped {
+                format!("{ranking_rule} {ranking_rule_id}: skipped")
+            } else {
+                format!("{ranking_rule} {ranking_rule_id}: {buckets}")
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn test_execution_trace() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello world");
+    let SearchResult { execution_trace, .. } = s.execute().unwrap();
+    assert!(execution_trace.is_none());
+
+    s.execution_trace(true);
+    s.scoring_strategy(ScoringStrategy::Detailed);
+    let SearchResult { documents_ids, execution_trace, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0, 1, 2]");
+    insta::assert_debug_snapshot!(describe(execution_trace), @r###"
+    [
+        "0 words: 2",
+        "1 proximity: 1",
+        "1 proximity: 1",
+    ]
+    "###);
+}
+
+#[test]
+fn test_execution_trace_skipped_rules() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    // the bucket of the single document matching both words is not sorted by proximity
+    let mut s = Search::new(&txn, &index);
+    s.query("hello world");
+    s.execution_trace(true);
+    let SearchResult { execution_trace, .. } = s.execute().unwrap();
+    insta::assert_debug_snapshot!(describe(execution_trace), @r###"
+    [
+        "0 words: 2",
+        "1 proximity: skipped",
+        "1 proximity: 1",
+    ]
+    "###);
+
+    // the first bucket of the words ranking rule is before the requested page
+    s.scoring_strategy(ScoringStrategy::Detailed);
+    s.offset(2);
+    let SearchResult { documents_ids, execution_trace, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[2]");
+    insta::assert_debug_snapshot!(describe(execution_trace), @r###"
+    [
+        "0 words: 2",
+        "1 proximity: skipped",
+        "1 proximity: 1",
+    ]
+    "###);
+}
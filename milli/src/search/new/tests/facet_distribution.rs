@@ -0,0 +1,202 @@
+/*!
+This module tests the `Search::facet_distribution` and `Search::facet_distribution_with_cache`
+methods:
+
+1. the counts are computed against the search universe, not the whole index
+2. values that do not appear in the universe are not returned
+3. an error is returned if one of the fields is not filterable
+4. the facet values read by a call are kept in the cache and not read again by the next ones
+5. the numbers and the strings are counted separately, even when they have the same text form
+6. at most `max_values_per_facet` values are returned by field
+7. `facet_distribution` counts all the values exactly, keying the numbers by their text form
+*/
+
+use std::collections::HashMap;
+
+use big_s::S;
+use maplit::{hashmap, hashset};
+
+use crate::index::tests::TempIndex;
+use crate::{Criterion, FacetCounts, Filter, Search, SharedDatabaseCache};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_filterable_fields(hashset! { S("colour"), S("size") });
+            s.set_criteria(vec![Criterion::Words]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "the quick brown fox", "colour": "Blue", "size": 1 },
+            { "id": 1, "text": "the quick red fox", "colour": "Red", "size": 2 },
+            { "id": 2, "text": "the lazy dog", "colour": ["Blue", "Red"], "size": 1 },
+            { "id": 3, "text": "the quick dog", "colour": "Green", "size": 3 },
+            { "id": 4, "text": "a lazy fox", "colour": "Blue" },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_facet_distribution_placeholder() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let s = Search::new(&txn, &index);
+    let distribution = s
+        .facet_distribution_with_cache(
+            &[S("colour"), S("size")],
+            &mut SharedDatabaseCache::default(),
+        )
+        .unwrap();
+
+    let expected: HashMap<String, FacetCounts> = hashmap! {
+        S("colour") => FacetCounts {
+            strings: hashmap! { S("Blue") => 3, S("Red") => 2, S("Green") => 1 },
+            ..FacetCounts::default()
+        },
+        S("size") => FacetCounts {
+            numbers: vec![(1.0, 2), (2.0, 1), (3.0, 1)],
+            ..FacetCounts::default()
+        },
+    };
+    assert_eq!(distribution, expected);
+}
+
+#[test]
+fn test_facet_distribution_restricted_to_universe() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("fox");
+    let distribution = s
+        .facet_distribution_with_cache(&[S("colour")], &mut SharedDatabaseCache::default())
+        .unwrap();
+    assert_eq!(distribution[&S("colour")].strings, hashmap! { S("Blue") => 2, S("Red") => 1 });
+
+    let mut s = Search::new(&txn, &index);
+    s.filter(Filter::from_str("size = 1").unwrap().unwrap());
+    let distribution = s
+        .facet_distribution_with_cache(
+            &[S("colour"), S("size")],
+            &mut SharedDatabaseCache::default(),
+        )
+        .unwrap();
+    assert_eq!(distribution[&S("colour")].strings, hashmap! { S("Blue") => 2, S("Red") => 1 });
+    assert_eq!(distribution[&S("size")].numbers, vec![(1.0, 2)]);
+}
+
+#[test]
+fn test_facet_distribution_invalid_field() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let s = Search::new(&txn, &index);
+    let error = s
+        .facet_distribution_with_cache(&[S("text")], &mut SharedDatabaseCache::default())
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        crate::Error::UserError(crate::UserError::InvalidFacetsDistribution { .. })
+    ));
+}
+
+#[test]
+fn test_facet_distribution_shared_cache() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+    let mut cache = SharedDatabaseCache::default();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("fox");
+    let first = s.facet_distribution_with_cache(&[S("colour"), S("size")], &mut cache).unwrap();
+    let cached_values = cache.db_cache.cached_values();
+    assert_ne!(cached_values, 0);
+
+    let second = s.facet_distribution_with_cache(&[S("colour"), S("size")], &mut cache).unwrap();
+    assert_eq!(first, second);
+    // the words and facet values were all read by the first call
+    assert_eq!(cache.db_cache.cached_values(), cached_values);
+}
+
+#[test]
+fn test_facet_distribution_numbers_and_strings() {
+    let index = create_index();
+    index.add_documents(documents!([{ "id": 5, "text": "the end", "size": "1" }])).unwrap();
+    let txn = index.read_txn().unwrap();
+
+    let s = Search::new(&txn, &index);
+    let distribution =
+        s.facet_distribution_with_cache(&[S("size")], &mut SharedDatabaseCache::default()).unwrap();
+    let expected = FacetCounts {
+        numbers: vec![(1.0, 2), (2.0, 1), (3.0, 1)],
+        strings: hashmap! { S("1") => 1 },
+        truncated: false,
+    };
+    assert_eq!(distribution[&S("size")], expected);
+}
+
+#[test]
+fn test_facet_distribution_max_values_per_facet() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.max_values_per_facet(2);
+    let distribution = s
+        .facet_distribution_with_cache(
+            &[S("colour"), S("size")],
+            &mut SharedDatabaseCache::default(),
+        )
+        .unwrap();
+    let colour = &distribution[&S("colour")];
+    assert_eq!(colour.strings.len(), 2);
+    assert!(colour.truncated);
+    let expected = FacetCounts {
+        numbers: vec![(1.0, 2), (2.0, 1)],
+        truncated: true,
+        ..FacetCounts::default()
+    };
+    assert_eq!(distribution[&S("size")], expected);
+
+    s.query("quick");
+    let distribution =
+        s.facet_distribution_with_cache(&[S("size")], &mut SharedDatabaseCache::default()).unwrap();
+    // the values of the other documents don't count toward the limit
+    let expected = FacetCounts {
+        numbers: vec![(1.0, 1), (2.0, 1)],
+        truncated: true,
+        ..FacetCounts::default()
+    };
+    assert_eq!(distribution[&S("size")], expected);
+}
+
+#[test]
+fn test_facet_distribution_exact_counts() {
+    let index = create_index();
+    index.add_documents(documents!([{ "id": 5, "text": "the end", "size": ["1", 1] }])).unwrap();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    // only the variant reading through the cache of the caller is truncated
+    s.max_values_per_facet(1);
+    let distribution = s.facet_distribution(&[S("colour"), S("size")]).unwrap();
+
+    let expected: HashMap<String, HashMap<String, u64>> = hashmap! {
+        S("colour") => hashmap! { S("Blue") => 3, S("Red") => 2, S("Green") => 1 },
+        // the document with both the number and the string is counted once
+        S("size") => hashmap! { S("1") => 3, S("2") => 1, S("3") => 1 },
+    };
+    assert_eq!(distribution, expected);
+
+    s.query("quick");
+    let distribution = s.facet_distribution(&[S("size")]).unwrap();
+    assert_eq!(distribution[&S("size")], hashmap! { S("1") => 1, S("2") => 1, S("3") => 1 });
+}
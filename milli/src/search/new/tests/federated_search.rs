@@ -0,0 +1,63 @@
+/*!
+This module tests the `FederatedSearch` over several indexes:
+
+1. the results of the indexes are merged by decreasing ranking score
+2. the documents with the same score are ordered by index
+3. every index ranks its documents with its own ranking rules
+4. the pagination applies to the merged results
+*/
+
+use crate::index::tests::TempIndex;
+use crate::search::new::tests::collect_field_values;
+use crate::{Criterion, FederatedSearch, IndexTag};
+
+fn create_index(criteria: Vec<Criterion>, texts: &[(&str, &str)]) -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_criteria(criteria);
+        })
+        .unwrap();
+
+    let documents: Vec<_> =
+        texts.iter().map(|(id, text)| serde_json::json!({ "id": id, "text": text })).collect();
+    index.add_documents(documents!(documents)).unwrap();
+    index
+}
+
+#[test]
+fn test_federated_search() {
+    let first =
+        create_index(vec![Criterion::Words, Criterion::Typo], &[("a0", "quack"), ("a1", "quick")]);
+    let second = create_index(vec![Criterion::Typo], &[("b0", "quicc"), ("b1", "quick")]);
+    let first_txn = first.read_txn().unwrap();
+    let second_txn = second.read_txn().unwrap();
+
+    let mut search = FederatedSearch::new();
+    search.query("quick");
+    let first_tag = search.add_index(&first, &first_txn);
+    let second_tag = search.add_index(&second, &second_txn);
+    assert_eq!((first_tag, second_tag), (IndexTag(0), IndexTag(1)));
+
+    let results = search.execute().unwrap();
+    let external_id = |(tag, docid, _): &(IndexTag, u32, f64)| {
+        let (index, txn) =
+            if *tag == first_tag { (&first, &first_txn) } else { (&second, &second_txn) };
+        collect_field_values(index, txn, "id", &[*docid]).remove(0)
+    };
+
+    let ids: Vec<_> = results.iter().map(external_id).collect();
+    assert_eq!(ids.len(), 4);
+    // the exact matches come first, in the order of their index
+    assert_eq!(ids[..2], ["a1", "b1"]);
+    assert_eq!(results[0].2, 1.0);
+    assert_eq!(results[1].2, 1.0);
+    assert!(results.windows(2).all(|pair| pair[0].2 >= pair[1].2));
+
+    search.offset(1).limit(2);
+    let page: Vec<_> = search.execute().unwrap().iter().map(external_id).collect();
+    assert_eq!(page, ids[1..3]);
+}
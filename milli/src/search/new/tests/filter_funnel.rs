@@ -0,0 +1,68 @@
+/*!
+This module tests the `filter_funnel` search option:
+
+1. the number of documents remaining after each clause of the filter is reported, in order
+2. a filter made of a single clause reports the documents matching it
+3. nothing is reported when the option is disabled
+*/
+
+use big_s::S;
+use maplit::hashset;
+
+use crate::index::tests::TempIndex;
+use crate::{Filter, Search, SearchResult};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_filterable_fields(hashset! { S("price"), S("color") });
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "price": 10, "color": "red" },
+            { "id": 1, "price": 20, "color": "blue" },
+            { "id": 2, "price": 30, "color": "red" },
+            { "id": 3, "price": 40, "color": "green" },
+            { "id": 4, "price": 50, "color": "red" },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_filter_funnel() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.filter(Filter::from_str("price < 45 AND color = red AND price > 15").unwrap().unwrap());
+    let SearchResult { documents_ids, filter_funnel, .. } = s.execute().unwrap();
+    assert_eq!(documents_ids, vec![2]);
+    assert_eq!(filter_funnel, None);
+
+    s.filter_funnel(true);
+    let SearchResult { documents_ids, filter_funnel, .. } = s.execute().unwrap();
+    assert_eq!(documents_ids, vec![2]);
+    assert_eq!(
+        filter_funnel,
+        Some(vec![(S("{price} < {45}"), 4), (S("{color} = {red}"), 2), (S("{price} > {15}"), 1),])
+    );
+}
+
+#[test]
+fn test_filter_funnel_single_clause() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.filter(Filter::from_str("color = red OR color = green").unwrap().unwrap());
+    s.filter_funnel(true);
+    let SearchResult { documents_ids, filter_funnel, .. } = s.execute().unwrap();
+    assert_eq!(documents_ids, vec![0, 2, 3, 4]);
+    assert_eq!(filter_funnel, Some(vec![(S("OR[{color} = {red}, {color} = {green}, ]"), 4)]));
+}
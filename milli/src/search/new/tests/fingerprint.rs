@@ -0,0 +1,77 @@
+/*!
+This module tests the fingerprint of the search results:
+
+1. it lists the primary keys of the returned documents, in order
+2. it optionally lists the ranks of the documents for every ranking rule
+3. it is identical across executions of the same search
+*/
+
+use crate::index::tests::TempIndex;
+use crate::score_details::ScoringStrategy;
+use crate::{Criterion, Search};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_criteria(vec![Criterion::Words, Criterion::Typo]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": "b", "text": "the quick brown fox" },
+            { "id": "a", "text": "the quick brown" },
+            { "id": "c", "text": "the quikc brown fox" },
+            { "id": "d", "text": "the lazy dog" },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_fingerprint() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("quick brown fox");
+    s.scoring_strategy(ScoringStrategy::Detailed);
+    let result = s.execute().unwrap();
+
+    insta::assert_snapshot!(result.fingerprint(&index, &txn, false).unwrap(), @r###"
+    b
+    c
+    a
+    "###);
+    insta::assert_snapshot!(result.fingerprint(&index, &txn, true).unwrap(), @r###"
+    b 3/3 3/3
+    c 3/3 2/3
+    a 2/3 3/3
+    "###);
+
+    let again = s.execute().unwrap();
+    assert_eq!(
+        result.fingerprint(&index, &txn, true).unwrap(),
+        again.fingerprint(&index, &txn, true).unwrap()
+    );
+}
+
+#[test]
+fn test_fingerprint_without_scores() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("quick brown fox");
+    let result = s.execute().unwrap();
+
+    // the ranks are not computed without a detailed scoring strategy
+    assert_eq!(
+        result.fingerprint(&index, &txn, true).unwrap(),
+        result.fingerprint(&index, &txn, false).unwrap()
+    );
+}
@@ -9,7 +9,10 @@ use maplit::hashset;
 use crate::index::tests::TempIndex;
 use crate::score_details::ScoreDetails;
 use crate::search::new::tests::collect_field_values;
-use crate::{AscDesc, Criterion, GeoSortStrategy, Member, Search, SearchResult};
+use crate::{
+    AscDesc, Criterion, Error, GeoDecay, GeoSortStrategy, Index, Member, Search, SearchResult,
+    UserError,
+};
 
 fn create_index() -> TempIndex {
     let index = TempIndex::new();
@@ -171,6 +174,63 @@ fn test_geo_sort_around_the_edge_of_the_flat_earth() {
     insta::assert_snapshot!(format!("{scores:#?}"));
 }
 
+#[test]
+fn test_geo_sort_rtree_concurrently() {
+    let index = create_index();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "_geo": { "lat": 0, "lng": 0 } },
+            { "id": 1, "_geo": { "lat": 88, "lng": 0 } },
+            { "id": 2, "_geo": { "lat": -89, "lng": 0 } },
+
+            { "id": 3, "_geo": { "lat": 0, "lng": 178 } },
+            { "id": 4, "_geo": { "lat": 0, "lng": -179 } },
+        ]))
+        .unwrap();
+
+    // the queries of `test_geo_sort_around_the_edge_of_the_flat_earth`
+    let mut sorts = Vec::new();
+    for point in [[0., 0.], [85., 0.], [-85., 0.], [0., 175.], [0., -175.]] {
+        sorts.push(AscDesc::Asc(Member::Geo(point)));
+        sorts.push(AscDesc::Desc(Member::Geo(point)));
+    }
+    let execute = |index: &Index, sort: &AscDesc| {
+        let rtxn = index.read_txn().unwrap();
+        let mut s = Search::new(&rtxn, index);
+        s.scoring_strategy(crate::score_details::ScoringStrategy::Detailed);
+        s.geo_sort_strategy(GeoSortStrategy::AlwaysRtree(2));
+        s.sort_criteria(vec![sort.clone()]);
+        let SearchResult { documents_ids, document_scores, .. } = s.execute().unwrap();
+        (documents_ids, document_scores)
+    };
+
+    let index: &Index = &index;
+    let expected: Vec<_> = sorts.iter().map(|sort| execute(index, sort)).collect();
+    insta::assert_snapshot!(format!("{:?}", expected[0].0), @"[0, 1, 2, 3, 4]");
+
+    // each thread reads the rtree with its own transaction, in a different order
+    std::thread::scope(|scope| {
+        let threads: Vec<_> = (0..4)
+            .map(|shift| {
+                let sorts = &sorts;
+                scope.spawn(move || {
+                    let mut results = vec![None; sorts.len()];
+                    for _ in 0..10 {
+                        for i in (0..sorts.len()).map(|i| (i + shift * 3) % sorts.len()) {
+                            results[i] = Some(execute(index, &sorts[i]));
+                        }
+                    }
+                    results.into_iter().map(Option::unwrap).collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        for thread in threads {
+            assert_eq!(thread.join().unwrap(), expected);
+        }
+    });
+}
+
 #[test]
 fn geo_sort_mixed_with_words() {
     let index = create_index();
@@ -214,6 +274,7 @@ fn geo_sort_mixed_with_words() {
                         0.0,
                         0.0,
                     ],
+                    target_shape: None,
                     ascending: true,
                     value: Some(
                         [
@@ -221,6 +282,8 @@ fn geo_sort_mixed_with_words() {
                             0.0,
                         ],
                     ),
+                    target_altitude: None,
+                    altitude: None,
                 },
             ),
         ],
@@ -237,6 +300,7 @@ fn geo_sort_mixed_with_words() {
                         0.0,
                         0.0,
                     ],
+                    target_shape: None,
                     ascending: true,
                     value: Some(
                         [
@@ -244,6 +308,8 @@ fn geo_sort_mixed_with_words() {
                             -179.0,
                         ],
                     ),
+                    target_altitude: None,
+                    altitude: None,
                 },
             ),
         ],
@@ -268,6 +334,7 @@ fn geo_sort_mixed_with_words() {
                         0.0,
                         0.0,
                     ],
+                    target_shape: None,
                     ascending: true,
                     value: Some(
                         [
@@ -275,6 +342,8 @@ fn geo_sort_mixed_with_words() {
                             0.0,
                         ],
                     ),
+                    target_altitude: None,
+                    altitude: None,
                 },
             ),
         ],
@@ -307,3 +376,508 @@ fn geo_sort_without_any_geo_faceted_documents() {
     insta::assert_snapshot!(format!("{ids:?}"), @"[0, 2, 3]");
     insta::assert_snapshot!(format!("{scores:#?}"));
 }
+
+#[test]
+fn geo_sort_rejects_multiple_geo_points() {
+    let index = create_index();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "_geo": { "lat": 0, "lng": 0 } },
+            { "id": 1, "_geo": { "lat": 1, "lng": 1 } },
+        ]))
+        .unwrap();
+
+    let rtxn = index.read_txn().unwrap();
+
+    let invalid_criteria = [
+        // the same center in both orders
+        vec![AscDesc::Asc(Member::Geo([0., 0.])), AscDesc::Desc(Member::Geo([0., 0.]))],
+        // two different centers
+        vec![AscDesc::Asc(Member::Geo([0., 0.])), AscDesc::Asc(Member::Geo([1., 1.]))],
+        // the same geo sort twice
+        vec![AscDesc::Asc(Member::Geo([0., 0.])), AscDesc::Asc(Member::Geo([0., 0.]))],
+    ];
+
+    for criteria in invalid_criteria {
+        let mut s = Search::new(&rtxn, &index);
+        s.sort_criteria(criteria);
+        let error = s.execute().unwrap_err();
+        assert!(
+            matches!(
+                &error,
+                crate::Error::UserError(crate::UserError::MultipleGeoSort(geo_sorts)) if geo_sorts.len() >= 2
+            ),
+            "{error}"
+        );
+    }
+
+    insta::assert_snapshot!(
+        crate::UserError::MultipleGeoSort(vec![S("_geoPoint(0, 0):asc"), S("_geoPoint(1, 1):desc")]),
+        @"Only one `_geoPoint` can be used in the sort parameter, but found `_geoPoint(0, 0):asc`, `_geoPoint(1, 1):desc`."
+    );
+}
+
+#[test]
+fn geo_sort_rejects_non_sortable_geo() {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_sortable_fields(hashset! { S("id") });
+            s.set_criteria(vec![Criterion::Words, Criterion::Sort]);
+        })
+        .unwrap();
+    index.add_documents(documents!([{ "id": 0, "_geo": { "lat": 0, "lng": 0 } }])).unwrap();
+
+    let rtxn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&rtxn, &index);
+    s.sort_criteria(vec![AscDesc::Asc(Member::Geo([0., 0.]))]);
+    let error = s.execute().unwrap_err();
+    assert!(matches!(
+        error,
+        crate::Error::UserError(crate::UserError::InvalidSortableAttribute { ref field, .. }) if field == "_geo"
+    ));
+}
+
+#[test]
+fn test_geo_sort_parallelism() {
+    let index = create_index();
+
+    // a grid of points, many of them at the same distance from the sort point
+    let documents: Vec<_> = (0..400)
+        .map(|id| {
+            let lat = (id / 20) as f64 - 10.;
+            let lng = (id % 20) as f64 - 10.;
+            serde_json::json!({ "id": id, "_geo": { "lat": lat, "lng": lng } })
+        })
+        .collect();
+    index.add_documents(documents!(documents)).unwrap();
+
+    let rtxn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&rtxn, &index);
+    s.scoring_strategy(crate::score_details::ScoringStrategy::Detailed);
+    s.limit(400);
+
+    for sort in [AscDesc::Asc(Member::Geo([0., 0.])), AscDesc::Desc(Member::Geo([3., -4.]))] {
+        s.sort_criteria(vec![sort]);
+
+        s.parallelism(1);
+        let (serial_ids, serial_scores) =
+            execute_iterative_and_rtree_returns_the_same(&rtxn, &index, &mut s);
+
+        s.parallelism(4);
+        let (parallel_ids, parallel_scores) =
+            execute_iterative_and_rtree_returns_the_same(&rtxn, &index, &mut s);
+
+        assert_eq!(serial_ids, parallel_ids);
+        assert_eq!(serial_scores, parallel_scores);
+    }
+}
+
+#[test]
+fn test_geo_sort_with_altitude() {
+    let index = create_index();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "_geo": { "lat": 0, "lng": 0, "alt": 1_000_000 } },
+            { "id": 1, "_geo": { "lat": 1, "lng": 0 } },
+            { "id": 2, "_geo": { "lat": 2, "lng": 0, "alt": 0 } },
+            { "id": 3, "_geo": { "lat": 3, "lng": 0 } },
+            { "id": 4, "_geo": { "lat": 0, "lng": 4, "alt": "500000" } },
+            { "id": 5, "_geo": { "lat": 5, "lng": 0, "alt": 0 } },
+            { "id": 6 },
+        ]))
+        .unwrap();
+
+    let rtxn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&rtxn, &index);
+    s.scoring_strategy(crate::score_details::ScoringStrategy::Detailed);
+
+    // without an altitude, the documents are sorted on the horizontal plane only
+    s.sort_criteria(vec![AscDesc::Asc(Member::Geo([0., 0.]))]);
+    let (ids, _scores) = execute_iterative_and_rtree_returns_the_same(&rtxn, &index, &mut s);
+    assert_eq!(ids, [0, 1, 2, 3, 4, 5, 6]);
+
+    // the documents without an altitude are sorted by their horizontal distance
+    s.sort_criteria(vec![AscDesc::Asc(Member::Geo3D([0., 0., 0.]))]);
+    let (ids, scores) = execute_iterative_and_rtree_returns_the_same(&rtxn, &index, &mut s);
+    assert_eq!(ids, [1, 2, 3, 5, 4, 0, 6]);
+    let distances: Vec<_> = scores
+        .iter()
+        .map(|scores| match scores.last() {
+            Some(ScoreDetails::GeoSort(geo_sort)) => geo_sort.distance(),
+            _ => panic!("expected a geo sort score"),
+        })
+        .collect();
+    assert_eq!(distances[0], Some(crate::distance_between_two_points(&[0., 0.], &[1., 0.])));
+    assert_eq!(distances[5], Some(1_000_000.));
+    assert_eq!(distances[6], None);
+
+    s.sort_criteria(vec![AscDesc::Desc(Member::Geo3D([0., 0., 0.]))]);
+    let (ids, _scores) = execute_iterative_and_rtree_returns_the_same(&rtxn, &index, &mut s);
+    assert_eq!(ids, [0, 4, 5, 3, 2, 1, 6]);
+}
+
+#[test]
+fn test_geo_sort_with_geojson_points() {
+    let index = create_index();
+
+    // the coordinates of a GeoJSON point are `[lng, lat]`
+    index
+        .add_documents(documents!([
+            { "id": 0, "_geo": { "lat": 0, "lng": 0 } },
+            { "id": 1, "_geo": { "type": "Point", "coordinates": [5, 1] } },
+            { "id": 2, "_geo": { "lat": 5, "lng": 1 } },
+            { "id": 3, "_geo": { "type": "Point", "coordinates": [4.0, 1.0, 0.0] } },
+            { "id": 4 },
+            // a GeoJSON point with invalid coordinates is indexed without coordinates
+            { "id": 5, "_geo": { "type": "Point", "coordinates": [42] } },
+        ]))
+        .unwrap();
+
+    let rtxn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&rtxn, &index);
+    s.scoring_strategy(crate::score_details::ScoringStrategy::Detailed);
+
+    s.sort_criteria(vec![AscDesc::Asc(Member::Geo([1., 5.]))]);
+    let (ids, _scores) = execute_iterative_and_rtree_returns_the_same(&rtxn, &index, &mut s);
+    insta::assert_snapshot!(format!("{ids:?}"), @"[1, 3, 0, 2, 4, 5]");
+
+    s.sort_criteria(vec![AscDesc::Desc(Member::Geo([1., 5.]))]);
+    let (ids, _scores) = execute_iterative_and_rtree_returns_the_same(&rtxn, &index, &mut s);
+    insta::assert_snapshot!(format!("{ids:?}"), @"[2, 0, 3, 1, 4, 5]");
+
+    let geo_faceted = index.geo_faceted_documents_ids(&rtxn).unwrap();
+    insta::assert_snapshot!(format!("{geo_faceted:?}"), @"RoaringBitmap<[0, 1, 2, 3]>");
+}
+
+#[test]
+fn test_geo_sort_with_custom_fields() {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_sortable_fields(hashset! { S("latitude"), S("longitude") });
+            s.set_criteria(vec![Criterion::Words, Criterion::Sort]);
+        })
+        .unwrap();
+    index
+        .add_documents(documents!([
+            { "id": 0, "latitude": 3, "longitude": 0 },
+            { "id": 1, "latitude": 1, "longitude": 0 },
+            { "id": 2, "latitude": 0, "longitude": -2 },
+            { "id": 3, "latitude": "unknown", "longitude": 0 },
+            { "id": 4 },
+        ]))
+        .unwrap();
+
+    let rtxn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&rtxn, &index);
+    s.geo_field(S("latitude"), S("longitude"));
+    s.sort_criteria(vec![AscDesc::Asc(Member::Geo([0., 0.]))]);
+    for strategy in [GeoSortStrategy::AlwaysIterative(2), GeoSortStrategy::Dynamic(1)] {
+        s.geo_sort_strategy(strategy);
+        let SearchResult { documents_ids, .. } = s.execute().unwrap();
+        let ids = collect_field_values(&index, &rtxn, "id", &documents_ids);
+        // the documents without a number in both fields are returned last
+        assert_eq!(ids, vec!["1", "2", "0", "3", "4"], "{strategy:?}");
+    }
+
+    s.sort_criteria(vec![AscDesc::Desc(Member::Geo([0., 0.]))]);
+    s.geo_sort_strategy(GeoSortStrategy::AlwaysIterative(2));
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    let ids = collect_field_values(&index, &rtxn, "id", &documents_ids);
+    assert_eq!(ids, vec!["0", "2", "1", "3", "4"]);
+
+    // the rtree is only built on `_geo`
+    s.geo_sort_strategy(GeoSortStrategy::AlwaysRtree(2));
+    let error = s.execute().unwrap_err();
+    assert!(matches!(
+        error,
+        crate::Error::UserError(crate::UserError::GeoRtreeOnCustomFields(ref lat, ref lng)) if lat == "latitude" && lng == "longitude"
+    ));
+
+    // the geo fields must be sortable
+    s.geo_sort_strategy(GeoSortStrategy::default());
+    s.geo_field(S("latitude"), S("lng"));
+    let error = s.execute().unwrap_err();
+    assert!(matches!(
+        error,
+        crate::Error::UserError(crate::UserError::InvalidSortableAttribute { ref field, .. }) if field == "lng"
+    ));
+}
+
+#[test]
+fn test_geo_sort_distance_quantum() {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_sortable_fields(hashset! { S("_geo"), S("rank") });
+            s.set_criteria(vec![Criterion::Words, Criterion::Sort]);
+        })
+        .unwrap();
+    index
+        .add_documents(documents!([
+            { "id": 0, "rank": 2, "_geo": { "lat": 0.001, "lng": 0 } },
+            { "id": 1, "rank": 1, "_geo": { "lat": 0.005, "lng": 0 } },
+            { "id": 2, "rank": 0, "_geo": { "lat": 0.02, "lng": 0 } },
+        ]))
+        .unwrap();
+
+    let rtxn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&rtxn, &index);
+    s.sort_criteria(vec![
+        AscDesc::Asc(Member::Geo([0., 0.])),
+        AscDesc::Asc(Member::Field(S("rank"))),
+    ]);
+    let (ids, _scores) = execute_iterative_and_rtree_returns_the_same(&rtxn, &index, &mut s);
+    insta::assert_snapshot!(format!("{ids:?}"), @"[0, 1, 2]");
+
+    // the documents 0 and 1 are both in the first kilometer, the rank breaks their tie
+    s.geo_distance_quantum(1000.);
+    let (ids, _scores) = execute_iterative_and_rtree_returns_the_same(&rtxn, &index, &mut s);
+    insta::assert_snapshot!(format!("{ids:?}"), @"[1, 0, 2]");
+
+    s.sort_criteria(vec![
+        AscDesc::Desc(Member::Geo([0., 0.])),
+        AscDesc::Asc(Member::Field(S("rank"))),
+    ]);
+    let (ids, _scores) = execute_iterative_and_rtree_returns_the_same(&rtxn, &index, &mut s);
+    insta::assert_snapshot!(format!("{ids:?}"), @"[2, 1, 0]");
+}
+
+#[test]
+fn test_geo_sort_route() {
+    let index = create_index();
+
+    // the route goes east along the equator, then north along the first meridian east of it
+    let route = vec![[0., 0.], [0., 1.], [1., 1.]];
+    index
+        .add_documents(documents!([
+            { "id": 3, "_geo": { "lat": 0.5, "lng": 0.8 } },
+            { "id": 1, "_geo": { "lat": 0.5, "lng": 1.01 } },
+            { "id": 4, "_geo": { "lat": 3, "lng": 3 } },
+            { "id": 0, "_geo": { "lat": 0.001, "lng": 0.5 } },
+            { "id": 2, "_geo": { "lat": -0.05, "lng": 0.2 } },
+            { "id": 5 },
+        ]))
+        .unwrap();
+
+    let rtxn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&rtxn, &index);
+    s.scoring_strategy(crate::score_details::ScoringStrategy::Detailed);
+
+    s.sort_criteria(vec![AscDesc::Asc(Member::GeoRoute(route.clone()))]);
+    let (ids, scores) = execute_iterative_and_rtree_returns_the_same(&rtxn, &index, &mut s);
+    assert_eq!(ids, [0, 1, 2, 3, 4, 5]);
+
+    // the distance is the one to the nearest point of the route
+    let Some(ScoreDetails::GeoSort(geo_sort)) = scores[0].last() else {
+        panic!("expected a geo sort score");
+    };
+    let expected = crate::distance_between_two_points(&[0., 0.5], &[0.001, 0.5]);
+    assert!((geo_sort.distance().unwrap() - expected).abs() < 1.);
+
+    // the details are keyed by the route, not by its point nearest to the document
+    let details = ScoreDetails::to_json_map(scores[0].iter());
+    let key = format!("{}:asc", Member::GeoRoute(route.clone()));
+    assert!(details.contains_key(&key), "{details:?}");
+
+    s.sort_criteria(vec![AscDesc::Desc(Member::GeoRoute(route))]);
+    let (ids, _scores) = execute_iterative_and_rtree_returns_the_same(&rtxn, &index, &mut s);
+    assert_eq!(ids, [4, 3, 2, 1, 0, 5]);
+}
+
+#[test]
+fn test_geo_sort_polygon_vertices() {
+    let index = create_index();
+
+    let triangle = vec![[0., 0.], [0., 10.], [10., 0.]];
+    index
+        .add_documents(documents!([
+            { "id": 2, "_geo": { "lat": 5, "lng": 5 } },
+            { "id": 4 },
+            { "id": 1, "_geo": { "lat": 0.5, "lng": 9.5 } },
+            { "id": 3, "_geo": { "lat": 9, "lng": 0.2 } },
+            { "id": 0, "_geo": { "lat": 0.1, "lng": 0.1 } },
+        ]))
+        .unwrap();
+
+    let rtxn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&rtxn, &index);
+    s.scoring_strategy(crate::score_details::ScoringStrategy::Detailed);
+
+    s.sort_criteria(vec![AscDesc::Asc(Member::GeoPolygonVertices(triangle.clone()))]);
+    let (ids, scores) = execute_iterative_and_rtree_returns_the_same(&rtxn, &index, &mut s);
+    assert_eq!(ids, [0, 1, 3, 2, 4]);
+
+    // the distance is the one to the nearest vertex of the polygon
+    let Some(ScoreDetails::GeoSort(geo_sort)) = scores[1].last() else {
+        panic!("expected a geo sort score");
+    };
+    let expected = crate::distance_between_two_points(&[0., 10.], &[0.5, 9.5]);
+    assert!((geo_sort.distance().unwrap() - expected).abs() < 1.);
+
+    let details = ScoreDetails::to_json_map(scores[1].iter());
+    let key = format!("{}:asc", Member::GeoPolygonVertices(triangle.clone()));
+    assert!(details.contains_key(&key), "{details:?}");
+
+    s.sort_criteria(vec![AscDesc::Desc(Member::GeoPolygonVertices(triangle))]);
+    let (ids, _scores) = execute_iterative_and_rtree_returns_the_same(&rtxn, &index, &mut s);
+    assert_eq!(ids, [2, 3, 1, 0, 4]);
+}
+
+#[test]
+fn test_geo_sort_polygon_vertices_around_the_antimeridian() {
+    let index = create_index();
+
+    // the rtree is searched around the vertices, across the antimeridian and up to the pole
+    let vertices = vec![[80., 179.9], [85., 179.99]];
+    index
+        .add_documents(documents!([
+            { "id": 1, "_geo": { "lat": 80, "lng": -179.9 } },
+            { "id": 3, "_geo": { "lat": 89.9, "lng": 0 } },
+            { "id": 0, "_geo": { "lat": 85, "lng": -179.99 } },
+            { "id": 4, "_geo": { "lat": -80, "lng": 179.9 } },
+            { "id": 2, "_geo": { "lat": 80, "lng": 170 } },
+        ]))
+        .unwrap();
+
+    let rtxn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&rtxn, &index);
+    s.scoring_strategy(crate::score_details::ScoringStrategy::Detailed);
+
+    s.sort_criteria(vec![AscDesc::Asc(Member::GeoPolygonVertices(vertices))]);
+    let (ids, _scores) = execute_iterative_and_rtree_returns_the_same(&rtxn, &index, &mut s);
+    assert_eq!(ids, [0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn test_geo_sort_distance_buckets() {
+    let index = create_index();
+
+    // a hundredth of a degree of latitude is about 1.1km
+    index
+        .add_documents(documents!([
+            { "id": 0, "_geo": { "lat": 0.005, "lng": 0 } },
+            { "id": 1, "_geo": { "lat": 0, "lng": 0 } },
+            { "id": 2, "_geo": { "lat": 0.05, "lng": 0 } },
+            { "id": 3, "_geo": { "lat": 0.5, "lng": 0 } },
+            { "id": 4, "_geo": { "lat": 3, "lng": 0 } },
+            { "id": 5 },
+        ]))
+        .unwrap();
+
+    let rtxn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&rtxn, &index);
+    s.sort_criteria(vec![AscDesc::Asc(Member::Geo([0., 0.]))]);
+    let (ids, _scores) = execute_iterative_and_rtree_returns_the_same(&rtxn, &index, &mut s);
+    insta::assert_snapshot!(format!("{ids:?}"), @"[1, 0, 2, 3, 4, 5]");
+    assert!(s.execute().unwrap().geo_distance_buckets.is_none());
+
+    // the documents of a band are returned together, in the order of the next ranking rules
+    s.geo_distance_buckets(vec![100_000., 1_000., 10_000.]);
+    let (ids, _scores) = execute_iterative_and_rtree_returns_the_same(&rtxn, &index, &mut s);
+    insta::assert_snapshot!(format!("{ids:?}"), @"[0, 1, 2, 3, 4, 5]");
+
+    let buckets = s.execute().unwrap().geo_distance_buckets.unwrap();
+    assert_eq!(buckets.edges, [1_000., 10_000., 100_000.]);
+    let bands: Vec<Vec<u32>> = buckets.bands.iter().map(|band| band.iter().collect()).collect();
+    insta::assert_snapshot!(format!("{bands:?}"), @"[[0, 1], [2], [3]]");
+    insta::assert_snapshot!(format!("{:?}", buckets.overflow.iter().collect::<Vec<_>>()), @"[4]");
+    insta::assert_snapshot!(format!("{:?}", buckets.without_geo.iter().collect::<Vec<_>>()), @"[5]");
+
+    // the bands are the same whatever the direction of the sort
+    s.sort_criteria(vec![AscDesc::Desc(Member::Geo([0., 0.]))]);
+    let (ids, _scores) = execute_iterative_and_rtree_returns_the_same(&rtxn, &index, &mut s);
+    insta::assert_snapshot!(format!("{ids:?}"), @"[4, 3, 2, 0, 1, 5]");
+}
+
+#[test]
+fn test_geo_score() {
+    let index = TempIndex::new();
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec![S("text")]);
+            s.set_sortable_fields(hashset! { S("_geo") });
+            s.set_criteria(vec![Criterion::Words, Criterion::Proximity, Criterion::Sort]);
+        })
+        .unwrap();
+
+    // a thousandth of a degree of latitude is about 110m
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "hello world", "_geo": { "lat": 0.045, "lng": 0 } },
+            { "id": 1, "text": "hello big world", "_geo": { "lat": 0.0009, "lng": 0 } },
+            { "id": 2, "text": "hello world" },
+        ]))
+        .unwrap();
+
+    let rtxn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&rtxn, &index);
+    s.query("hello world");
+    s.sort_criteria(vec![AscDesc::Asc(Member::Geo([0., 0.]))]);
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0, 2, 1]");
+
+    // 100m away is close for a scale of 1km, the better text match 5km away is outranked
+    s.geo_score(0.5, 1_000., GeoDecay::Exponential).unwrap();
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[1, 0, 2]");
+
+    // but both documents are far for a scale of 10m
+    s.geo_score(0.5, 10., GeoDecay::Exponential).unwrap();
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0, 2, 1]");
+
+    s.geo_score(0.5, 1_000., GeoDecay::Linear).unwrap();
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[1, 0, 2]");
+
+    s.geo_score(0.5, 50., GeoDecay::Linear).unwrap();
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0, 2, 1]");
+
+    // the page is the same as the one of the full ranking
+    s.geo_score(0.5, 1_000., GeoDecay::Exponential).unwrap();
+    s.offset(1).limit(1);
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0]");
+}
+
+#[test]
+fn test_geo_score_invalid() {
+    let index = TempIndex::new();
+    let rtxn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&rtxn, &index);
+    for (weight, scale_meters) in [
+        (-0.1, 1_000.),
+        (1.1, 1_000.),
+        (f64::NAN, 1_000.),
+        (0.5, 0.),
+        (0.5, -1.),
+        (0.5, f64::INFINITY),
+    ] {
+        let error = s.geo_score(weight, scale_meters, GeoDecay::Exponential);
+        assert!(matches!(error, Err(Error::UserError(UserError::InvalidGeoScore { .. }))));
+    }
+}
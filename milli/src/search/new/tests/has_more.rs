@@ -0,0 +1,66 @@
+/*!
+This module tests the `has_more` search option:
+
+1. when enabled, `has_more` tells whether other documents follow the returned page
+2. the extra document used to compute it is never returned
+3. when disabled, `has_more` is always false
+*/
+
+use crate::index::tests::TempIndex;
+use crate::search::new::tests::collect_field_values;
+use crate::{Criterion, Search, SearchResult};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_criteria(vec![Criterion::Words]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "hello world" },
+            { "id": 1, "text": "hello there" },
+            { "id": 2, "text": "hello again" },
+            { "id": 3, "text": "hello you" },
+            { "id": 4, "text": "hello" },
+        ]))
+        .unwrap();
+    index
+}
+
+fn search(index: &TempIndex, has_more: bool, offset: usize, limit: usize) -> (Vec<String>, bool) {
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, index);
+    s.query("hello");
+    s.offset(offset);
+    s.limit(limit);
+    s.has_more(has_more);
+    let SearchResult { documents_ids, document_scores, has_more, .. } = s.execute().unwrap();
+    assert_eq!(documents_ids.len(), document_scores.len());
+    (collect_field_values(index, &txn, "id", &documents_ids), has_more)
+}
+
+#[test]
+fn test_has_more() {
+    let index = create_index();
+
+    assert_eq!(search(&index, true, 0, 2), (vec!["0".to_owned(), "1".to_owned()], true));
+    assert_eq!(search(&index, true, 2, 2), (vec!["2".to_owned(), "3".to_owned()], true));
+    assert_eq!(search(&index, true, 4, 2), (vec!["4".to_owned()], false));
+    assert_eq!(search(&index, true, 3, 2), (vec!["3".to_owned(), "4".to_owned()], false));
+    assert_eq!(search(&index, true, 0, 0), (vec![], true));
+}
+
+#[test]
+fn test_has_more_disabled() {
+    let index = create_index();
+
+    assert_eq!(search(&index, false, 0, 2), (vec!["0".to_owned(), "1".to_owned()], false));
+    assert_eq!(search(&index, false, 4, 2), (vec!["4".to_owned()], false));
+}
@@ -0,0 +1,128 @@
+/*!
+This module tests that a search context created from the interner pool of a previous search,
+using `SearchContext::with_interner_pool` and `SearchContext::into_interner_pool`, returns the
+same results as a fresh search context, including when the queries use synonyms.
+
+It also tests that the vocabulary of a pool created with `InternerPool::with_vocabulary`
+keeps its interned indexes across the searches.
+*/
+
+use std::collections::BTreeMap;
+
+use crate::index::tests::TempIndex;
+use crate::score_details::ScoringStrategy;
+use crate::search::new::tests::collect_field_values;
+use crate::{
+    execute_search, DedupInterner, DefaultSearchLogger, GeoSortStrategy, InternerPool,
+    SearchContext, TermsMatchingStrategy, TimeBudget,
+};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            let mut synonyms = BTreeMap::new();
+            synonyms.insert("nyc".to_owned(), vec!["new york".to_owned()]);
+            synonyms.insert("song".to_owned(), vec!["tune".to_owned()]);
+            s.set_synonyms(synonyms);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "a song about new york" },
+            { "id": 1, "text": "the nyc tune" },
+            { "id": 2, "text": "the summer of love" },
+            { "id": 3, "text": "love songs from new york city" },
+            { "id": 4, "text": "a tune for the summer" },
+        ]))
+        .unwrap();
+    index
+}
+
+fn search(ctx: &mut SearchContext, query: &str) -> Vec<u32> {
+    let universe = ctx.index.documents_ids(ctx.txn).unwrap();
+    execute_search(
+        ctx,
+        Some(query),
+        TermsMatchingStrategy::Last,
+        ScoringStrategy::Skip,
+        false,
+        universe,
+        &None,
+        &None,
+        GeoSortStrategy::default(),
+        0,
+        20,
+        None,
+        &mut DefaultSearchLogger,
+        &mut DefaultSearchLogger,
+        TimeBudget::max(),
+        None,
+        None,
+    )
+    .unwrap()
+    .documents_ids
+}
+
+#[test]
+fn test_pool_reuse_returns_identical_results() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let queries = ["nyc song", "summer love", "songs of new york", "tune", "nyc song"];
+
+    let mut pool = InternerPool::default();
+    for query in queries {
+        let mut ctx = SearchContext::new(&index, &txn).unwrap();
+        let expected = search(&mut ctx, query);
+
+        let mut ctx = SearchContext::with_interner_pool(&index, &txn, pool).unwrap();
+        let docids = search(&mut ctx, query);
+        pool = ctx.into_interner_pool();
+
+        assert_eq!(
+            collect_field_values(&index, &txn, "id", &docids),
+            collect_field_values(&index, &txn, "id", &expected),
+            "query: {query}"
+        );
+    }
+}
+
+#[test]
+fn test_pool_vocabulary_is_kept() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let vocabulary = DedupInterner::from_words(&["summer", "love", "song"]);
+    let summer = vocabulary.lookup(&"summer".to_owned()).unwrap();
+    let song = vocabulary.lookup(&"song".to_owned()).unwrap();
+    assert_eq!(vocabulary.len(), 3);
+
+    let mut pool = InternerPool::with_vocabulary(vocabulary);
+    for query in ["nyc song", "summer love", "a tune for the summer"] {
+        let mut ctx = SearchContext::new(&index, &txn).unwrap();
+        let expected = search(&mut ctx, query);
+
+        let mut ctx = SearchContext::with_interner_pool(&index, &txn, pool).unwrap();
+        let docids = search(&mut ctx, query);
+        assert_eq!(ctx.word_interner.lookup(&"summer".to_owned()), Some(summer));
+        assert_eq!(ctx.word_interner.lookup(&"song".to_owned()), Some(song));
+        pool = ctx.into_interner_pool();
+
+        assert_eq!(
+            collect_field_values(&index, &txn, "id", &docids),
+            collect_field_values(&index, &txn, "id", &expected),
+            "query: {query}"
+        );
+    }
+
+    // the words interned by the searches are forgotten, the vocabulary is kept
+    let ctx = SearchContext::with_interner_pool(&index, &txn, pool).unwrap();
+    assert_eq!(ctx.word_interner.len(), 3);
+    assert_eq!(ctx.word_interner.get(summer), "summer");
+    assert_eq!(ctx.word_interner.lookup(&"nyc".to_owned()), None);
+}
@@ -0,0 +1,59 @@
+/*!
+This module tests the `invert_proximity` search option:
+
+1. the proximity buckets are returned in the reverse order, the documents whose words are the
+farthest apart first
+2. the ranking score of the proximity rule grows with the distance between the words
+*/
+
+use crate::index::tests::TempIndex;
+use crate::score_details::ScoreDetails;
+use crate::{Criterion, Search, SearchResult};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_criteria(vec![Criterion::Words, Criterion::Proximity]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "the hello big blue world" },
+            { "id": 1, "text": "the hello world" },
+            { "id": 2, "text": "the hello a b c d e world" },
+            { "id": 3, "text": "the hello big world" },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_invert_proximity() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello world");
+    let SearchResult { documents_ids: normal, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{normal:?}"), @"[1, 3, 0, 2]");
+
+    s.invert_proximity(true);
+    let SearchResult { documents_ids: inverted, document_scores, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{inverted:?}"), @"[2, 0, 3, 1]");
+    assert_eq!(inverted, normal.into_iter().rev().collect::<Vec<_>>());
+
+    let proximity_ranks: Vec<_> = document_scores
+        .iter()
+        .flat_map(|scores| scores.iter())
+        .filter_map(|score| match score {
+            ScoreDetails::Proximity(rank) => Some(rank.rank),
+            _ => None,
+        })
+        .collect();
+    assert!(proximity_ranks.windows(2).all(|ranks| ranks[0] > ranks[1]));
+}
@@ -0,0 +1,116 @@
+/*!
+This module tests the positions of the matches of the query in the returned documents:
+
+1. they are reported per field, with the position of the word in the field
+2. the typo and prefix derivations of a term report the term of the query they matched
+3. the words of a phrase are all reported, with the whole phrase as their term
+4. the nested fields are matched once flattened, like they are indexed
+*/
+
+use crate::index::tests::TempIndex;
+use crate::{MatchPosition, Search};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec![
+                "title".to_owned(),
+                "description".to_owned(),
+                "author".to_owned(),
+            ]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            {
+                "id": 0,
+                "title": "the quick brown fox",
+                "description": "a fox jumps over the lazy dog. The fox is quikc",
+            },
+            {
+                "id": 1,
+                "title": "a lazy dog",
+                "description": "sleeping all day",
+            },
+            {
+                "id": 2,
+                "title": "a novel",
+                "author": { "name": "jack london", "country": "usa" },
+            },
+        ]))
+        .unwrap();
+    index
+}
+
+fn positions(matches: &[MatchPosition], field_id: u16) -> Vec<(u16, &str)> {
+    matches
+        .iter()
+        .filter(|m| m.field_id == field_id)
+        .map(|m| (m.word_position, m.term.as_str()))
+        .collect()
+}
+
+#[test]
+fn test_match_positions() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+    let fields_ids_map = index.fields_ids_map(&txn).unwrap();
+    let title = fields_ids_map.id("title").unwrap();
+    let description = fields_ids_map.id("description").unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("quick fox ju");
+    let result = s.execute().unwrap();
+    assert_eq!(result.documents_ids, vec![0]);
+
+    let matches = result.match_positions(&index, &txn).unwrap();
+    assert_eq!(matches.len(), 1);
+    assert_eq!(positions(&matches[0], title), vec![(1, "quick"), (3, "fox")]);
+    // the hard separator of the sentence adds 8 to the positions of the next words
+    assert_eq!(
+        positions(&matches[0], description),
+        vec![(1, "fox"), (2, "ju"), (15, "fox"), (17, "quick")]
+    );
+}
+
+#[test]
+fn test_match_positions_of_phrase() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+    let fields_ids_map = index.fields_ids_map(&txn).unwrap();
+    let title = fields_ids_map.id("title").unwrap();
+    let description = fields_ids_map.id("description").unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("\"lazy dog\"");
+    let result = s.execute().unwrap();
+    // the document matching in its title is ranked first
+    assert_eq!(result.documents_ids, vec![1, 0]);
+
+    let matches = result.match_positions(&index, &txn).unwrap();
+    assert_eq!(positions(&matches[0], title), vec![(1, "lazy dog"), (2, "lazy dog")]);
+    assert_eq!(positions(&matches[1], title), vec![]);
+    assert_eq!(positions(&matches[1], description), vec![(5, "lazy dog"), (6, "lazy dog")]);
+}
+
+#[test]
+fn test_match_positions_of_nested_field() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+    let fields_ids_map = index.fields_ids_map(&txn).unwrap();
+    let author_name = fields_ids_map.id("author.name").unwrap();
+    let author_country = fields_ids_map.id("author.country").unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("london");
+    let result = s.execute().unwrap();
+    assert_eq!(result.documents_ids, vec![2]);
+
+    let matches = result.match_positions(&index, &txn).unwrap();
+    assert_eq!(positions(&matches[0], author_name), vec![(1, "london")]);
+    assert_eq!(positions(&matches[0], author_country), vec![]);
+}
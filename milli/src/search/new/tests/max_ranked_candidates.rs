@@ -0,0 +1,127 @@
+/*!
+This module tests the `max_ranked_candidates` search option:
+
+1. above the cap, only the first candidates in the order of the cheap ranking rules are
+ranked by the expensive ones and returned
+2. the expensive ranking rules iterate over the capped candidates only
+3. the capping is reported to the search logger
+*/
+
+use crate::index::tests::TempIndex;
+use crate::score_details::ScoringStrategy;
+use crate::{
+    execute_search, BinarySearchEvent, BinarySearchLogger, Criterion, DefaultSearchLogger,
+    GeoSortStrategy, Search, SearchContext, SearchResult, TermsMatchingStrategy, TimeBudget,
+};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_criteria(vec![Criterion::Words, Criterion::Proximity]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "hello there world" },
+            { "id": 1, "text": "hello big world" },
+            { "id": 2, "text": "hello the whole world" },
+            { "id": 3, "text": "hello world" },
+            { "id": 4, "text": "hello world" },
+            { "id": 5, "text": "hello world" },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_max_ranked_candidates() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello world");
+    let SearchResult { documents_ids, candidates, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[3, 4, 5, 0, 1, 2]");
+    assert_eq!(candidates.len(), 6);
+
+    // the best documents have the biggest ids and are not ranked anymore
+    s.max_ranked_candidates(3);
+    let SearchResult { documents_ids, candidates, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0, 1, 2]");
+    assert_eq!(candidates.len(), 3);
+
+    s.max_ranked_candidates(10);
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[3, 4, 5, 0, 1, 2]");
+}
+
+#[test]
+fn test_max_ranked_candidates_in_words_order() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    // the only document containing all the words is ranked before the smaller ids
+    let mut s = Search::new(&txn, &index);
+    s.query("hello world whole");
+    s.terms_matching_strategy(TermsMatchingStrategy::Last);
+    s.max_ranked_candidates(2);
+    let SearchResult { documents_ids, candidates, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[2, 0]");
+    assert_eq!(candidates.len(), 2);
+
+    // the next page doesn't rank the dropped candidates
+    s.offset(1);
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0]");
+}
+
+#[test]
+fn test_max_ranked_candidates_logged() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut ctx = SearchContext::new(&index, &txn).unwrap();
+    ctx.max_ranked_candidates = Some(2);
+    let universe = index.documents_ids(&txn).unwrap();
+    let mut logger = BinarySearchLogger::new(Vec::new());
+    execute_search(
+        &mut ctx,
+        Some("hello world"),
+        TermsMatchingStrategy::Last,
+        ScoringStrategy::Skip,
+        false,
+        universe,
+        &None,
+        &None,
+        GeoSortStrategy::default(),
+        0,
+        20,
+        None,
+        &mut DefaultSearchLogger,
+        &mut logger,
+        TimeBudget::max(),
+        None,
+        None,
+    )
+    .unwrap();
+    let bytes = logger.finish().unwrap();
+
+    let events = BinarySearchEvent::decode_all(bytes.as_slice()).unwrap();
+    assert!(events.contains(&BinarySearchEvent::CappedUniverse { universe_len: 6, capped_len: 2 }));
+    // the proximity rule comes after the words rule
+    let start_universes: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            BinarySearchEvent::RankingRuleStartIteration { ranking_rule_idx: 1, universe_len } => {
+                Some(*universe_len)
+            }
+            _ => None,
+        })
+        .collect();
+    assert!(start_universes.iter().all(|len| *len <= 2), "{start_universes:?}");
+}
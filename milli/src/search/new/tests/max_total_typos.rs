@@ -0,0 +1,75 @@
+/*!
+This module tests the `max_total_typos` search option:
+
+1. the typos of all the words of the query are counted together
+2. the documents over the limit are neither returned nor counted as candidates
+3. the limit is enforced by the typo ranking rule, or on the universe when it isn't used
+*/
+
+use crate::index::tests::TempIndex;
+use crate::{Criterion, Search, SearchResult, TermsMatchingStrategy};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_criteria(vec![Criterion::Words, Criterion::Typo]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "quick brown fox" },
+            { "id": 1, "text": "quikc brown fox" },
+            { "id": 2, "text": "quick browm fox" },
+            { "id": 3, "text": "quikc browm fox" },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_max_total_typos() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.terms_matching_strategy(TermsMatchingStrategy::All);
+    s.query("quick brown fox");
+    let SearchResult { documents_ids, candidates, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0, 1, 2, 3]");
+    assert_eq!(candidates.len(), 4);
+
+    // only one of the words may be misspelled
+    s.max_total_typos(1);
+    let SearchResult { documents_ids, candidates, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0, 1, 2]");
+    assert_eq!(candidates.len(), 3);
+
+    s.max_total_typos(0);
+    let SearchResult { documents_ids, candidates, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0]");
+    assert_eq!(candidates.len(), 1);
+}
+
+#[test]
+fn test_max_total_typos_without_typo_rule() {
+    let index = create_index();
+    index
+        .update_settings(|s| {
+            s.set_criteria(vec![Criterion::Words]);
+        })
+        .unwrap();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.terms_matching_strategy(TermsMatchingStrategy::All);
+    s.query("quick brown fox");
+    s.max_total_typos(1);
+    let SearchResult { documents_ids, candidates, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0, 1, 2]");
+    assert_eq!(candidates.len(), 3);
+}
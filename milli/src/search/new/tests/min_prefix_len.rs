@@ -0,0 +1,52 @@
+/*!
+This module tests the `min_prefix_len` search option:
+
+1. the last word of the query shorter than the minimum length is matched exactly
+2. the last word at least as long as the minimum length is matched as a prefix
+3. by default, the last word is always matched as a prefix
+*/
+
+use crate::index::tests::TempIndex;
+use crate::{Criterion, Search, SearchResult};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_criteria(vec![Criterion::Words]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "size s" },
+            { "id": 1, "text": "red shoes" },
+            { "id": 2, "text": "blue shirt" },
+            { "id": 3, "text": "sun hat" },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_min_prefix_len() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("s");
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0, 1, 2, 3]");
+
+    // below the minimum length, the last word is matched exactly
+    s.min_prefix_len(2);
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0]");
+
+    s.query("sh");
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[1, 2]");
+}
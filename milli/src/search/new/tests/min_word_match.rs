@@ -0,0 +1,73 @@
+/*!
+This module tests the `min_word_match_ratio` search option:
+
+1. the documents matching fewer than `ceil(ratio * words)` words of the query are removed
+2. the documents matching enough words are kept whichever words they match
+3. a ratio of 1 only keeps the documents matching all the words
+*/
+
+use crate::index::tests::TempIndex;
+use crate::{Criterion, Search, SearchResult};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_criteria(vec![Criterion::Words]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "sun moon star rain" },
+            { "id": 1, "text": "sun moon" },
+            { "id": 2, "text": "sun star" },
+            { "id": 3, "text": "sun" },
+            { "id": 4, "text": "nothing to see here" },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_min_word_match_ratio() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("sun moon star rain");
+    let SearchResult { documents_ids, candidates, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0, 1, 2, 3]");
+    assert_eq!(candidates.len(), 4);
+
+    // at least two of the four words must be matched
+    s.min_word_match_ratio(0.5);
+    let SearchResult { documents_ids, candidates, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0, 1, 2]");
+    assert_eq!(candidates.len(), 3);
+
+    // the ratio is rounded up, three words must be matched
+    s.min_word_match_ratio(0.6);
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0]");
+
+    s.min_word_match_ratio(1.);
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0]");
+}
+
+#[test]
+fn test_min_word_match_ratio_distinct_words() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    // the repeated word is only counted once, two of the three distinct words are required
+    let mut s = Search::new(&txn, &index);
+    s.query("sun sun moon star");
+    s.min_word_match_ratio(0.5);
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0, 1, 2]");
+}
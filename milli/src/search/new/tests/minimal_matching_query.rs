@@ -0,0 +1,61 @@
+/*!
+This module tests `Search::minimal_matching_query`:
+
+1. the words of the query are removed greedily, in query order, while the document still matches
+2. words matched through a typo are returned as written in the query
+3. the words of an n-gram matching the document are kept together
+4. a document which is not part of the results gets an empty list
+*/
+
+use crate::index::tests::TempIndex;
+use crate::{Search, TermsMatchingStrategy};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "the quick brown fox" },
+            { "id": 1, "text": "a lazy dog" },
+            { "id": 2, "text": "sunflower seeds" },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_minimal_matching_query() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.terms_matching_strategy(TermsMatchingStrategy::Last);
+    s.query("quick browm cat");
+
+    let docid = index.external_documents_ids().get(&txn, "0").unwrap().unwrap();
+    // the document still matches `browm cat` with the `Last` strategy, but not `cat`
+    assert_eq!(s.minimal_matching_query(docid).unwrap(), vec!["browm"]);
+
+    let docid = index.external_documents_ids().get(&txn, "1").unwrap().unwrap();
+    assert!(s.minimal_matching_query(docid).unwrap().is_empty());
+}
+
+#[test]
+fn test_minimal_matching_query_of_ngram() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.terms_matching_strategy(TermsMatchingStrategy::All);
+    s.query("sun flower seeds");
+
+    let docid = index.external_documents_ids().get(&txn, "2").unwrap().unwrap();
+    assert_eq!(s.minimal_matching_query(docid).unwrap(), vec!["sun", "flower"]);
+}
@@ -1,37 +1,70 @@
+use crate::index::temp_index::collect_field_values;
+
+pub mod any_exact;
 pub mod attribute_fid;
 pub mod attribute_position;
+#[cfg(feature = "bench-harness")]
+pub mod bench_harness;
+pub mod bucket_limits;
+pub mod cancel;
+pub mod compare_results;
+pub mod cursor;
 pub mod cutoff;
+#[cfg(feature = "search-capture")]
+pub mod db_cache_capture;
+pub mod dead_ends_cache;
+pub mod degradation_reasons;
+pub mod deleted_documents;
+pub mod disable_ranking_rules;
 pub mod distinct;
+pub mod diversify;
+pub mod duplicate_tokens;
+pub mod effective_query_terms;
 pub mod exactness;
+pub mod execute_into;
+pub mod execution_trace;
+pub mod facet_distribution;
+pub mod federated_search;
+pub mod filter_funnel;
+pub mod fingerprint;
 pub mod geo_sort;
+pub mod has_more;
 pub mod integration;
+pub mod interner_pool;
+pub mod invert_proximity;
 #[cfg(feature = "all-tokenizations")]
 pub mod language;
+pub mod match_positions;
+pub mod max_ranked_candidates;
+pub mod max_total_typos;
+pub mod min_prefix_len;
+pub mod min_word_match;
+pub mod minimal_matching_query;
+pub mod multi_search;
 pub mod ngram_split_words;
+pub mod normalizer;
+pub mod ordered_proximity;
+pub mod page_boundary;
+pub mod phrase_slop;
+pub mod pin;
 pub mod proximity;
+pub mod proximity_fields;
+pub mod proximity_stop_words;
 pub mod proximity_typo;
+pub mod query_graph_cache;
+pub mod query_stats;
+pub mod query_synonyms;
+pub mod ranking_score_threshold;
+pub mod searchable_fields;
+pub mod skip_ranking;
 pub mod sort;
+pub mod sortable_field_extent;
+pub mod spec;
 pub mod stop_words;
+pub mod term_contributions;
+pub mod term_proximity;
 pub mod typo;
 pub mod typo_proximity;
+pub mod word_filter;
+pub mod word_window;
 pub mod words_tms;
-
-fn collect_field_values(
-    index: &crate::Index,
-    txn: &heed::RoTxn<'_>,
-    fid: &str,
-    docids: &[u32],
-) -> Vec<String> {
-    let mut values = vec![];
-    let fid = index.fields_ids_map(txn).unwrap().id(fid).unwrap();
-    for doc in index.documents(txn, docids.iter().copied()).unwrap() {
-        if let Some(v) = doc.1.get(fid) {
-            let v: serde_json::Value = serde_json::from_slice(v).unwrap();
-            let v = v.to_string();
-            values.push(v);
-        } else {
-            values.push("__does_not_exist__".to_owned());
-        }
-    }
-    values
-}
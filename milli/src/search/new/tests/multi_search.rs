@@ -0,0 +1,89 @@
+/*!
+This module tests the `MultiSearch` coordinator:
+
+1. the searches return the same results as when executed on their own
+2. the results are returned in the order the searches were added
+3. the searches all observe the transaction of the multi-search
+4. an invalid search is rejected when it is added
+*/
+
+use crate::index::tests::TempIndex;
+use crate::search::new::tests::collect_field_values;
+use crate::{MultiSearch, Search, SearchResult, SearchSpec};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "the quick brown fox" },
+            { "id": 1, "text": "the lazy dog" },
+            { "id": 2, "text": "a quick dog" },
+            { "id": 3, "text": "the brown dog jumps" },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_multi_search_same_results() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let queries = ["quick", "dog", "brown dog", "quick", "the dog"];
+
+    let mut multi = MultiSearch::new(&index).unwrap();
+    for query in queries {
+        multi.add(SearchSpec { query: Some(query.to_owned()), ..Default::default() }).unwrap();
+    }
+    let results = multi.execute_all().unwrap();
+    assert_eq!(results.len(), queries.len());
+
+    for (query, result) in queries.iter().zip(results) {
+        let mut s = Search::new(&txn, &index);
+        s.query(*query);
+        let SearchResult { documents_ids, candidates, .. } = s.execute().unwrap();
+        assert_eq!(result.documents_ids, documents_ids, "query: {query}");
+        assert_eq!(result.candidates, candidates, "query: {query}");
+    }
+}
+
+#[test]
+fn test_multi_search_consistent_view() {
+    let index = create_index();
+    let spec = SearchSpec { query: Some("dog".to_owned()), ..Default::default() };
+
+    let mut multi = MultiSearch::new(&index).unwrap();
+    multi.add(spec.clone()).unwrap();
+
+    index.add_documents(documents!([{ "id": 4, "text": "a dog" }])).unwrap();
+
+    multi.add(spec).unwrap();
+
+    let results = multi.execute_all().unwrap();
+    let ids: Vec<_> = results
+        .iter()
+        .map(|result| collect_field_values(&index, multi.rtxn(), "id", &result.documents_ids))
+        .collect();
+    // the document added after the transaction was created is not seen by any search
+    assert_eq!(ids[0], ids[1]);
+    assert_eq!(ids[0].len(), 3);
+    assert!(!ids[0].contains(&"4".to_owned()));
+}
+
+#[test]
+fn test_multi_search_invalid_search() {
+    let index = create_index();
+
+    let mut multi = MultiSearch::new(&index).unwrap();
+    let spec = SearchSpec { sort: Some(vec!["text:sideways".to_owned()]), ..Default::default() };
+    assert!(multi.add(spec).is_err());
+    assert!(multi.execute_all().unwrap().is_empty());
+}
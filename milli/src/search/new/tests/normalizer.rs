@@ -0,0 +1,83 @@
+/*!
+This module tests the `normalizer` search option:
+
+1. by default, the query words are normalized like the indexed words
+2. without diacritic folding, the query resolves to words keeping their diacritics,
+which don't match the words of an index that folds them
+3. the query words are only lowercased when requested
+*/
+
+use crate::index::tests::TempIndex;
+use crate::search::new::extract_tokens;
+use crate::search::new::query_term::ExtractedTokens;
+use crate::search::new::tests::collect_field_values;
+use crate::{NormalizerConfig, Search, SearchContext, SearchResult};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "café crème" },
+            { "id": 1, "text": "cafe" },
+            { "id": 2, "text": "tea" },
+        ]))
+        .unwrap();
+    index
+}
+
+fn query_words(index: &TempIndex, normalizer: Option<NormalizerConfig>) -> Vec<String> {
+    let txn = index.read_txn().unwrap();
+    let mut ctx = SearchContext::new(index, &txn).unwrap();
+    ctx.normalizer = normalizer;
+    let ExtractedTokens { query_terms, .. } =
+        extract_tokens(&mut ctx, "Café Crème", None, None).unwrap();
+    query_terms.iter().map(|term| ctx.term_interner.get(term.value).original_word(&ctx)).collect()
+}
+
+fn search(index: &TempIndex, normalizer: Option<NormalizerConfig>) -> Vec<String> {
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, index);
+    s.query("Café");
+    if let Some(normalizer) = normalizer {
+        s.normalizer(normalizer);
+    }
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    collect_field_values(index, &txn, "id", &documents_ids)
+}
+
+#[test]
+fn test_normalizer_diacritics() {
+    let index = create_index();
+    let keep_diacritics = NormalizerConfig { fold_diacritics: false, ..Default::default() };
+
+    assert_eq!(query_words(&index, None), vec!["cafe", "creme"]);
+    assert_eq!(query_words(&index, Some(NormalizerConfig::default())), vec!["cafe", "creme"]);
+    // the tokenizer may decompose the accented letters, but always keeps their diacritics
+    let words = query_words(&index, Some(keep_diacritics.clone()));
+    assert_eq!(words.len(), 2);
+    assert!(words[0].starts_with("caf") && words[1].starts_with("cr"));
+    assert!(words.iter().all(|word| !word.is_ascii()), "{words:?}");
+
+    assert_eq!(search(&index, None), vec!["0", "1"]);
+    // the index only contains the folded words
+    assert!(search(&index, Some(keep_diacritics)).is_empty());
+}
+
+#[test]
+fn test_normalizer_lowercase() {
+    let index = create_index();
+    let keep_case =
+        NormalizerConfig { lowercase: false, fold_diacritics: false, ..Default::default() };
+
+    let words = query_words(&index, Some(keep_case));
+    assert!(words[0].starts_with("Caf") && words[1].starts_with("Cr"), "{words:?}");
+}
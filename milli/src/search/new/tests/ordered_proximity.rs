@@ -0,0 +1,66 @@
+/*!
+This module tests the `ordered_proximity` search option:
+
+1. by default, a pair of words appearing in the reverse order of the query costs one more proximity
+2. when the order is disabled, the reversed pair costs as much as the pair in the query order
+*/
+
+use crate::index::tests::TempIndex;
+use crate::score_details::{ScoreDetails, ScoringStrategy};
+use crate::search::new::tests::collect_field_values;
+use crate::{Criterion, Search, SearchResult, TermsMatchingStrategy};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_criteria(vec![Criterion::Words, Criterion::Proximity]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "jean bob" },
+            { "id": 1, "text": "bob and jean" },
+            { "id": 2, "text": "bob jean" },
+        ]))
+        .unwrap();
+    index
+}
+
+fn top_bucket_ids(index: &TempIndex, ordered_proximity: bool) -> Vec<String> {
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, index);
+    s.terms_matching_strategy(TermsMatchingStrategy::All);
+    s.scoring_strategy(ScoringStrategy::Detailed);
+    s.ordered_proximity(ordered_proximity);
+    s.query("bob jean");
+    let SearchResult { documents_ids, document_scores, .. } = s.execute().unwrap();
+
+    let best_score = ScoreDetails::global_score(document_scores[0].iter());
+    let top_bucket: Vec<_> = documents_ids
+        .iter()
+        .zip(document_scores.iter())
+        .filter(|(_, scores)| ScoreDetails::global_score(scores.iter()) == best_score)
+        .map(|(docid, _)| *docid)
+        .collect();
+    collect_field_values(index, &txn, "id", &top_bucket)
+}
+
+#[test]
+fn test_ordered_proximity() {
+    let index = create_index();
+    assert_eq!(top_bucket_ids(&index, true), vec!["2"]);
+}
+
+#[test]
+fn test_unordered_proximity() {
+    let index = create_index();
+    let mut top_bucket = top_bucket_ids(&index, false);
+    top_bucket.sort();
+    assert_eq!(top_bucket, vec!["0", "2"]);
+}
@@ -0,0 +1,62 @@
+/*!
+This module tests the `page_boundary` search option:
+
+1. the boundary gives the bucket of each ranking rule containing the last returned document
+2. the boundary stops at the last ranking rule that split the candidates
+3. the boundary is only computed when requested
+*/
+
+use crate::index::tests::TempIndex;
+use crate::score_details::ScoringStrategy;
+use crate::{Criterion, PageBoundary, Search, SearchResult, TermsMatchingStrategy};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_criteria(vec![Criterion::Words, Criterion::Typo]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "hello world" },
+            { "id": 1, "text": "hello worlt" },
+            { "id": 2, "text": "hello" },
+        ]))
+        .unwrap();
+    index
+}
+
+fn search(index: &TempIndex, limit: usize, enabled: bool) -> Option<PageBoundary> {
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, index);
+    s.terms_matching_strategy(TermsMatchingStrategy::Last);
+    s.scoring_strategy(ScoringStrategy::Detailed);
+    s.query("hello world");
+    s.limit(limit);
+    s.page_boundary(enabled);
+    let SearchResult { documents_ids, page_boundary, .. } = s.execute().unwrap();
+    assert_eq!(documents_ids.len(), limit);
+    page_boundary
+}
+
+fn boundary(ranking_rule: usize, bucket_ranks: &[usize]) -> Option<PageBoundary> {
+    let ranking_rule_id = ["words", "typo"][ranking_rule].to_owned();
+    Some(PageBoundary { ranking_rule, ranking_rule_id, bucket_ranks: bucket_ranks.to_vec() })
+}
+
+#[test]
+fn test_page_boundary() {
+    let index = create_index();
+
+    assert_eq!(search(&index, 1, true), boundary(1, &[0, 0]));
+    assert_eq!(search(&index, 2, true), boundary(1, &[0, 1]));
+    assert_eq!(search(&index, 3, true), boundary(1, &[1, 0]));
+
+    assert_eq!(search(&index, 2, false), None);
+}
@@ -0,0 +1,53 @@
+/*!
+This module tests the `phrase_slop` search option:
+
+1. without slop, the words of a phrase must be next to each other
+2. with a slop, the words of a phrase can be separated by as many words
+3. the documents containing the exact phrase are ranked first
+*/
+
+use crate::index::tests::TempIndex;
+use crate::search::new::tests::collect_field_values;
+use crate::{Search, SearchResult};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "new big york" },
+            { "id": 1, "text": "new york city" },
+            { "id": 2, "text": "new and big york" },
+            { "id": 3, "text": "new things are coming to york" },
+        ]))
+        .unwrap();
+    index
+}
+
+fn search(index: &TempIndex, slop: u8) -> Vec<String> {
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, index);
+    s.query("\"new york\"");
+    s.phrase_slop(slop);
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    collect_field_values(index, &txn, "id", &documents_ids)
+}
+
+#[test]
+fn test_phrase_slop() {
+    let index = create_index();
+
+    assert_eq!(search(&index, 0), vec!["1"]);
+    assert_eq!(search(&index, 1), vec!["1", "0"]);
+    assert_eq!(search(&index, 2), vec!["1", "0", "2"]);
+    // the words are too far apart to be in the proximity databases
+    assert_eq!(search(&index, 5), vec!["1", "0", "2"]);
+}
@@ -0,0 +1,82 @@
+/*!
+This module tests the `pin` search option:
+
+1. the pinned documents are returned first, in the order they were pinned
+2. the ranked documents follow without the pinned documents
+3. the pinned documents that are not part of the universe are ignored
+*/
+
+use crate::index::tests::TempIndex;
+use crate::{Criterion, Search, SearchResult, TermsMatchingStrategy};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_criteria(vec![Criterion::Words, Criterion::Proximity]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "hello there big world" },
+            { "id": 1, "text": "hello world" },
+            { "id": 2, "text": "hello world again" },
+            { "id": 3, "text": "hello" },
+            { "id": 4, "text": "goodbye" },
+            { "id": 5, "text": "world" },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_pin() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello world");
+    s.terms_matching_strategy(TermsMatchingStrategy::Last);
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[1, 2, 0, 3]");
+
+    s.pin(vec![3, 0]);
+    let SearchResult { documents_ids, candidates, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[3, 0, 1, 2]");
+    insta::assert_snapshot!(format!("{candidates:?}"), @"RoaringBitmap<[0, 1, 2, 3]>");
+
+    // the offset and the limit apply to the pinned documents too
+    s.offset(1);
+    s.limit(2);
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0, 1]");
+}
+
+#[test]
+fn test_pin_outside_of_the_universe() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("hello world");
+    s.terms_matching_strategy(TermsMatchingStrategy::Last);
+    // the document 4 doesn't match the query and the document 42 doesn't exist
+    s.pin(vec![4, 2, 42, 2]);
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[2, 1, 0, 3]");
+}
+
+#[test]
+fn test_pin_placeholder() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.pin(vec![5, 2]);
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[5, 2, 0, 1, 3, 4]");
+}
@@ -0,0 +1,108 @@
+/*!
+This module tests the `proximity_field_weights` search option:
+
+1. the documents of a proximity bucket are sorted by the weights of the fields in which the
+words of the query are close
+2. the documents of different proximity buckets are never reordered
+3. the JSON details of the proximity fields complete the ones of the proximity, or stand on
+their own when the proximity is missing
+4. the negative, infinite and NaN weights are rejected
+*/
+
+use std::collections::HashMap;
+
+use crate::index::tests::TempIndex;
+use crate::score_details::{Rank, ScoreDetails};
+use crate::{Criterion, Error, Search, SearchResult, TermsMatchingStrategy, UserError};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["title".to_owned(), "text".to_owned()]);
+            s.set_criteria(vec![Criterion::Words, Criterion::Proximity]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "title": "a story about dogs", "text": "the hot dog stand" },
+            { "id": 1, "title": "hot dog", "text": "the best stand" },
+            { "id": 2, "title": "hot and spicy dog", "text": "the worst stand" },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_proximity_field_weights() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.terms_matching_strategy(TermsMatchingStrategy::All);
+    s.scoring_strategy(crate::score_details::ScoringStrategy::Detailed);
+    s.query("hot dog");
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0, 1, 2]");
+
+    s.proximity_field_weights(HashMap::from([("title".to_owned(), 2.0)])).unwrap();
+    let SearchResult { documents_ids, document_scores, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[1, 0, 2]");
+    // the ranks don't depend on the number of buckets, "hot dog" is as close as possible in the
+    // title, and half as close with the weight of the text
+    assert!(document_scores[0]
+        .contains(&ScoreDetails::ProximityFields(Rank { rank: 100, max_rank: 100 })));
+    assert!(document_scores[1]
+        .contains(&ScoreDetails::ProximityFields(Rank { rank: 51, max_rank: 100 })));
+
+    // the text weighing more than the title puts the documents back in their original order
+    s.proximity_field_weights(HashMap::from([("title".to_owned(), 0.5)])).unwrap();
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0, 1, 2]");
+}
+
+#[test]
+fn test_proximity_field_weights_invalid() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    for weight in [-1.0, f64::NAN, f64::INFINITY] {
+        let error = s.proximity_field_weights(HashMap::from([("title".to_owned(), weight)]));
+        assert!(matches!(
+            error,
+            Err(Error::UserError(UserError::InvalidProximityFieldWeight { .. }))
+        ));
+    }
+}
+
+#[test]
+fn test_proximity_fields_json_details() {
+    let proximity = ScoreDetails::Proximity(Rank { rank: 3, max_rank: 4 });
+    let proximity_fields = ScoreDetails::ProximityFields(Rank { rank: 1, max_rank: 2 });
+
+    let details = ScoreDetails::to_json_map([&proximity, &proximity_fields].into_iter());
+    insta::assert_json_snapshot!(details, @r###"
+    {
+      "proximity": {
+        "order": 0,
+        "fieldWeightScore": 0.5,
+        "score": 0.625
+      }
+    }
+    "###);
+
+    let details = ScoreDetails::to_json_map([&proximity_fields].into_iter());
+    insta::assert_json_snapshot!(details, @r###"
+    {
+      "proximityFields": {
+        "order": 0,
+        "fieldWeightScore": 0.5,
+        "score": 0.5
+      }
+    }
+    "###);
+}
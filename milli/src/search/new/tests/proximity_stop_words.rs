@@ -0,0 +1,60 @@
+/*!
+This module tests the `proximity_ignores_stopwords` search option:
+
+1. by default, the words of the query separated by stop words have no proximity condition
+2. when the option is enabled, the documents can contain the stop words of the query between
+the two words without increasing their proximity cost
+3. the proximity still ranks the documents where the words are farther apart
+*/
+
+use std::collections::BTreeSet;
+use std::iter::FromIterator;
+
+use crate::index::tests::TempIndex;
+use crate::score_details::ScoringStrategy;
+use crate::search::new::tests::collect_field_values;
+use crate::{Criterion, Search, SearchResult, TermsMatchingStrategy};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_stop_words(BTreeSet::from_iter(["of".to_owned(), "the".to_owned()]));
+            s.set_criteria(vec![Criterion::Words, Criterion::Proximity]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "the england of the old king" },
+            { "id": 1, "text": "king of england" },
+            { "id": 2, "text": "king england" },
+            { "id": 3, "text": "king and queen of england" },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_proximity_ignores_stop_words() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.terms_matching_strategy(TermsMatchingStrategy::All);
+    s.scoring_strategy(ScoringStrategy::Detailed);
+    s.query("king of england");
+
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    assert_eq!(collect_field_values(&index, &txn, "id", &documents_ids), vec!["0", "1", "2", "3"]);
+
+    s.proximity_ignores_stopwords(true);
+    let SearchResult { documents_ids, document_scores, .. } = s.execute().unwrap();
+    assert_eq!(collect_field_values(&index, &txn, "id", &documents_ids), vec!["1", "2", "3", "0"]);
+    // "king of england" is as close as "king england"
+    assert_eq!(document_scores[0], document_scores[1]);
+    assert_ne!(document_scores[1], document_scores[2]);
+}
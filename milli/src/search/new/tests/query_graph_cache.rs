@@ -0,0 +1,147 @@
+/*!
+This module tests the query graph cache of the search context:
+
+1. searching the same query again with the same context reuses its query graph
+2. the results are the same as without the cache
+3. the least recently used query graphs are evicted once the cache is full
+4. the query graphs are kept between the searches sharing a `SharedDatabaseCache`
+5. a query searched with other query synonyms doesn't reuse the query graph of the first one
+6. a `SharedDatabaseCache` whose interners are full is emptied before the next search
+*/
+
+use big_s::S;
+use maplit::hashmap;
+
+use crate::index::tests::TempIndex;
+use crate::score_details::ScoringStrategy;
+use crate::search::new::tests::collect_field_values;
+use crate::{
+    execute_search, DefaultSearchLogger, GeoSortStrategy, Search, SearchContext,
+    SharedDatabaseCache, TermsMatchingStrategy, TimeBudget,
+};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_stop_words(["the".to_owned()].into_iter().collect());
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "the quick brown fox jumps" },
+            { "id": 1, "text": "a quick fox" },
+            { "id": 2, "text": "the brown dog -fox" },
+            { "id": 3, "text": "jumps over the lazy dog" },
+        ]))
+        .unwrap();
+    index
+}
+
+fn search(index: &TempIndex, ctx: &mut SearchContext<'_>, query: &str) -> Vec<String> {
+    let universe = ctx.index.documents_ids(ctx.txn).unwrap();
+    let result = execute_search(
+        ctx,
+        Some(query),
+        TermsMatchingStrategy::Last,
+        ScoringStrategy::Skip,
+        false,
+        universe,
+        &None,
+        &None,
+        GeoSortStrategy::default(),
+        0,
+        20,
+        None,
+        &mut DefaultSearchLogger,
+        &mut DefaultSearchLogger,
+        TimeBudget::max(),
+        None,
+        None,
+    )
+    .unwrap();
+    collect_field_values(index, ctx.txn, "id", &result.documents_ids)
+}
+
+#[test]
+fn test_query_graph_cache() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let queries = ["the quick brown fox", "brown -fox", "\"quick fox\" jumps", "the"];
+
+    let mut ctx = SearchContext::new(&index, &txn).unwrap();
+    ctx.enable_query_graph_cache(4);
+    for query in queries {
+        let expected = search(&index, &mut SearchContext::new(&index, &txn).unwrap(), query);
+        assert_eq!(search(&index, &mut ctx, query), expected, "query: {query}");
+        assert_eq!(search(&index, &mut ctx, query), expected, "query: {query}");
+        assert_eq!(search(&index, &mut ctx, &format!("  {query} ")), expected, "query: {query}");
+    }
+    assert_eq!(ctx.query_graph_cache.as_ref().unwrap().hits, 2 * queries.len());
+}
+
+#[test]
+fn test_query_graph_cache_eviction() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut ctx = SearchContext::new(&index, &txn).unwrap();
+    ctx.enable_query_graph_cache(2);
+    search(&index, &mut ctx, "quick fox");
+    search(&index, &mut ctx, "brown dog");
+    // makes "quick fox" the most recently used query
+    search(&index, &mut ctx, "quick fox");
+    // evicts "brown dog"
+    search(&index, &mut ctx, "lazy dog");
+    search(&index, &mut ctx, "quick fox");
+    assert_eq!(ctx.query_graph_cache.as_ref().unwrap().hits, 2);
+    search(&index, &mut ctx, "brown dog");
+    assert_eq!(ctx.query_graph_cache.as_ref().unwrap().hits, 2);
+}
+
+#[test]
+fn test_query_graph_cache_shared_between_searches() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+    let mut cache = SharedDatabaseCache::default();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("quick fox");
+    let expected = s.execute().unwrap().documents_ids;
+    let first = s.execute_with_cache(Some(&mut cache)).unwrap().documents_ids;
+    let second = s.execute_with_cache(Some(&mut cache)).unwrap().documents_ids;
+    assert_eq!(first, expected);
+    assert_eq!(second, expected);
+    assert_eq!(cache.query_graph_cache.as_ref().unwrap().hits, 1);
+
+    s.query_synonyms(hashmap! { S("quick") => vec![S("lazy")] });
+    let with_synonyms = s.execute_with_cache(Some(&mut cache)).unwrap().documents_ids;
+    assert!(!expected.contains(&3));
+    assert!(with_synonyms.contains(&3));
+    assert_eq!(cache.query_graph_cache.as_ref().unwrap().hits, 1);
+}
+
+#[test]
+fn test_shared_cache_emptied_when_full() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+    let mut cache = SharedDatabaseCache::default();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("quick fox");
+    let expected = s.execute().unwrap().documents_ids;
+    s.execute_with_cache(Some(&mut cache)).unwrap();
+    let interned_words = cache.word_interner.len();
+
+    for i in 0..u16::MAX / 2 {
+        cache.word_interner.insert(format!("word{i}"));
+    }
+    assert_eq!(s.execute_with_cache(Some(&mut cache)).unwrap().documents_ids, expected);
+    assert_eq!(cache.word_interner.len(), interned_words);
+    assert_eq!(cache.query_graph_cache.as_ref().unwrap().hits, 0);
+}
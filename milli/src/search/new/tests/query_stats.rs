@@ -0,0 +1,80 @@
+/*!
+This module tests the `query_stats` search option:
+
+1. the statistics are only reported when they are enabled
+2. the interned words, query graph nodes, ranking rule graph edges and cached database values
+   of a multi-word query with typos are counted
+3. the words and values cached by the previous searches sharing the cache are not counted
+*/
+
+use crate::index::tests::TempIndex;
+use crate::{Criterion, QueryStats, Search, SearchResult, SharedDatabaseCache};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_criteria(vec![Criterion::Words, Criterion::Typo, Criterion::Proximity]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "the quick brown fox jumps over the lazy dog" },
+            { "id": 1, "text": "the quikc brown fox" },
+            { "id": 2, "text": "a brown dog sleeps" },
+            { "id": 3, "text": "quick foxes are quicker than dogs" },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_query_stats() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("quikc browm fox");
+    let SearchResult { query_stats, .. } = s.execute().unwrap();
+    assert_eq!(query_stats, None);
+
+    s.query_stats(true);
+    let SearchResult { documents_ids, query_stats, .. } = s.execute().unwrap();
+    assert!(!documents_ids.is_empty());
+    let QueryStats {
+        interned_words,
+        query_graph_nodes,
+        proximity_edges,
+        typo_edges,
+        cached_database_values,
+    } = query_stats.unwrap();
+    assert!(interned_words > 0);
+    assert!(query_graph_nodes > 0);
+    assert!(proximity_edges > 0);
+    assert!(typo_edges > 0);
+    assert!(cached_database_values > 0);
+}
+
+#[test]
+fn test_query_stats_shared_cache() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+    let mut cache = SharedDatabaseCache::default();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("quikc browm fox");
+    s.query_stats(true);
+    let first = s.execute_with_cache(Some(&mut cache)).unwrap().query_stats.unwrap();
+    assert!(first.interned_words > 0);
+    assert!(first.cached_database_values > 0);
+
+    // the same query only reuses the words and values of the first one
+    let second = s.execute_with_cache(Some(&mut cache)).unwrap().query_stats.unwrap();
+    assert_eq!(second.interned_words, 0);
+    assert_eq!(second.cached_database_values, 0);
+    assert_eq!(second.query_graph_nodes, first.query_graph_nodes);
+}
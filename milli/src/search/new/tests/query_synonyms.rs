@@ -0,0 +1,85 @@
+/*!
+This module tests the synonyms provided at search time with `Search::query_synonyms`:
+
+1. a runtime synonym of a single word makes a document match that wouldn't otherwise
+2. a runtime synonym of several consecutive words works like the index synonyms
+3. the runtime synonyms are normalized like the synonyms of the settings
+4. they are added to the synonyms of the index, without replacing them
+*/
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::index::tests::TempIndex;
+use crate::search::new::tests::collect_field_values;
+use crate::{Criterion, Search, SearchResult, TermsMatchingStrategy};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_criteria(vec![Criterion::Words]);
+            let mut synonyms = BTreeMap::new();
+            synonyms.insert("settee".to_owned(), vec!["sofa".to_owned()]);
+            s.set_synonyms(synonyms);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "a comfortable couch" },
+            { "id": 1, "text": "a leather sofa" },
+            { "id": 2, "text": "guided nyc tour" },
+            { "id": 3, "text": "a wooden chair" },
+        ]))
+        .unwrap();
+    index
+}
+
+fn search(index: &TempIndex, query: &str, synonyms: &[(&str, &str)]) -> Vec<String> {
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, index);
+    s.terms_matching_strategy(TermsMatchingStrategy::All);
+    s.query(query);
+    let mut query_synonyms: HashMap<String, Vec<String>> = HashMap::new();
+    for (word, synonym) in synonyms {
+        query_synonyms.entry(word.to_string()).or_default().push(synonym.to_string());
+    }
+    s.query_synonyms(query_synonyms);
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    collect_field_values(index, &txn, "id", &documents_ids)
+}
+
+#[test]
+fn test_query_synonyms_single_word() {
+    let index = create_index();
+
+    assert!(search(&index, "couch", &[]).contains(&"0".to_owned()));
+    assert!(!search(&index, "couch", &[]).contains(&"1".to_owned()));
+
+    let mut ids = search(&index, "couch", &[("couch", "sofa")]);
+    ids.sort();
+    assert_eq!(ids, vec!["0", "1"]);
+}
+
+#[test]
+fn test_query_synonyms_multiple_words() {
+    let index = create_index();
+
+    assert!(search(&index, "new york tour", &[]).is_empty());
+    assert_eq!(search(&index, "new york tour", &[("New York", "NYC")]), vec!["2"]);
+}
+
+#[test]
+fn test_query_synonyms_added_to_index_synonyms() {
+    let index = create_index();
+
+    assert_eq!(search(&index, "settee", &[]), vec!["1"]);
+
+    let mut ids = search(&index, "settee", &[("settee", "couch")]);
+    ids.sort();
+    assert_eq!(ids, vec!["0", "1"]);
+}
@@ -0,0 +1,59 @@
+/*!
+This module tests the `ranking_score_threshold` search option:
+
+1. the documents whose ranking score is below the threshold are not returned
+2. raising the threshold shrinks the results
+3. the number of documents cut by the threshold is reported in `below_threshold`
+*/
+
+use crate::index::tests::TempIndex;
+use crate::search::new::tests::collect_field_values;
+use crate::{Criterion, Search, SearchResult, TermsMatchingStrategy};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_criteria(vec![Criterion::Words, Criterion::Proximity]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "the quick fox" },
+            { "id": 1, "text": "a quick brown fox" },
+            { "id": 2, "text": "the quick turtle" },
+        ]))
+        .unwrap();
+    index
+}
+
+fn search(index: &TempIndex, threshold: f64) -> (Vec<String>, u64) {
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, index);
+    s.terms_matching_strategy(TermsMatchingStrategy::Last);
+    s.query("quick fox");
+    s.ranking_score_threshold(threshold);
+    let SearchResult { documents_ids, below_threshold, .. } = s.execute().unwrap();
+    (collect_field_values(index, &txn, "id", &documents_ids), below_threshold)
+}
+
+#[test]
+fn test_ranking_score_threshold() {
+    let index = create_index();
+
+    assert_eq!(search(&index, 0.0), (vec!["0".to_owned(), "1".to_owned(), "2".to_owned()], 0));
+    assert_eq!(search(&index, 0.99), (vec!["0".to_owned()], 2));
+
+    let mut previous_len = usize::MAX;
+    for threshold in [0.0, 0.25, 0.5, 0.75, 0.99, 1.0] {
+        let (ids, below_threshold) = search(&index, threshold);
+        assert!(ids.len() <= previous_len, "threshold: {threshold}");
+        assert_eq!(ids.len() as u64 + below_threshold, 3, "threshold: {threshold}");
+        previous_len = ids.len();
+    }
+}
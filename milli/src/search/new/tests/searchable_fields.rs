@@ -0,0 +1,67 @@
+/*!
+This module tests the `Search::searchable_fields` option:
+
+1. words that only appear in the other fields don't match any document
+2. the restriction applies to the universe as well as to the `words` ranking rule
+3. the restriction overrides the searchable attributes of the index
+*/
+
+use crate::index::tests::TempIndex;
+use crate::search::new::tests::collect_field_values;
+use crate::{Criterion, Search, SearchResult, TermsMatchingStrategy};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["title".to_owned(), "body".to_owned()]);
+            s.set_criteria(vec![Criterion::Words]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "title": "the quick fox", "body": "jumps over the lazy dog" },
+            { "id": 1, "title": "the lazy dog", "body": "sleeps all day" },
+            { "id": 2, "title": "a sleepy cat", "body": "is as lazy as the dog" },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_word_only_in_other_field() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("jumps");
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    assert_eq!(collect_field_values(&index, &txn, "id", &documents_ids), vec!["0"]);
+
+    s.searchable_fields(vec!["title".to_owned()]);
+    let SearchResult { documents_ids, candidates, .. } = s.execute().unwrap();
+    assert!(documents_ids.is_empty());
+    assert!(candidates.is_empty());
+}
+
+#[test]
+fn test_restricted_universe_and_words() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.terms_matching_strategy(TermsMatchingStrategy::Last);
+    s.searchable_fields(vec!["title".to_owned()]);
+    s.query("lazy dog sleeps");
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    // `sleeps` only appears in a body, `lazy dog` only in the title of the document 1
+    assert_eq!(collect_field_values(&index, &txn, "id", &documents_ids), vec!["1"]);
+
+    s.searchable_fields(vec!["body".to_owned()]);
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    // `lazy` is mandatory with the `Last` strategy and doesn't appear in the body of the document 1
+    assert_eq!(collect_field_values(&index, &txn, "id", &documents_ids), vec!["0", "2"]);
+}
@@ -0,0 +1,88 @@
+/*!
+This module tests the `skip_ranking_when_under` search option:
+
+1. when there are at most `threshold` candidates, they are returned in their internal order
+without any score details
+2. the pagination still applies to the unranked candidates
+3. above the threshold, or when a ranking score threshold is set, the candidates are ranked
+*/
+
+use crate::index::tests::TempIndex;
+use crate::search::new::tests::collect_field_values;
+use crate::{Criterion, Search, SearchResult, TermsMatchingStrategy};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_criteria(vec![Criterion::Words, Criterion::Proximity]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "the fox is not quick at all" },
+            { "id": 1, "text": "the quick fox" },
+            { "id": 2, "text": "a quick brown fox" },
+            { "id": 3, "text": "a slow turtle" },
+        ]))
+        .unwrap();
+    index
+}
+
+fn search(
+    index: &TempIndex,
+    threshold: Option<usize>,
+    offset: usize,
+    limit: usize,
+) -> (Vec<String>, bool) {
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, index);
+    s.terms_matching_strategy(TermsMatchingStrategy::All);
+    s.query("quick fox");
+    s.offset(offset);
+    s.limit(limit);
+    if let Some(threshold) = threshold {
+        s.skip_ranking_when_under(threshold);
+    }
+    let SearchResult { documents_ids, document_scores, candidates, .. } = s.execute().unwrap();
+    assert_eq!(candidates.len(), 3);
+    let ranked = document_scores.iter().all(|scores| !scores.is_empty());
+    (collect_field_values(index, &txn, "id", &documents_ids), ranked)
+}
+
+#[test]
+fn test_skip_ranking_under_threshold() {
+    let index = create_index();
+
+    assert_eq!(search(&index, None, 0, 20), (vec!["1".into(), "2".into(), "0".into()], true));
+    assert_eq!(search(&index, Some(2), 0, 20), (vec!["1".into(), "2".into(), "0".into()], true));
+    assert_eq!(search(&index, Some(3), 0, 20), (vec!["0".into(), "1".into(), "2".into()], false));
+    assert_eq!(search(&index, Some(100), 0, 20), (vec!["0".into(), "1".into(), "2".into()], false));
+}
+
+#[test]
+fn test_skip_ranking_paginated() {
+    let index = create_index();
+
+    assert_eq!(search(&index, Some(3), 0, 2), (vec!["0".into(), "1".into()], false));
+    assert_eq!(search(&index, Some(3), 2, 2), (vec!["2".into()], false));
+}
+
+#[test]
+fn test_skip_ranking_with_ranking_score_threshold() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.terms_matching_strategy(TermsMatchingStrategy::All);
+    s.query("quick fox");
+    s.skip_ranking_when_under(100);
+    s.ranking_score_threshold(0.0);
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    assert_eq!(collect_field_values(&index, &txn, "id", &documents_ids), vec!["1", "2", "0"]);
+}
@@ -354,3 +354,48 @@ fn test_redacted() {
     insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0, 2, 4, 5, 22, 23, 13, 1, 3, 12, 21, 11, 20, 6, 7, 8, 9, 10, 14, 15]");
     insta::assert_json_snapshot!(document_scores_json);
 }
+
+#[test]
+fn test_sort_by_external() {
+    let index = TempIndex::new();
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_sortable_fields(hashset! { S("letter") });
+            s.set_criteria(vec![Criterion::Sort]);
+        })
+        .unwrap();
+    index
+        .add_documents(documents!([
+            { "id": 0, "letter": "A" },
+            { "id": 1, "letter": "A" },
+            { "id": 2, "letter": "B" },
+            { "id": 3, "letter": "B" },
+            { "id": 4, "letter": "A" },
+        ]))
+        .unwrap();
+
+    let txn = index.read_txn().unwrap();
+    let stock = maplit::hashmap! { 0 => 3.0, 1 => 10.0, 2 => 7.0, 3 => 10.0 };
+
+    // Sort by the external key only, the document 4 is missing from the map
+    let mut s = Search::new(&txn, &index);
+    s.sort_by_external(stock.clone(), AscDesc::Desc(Member::Field(S("stock"))));
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    let ids = collect_field_values(&index, &txn, "id", &documents_ids);
+    assert_eq!(ids, vec!["1", "3", "2", "0", "4"]);
+
+    let mut s = Search::new(&txn, &index);
+    s.sort_by_external(stock.clone(), AscDesc::Asc(Member::Field(S("stock"))));
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    let ids = collect_field_values(&index, &txn, "id", &documents_ids);
+    assert_eq!(ids, vec!["0", "2", "1", "3", "4"]);
+
+    // The external key composes with the other sort criteria
+    let mut s = Search::new(&txn, &index);
+    s.sort_criteria(vec![AscDesc::Desc(Member::Field(S("letter")))]);
+    s.sort_by_external(stock, AscDesc::Desc(Member::Field(S("stock"))));
+    let SearchResult { documents_ids, .. } = s.execute().unwrap();
+    let ids = collect_field_values(&index, &txn, "id", &documents_ids);
+    assert_eq!(ids, vec!["3", "2", "1", "0", "4"]);
+}
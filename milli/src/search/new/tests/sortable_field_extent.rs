@@ -0,0 +1,118 @@
+/*!
+This module tests the `sortable_field_extent` search method:
+
+1. the smallest and largest numbers of the field are computed in the filtered universe
+2. the distinct strings of the field are returned in lexicographic order
+3. an error is returned if the field is not sortable
+4. the words and facet values read by a call are kept in the cache and not read again by the
+   next calls
+5. the strings are truncated once enough of them are found
+*/
+
+use big_s::S;
+use maplit::hashset;
+
+use crate::documents::documents_batch_reader_from_objects;
+use crate::index::tests::TempIndex;
+use crate::{
+    Error, FieldExtent, Filter, Search, SharedDatabaseCache, UserError, DEFAULT_VALUES_PER_FACET,
+};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_filterable_fields(hashset! { S("category") });
+            s.set_sortable_fields(hashset! { S("price"), S("size") });
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "category": "shoes", "price": 49.5, "size": "M" },
+            { "id": 1, "category": "shoes", "price": 120, "size": "L" },
+            { "id": 2, "category": "hats", "price": 15, "size": "S" },
+            { "id": 3, "category": "shoes", "price": 75, "size": "M" },
+            { "id": 4, "category": "hats", "price": 300, "size": 42 },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_sortable_field_extent() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+    let mut cache = SharedDatabaseCache::default();
+
+    let s = Search::new(&txn, &index);
+    let extent = s.sortable_field_extent(S("price"), &mut cache).unwrap();
+    assert_eq!(extent.numbers, Some((15.0, 300.0)));
+
+    let mut s = Search::new(&txn, &index);
+    s.filter(Filter::from_str("category = shoes").unwrap().unwrap());
+    let extent = s.sortable_field_extent(S("price"), &mut cache).unwrap();
+    assert_eq!(
+        extent,
+        FieldExtent { numbers: Some((49.5, 120.0)), strings: vec![], strings_truncated: false }
+    );
+
+    let extent = s.sortable_field_extent(S("size"), &mut cache).unwrap();
+    assert_eq!(
+        extent,
+        FieldExtent { numbers: None, strings: vec![S("L"), S("M")], strings_truncated: false }
+    );
+
+    let s = Search::new(&txn, &index);
+    let extent = s.sortable_field_extent(S("size"), &mut cache).unwrap();
+    assert_eq!(extent.numbers, Some((42.0, 42.0)));
+    assert_eq!(extent.strings, vec![S("L"), S("M"), S("S")]);
+
+    let error = s.sortable_field_extent(S("category"), &mut cache).unwrap_err();
+    assert!(matches!(error, Error::UserError(UserError::InvalidSortableAttribute { .. })));
+}
+
+#[test]
+fn test_sortable_field_extent_shared_cache() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+    let mut cache = SharedDatabaseCache::default();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("shoes");
+    let first = s.sortable_field_extent(S("size"), &mut cache).unwrap();
+    let cached_values = cache.db_cache.cached_values();
+    assert_ne!(cached_values, 0);
+
+    s.filter(Filter::from_str("price > 100").unwrap().unwrap());
+    let second = s.sortable_field_extent(S("size"), &mut cache).unwrap();
+    assert_ne!(first, second);
+    // the words and the values of the field were read by the first call, the filter doesn't
+    // use the cache
+    assert_eq!(cache.db_cache.cached_values(), cached_values);
+}
+
+#[test]
+fn test_sortable_field_extent_truncated() {
+    let index = create_index();
+    let documents: Vec<_> = (0..=DEFAULT_VALUES_PER_FACET)
+        .map(|i| {
+            serde_json::json!({ "id": 10 + i, "size": format!("size{i:03}") })
+                .as_object()
+                .unwrap()
+                .clone()
+        })
+        .collect();
+    index.add_documents(documents_batch_reader_from_objects(documents)).unwrap();
+    let txn = index.read_txn().unwrap();
+    let mut cache = SharedDatabaseCache::default();
+
+    let mut s = Search::new(&txn, &index);
+    s.filter(Filter::from_str("NOT category EXISTS").unwrap().unwrap());
+    let extent = s.sortable_field_extent(S("size"), &mut cache).unwrap();
+    assert_eq!(extent.strings.len(), DEFAULT_VALUES_PER_FACET);
+    assert_eq!(extent.strings.first().map(String::as_str), Some("size000"));
+    assert!(extent.strings_truncated);
+}
@@ -0,0 +1,132 @@
+/*!
+This module tests building a search from a `SearchSpec`:
+
+1. a spec is serialized and deserialized back to the same spec, unknown fields are rejected
+2. the query, filter, sort criteria, geo sort and pagination of the spec are applied
+3. invalid sort criteria and filters are rejected
+4. the ranking rules of a search built from a spec follow its sort criteria, the same way after
+   the spec is serialized and deserialized again
+*/
+
+use big_s::S;
+use maplit::hashset;
+
+use crate::index::tests::TempIndex;
+use crate::{Criterion, Member, Search, SearchResult, SearchSpec};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec![S("text")]);
+            s.set_filterable_fields(hashset! { S("rank") });
+            s.set_sortable_fields(hashset! { S("rank"), S("_geo") });
+            s.set_criteria(vec![Criterion::Words, Criterion::Sort]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "hello world", "rank": 3, "_geo": { "lat": 0, "lng": 0 } },
+            { "id": 1, "text": "hello", "rank": 1, "_geo": { "lat": 1, "lng": 1 } },
+            { "id": 2, "text": "world", "rank": 2, "_geo": { "lat": 2, "lng": 2 } },
+            { "id": 3, "text": "hello there", "rank": 0, "_geo": { "lat": 3, "lng": 3 } },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_deserialize_spec() {
+    let spec: SearchSpec = serde_json::from_str(
+        r#"{
+            "query": "hello",
+            "filter": ["rank > 0"],
+            "sort": ["rank:desc", "_geoPoint(1, 1):asc"],
+            "offset": 1,
+            "limit": 2,
+            "geoStrategy": { "alwaysRtree": 100 }
+        }"#,
+    )
+    .unwrap();
+
+    let json = serde_json::to_string(&spec).unwrap();
+    let spec2: SearchSpec = serde_json::from_str(&json).unwrap();
+    assert_eq!(spec, spec2);
+
+    let error = serde_json::from_str::<SearchSpec>(r#"{ "unknown": true }"#).unwrap_err();
+    assert!(error.to_string().contains("unknown field"));
+}
+
+#[test]
+fn test_search_from_spec() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let spec: SearchSpec =
+        serde_json::from_str(r#"{ "query": "hello", "filter": "rank > 0", "sort": ["rank:asc"] }"#)
+            .unwrap();
+    let search = Search::from_spec(&txn, &index, &spec).unwrap();
+    assert_eq!(search.limit, 20);
+    assert_eq!(search.offset, 0);
+    let SearchResult { documents_ids, .. } = search.execute().unwrap();
+    assert_eq!(documents_ids, vec![1, 0]);
+
+    let spec: SearchSpec =
+        serde_json::from_str(r#"{ "sort": ["_geoPoint(3, 3):asc"], "limit": 1 }"#).unwrap();
+    let search = Search::from_spec(&txn, &index, &spec).unwrap();
+    assert_eq!(search.sort_criteria.as_ref().unwrap()[0].member(), &Member::Geo([3.0, 3.0]));
+    let SearchResult { documents_ids, .. } = search.execute().unwrap();
+    assert_eq!(documents_ids, vec![3]);
+}
+
+#[test]
+fn test_invalid_spec() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let spec = SearchSpec { sort: Some(vec![S("rank")]), ..Default::default() };
+    let error = Search::from_spec(&txn, &index, &spec).unwrap_err();
+    assert!(matches!(error, crate::Error::UserError(crate::UserError::SortError(_))));
+
+    let spec = SearchSpec { filter: Some(serde_json::json!(42)), ..Default::default() };
+    let error = Search::from_spec(&txn, &index, &spec).unwrap_err();
+    assert!(matches!(
+        error,
+        crate::Error::UserError(crate::UserError::InvalidFilterExpression(..))
+    ));
+}
+
+#[test]
+fn test_ranking_rule_descriptors_round_trip() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let spec: SearchSpec = serde_json::from_str(
+        r#"{ "query": "hello", "sort": ["rank:desc", "_geoPoint(1, 1):asc"], "limit": 2 }"#,
+    )
+    .unwrap();
+    let search = Search::from_spec(&txn, &index, &spec).unwrap();
+    let descriptors = search.ranking_rule_descriptors().unwrap();
+    insta::assert_snapshot!(format!("{descriptors:?}"), @r###"["words", "rank:desc", "geo_sort"]"###);
+
+    let json = serde_json::to_string(&spec).unwrap();
+    let spec: SearchSpec = serde_json::from_str(&json).unwrap();
+    let search = Search::from_spec(&txn, &index, &spec).unwrap();
+    assert_eq!(search.ranking_rule_descriptors().unwrap(), descriptors);
+
+    // the same sort criteria given to the setter
+    let mut search = Search::new(&txn, &index);
+    search.query("hello");
+    search
+        .sort_criteria(vec!["rank:desc".parse().unwrap(), "_geoPoint(1, 1):asc".parse().unwrap()]);
+    assert_eq!(search.ranking_rule_descriptors().unwrap(), descriptors);
+
+    // a placeholder search doesn't apply the rules needing a query
+    let spec = SearchSpec { sort: Some(vec![S("rank:asc")]), ..Default::default() };
+    let search = Search::from_spec(&txn, &index, &spec).unwrap();
+    let descriptors = search.ranking_rule_descriptors().unwrap();
+    insta::assert_snapshot!(format!("{descriptors:?}"), @r###"["rank:asc"]"###);
+}
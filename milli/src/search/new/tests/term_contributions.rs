@@ -0,0 +1,60 @@
+/*!
+This module tests the `term_contributions` method of the search:
+
+1. the typo candidates of a word are counted separately from the word itself
+2. only the documents of the results are counted
+3. the words contributing the most documents come first
+*/
+
+use maplit::hashset;
+
+use crate::index::tests::TempIndex;
+use crate::{Criterion, Filter, Search};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_filterable_fields(hashset! { "id".to_owned() });
+            s.set_criteria(vec![Criterion::Words, Criterion::Typo]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "summer day" },
+            { "id": 1, "text": "summer night" },
+            { "id": 2, "text": "sumer camp" },
+            { "id": 3, "text": "winter camp" },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_term_contributions() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("summer");
+    let contributions = s.term_contributions().unwrap();
+    insta::assert_snapshot!(format!("{contributions:?}"), @r###"[("summer", 2), ("sumer", 1)]"###);
+
+    let filter = Filter::from_str("id != 0").unwrap().unwrap();
+    s.filter(filter);
+    let contributions = s.term_contributions().unwrap();
+    insta::assert_snapshot!(format!("{contributions:?}"), @r###"[("sumer", 1), ("summer", 1)]"###);
+}
+
+#[test]
+fn test_term_contributions_placeholder() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let s = Search::new(&txn, &index);
+    assert!(s.term_contributions().unwrap().is_empty());
+}
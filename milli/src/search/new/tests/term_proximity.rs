@@ -0,0 +1,67 @@
+/*!
+This module tests the `Search::term_proximity` method:
+
+1. the cost is `0` when the words of the query are next to each other, in order
+2. the cost grows with the distance between the words
+3. there is no cost for documents missing some words, not matching the query,
+or for queries of a single word
+*/
+
+use crate::index::tests::TempIndex;
+use crate::{Criterion, Search, TermsMatchingStrategy};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+            s.set_criteria(vec![Criterion::Words, Criterion::Proximity]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "the quick fox" },
+            { "id": 1, "text": "a quick brown fox" },
+            { "id": 2, "text": "the fox is not that quick" },
+            { "id": 3, "text": "a quick turtle" },
+            { "id": 4, "text": "a slow turtle" },
+        ]))
+        .unwrap();
+    index
+}
+
+#[test]
+fn test_term_proximity() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.terms_matching_strategy(TermsMatchingStrategy::Last);
+    s.query("quick fox");
+
+    assert_eq!(s.term_proximity(0).unwrap(), Some(0));
+    assert_eq!(s.term_proximity(1).unwrap(), Some(1));
+    let reversed = s.term_proximity(2).unwrap().unwrap();
+    assert!(reversed > 1, "{reversed}");
+
+    // matches the query but not all of its words
+    assert_eq!(s.term_proximity(3).unwrap(), None);
+    // doesn't match the query
+    assert_eq!(s.term_proximity(4).unwrap(), None);
+}
+
+#[test]
+fn test_term_proximity_single_word() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let mut s = Search::new(&txn, &index);
+    s.query("quick");
+    assert_eq!(s.term_proximity(0).unwrap(), None);
+
+    let s = Search::new(&txn, &index);
+    assert_eq!(s.term_proximity(0).unwrap(), None);
+}
@@ -16,12 +16,16 @@ if `words` doesn't exist before it.
 12. Prefix tolerance costs nothing according to the typo ranking rule
 13. Split words cost 1 typo according to the typo ranking rule
 14. Synonyms cost nothing according to the typo ranking rule
+15. The typo derivations of a word are the same whether other words of the query share its stem or not
 */
 
 use std::collections::BTreeMap;
 
+use charabia::TokenizerBuilder;
+
 use crate::index::tests::TempIndex;
 use crate::search::new::tests::collect_field_values;
+use crate::search::new::{located_query_terms_from_tokens, ExtractedTokens, SearchContext};
 use crate::{Criterion, Search, SearchResult, TermsMatchingStrategy};
 
 fn create_index() -> TempIndex {
@@ -635,3 +639,58 @@ fn test_typo_synonyms() {
     ]
     "###);
 }
+
+/// The words derived from each term of the query, the typo derivations of the words sharing
+/// their first letter being computed together.
+fn query_derivations(index: &TempIndex, query: &str) -> Vec<(String, Vec<String>)> {
+    let txn = index.read_txn().unwrap();
+    let mut ctx = SearchContext::new(index, &txn).unwrap();
+    let mut builder = TokenizerBuilder::default();
+    let tokenizer = builder.build();
+    let tokens = tokenizer.tokenize(query);
+    let ExtractedTokens { query_terms, .. } =
+        located_query_terms_from_tokens(&mut ctx, tokens, None).unwrap();
+
+    let mut derivations = Vec::new();
+    for term in query_terms {
+        term.value.compute_fully_if_needed(&mut ctx).unwrap();
+        let term = ctx.term_interner.get(term.value);
+        let (words, _phrases) = term.all_computed_derivations();
+        let mut words: Vec<_> =
+            words.into_iter().map(|word| ctx.word_interner.get(word).to_owned()).collect();
+        words.sort();
+        derivations.push((term.original_word(&ctx), words));
+    }
+    derivations
+}
+
+#[test]
+fn test_typo_derivations_overlapping_words() {
+    let index = create_index();
+
+    let query = "netwolk network networks interconnection interconections sunflawer sunflowering ";
+    let grouped = query_derivations(&index, query);
+    insta::assert_debug_snapshot!(grouped.iter().map(|(word, _)| word).collect::<Vec<_>>(), @r###"
+    [
+        "netwolk",
+        "network",
+        "networks",
+        "interconnection",
+        "interconections",
+        "sunflawer",
+        "sunflowering",
+    ]
+    "###);
+    insta::assert_debug_snapshot!(grouped[4].1, @r###"
+    [
+        "interconections",
+        "interconnection",
+        "interconnections",
+    ]
+    "###);
+
+    for (word, derivations) in grouped {
+        let alone = query_derivations(&index, &format!("{word} "));
+        assert_eq!(alone, vec![(word, derivations)]);
+    }
+}
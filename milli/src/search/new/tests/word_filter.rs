@@ -0,0 +1,112 @@
+/*!
+This module tests the `WordBloomFilter` given to a search with `Search::word_filter`:
+
+1. the filter contains all the words of the index
+2. the results of a search are the same with and without the filter
+3. the lookups of the query words that are not indexed are skipped
+*/
+
+use crate::index::tests::TempIndex;
+use crate::score_details::ScoringStrategy;
+use crate::search::new::tests::collect_field_values;
+use crate::{
+    execute_search, DefaultSearchLogger, GeoSortStrategy, Search, SearchContext, SearchResult,
+    TermsMatchingStrategy, TimeBudget, WordBloomFilter,
+};
+
+fn create_index() -> TempIndex {
+    let index = TempIndex::new();
+
+    index
+        .update_settings(|s| {
+            s.set_primary_key("id".to_owned());
+            s.set_searchable_fields(vec!["text".to_owned()]);
+        })
+        .unwrap();
+
+    index
+        .add_documents(documents!([
+            { "id": 0, "text": "the summer of love" },
+            { "id": 1, "text": "a summary of the lovely songs" },
+            { "id": 2, "text": "songs for the summer" },
+            { "id": 3, "text": "winter is coming" },
+        ]))
+        .unwrap();
+    index
+}
+
+fn search(ctx: &mut SearchContext, query: &str) -> Vec<u32> {
+    let universe = ctx.index.documents_ids(ctx.txn).unwrap();
+    execute_search(
+        ctx,
+        Some(query),
+        TermsMatchingStrategy::Last,
+        ScoringStrategy::Skip,
+        false,
+        universe,
+        &None,
+        &None,
+        GeoSortStrategy::default(),
+        0,
+        20,
+        None,
+        &mut DefaultSearchLogger,
+        &mut DefaultSearchLogger,
+        TimeBudget::max(),
+        None,
+        None,
+    )
+    .unwrap()
+    .documents_ids
+}
+
+#[test]
+fn test_word_filter_contains_indexed_words() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+
+    let word_filter = WordBloomFilter::from_index(&index, &txn).unwrap();
+    let words_fst = index.words_fst(&txn).unwrap();
+    for word in words_fst.into_stream().into_strs().unwrap() {
+        assert!(word_filter.may_contain(&word), "{word}");
+    }
+}
+
+#[test]
+fn test_word_filter_returns_identical_results() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+    let word_filter = WordBloomFilter::from_index(&index, &txn).unwrap();
+
+    for query in ["sumer lvoe", "summer songs", "smmer sngs", "wintr is cming", "unknown"] {
+        let mut s = Search::new(&txn, &index);
+        s.terms_matching_strategy(TermsMatchingStrategy::Last);
+        s.query(query);
+        let SearchResult { documents_ids: expected, .. } = s.execute().unwrap();
+
+        s.word_filter(&word_filter);
+        let SearchResult { documents_ids, .. } = s.execute().unwrap();
+        assert_eq!(
+            collect_field_values(&index, &txn, "id", &documents_ids),
+            collect_field_values(&index, &txn, "id", &expected),
+            "{query}"
+        );
+    }
+}
+
+#[test]
+fn test_word_filter_skips_absent_words() {
+    let index = create_index();
+    let txn = index.read_txn().unwrap();
+    let word_filter = WordBloomFilter::from_index(&index, &txn).unwrap();
+
+    let mut ctx = SearchContext::new(&index, &txn).unwrap();
+    let expected = search(&mut ctx, "sumer lvoe sngs");
+    assert_eq!(ctx.db_cache.skipped_word_lookups, 0);
+
+    let mut ctx = SearchContext::new(&index, &txn).unwrap();
+    ctx.word_filter = Some(&word_filter);
+    assert_eq!(search(&mut ctx, "sumer lvoe sngs"), expected);
+    // the original words of the query are not indexed, only their typos are
+    assert!(ctx.db_cache.skipped_word_lookups > 0);
+}
@@ -0,0 +1,56 @@
+use std::borrow::Cow;
+
+use heed::RoTxn;
+
+use crate::{Index, Result};
+
+/// The number of bits of the filter per word of the dictionary.
+const BITS_PER_WORD: usize = 10;
+/// The number of bits set for each word, optimal for [`BITS_PER_WORD`] with a false
+/// positive rate of about 1%.
+const HASHES: u64 = 7;
+
+/// A bloom filter over the words of the dictionary of an index, used to skip the database
+/// lookups of the query words that are definitely not indexed.
+///
+/// The filter never misses a word it was built from, but it may contain words that aren't:
+/// their lookup then falls through to the database as usual. It is built from the words
+/// FST of the index and must be rebuilt once new words are indexed, as the words added
+/// after its creation would otherwise be considered absent.
+pub struct WordBloomFilter {
+    bits: Vec<u64>,
+}
+
+impl WordBloomFilter {
+    /// Build the filter from the words dictionary of the index.
+    pub fn from_index(index: &Index, rtxn: &RoTxn<'_>) -> Result<Self> {
+        let words_fst = index.words_fst(rtxn)?;
+        Ok(Self::from_words_fst(&words_fst))
+    }
+
+    /// Build the filter from the given words dictionary.
+    pub fn from_words_fst(words_fst: &fst::Set<Cow<'_, [u8]>>) -> Self {
+        let len = (words_fst.len() * BITS_PER_WORD).div_ceil(64).max(1);
+        let mut filter = Self { bits: vec![0; len] };
+        let mut stream = words_fst.stream();
+        while let Some(word) = fst::Streamer::next(&mut stream) {
+            for bit in filter.bit_positions(word) {
+                filter.bits[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+        filter
+    }
+
+    /// Return `false` when the word is definitely not in the dictionary.
+    pub fn may_contain(&self, word: &str) -> bool {
+        self.bit_positions(word.as_bytes()).all(|bit| self.bits[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    /// The positions of the bits of the word, derived from two hashes of it.
+    fn bit_positions(&self, word: &[u8]) -> impl Iterator<Item = usize> {
+        let len = self.bits.len() as u64 * 64;
+        let h1 = fxhash::hash64(word);
+        let h2 = fxhash::hash64(&h1) | 1;
+        (0..HASHES).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % len) as usize)
+    }
+}
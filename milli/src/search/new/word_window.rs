@@ -0,0 +1,211 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use roaring::{MultiOps, RoaringBitmap};
+
+use super::interner::Interned;
+use super::query_term::{LocatedQueryTerm, QueryTermSubset};
+use super::resolve_query_graph::{
+    compute_query_term_subset_docids, compute_query_term_subset_docids_within_field_id,
+    compute_query_term_subset_docids_within_position,
+};
+use super::SearchContext;
+use crate::{Result, TimeBudget};
+
+/// A term of the query located in the documents by the word position databases.
+enum WindowTerm {
+    /// A term of the query, located by its words, its prefix and the first word of its phrases.
+    Subset(QueryTermSubset),
+    /// A word of a quoted phrase, each of them must be in the window.
+    PhraseWord(Interned<String>),
+}
+
+impl WindowTerm {
+    fn of_located_terms(ctx: &SearchContext<'_>, located_terms: &[LocatedQueryTerm]) -> Vec<Self> {
+        let mut terms = Vec::new();
+        for located_term in located_terms {
+            let subset = QueryTermSubset::full(located_term.value);
+            match subset.original_phrase(ctx) {
+                Some(phrase) => {
+                    let words = phrase.words(ctx).into_iter().flatten();
+                    terms.extend(words.map(WindowTerm::PhraseWord));
+                }
+                None => terms.push(WindowTerm::Subset(subset)),
+            }
+        }
+        terms
+    }
+
+    /// The bucketed positions of the words of the term, in any field.
+    fn positions(&self, ctx: &mut SearchContext<'_>) -> Result<BTreeSet<u16>> {
+        let subset = match self {
+            WindowTerm::Subset(subset) => subset,
+            WindowTerm::PhraseWord(word) => {
+                return Ok(ctx.get_db_word_positions(*word)?.into_iter().collect())
+            }
+        };
+        let mut positions = BTreeSet::new();
+        for word in subset.all_single_words_except_prefix_db(ctx)? {
+            positions.extend(ctx.get_db_word_positions(word.interned())?);
+        }
+        for phrase in subset.all_phrases(ctx)? {
+            if let Some(word) = phrase.words(ctx).iter().flatten().next() {
+                positions.extend(ctx.get_db_word_positions(*word)?);
+            }
+        }
+        if let Some(word_prefix) = subset.use_prefix_db(ctx) {
+            positions.extend(ctx.get_db_word_prefix_positions(word_prefix.interned())?);
+        }
+        Ok(positions)
+    }
+
+    fn docids_at_position(
+        &self,
+        ctx: &mut SearchContext<'_>,
+        universe: &RoaringBitmap,
+        position: u16,
+    ) -> Result<RoaringBitmap> {
+        match self {
+            WindowTerm::Subset(subset) => compute_query_term_subset_docids_within_position(
+                ctx,
+                Some(universe),
+                subset,
+                position,
+            ),
+            WindowTerm::PhraseWord(word) => Ok(ctx
+                .get_db_word_position_docids(Some(universe), *word, position)?
+                .unwrap_or_default()),
+        }
+    }
+
+    /// The fields containing the words of the term.
+    fn fids(&self, ctx: &mut SearchContext<'_>) -> Result<BTreeSet<u16>> {
+        match self {
+            WindowTerm::Subset(subset) => term_subset_fids(ctx, subset),
+            WindowTerm::PhraseWord(word) => Ok(ctx.get_db_word_fids(*word)?.into_iter().collect()),
+        }
+    }
+
+    fn docids_in_field(
+        &self,
+        ctx: &mut SearchContext<'_>,
+        universe: &RoaringBitmap,
+        fid: u16,
+    ) -> Result<RoaringBitmap> {
+        match self {
+            WindowTerm::Subset(subset) => {
+                compute_query_term_subset_docids_within_field_id(ctx, Some(universe), subset, fid)
+            }
+            WindowTerm::PhraseWord(word) => {
+                Ok(ctx.get_db_word_fid_docids(Some(universe), *word, fid)?.unwrap_or_default())
+            }
+        }
+    }
+}
+
+/// Return the fields containing the words of the given term, its prefix, or the first word of
+/// its phrases.
+pub(super) fn term_subset_fids(
+    ctx: &mut SearchContext<'_>,
+    subset: &QueryTermSubset,
+) -> Result<BTreeSet<u16>> {
+    let mut fids = BTreeSet::new();
+    for word in subset.all_single_words_except_prefix_db(ctx)? {
+        fids.extend(ctx.get_db_word_fids(word.interned())?);
+    }
+    for phrase in subset.all_phrases(ctx)? {
+        if let Some(word) = phrase.words(ctx).iter().flatten().next() {
+            fids.extend(ctx.get_db_word_fids(*word)?);
+        }
+    }
+    if let Some(word_prefix) = subset.use_prefix_db(ctx) {
+        fids.extend(ctx.get_db_word_prefix_fids(word_prefix.interned())?);
+    }
+    Ok(fids)
+}
+
+/// Return the documents of the universe where all the terms of the query are at most `window`
+/// positions apart, along with whether the time budget was exceeded before all the windows
+/// were checked, in which case only the documents found so far are returned.
+///
+/// The documents are only read from the word fid and word position databases. As the
+/// positions don't tell in which field a word is and are bucketed after 16, the documents must
+/// contain all the terms in a common field and at bucketed positions at most `window` apart,
+/// which is approximate for the words far in their field. The words of a phrase must all be in
+/// the window, in any order.
+pub(crate) fn word_window_docids(
+    ctx: &mut SearchContext<'_>,
+    located_terms: &[LocatedQueryTerm],
+    universe: &RoaringBitmap,
+    window: u16,
+    time_budget: &TimeBudget,
+) -> Result<(RoaringBitmap, bool)> {
+    let mut docids = universe.clone();
+    for located_term in located_terms {
+        let subset = QueryTermSubset::full(located_term.value);
+        docids &= compute_query_term_subset_docids(ctx, Some(&docids), &subset)?;
+    }
+
+    let terms = WindowTerm::of_located_terms(ctx, located_terms);
+    if docids.is_empty() || terms.len() <= 1 {
+        return Ok((docids, false));
+    }
+
+    // all the terms must be found in a common field
+    let mut common_fids: Option<BTreeSet<u16>> = None;
+    for term in &terms {
+        let fids = term.fids(ctx)?;
+        common_fids = Some(match common_fids {
+            Some(common_fids) => common_fids.intersection(&fids).copied().collect(),
+            None => fids,
+        });
+    }
+    let mut in_common_field = RoaringBitmap::new();
+    for fid in common_fids.into_iter().flatten() {
+        let mut fid_docids = docids.clone();
+        for term in &terms {
+            fid_docids &= term.docids_in_field(ctx, &fid_docids, fid)?;
+        }
+        in_common_field |= fid_docids;
+    }
+    docids &= in_common_field;
+    if docids.is_empty() {
+        return Ok((docids, false));
+    }
+
+    let mut terms_positions_docids = Vec::with_capacity(terms.len());
+    for term in &terms {
+        let mut positions_docids = BTreeMap::new();
+        for position in term.positions(ctx)? {
+            let position_docids = term.docids_at_position(ctx, &docids, position)?;
+            if !position_docids.is_empty() {
+                positions_docids.insert(position, position_docids);
+            }
+        }
+        terms_positions_docids.push(positions_docids);
+    }
+
+    // a window starts at the position of one of the terms, they are tried one by one
+    let starts: BTreeSet<u16> =
+        terms_positions_docids.iter().flat_map(|positions| positions.keys().copied()).collect();
+    let mut in_window = RoaringBitmap::new();
+    for start in starts {
+        ctx.check_cancelled()?;
+        if time_budget.exceeded() {
+            return Ok((in_window, true));
+        }
+
+        let end = start.saturating_add(window);
+        let mut start_docids = &docids - &in_window;
+        for positions_docids in &terms_positions_docids {
+            if start_docids.is_empty() {
+                break;
+            }
+            let term_docids: RoaringBitmap =
+                positions_docids.range(start..=end).map(|(_, docids)| docids).union();
+            start_docids &= term_docids;
+        }
+        in_window |= start_docids;
+    }
+
+    Ok((in_window, false))
+}
@@ -140,7 +140,16 @@ impl<'a> Similar<'a> {
             documents_ids,
             document_scores,
             degraded: false,
+            degradation_reasons: None,
             used_negative_operator: false,
+            has_more: false,
+            filter_funnel: None,
+            below_threshold: 0,
+            page_boundary: None,
+            geo_distance_buckets: None,
+            query_stats: None,
+            execution_trace: None,
+            next_cursor: None,
         })
     }
 }
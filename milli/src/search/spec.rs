@@ -0,0 +1,67 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::search::new::GeoSortStrategy;
+use crate::{AscDesc, Filter, Index, Result, Search, SortError};
+
+/// A serializable description of a search request.
+///
+/// Every missing parameter falls back to the default of the corresponding [`Search`] setter.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SearchSpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// A filter expression, either a string or an array of strings and arrays of strings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<Value>,
+    /// The sort criteria, e.g. `price:asc` or `_geoPoint(48.8, 2.3):desc`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub geo_strategy: Option<GeoSortStrategy>,
+}
+
+impl<'a> Search<'a> {
+    /// Build a [`Search`] from a [`SearchSpec`], validating the filter and sort expressions.
+    pub fn from_spec(
+        rtxn: &'a heed::RoTxn<'a>,
+        index: &'a Index,
+        spec: &'a SearchSpec,
+    ) -> Result<Search<'a>> {
+        let mut search = Search::new(rtxn, index);
+
+        if let Some(query) = &spec.query {
+            search.query(query);
+        }
+        if let Some(filter) = &spec.filter {
+            if let Some(filter) = Filter::from_json(filter)? {
+                search.filter(filter);
+            }
+        }
+        if let Some(sort) = &spec.sort {
+            let criteria = sort
+                .iter()
+                .map(|s| AscDesc::from_str(s).map_err(SortError::from))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            search.sort_criteria(criteria);
+        }
+        if let Some(offset) = spec.offset {
+            search.offset(offset);
+        }
+        if let Some(limit) = spec.limit {
+            search.limit(limit);
+        }
+        if let Some(geo_strategy) = spec.geo_strategy {
+            search.geo_strategy = geo_strategy;
+        }
+
+        Ok(search)
+    }
+}
@@ -232,20 +232,61 @@ pub fn extract_finite_float_from_value(value: Value) -> StdResult<f64, Value> {
     }
 }
 
+/// Convert a GeoJSON `Point`, whose coordinates are `[lng, lat]` or `[lng, lat, alt]`, into the
+/// `{ "lat", "lng", "alt" }` object of the `_geo` field, the other values are returned unchanged.
+///
+/// Returns the value back as an error when it is a GeoJSON `Point` with invalid coordinates.
+pub fn normalize_geojson_point(value: Value) -> StdResult<Value, Value> {
+    let Value::Object(object) = &value else { return Ok(value) };
+    if object.get("type").and_then(Value::as_str) != Some("Point") {
+        return Ok(value);
+    }
+
+    let coordinates = match object.get("coordinates") {
+        Some(Value::Array(coordinates)) if object.len() == 2 => coordinates,
+        _ => return Err(value),
+    };
+    let valid = matches!(coordinates.len(), 2 | 3)
+        && coordinates.iter().all(|c| extract_finite_float_from_value(c.clone()).is_ok());
+    if !valid {
+        return Err(value);
+    }
+
+    let mut geo = serde_json::Map::new();
+    geo.insert("lat".to_string(), coordinates[1].clone());
+    geo.insert("lng".to_string(), coordinates[0].clone());
+    if let Some(alt) = coordinates.get(2) {
+        geo.insert("alt".to_string(), alt.clone());
+    }
+    Ok(Value::Object(geo))
+}
+
 pub fn validate_geo_from_json(id: &DocumentId, bytes: &[u8]) -> Result<StdResult<(), GeoError>> {
     use GeoError::*;
     let debug_id = || {
         serde_json::from_slice(id.value().as_bytes()).unwrap_or_else(|_| Value::from(id.debug()))
     };
-    match serde_json::from_slice(bytes).map_err(InternalError::SerdeJson)? {
+    let value = serde_json::from_slice(bytes).map_err(InternalError::SerdeJson)?;
+    let value = match normalize_geojson_point(value) {
+        Ok(value) => value,
+        // a GeoJSON point with invalid coordinates doesn't have the `lat` and `lng` fields,
+        // the document is indexed without coordinates and sorted after the geolocated ones
+        Err(_) => return Ok(Ok(())),
+    };
+    match value {
         Value::Object(mut object) => match (object.remove("lat"), object.remove("lng")) {
             (Some(lat), Some(lng)) => {
+                // the altitude is optional, it is only used to sort the documents in 3D
+                let alt = object.remove("alt").filter(|alt| !alt.is_null());
                 match (extract_finite_float_from_value(lat), extract_finite_float_from_value(lng)) {
                     (Ok(_), Ok(_)) if !object.is_empty() => Ok(Err(UnexpectedExtraFields {
                         document_id: debug_id(),
                         value: object.into(),
                     })),
-                    (Ok(_), Ok(_)) => Ok(Ok(())),
+                    (Ok(_), Ok(_)) => match alt.map(extract_finite_float_from_value) {
+                        Some(Err(value)) => Ok(Err(BadAltitude { document_id: debug_id(), value })),
+                        _ => Ok(Ok(())),
+                    },
                     (Err(value), Ok(_)) => Ok(Err(BadLatitude { document_id: debug_id(), value })),
                     (Ok(_), Err(value)) => Ok(Err(BadLongitude { document_id: debug_id(), value })),
                     (Err(lat), Err(lng)) => {
@@ -8,11 +8,12 @@ use obkv::{KvReader, KvWriterU16};
 use roaring::RoaringBitmap;
 use serde_json::Value;
 
+use super::super::Transform;
 use super::helpers::{create_sorter, keep_latest_obkv, sorter_into_reader, GrenadParameters};
 use crate::error::{InternalError, SerializationError};
 use crate::update::del_add::{del_add_from_two_obkvs, DelAdd, KvReaderDelAdd};
 use crate::update::settings::{InnerIndexSettings, InnerIndexSettingsDiff};
-use crate::{FieldId, Result, MAX_POSITION_PER_ATTRIBUTE, MAX_WORD_LENGTH};
+use crate::{DocumentId, FieldId, Index, Result, MAX_POSITION_PER_ATTRIBUTE, MAX_WORD_LENGTH};
 
 /// Extracts the word and positions where this word appear and
 /// prefixes it by the document id.
@@ -242,6 +243,55 @@ fn tokens_from_document<'a>(
     Ok(document_writer.into_inner().map(|v| v.as_slice())?)
 }
 
+/// Tokenize the searchable fields of the given documents the same way they are indexed,
+/// calling `visit` with the position of each document in `docids`, the field id, and the
+/// words of the field with their position in it.
+pub(crate) fn visit_indexed_words(
+    index: &Index,
+    rtxn: &heed::RoTxn<'_>,
+    docids: &[DocumentId],
+    mut visit: impl FnMut(usize, FieldId, &[(usize, Token<'_>)]),
+) -> Result<()> {
+    let settings = InnerIndexSettings::from_index(index, rtxn)?;
+    let separators: Option<Vec<_>> =
+        settings.allowed_separators.as_ref().map(|s| s.iter().map(String::as_str).collect());
+    let dictionary: Option<Vec<_>> =
+        settings.dictionary.as_ref().map(|s| s.iter().map(String::as_str).collect());
+    let builder = tokenizer_builder(
+        settings.stop_words.as_ref(),
+        separators.as_deref(),
+        dictionary.as_deref(),
+    );
+    let tokenizer = builder.into_tokenizer();
+    let mut fields_ids_map = index.fields_ids_map(rtxn)?;
+
+    let mut buffer = String::new();
+    for (document, (_, obkv)) in
+        index.documents(rtxn, docids.iter().copied())?.into_iter().enumerate()
+    {
+        // the documents are stored as they were sent, their nested fields are indexed flattened
+        let flattened = Transform::flatten_from_fields_ids_map(&obkv, &mut fields_ids_map)?;
+        let obkv = flattened.as_deref().map_or(obkv, KvReader::new);
+        for (field_id, field_bytes) in obkv.iter() {
+            if !settings.searchable_fields_ids.contains(&field_id) {
+                continue;
+            }
+            let value: Value =
+                serde_json::from_slice(field_bytes).map_err(InternalError::SerdeJson)?;
+            buffer.clear();
+            if let Some(field) = json_to_string(&value, &mut buffer) {
+                let locales = settings.localized_searchable_fields_ids.locales(field_id);
+                let words: Vec<_> =
+                    process_tokens(tokenizer.tokenize_with_allow_list(field, locales))
+                        .take_while(|(p, _)| (*p as u32) < MAX_POSITION_PER_ATTRIBUTE)
+                        .collect();
+                visit(document, field_id, &words);
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Transform a JSON value into a string that can be indexed.
 fn json_to_string<'a>(value: &'a Value, buffer: &'a mut String) -> Option<&'a str> {
     fn inner(value: &Value, output: &mut String) -> bool {
@@ -16,7 +16,7 @@ use super::helpers::{
     create_sorter, create_writer, keep_first, obkvs_keep_last_addition_merge_deletions,
     obkvs_merge_additions_and_deletions, sorter_into_reader, MergeFn,
 };
-use super::{IndexDocumentsMethod, IndexerConfig};
+use super::{normalize_geojson_point, IndexDocumentsMethod, IndexerConfig};
 use crate::documents::{DocumentsBatchIndex, EnrichedDocument, EnrichedDocumentsBatchReader};
 use crate::error::{Error, InternalError, UserError};
 use crate::index::{db_name, main_key};
@@ -551,7 +551,7 @@ impl<'a, 'i> Transform<'a, 'i> {
         skip(obkv, fields_ids_map),
         target = "indexing::transform"
     )]
-    fn flatten_from_fields_ids_map(
+    pub(crate) fn flatten_from_fields_ids_map(
         obkv: &KvReader<'_, FieldId>,
         fields_ids_map: &mut FieldsIdsMap,
     ) -> Result<Option<Vec<u8>>> {
@@ -580,8 +580,12 @@ impl<'a, 'i> Transform<'a, 'i> {
                     process: "Flatten from fields ids map.",
                 })?;
 
-                let value = serde_json::from_slice::<Value>(value)
+                let mut value = serde_json::from_slice::<Value>(value)
                     .map_err(crate::error::InternalError::SerdeJson)?;
+                // a GeoJSON point is indexed as the `lat` and `lng` fields of `_geo`
+                if key == "_geo" {
+                    value = normalize_geojson_point(value).unwrap_or_else(|value| value);
+                }
                 doc.insert(key.to_string(), value);
             } else {
                 key_value.push((key, value.into()));